@@ -0,0 +1,27 @@
+//! Build script: embeds the default configuration named by `SENTINEL_DEFAULT_CONFIG`.
+//!
+//! Preconfigured images are built with
+//! `SENTINEL_DEFAULT_CONFIG=/path/to/oem.toml cargo build --release`; without the variable
+//! an empty file is embedded and the built-in defaults apply.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+fn main() {
+    println!("cargo:rerun-if-env-changed=SENTINEL_DEFAULT_CONFIG");
+
+    let contents = match env::var("SENTINEL_DEFAULT_CONFIG") {
+        Ok(path) if !path.is_empty() => {
+            println!("cargo:rerun-if-changed={}", path);
+            fs::read_to_string(&path).unwrap_or_else(|e| {
+                panic!("Failed to read SENTINEL_DEFAULT_CONFIG {}: {}", path, e)
+            })
+        }
+        _ => String::new(),
+    };
+
+    let out_dir = env::var("OUT_DIR").expect("OUT_DIR is set by cargo");
+    fs::write(Path::new(&out_dir).join("default_config.toml"), contents)
+        .expect("Failed to write the embedded default configuration");
+}
@@ -0,0 +1,209 @@
+//! Local Alerting
+//!
+//! This module evaluates collected sensor data against temperature thresholds and
+//! raises alerts when a sensor changes state (normal, warning, critical), then hands
+//! them to the configured notifiers. Alerts only fire on state changes so a host that
+//! stays hot does not produce a notification every interval.
+
+use log::{info, warn};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::alert::digest::Digest;
+use crate::alert::gotify_notifier::GotifyNotifier;
+use crate::alert::ntfy_notifier::NtfyNotifier;
+use crate::alert::smtp_notifier::SmtpNotifier;
+use crate::config::remote_config::RemoteConfig;
+use crate::data::models::SensorData;
+use crate::data::units::Celsius;
+
+/// Last known severity per alert source, used to detect state changes.
+static ALERT_STATES: Mutex<Option<HashMap<String, AlertSeverity>>> = Mutex::new(None);
+
+/// Shared HTTP agent for webhook-style notifiers.
+static NOTIFICATION_AGENT: OnceLock<ureq::Agent> = OnceLock::new();
+
+/// Severity of an alert. `Resolved` is raised when a source returns to normal.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum AlertSeverity {
+    Resolved,
+    Warning,
+    Critical,
+}
+
+impl fmt::Display for AlertSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let label = match self {
+            AlertSeverity::Resolved => "RESOLVED",
+            AlertSeverity::Warning => "WARNING",
+            AlertSeverity::Critical => "CRITICAL",
+        };
+        write!(f, "{}", label)
+    }
+}
+
+/// A locally raised alert.
+#[derive(Serialize, Debug, Clone)]
+pub struct Alert {
+    pub severity: AlertSeverity,
+    /// Identifier of the sensor or subsystem the alert concerns.
+    pub source: String,
+    pub message: String,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+impl Alert {
+    pub fn new(severity: AlertSeverity, source: &str, message: String) -> Self {
+        Self {
+            severity,
+            source: source.to_string(),
+            message,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+impl fmt::Display for Alert {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "[{}] {}: {}", self.severity, self.source, self.message)
+    }
+}
+
+/// Static utility class for evaluating and dispatching alerts.
+pub struct AlertUtil;
+
+impl AlertUtil {
+    /// Evaluates the sensor data, dispatches any resulting alerts, flushes batched notifiers
+    /// and sends the report digest when it is due.
+    pub fn process(sensor_data: &SensorData) {
+        let alerts = Self::evaluate(sensor_data);
+        Self::dispatch(alerts);
+        SmtpNotifier::flush_if_due();
+        Digest::record(sensor_data);
+        Digest::send_if_due();
+    }
+
+    /// Raises a single alert outside of threshold evaluation (e.g. from a subsystem).
+    pub fn raise(alert: Alert) {
+        Self::dispatch(vec![alert]);
+    }
+
+    /// Compares CPU package and core temperatures against their thresholds and returns
+    /// alerts for every source whose severity changed since the previous evaluation.
+    ///
+    /// Thresholds reported by the sensor are used when available; otherwise the configured
+    /// `alert_warning_celsius`/`alert_critical_celsius` fallbacks apply.
+    pub fn evaluate(sensor_data: &SensorData) -> Vec<Alert> {
+        let mut readings = Vec::new();
+        for package in &sensor_data.cpu_packages {
+            readings.push((
+                format!("{} package {}", package.adapter_name, package.package_id),
+                package.package_temperature,
+                package.high_threshold,
+                package.critical_threshold,
+            ));
+            for core in &package.cores {
+                readings.push((
+                    format!("{} {}", package.adapter_name, core.core_name),
+                    core.temperature,
+                    core.high_threshold,
+                    core.critical_threshold,
+                ));
+            }
+        }
+
+        let mut states = ALERT_STATES.lock().unwrap_or_else(|e| e.into_inner());
+        let states = states.get_or_insert_with(HashMap::new);
+
+        let mut alerts = Vec::new();
+        for (source, temperature, high, critical) in readings {
+            let severity = Self::classify(temperature, high, critical);
+            let previous = states.insert(source.clone(), severity);
+
+            let changed = match previous {
+                Some(previous) => previous != severity,
+                None => severity != AlertSeverity::Resolved,
+            };
+            if changed {
+                let message = match severity {
+                    AlertSeverity::Resolved => {
+                        format!("temperature back to normal at {:.1}", temperature)
+                    }
+                    _ => format!("temperature at {:.1}", temperature),
+                };
+                alerts.push(Alert::new(severity, &source, message));
+            }
+        }
+        alerts
+    }
+
+    /// Classifies a temperature; `Resolved` means "normal".
+    ///
+    /// Non-positive thresholds are treated as unknown and replaced by the configured fallbacks.
+    pub fn classify(temperature: Celsius, high: Celsius, critical: Celsius) -> AlertSeverity {
+        Self::classify_with(
+            temperature.0,
+            high.0,
+            critical.0,
+            RemoteConfig::alert_warning_celsius().map(|limit| limit.0),
+            RemoteConfig::alert_critical_celsius().map(|limit| limit.0),
+        )
+    }
+
+    /// Classifies a value against thresholds of the same unit, with explicit fallbacks.
+    pub fn classify_with(
+        temperature: f32,
+        high: f32,
+        critical: f32,
+        fallback_high: Option<f32>,
+        fallback_critical: Option<f32>,
+    ) -> AlertSeverity {
+        let high = Some(high).filter(|t| *t > 0.0).or(fallback_high);
+        let critical = Some(critical).filter(|t| *t > 0.0).or(fallback_critical);
+
+        if critical.is_some_and(|limit| temperature >= limit) {
+            AlertSeverity::Critical
+        } else if high.is_some_and(|limit| temperature >= limit) {
+            AlertSeverity::Warning
+        } else {
+            AlertSeverity::Resolved
+        }
+    }
+
+    /// Returns the HTTP agent used by webhook-style notifiers, with a bounded timeout so a
+    /// slow notification service cannot stall data collection.
+    pub fn notification_agent() -> &'static ureq::Agent {
+        NOTIFICATION_AGENT.get_or_init(|| {
+            ureq::Agent::config_builder()
+                .timeout_global(Some(Duration::from_secs(10)))
+                .build()
+                .into()
+        })
+    }
+
+    /// Logs the alerts and forwards them to every enabled notifier.
+    fn dispatch(alerts: Vec<Alert>) {
+        if alerts.is_empty() {
+            return;
+        }
+
+        for alert in &alerts {
+            match alert.severity {
+                AlertSeverity::Resolved => info!("Alert: {}", alert),
+                _ => warn!("Alert: {}", alert),
+            }
+        }
+
+        Digest::count_alerts(&alerts);
+        SmtpNotifier::enqueue(&alerts);
+        GotifyNotifier::notify(&alerts);
+        NtfyNotifier::notify(&alerts);
+    }
+}
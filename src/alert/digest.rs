@@ -0,0 +1,323 @@
+//! Report Digests
+//!
+//! With a `[digest]` section, the agent summarizes the collected data once a day (or once
+//! a week) at a fixed local time and sends the summary through every configured notifier
+//! (SMTP, Gotify, ntfy): minimum, maximum and average temperature per CPU package, how
+//! much each disk grew, and how many alerts were raised. Statistics are kept in memory, so
+//! a digest only covers the time since the agent started if it was restarted in between.
+
+use log::{debug, info};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::alert::alert_util::{Alert, AlertSeverity};
+use crate::alert::gotify_notifier::GotifyNotifier;
+use crate::alert::ntfy_notifier::NtfyNotifier;
+use crate::alert::smtp_notifier::SmtpNotifier;
+use crate::config::config_instance::Config;
+use crate::data::models::{CpuPackageData, DiskInfo, SensorData};
+use crate::system::state::AgentState;
+
+/// How often a digest is sent.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DigestSchedule {
+    #[default]
+    Daily,
+    Weekly,
+}
+
+/// Day of the week weekly digests are sent on.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Weekday {
+    Sunday,
+    #[default]
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+}
+
+/// A local time of day, given as `"HH:MM"`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct TimeOfDay {
+    /// Minutes since midnight.
+    pub minute_of_day: u32,
+}
+
+impl TryFrom<String> for TimeOfDay {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let parsed = value.trim().split_once(':').and_then(|(hour, minute)| {
+            let hour: u32 = hour.parse().ok().filter(|h| *h < 24)?;
+            let minute: u32 = minute.parse().ok().filter(|m| *m < 60)?;
+            Some(hour * 60 + minute)
+        });
+        parsed
+            .map(|minute_of_day| TimeOfDay { minute_of_day })
+            .ok_or_else(|| format!("invalid time of day '{}' (expected e.g. \"08:00\")", value))
+    }
+}
+
+/// Configuration of the `[digest]` section; digests are sent when present.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DigestConfig {
+    pub schedule: DigestSchedule,
+    /// Local time the digest is sent at.
+    pub at: TimeOfDay,
+    /// Day weekly digests are sent on; ignored for daily digests.
+    pub weekday: Weekday,
+}
+
+impl Default for DigestConfig {
+    fn default() -> Self {
+        Self {
+            schedule: DigestSchedule::Daily,
+            at: TimeOfDay {
+                minute_of_day: 8 * 60,
+            },
+            weekday: Weekday::Monday,
+        }
+    }
+}
+
+/// Minimum, maximum and running sum of one temperature source.
+#[derive(Debug, Clone, Copy)]
+struct TemperatureStats {
+    min: f32,
+    max: f32,
+    sum: f64,
+    samples: u64,
+}
+
+/// Statistics collected since the last digest.
+#[derive(Debug, Default)]
+struct DigestStats {
+    temperatures: BTreeMap<String, TemperatureStats>,
+    /// Used bytes per disk at the first and the latest sample.
+    disks: BTreeMap<String, (u64, u64)>,
+    alerts: BTreeMap<AlertSeverity, u64>,
+    reports: u64,
+}
+
+static STATS: Mutex<Option<DigestStats>> = Mutex::new(None);
+
+const GIB: f64 = 1024.0 * 1024.0 * 1024.0;
+
+impl DigestStats {
+    fn record(&mut self, packages: &[CpuPackageData], disks: &[DiskInfo]) {
+        self.reports += 1;
+        for package in packages {
+            let source = format!("{} package {}", package.adapter_name, package.package_id);
+            let temperature = package.package_temperature.0;
+            self.temperatures
+                .entry(source)
+                .and_modify(|stats| {
+                    stats.min = stats.min.min(temperature);
+                    stats.max = stats.max.max(temperature);
+                    stats.sum += f64::from(temperature);
+                    stats.samples += 1;
+                })
+                .or_insert(TemperatureStats {
+                    min: temperature,
+                    max: temperature,
+                    sum: f64::from(temperature),
+                    samples: 1,
+                });
+        }
+        for disk in disks {
+            let used = disk.total_space.0.saturating_sub(disk.available_space.0);
+            self.disks
+                .entry(disk.name.clone())
+                .and_modify(|(_, last)| *last = used)
+                .or_insert((used, used));
+        }
+    }
+
+    fn count(&mut self, alerts: &[Alert]) {
+        for alert in alerts {
+            *self.alerts.entry(alert.severity).or_default() += 1;
+        }
+    }
+
+    /// Renders the statistics as a plain-text summary.
+    fn summary(&self) -> String {
+        let mut text = format!("{} report(s) collected.\n", self.reports);
+
+        if !self.temperatures.is_empty() {
+            text.push_str("\nTemperatures (min / avg / max):\n");
+            for (source, stats) in &self.temperatures {
+                let _ = writeln!(
+                    text,
+                    "  {}: {:.1} / {:.1} / {:.1} °C",
+                    source,
+                    stats.min,
+                    stats.sum / stats.samples as f64,
+                    stats.max
+                );
+            }
+        }
+
+        if !self.disks.is_empty() {
+            text.push_str("\nDisk usage:\n");
+            for (name, (first, last)) in &self.disks {
+                let growth = *last as i128 - *first as i128;
+                let _ = writeln!(
+                    text,
+                    "  {}: {:.2} GiB used ({:+.2} GiB)",
+                    name,
+                    *last as f64 / GIB,
+                    growth as f64 / GIB
+                );
+            }
+        }
+
+        let count = |severity| self.alerts.get(&severity).copied().unwrap_or_default();
+        let _ = write!(
+            text,
+            "\nAlerts: {} critical, {} warning, {} resolved.",
+            count(AlertSeverity::Critical),
+            count(AlertSeverity::Warning),
+            count(AlertSeverity::Resolved)
+        );
+        text
+    }
+}
+
+/// Static utility class for scheduled report digests.
+pub struct Digest;
+
+impl Digest {
+    /// Adds a report to the statistics of the next digest, if digests are configured.
+    pub fn record(sensor_data: &SensorData) {
+        if Config::get().digest.is_none() {
+            return;
+        }
+        let mut stats = STATS.lock().unwrap_or_else(|e| e.into_inner());
+        stats
+            .get_or_insert_with(DigestStats::default)
+            .record(&sensor_data.cpu_packages, &sensor_data.disks);
+    }
+
+    /// Counts raised alerts towards the next digest, if digests are configured.
+    pub fn count_alerts(alerts: &[Alert]) {
+        if Config::get().digest.is_none() {
+            return;
+        }
+        let mut stats = STATS.lock().unwrap_or_else(|e| e.into_inner());
+        stats.get_or_insert_with(DigestStats::default).count(alerts);
+    }
+
+    /// Sends the digest once its scheduled time has passed on a day it has not been sent
+    /// on yet. The day of the last digest is persisted, so restarts do not send it twice.
+    pub fn send_if_due() {
+        let Some(config) = &Config::get().digest else {
+            return;
+        };
+        let Some(now) = Self::local_time() else {
+            return;
+        };
+        let day = i64::from(now.tm_year) * 1000 + i64::from(now.tm_yday);
+        let Some(last_day) = AgentState::load().last_digest_day else {
+            // Start the first period now instead of sending a digest of a few minutes.
+            AgentState::update(|state| state.last_digest_day = Some(day));
+            return;
+        };
+
+        let minute_of_day = (now.tm_hour * 60 + now.tm_min) as u32;
+        let on_schedule = match config.schedule {
+            DigestSchedule::Daily => true,
+            DigestSchedule::Weekly => now.tm_wday == config.weekday as i32,
+        };
+        if last_day == day || !on_schedule || minute_of_day < config.at.minute_of_day {
+            return;
+        }
+
+        let stats = STATS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .take()
+            .unwrap_or_default();
+        let title = match config.schedule {
+            DigestSchedule::Daily => "daily digest",
+            DigestSchedule::Weekly => "weekly digest",
+        };
+        let summary = stats.summary();
+        debug!("Sending {}:\n{}", title, summary);
+        SmtpNotifier::send_message(title, &summary);
+        GotifyNotifier::send_message(title, &summary);
+        NtfyNotifier::send_message(title, &summary);
+        info!("Sent {} covering {} report(s).", title, stats.reports);
+
+        AgentState::update(|state| state.last_digest_day = Some(day));
+    }
+
+    fn local_time() -> Option<libc::tm> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default() as libc::time_t;
+        // SAFETY: `localtime_r` only writes to the provided `tm`.
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        if unsafe { libc::localtime_r(&now, &mut tm) }.is_null() {
+            return None;
+        }
+        Some(tm)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::units::{Bytes, Celsius};
+
+    fn package(temperature: f32) -> CpuPackageData {
+        CpuPackageData {
+            package_id: "0".to_string(),
+            adapter_name: "coretemp".to_string(),
+            package_temperature: Celsius(temperature),
+            high_threshold: Celsius(0.0),
+            critical_threshold: Celsius(0.0),
+            cores: Vec::new(),
+        }
+    }
+
+    fn disk(available_gib: u64) -> DiskInfo {
+        DiskInfo {
+            name: "sda".to_string(),
+            total_space: Bytes(100 << 30),
+            available_space: Bytes(available_gib << 30),
+            read_bytes: Bytes(0),
+            written_bytes: Bytes(0),
+            total_read_bytes: 0,
+            total_written_bytes: 0,
+            read_bytes_per_sec: None,
+            written_bytes_per_sec: None,
+            counter_reset: false,
+        }
+    }
+
+    #[test]
+    fn summarizes_temperatures_disks_and_alerts() {
+        let mut stats = DigestStats::default();
+        for (temperature, available) in [(40.0, 60), (60.0, 58), (50.0, 55)] {
+            stats.record(&[package(temperature)], &[disk(available)]);
+        }
+        stats.count(&[Alert::new(AlertSeverity::Warning, "x", String::new())]);
+
+        let summary = stats.summary();
+        assert!(summary.starts_with("3 report(s) collected."));
+        assert!(summary.contains("coretemp package 0: 40.0 / 50.0 / 60.0 °C"));
+        assert!(summary.contains("sda: 45.00 GiB used (+5.00 GiB)"));
+        assert!(summary.ends_with("Alerts: 0 critical, 1 warning, 0 resolved."));
+    }
+}
@@ -0,0 +1,105 @@
+//! Gotify Notifier
+//!
+//! This module pushes local alerts to a self-hosted Gotify server, mapping alert
+//! severity to Gotify message priorities.
+
+use log::{debug, error};
+use serde::Deserialize;
+use serde_json::json;
+
+use crate::alert::alert_util::{Alert, AlertSeverity, AlertUtil};
+use crate::config::config_instance::Config;
+
+/// Configuration of the `[gotify]` section. The notifier is enabled when the section is present.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GotifyConfig {
+    /// Base URL of the Gotify server (e.g. `https://gotify.example.com`).
+    pub url: String,
+    /// Application token used to publish messages.
+    pub token: String,
+    /// Gotify priority (0-10) for resolved alerts.
+    pub resolved_priority: u8,
+    /// Gotify priority (0-10) for warnings.
+    pub warning_priority: u8,
+    /// Gotify priority (0-10) for critical alerts.
+    pub critical_priority: u8,
+}
+
+impl Default for GotifyConfig {
+    fn default() -> Self {
+        Self {
+            url: String::new(),
+            token: String::new(),
+            resolved_priority: 2,
+            warning_priority: 5,
+            critical_priority: 8,
+        }
+    }
+}
+
+impl GotifyConfig {
+    fn priority_for(&self, severity: AlertSeverity) -> u8 {
+        match severity {
+            AlertSeverity::Resolved => self.resolved_priority,
+            AlertSeverity::Warning => self.warning_priority,
+            AlertSeverity::Critical => self.critical_priority,
+        }
+    }
+}
+
+/// Static utility class for pushing alerts to Gotify.
+pub struct GotifyNotifier;
+
+impl GotifyNotifier {
+    /// Pushes each alert as a Gotify message, if Gotify is configured.
+    pub fn notify(alerts: &[Alert]) {
+        let Some(config) = Config::get().gotify.as_ref() else {
+            return;
+        };
+
+        for alert in alerts {
+            let title = format!("{} {}", alert.severity, alert.source);
+            match Self::push(
+                config,
+                &title,
+                &alert.message,
+                config.priority_for(alert.severity),
+            ) {
+                Ok(_) => debug!("Pushed alert to Gotify: {}", alert),
+                Err(e) => error!("Failed to push alert to Gotify: {}", e),
+            }
+        }
+    }
+
+    /// Pushes a message that is not an alert (e.g. a digest) at the resolved priority, if
+    /// Gotify is configured.
+    pub fn send_message(title: &str, message: &str) {
+        let Some(config) = Config::get().gotify.as_ref() else {
+            return;
+        };
+        match Self::push(config, title, message, config.resolved_priority) {
+            Ok(_) => debug!("Pushed {} to Gotify.", title),
+            Err(e) => error!("Failed to push {} to Gotify: {}", title, e),
+        }
+    }
+
+    fn push(
+        config: &GotifyConfig,
+        title: &str,
+        message: &str,
+        priority: u8,
+    ) -> Result<(), ureq::Error> {
+        let url = format!("{}/message", config.url.trim_end_matches('/'));
+        let message = json!({
+            "title": title,
+            "message": message,
+            "priority": priority,
+        });
+        AlertUtil::notification_agent()
+            .post(&url)
+            .header("X-Gotify-Key", &config.token)
+            .send_json(&message)
+            .map(|_| ())
+    }
+}
@@ -0,0 +1,5 @@
+pub mod alert_util;
+pub mod digest;
+pub mod gotify_notifier;
+pub mod ntfy_notifier;
+pub mod smtp_notifier;
@@ -0,0 +1,114 @@
+//! ntfy Notifier
+//!
+//! This module publishes local alerts to an ntfy topic (ntfy.sh or a self-hosted
+//! instance), mapping alert severity to ntfy message priorities.
+
+use log::{debug, error};
+use serde::Deserialize;
+
+use crate::alert::alert_util::{Alert, AlertSeverity, AlertUtil};
+use crate::config::config_instance::Config;
+
+/// Configuration of the `[ntfy]` section. The notifier is enabled when the section is present.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct NtfyConfig {
+    /// Base URL of the ntfy server.
+    pub url: String,
+    /// Topic to publish to.
+    pub topic: String,
+    /// Optional access token for protected topics.
+    pub token: Option<String>,
+    /// ntfy priority (1-5) for resolved alerts.
+    pub resolved_priority: u8,
+    /// ntfy priority (1-5) for warnings.
+    pub warning_priority: u8,
+    /// ntfy priority (1-5) for critical alerts.
+    pub critical_priority: u8,
+}
+
+impl Default for NtfyConfig {
+    fn default() -> Self {
+        Self {
+            url: "https://ntfy.sh".to_string(),
+            topic: String::new(),
+            token: None,
+            resolved_priority: 2,
+            warning_priority: 4,
+            critical_priority: 5,
+        }
+    }
+}
+
+impl NtfyConfig {
+    fn priority_for(&self, severity: AlertSeverity) -> u8 {
+        match severity {
+            AlertSeverity::Resolved => self.resolved_priority,
+            AlertSeverity::Warning => self.warning_priority,
+            AlertSeverity::Critical => self.critical_priority,
+        }
+    }
+}
+
+/// Static utility class for publishing alerts to ntfy.
+pub struct NtfyNotifier;
+
+impl NtfyNotifier {
+    /// Publishes each alert to the configured topic, if ntfy is configured.
+    pub fn notify(alerts: &[Alert]) {
+        let Some(config) = Config::get().ntfy.as_ref() else {
+            return;
+        };
+
+        for alert in alerts {
+            let tag = match alert.severity {
+                AlertSeverity::Resolved => "white_check_mark",
+                AlertSeverity::Warning => "warning",
+                AlertSeverity::Critical => "rotating_light",
+            };
+            let title = format!("{} {}", alert.severity, alert.source);
+            let priority = config.priority_for(alert.severity);
+            match Self::publish(config, &title, &alert.message, priority, tag) {
+                Ok(_) => debug!("Published alert to ntfy: {}", alert),
+                Err(e) => error!("Failed to publish alert to ntfy: {}", e),
+            }
+        }
+    }
+
+    /// Publishes a message that is not an alert (e.g. a digest) at the resolved priority,
+    /// if ntfy is configured.
+    pub fn send_message(title: &str, message: &str) {
+        let Some(config) = Config::get().ntfy.as_ref() else {
+            return;
+        };
+        match Self::publish(
+            config,
+            title,
+            message,
+            config.resolved_priority,
+            "bar_chart",
+        ) {
+            Ok(_) => debug!("Published {} to ntfy.", title),
+            Err(e) => error!("Failed to publish {} to ntfy: {}", title, e),
+        }
+    }
+
+    fn publish(
+        config: &NtfyConfig,
+        title: &str,
+        message: &str,
+        priority: u8,
+        tag: &str,
+    ) -> Result<(), ureq::Error> {
+        let url = format!("{}/{}", config.url.trim_end_matches('/'), config.topic);
+        let mut request = AlertUtil::notification_agent()
+            .post(&url)
+            .header("Title", title)
+            .header("Priority", priority.to_string())
+            .header("Tags", tag);
+        if let Some(token) = &config.token {
+            request = request.header("Authorization", format!("Bearer {}", token));
+        }
+        request.send(message).map(|_| ())
+    }
+}
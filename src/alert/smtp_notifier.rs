@@ -0,0 +1,170 @@
+//! SMTP Notifier
+//!
+//! This module emails local alerts via SMTP (implicit TLS, STARTTLS or plain) with
+//! optional authentication. Alerts are collected into a digest and sent at most once
+//! per batch window, so a flapping sensor cannot cause a mail storm.
+
+use lettre::message::Mailbox;
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{Message, SmtpTransport, Transport};
+use log::{error, info};
+use serde::Deserialize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::alert::alert_util::Alert;
+use crate::config::config_instance::Config;
+
+/// Alerts waiting to be sent, and the time the oldest of them was queued.
+static PENDING: Mutex<Vec<Alert>> = Mutex::new(Vec::new());
+static BATCH_STARTED: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Connection security for the SMTP server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// Implicit TLS (usually port 465).
+    Tls,
+    /// Plain connection upgraded via STARTTLS (usually port 587).
+    #[default]
+    Starttls,
+    /// Unencrypted connection; only suitable for a local relay.
+    None,
+}
+
+/// Configuration of the `[smtp]` section. The notifier is enabled when the section is present.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SmtpConfig {
+    pub host: String,
+    /// Port; defaults to the standard port for the selected security mode.
+    pub port: Option<u16>,
+    pub security: SmtpSecurity,
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub from: String,
+    pub to: Vec<String>,
+    /// Seconds to collect alerts into a single digest mail; `0` sends every alert immediately.
+    pub batch_secs: u64,
+}
+
+impl Default for SmtpConfig {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: None,
+            security: SmtpSecurity::Starttls,
+            username: None,
+            password: None,
+            from: "gilded-sentinel@localhost".to_string(),
+            to: Vec::new(),
+            batch_secs: 300,
+        }
+    }
+}
+
+/// Static utility class for emailing alerts.
+pub struct SmtpNotifier;
+
+impl SmtpNotifier {
+    /// Queues alerts for the next digest, if SMTP notifications are configured.
+    pub fn enqueue(alerts: &[Alert]) {
+        if Config::get().smtp.is_none() {
+            return;
+        }
+
+        PENDING
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .extend_from_slice(alerts);
+        BATCH_STARTED
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .get_or_insert_with(Instant::now);
+        Self::flush_if_due();
+    }
+
+    /// Sends the queued alerts as one mail once the batch window has elapsed.
+    pub fn flush_if_due() {
+        let Some(config) = Config::get().smtp.as_ref() else {
+            return;
+        };
+
+        let mut started = BATCH_STARTED.lock().unwrap_or_else(|e| e.into_inner());
+        if !started.is_some_and(|t| t.elapsed() >= Duration::from_secs(config.batch_secs)) {
+            return;
+        }
+
+        let alerts: Vec<Alert> =
+            std::mem::take(&mut *PENDING.lock().unwrap_or_else(|e| e.into_inner()));
+        *started = None;
+        if alerts.is_empty() {
+            return;
+        }
+
+        let hostname = sysinfo::System::host_name().unwrap_or_else(|| "<unknown>".to_string());
+        let subject = match alerts.as_slice() {
+            [alert] => format!("[Gilded-Sentinel] {}: {}", hostname, alert),
+            _ => format!("[Gilded-Sentinel] {}: {} alerts", hostname, alerts.len()),
+        };
+        let body = alerts
+            .iter()
+            .map(|alert| alert.to_string())
+            .collect::<Vec<_>>()
+            .join("\n");
+        match Self::send(config, subject, body) {
+            Ok(_) => info!("Emailed {} alert(s) to {:?}.", alerts.len(), config.to),
+            Err(e) => error!("Failed to email {} alert(s): {}", alerts.len(), e),
+        }
+    }
+
+    /// Mails a message that is not an alert (e.g. a digest) right away, bypassing the
+    /// batch window, if SMTP notifications are configured.
+    pub fn send_message(title: &str, message: &str) {
+        let Some(config) = Config::get().smtp.as_ref() else {
+            return;
+        };
+        let hostname = sysinfo::System::host_name().unwrap_or_else(|| "<unknown>".to_string());
+        let subject = format!("[Gilded-Sentinel] {}: {}", hostname, title);
+        match Self::send(config, subject, message.to_string()) {
+            Ok(_) => info!("Emailed {} to {:?}.", title, config.to),
+            Err(e) => error!("Failed to email {}: {}", title, e),
+        }
+    }
+
+    fn send(config: &SmtpConfig, subject: String, body: String) -> Result<(), String> {
+        let mut builder = Message::builder()
+            .from(Self::parse_mailbox(&config.from)?)
+            .subject(subject);
+        for recipient in &config.to {
+            builder = builder.to(Self::parse_mailbox(recipient)?);
+        }
+        let email = builder.body(body).map_err(|e| e.to_string())?;
+
+        let mut transport = match config.security {
+            SmtpSecurity::Tls => SmtpTransport::relay(&config.host).map_err(|e| e.to_string())?,
+            SmtpSecurity::Starttls => {
+                SmtpTransport::starttls_relay(&config.host).map_err(|e| e.to_string())?
+            }
+            SmtpSecurity::None => SmtpTransport::builder_dangerous(&config.host),
+        };
+        if let Some(port) = config.port {
+            transport = transport.port(port);
+        }
+        if let (Some(username), Some(password)) = (&config.username, &config.password) {
+            transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+        }
+
+        transport
+            .build()
+            .send(&email)
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    fn parse_mailbox(address: &str) -> Result<Mailbox, String> {
+        address
+            .parse()
+            .map_err(|e| format!("invalid address `{}`: {}", address, e))
+    }
+}
@@ -0,0 +1,606 @@
+//! Command-Line Interface
+//!
+//! This module defines the command-line arguments and subcommands of the Gilded-Sentinel
+//! client, and translates parsed arguments into the command to execute. The same definition
+//! generates the shell completion scripts (`completions <shell>`) and the man page (`man`)
+//! installed by packaging (`package`).
+
+use clap::{Arg, ArgAction, ArgGroup, ArgMatches, Command};
+use clap_complete::Shell;
+use std::io;
+
+/// The action requested on the command line.
+#[derive(Debug)]
+pub enum CliCommand {
+    /// Run the monitoring agent (default when no subcommand is given).
+    Run,
+    /// Resend previously traced or spooled payloads to a server.
+    Replay(ReplayOptions),
+    /// Print inventory and current readings as one JSON document.
+    Facts(FactsOptions),
+    /// Perform one read and report it as a Nagios/Icinga plugin.
+    Check(CheckOptions),
+    /// Write InfluxDB line protocol to stdout for Telegraf's `exec`/`execd` inputs.
+    Telegraf(TelegrafOptions),
+    /// Write collectd `PUTVAL` commands to stdout for collectd's `exec` plugin.
+    Collectd(CollectdOptions),
+    /// Query or adjust the running agent through its control socket.
+    Status(StatusOptions),
+    /// Write recent history, inventory and status to a tar archive (`bundle export`).
+    BundleExport(BundleExportOptions),
+    /// Send the history of an exported bundle to a server (`bundle import`).
+    BundleImport(BundleImportOptions),
+    /// Read and decode one model-specific register (`debug msr read`).
+    DebugMsrRead(MsrReadOptions),
+    /// Print the completion script for a shell.
+    Completions(CompletionsOptions),
+    /// Print the man page in roff format.
+    Man,
+    /// Write the files of a distribution package.
+    Package(PackageOptions),
+}
+
+/// Options for the `replay` subcommand.
+#[derive(Debug)]
+pub struct ReplayOptions {
+    /// Payload files or directories containing payload files.
+    pub paths: Vec<String>,
+    /// Server to send to instead of the configured one.
+    pub target: Option<String>,
+    /// Playback speed relative to the original timing; `0` sends as fast as possible.
+    pub speed: f64,
+}
+
+/// Options for the `facts` subcommand.
+#[derive(Debug)]
+pub struct FactsOptions {
+    /// Pretty-print the JSON document.
+    pub pretty: bool,
+}
+
+/// Metric evaluated by the `check` subcommand.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckMetric {
+    /// CPU package and core temperatures in °C.
+    Temperature,
+    /// Memory usage in percent.
+    Memory,
+    /// Usage of each disk in percent.
+    Disk,
+}
+
+/// Options for the `check` subcommand.
+#[derive(Debug)]
+pub struct CheckOptions {
+    /// Metric to check.
+    pub metric: CheckMetric,
+    /// Sensor label or disk name to check (e.g. `Package id 0`); all readings when absent.
+    pub sensor: Option<String>,
+    /// Warning threshold, overriding the configured or sensor-provided one.
+    pub warning: Option<f32>,
+    /// Critical threshold, overriding the configured or sensor-provided one.
+    pub critical: Option<f32>,
+}
+
+/// Options for the `status` subcommand.
+#[derive(Debug)]
+pub struct StatusOptions {
+    /// Log level to switch the running agent to.
+    pub set_log_level: Option<String>,
+    /// Seconds until the log level reverts; `0` keeps it until the next change.
+    pub duration_secs: Option<u64>,
+}
+
+/// Options for the `bundle export` subcommand.
+#[derive(Debug)]
+pub struct BundleExportOptions {
+    /// Archive to write; `gilded-sentinel-<host>-<time>.tar` in the working directory when absent.
+    pub output: Option<String>,
+    /// Minutes of history to include.
+    pub minutes: u64,
+}
+
+/// Options for the `bundle import` subcommand.
+#[derive(Debug)]
+pub struct BundleImportOptions {
+    /// Archive to import.
+    pub path: String,
+    /// Server to send to instead of the configured one.
+    pub target: Option<String>,
+}
+
+/// Options for the `telegraf` subcommand.
+#[derive(Debug)]
+pub struct TelegrafOptions {
+    /// Keep running and print a snapshot on every flush request (Telegraf `execd`).
+    pub execd: bool,
+    /// In `execd` mode, also print a snapshot every `interval_secs`.
+    pub self_timed: bool,
+}
+
+/// Options for the `collectd` subcommand.
+#[derive(Debug)]
+pub struct CollectdOptions {
+    /// Print one snapshot and exit instead of printing every interval.
+    pub once: bool,
+}
+
+/// Options for the `completions` subcommand.
+#[derive(Debug)]
+pub struct CompletionsOptions {
+    /// Shell to generate the script for.
+    pub shell: Shell,
+}
+
+/// Kind of package the `package` subcommand writes files for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PackageFormat {
+    /// Maintainer scripts and assets for `cargo deb`.
+    Deb,
+    /// A VIB for `esxcli software vib install`.
+    EsxiVib,
+}
+
+impl PackageFormat {
+    /// Directory the files are written to unless `--output` is given.
+    fn default_output(self) -> &'static str {
+        match self {
+            PackageFormat::Deb => "target/deb-assets",
+            PackageFormat::EsxiVib => "target/esxi-vib",
+        }
+    }
+}
+
+/// Options for the `package` subcommand.
+#[derive(Debug)]
+pub struct PackageOptions {
+    /// Kind of package to write (`--deb` or `--esxi-vib`).
+    pub format: PackageFormat,
+    /// Directory to write the files to.
+    pub output: String,
+    /// Configuration file to ship in the VIB instead of one generated from the effective
+    /// `server` and `interval_secs`.
+    pub config: Option<String>,
+    /// Acceptance level declared by the VIB (`community`, `partner`, `accepted`, `certified`).
+    pub acceptance_level: String,
+}
+
+/// Options for the `debug msr read` subcommand.
+#[derive(Debug)]
+pub struct MsrReadOptions {
+    /// Logical CPU whose register is read.
+    pub cpu: usize,
+    /// Register address.
+    pub address: u32,
+}
+
+impl CliCommand {
+    /// Builds the command to execute from the parsed command-line arguments.
+    pub fn from_matches(matches: &ArgMatches) -> Self {
+        match matches.subcommand() {
+            Some(("replay", args)) => CliCommand::Replay(ReplayOptions {
+                paths: args
+                    .get_many::<String>("paths")
+                    .map(|paths| paths.cloned().collect())
+                    .unwrap_or_default(),
+                target: args.get_one::<String>("target").cloned(),
+                speed: args.get_one::<f64>("speed").copied().unwrap_or(1.0),
+            }),
+            Some(("facts", args)) => CliCommand::Facts(FactsOptions {
+                pretty: args.get_flag("pretty"),
+            }),
+            Some(("check", args)) => CliCommand::Check(CheckOptions {
+                metric: match args.get_one::<String>("metric").map(String::as_str) {
+                    Some("memory") => CheckMetric::Memory,
+                    Some("disk") => CheckMetric::Disk,
+                    _ => CheckMetric::Temperature,
+                },
+                sensor: args.get_one::<String>("sensor").cloned(),
+                warning: args.get_one::<f32>("warning").copied(),
+                critical: args.get_one::<f32>("critical").copied(),
+            }),
+            Some(("telegraf", args)) => CliCommand::Telegraf(TelegrafOptions {
+                execd: args.get_flag("execd"),
+                self_timed: args.get_flag("self-timed"),
+            }),
+            Some(("collectd", args)) => CliCommand::Collectd(CollectdOptions {
+                once: args.get_flag("once"),
+            }),
+            Some(("status", args)) => CliCommand::Status(StatusOptions {
+                set_log_level: args.get_one::<String>("set-log-level").cloned(),
+                duration_secs: args.get_one::<u64>("duration").copied(),
+            }),
+            Some(("bundle", args)) => match args.subcommand() {
+                Some(("export", args)) => CliCommand::BundleExport(BundleExportOptions {
+                    output: args.get_one::<String>("output").cloned(),
+                    minutes: args.get_one::<u64>("minutes").copied().unwrap_or(60),
+                }),
+                Some(("import", args)) => CliCommand::BundleImport(BundleImportOptions {
+                    path: args.get_one::<String>("path").cloned().unwrap_or_default(),
+                    target: args.get_one::<String>("target").cloned(),
+                }),
+                _ => CliCommand::Run,
+            },
+            Some(("debug", args)) => match args.subcommand() {
+                Some(("msr", args)) => match args.subcommand() {
+                    Some(("read", args)) => CliCommand::DebugMsrRead(MsrReadOptions {
+                        cpu: args.get_one::<usize>("cpu").copied().unwrap_or_default(),
+                        address: args.get_one::<u32>("address").copied().unwrap_or_default(),
+                    }),
+                    _ => CliCommand::Run,
+                },
+                _ => CliCommand::Run,
+            },
+            Some(("completions", args)) => match args.get_one::<Shell>("shell") {
+                Some(shell) => CliCommand::Completions(CompletionsOptions { shell: *shell }),
+                None => CliCommand::Run,
+            },
+            Some(("man", _)) => CliCommand::Man,
+            Some(("package", args)) => {
+                let format = if args.get_flag("esxi-vib") {
+                    PackageFormat::EsxiVib
+                } else {
+                    PackageFormat::Deb
+                };
+                CliCommand::Package(PackageOptions {
+                    format,
+                    output: args
+                        .get_one::<String>("output")
+                        .cloned()
+                        .unwrap_or_else(|| format.default_output().to_string()),
+                    config: args.get_one::<String>("config").cloned(),
+                    acceptance_level: args
+                        .get_one::<String>("acceptance-level")
+                        .cloned()
+                        .unwrap_or_else(|| "community".to_string()),
+                })
+            }
+            _ if matches.get_flag("telegraf") => CliCommand::Telegraf(TelegrafOptions {
+                execd: true,
+                self_timed: false,
+            }),
+            _ => CliCommand::Run,
+        }
+    }
+}
+
+/// Parses a register address given in hex (`0x19c`) or decimal.
+fn parse_address(value: &str) -> Result<u32, String> {
+    let parsed = match value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+    {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => value.parse(),
+    };
+    parsed.map_err(|e| format!("invalid register address {:?}: {}", value, e))
+}
+
+/// Writes the completion script for `options.shell` to stdout.
+pub fn print_completions(options: &CompletionsOptions) {
+    let mut cli = build_cli();
+    let name = cli.get_name().to_string();
+    clap_complete::generate(options.shell, &mut cli, name, &mut io::stdout());
+}
+
+/// Writes the man page to stdout.
+pub fn print_man_page() -> io::Result<()> {
+    clap_mangen::Man::new(build_cli()).render(&mut io::stdout())
+}
+
+/// Builds the command-line interface definition.
+pub fn build_cli() -> Command {
+    Command::new("Gilded-Sentinel-Client")
+        .version(env!("CARGO_PKG_VERSION"))
+        .about(env!("CARGO_PKG_DESCRIPTION"))
+        .arg(
+            Arg::new("server")
+                .long("server")
+                .global(true)
+                .help("Server address to send data (e.g., 127.0.0.1:5000)")
+                .value_parser(clap::value_parser!(String)),
+        )
+        .arg(
+            Arg::new("interval")
+                .long("interval")
+                .global(true)
+                .help("Interval in seconds between data collection")
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .arg(
+            Arg::new("execution-method")
+                .long("execution-method")
+                .global(true)
+                .help("Command execution method: [std_command (default), no_fork, execv, libc, direct_check]")
+                .value_parser(clap::value_parser!(String)),
+        )
+        .arg(
+            Arg::new("plain")
+                .long("plain")
+                .global(true)
+                .help("Plain output without colors or decoration, one fact per line (screen readers)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("telegraf")
+                .long("telegraf")
+                .help("Run as a Telegraf execd input plugin (same as `telegraf --execd`)")
+                .action(ArgAction::SetTrue),
+        )
+        .arg(
+            Arg::new("trace-dir")
+                .long("trace-dir")
+                .global(true)
+                .help("Directory to write traces of outgoing requests and server responses")
+                .value_parser(clap::value_parser!(String)),
+        )
+        .arg(
+            Arg::new("trace-duration")
+                .long("trace-duration")
+                .global(true)
+                .help("Seconds after startup during which tracing stays active (default 600)")
+                .value_parser(clap::value_parser!(u64)),
+        )
+        .subcommand(
+            Command::new("replay")
+                .about("Resend traced or spooled payload files to a server")
+                .arg(
+                    Arg::new("paths")
+                        .help("Payload files, or directories containing them")
+                        .required(true)
+                        .action(ArgAction::Append)
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    Arg::new("target")
+                        .long("target")
+                        .help("Server address to replay to (defaults to the configured server)")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    Arg::new("speed")
+                        .long("speed")
+                        .help("Playback speed relative to the recorded timing; 0 disables delays")
+                        .default_value("1.0")
+                        .value_parser(clap::value_parser!(f64)),
+                ),
+        )
+        .subcommand(
+            Command::new("facts")
+                .about("Print inventory and current readings as a single JSON document")
+                .arg(
+                    Arg::new("pretty")
+                        .long("pretty")
+                        .help("Pretty-print the JSON output")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("check")
+                .about("Read sensors once and exit with Nagios/Icinga plugin status codes")
+                .arg(
+                    Arg::new("metric")
+                        .long("metric")
+                        .short('m')
+                        .help("Metric to check: temperature (°C), memory or disk (% used)")
+                        .value_parser(["temperature", "memory", "disk"])
+                        .default_value("temperature"),
+                )
+                .arg(
+                    Arg::new("sensor")
+                        .long("sensor")
+                        .short('s')
+                        .help("Sensor label or disk name to check, e.g. \"Package id 0\" (default: all)")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    Arg::new("warning")
+                        .long("warning")
+                        .short('w')
+                        .help("Warning threshold (default: [check] section, else the sensor's high threshold)")
+                        .value_parser(clap::value_parser!(f32)),
+                )
+                .arg(
+                    Arg::new("critical")
+                        .long("critical")
+                        .short('c')
+                        .help("Critical threshold (default: [check] section, else the sensor's critical threshold)")
+                        .value_parser(clap::value_parser!(f32)),
+                ),
+        )
+        .subcommand(
+            Command::new("telegraf")
+                .about("Write InfluxDB line protocol to stdout for Telegraf exec/execd inputs")
+                .arg(
+                    Arg::new("execd")
+                        .long("execd")
+                        .help("Keep running; emit on each stdin line or SIGHUP/SIGUSR1/SIGUSR2")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("self-timed")
+                        .long("self-timed")
+                        .help("With --execd, also emit every interval (Telegraf signal = \"none\")")
+                        .requires("execd")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("collectd")
+                .about("Write collectd PUTVAL commands to stdout for the collectd exec plugin")
+                .arg(
+                    Arg::new("once")
+                        .long("once")
+                        .help("Print one snapshot and exit")
+                        .action(ArgAction::SetTrue),
+                ),
+        )
+        .subcommand(
+            Command::new("status")
+                .about("Query or adjust the running agent through its control socket")
+                .arg(
+                    Arg::new("set-log-level")
+                        .long("set-log-level")
+                        .help("Switch the agent's log level (error, warn, info, debug, trace)")
+                        .value_parser(["error", "warn", "info", "debug", "trace"]),
+                )
+                .arg(
+                    Arg::new("duration")
+                        .long("duration")
+                        .help("Seconds until the log level reverts (default: log_level_revert_secs; 0 = never)")
+                        .requires("set-log-level")
+                        .value_parser(clap::value_parser!(u64)),
+                ),
+        )
+        .subcommand(
+            Command::new("bundle")
+                .about("Move data from air-gapped hosts on removable media")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("export")
+                        .about("Write recent history, inventory and agent status to a tar archive")
+                        .arg(
+                            Arg::new("output")
+                                .long("output")
+                                .short('o')
+                                .help("Archive to write (default: gilded-sentinel-<host>-<time>.tar)")
+                                .value_parser(clap::value_parser!(String)),
+                        )
+                        .arg(
+                            Arg::new("minutes")
+                                .long("minutes")
+                                .help("Minutes of history to include, up to history_minutes")
+                                .default_value("60")
+                                .value_parser(clap::value_parser!(u64)),
+                        ),
+                )
+                .subcommand(
+                    Command::new("import")
+                        .about("Send the history of an exported bundle to the server")
+                        .arg(
+                            Arg::new("path")
+                                .help("Bundle archive")
+                                .required(true)
+                                .value_parser(clap::value_parser!(String)),
+                        )
+                        .arg(
+                            Arg::new("target")
+                                .long("target")
+                                .help("Server to send to (default: the configured server)")
+                                .value_parser(clap::value_parser!(String)),
+                        ),
+                ),
+        )
+        .subcommand(
+            Command::new("completions")
+                .about("Print a shell completion script, e.g. for /etc/bash_completion.d")
+                .arg(
+                    Arg::new("shell")
+                        .help("Shell to generate the script for")
+                        .required(true)
+                        .value_parser(clap::value_parser!(Shell)),
+                ),
+        )
+        .subcommand(Command::new("man").about("Print the man page in roff format"))
+        .subcommand(
+            Command::new("package")
+                .about("Write the files a distribution package ships besides the executable")
+                .arg(
+                    Arg::new("deb")
+                        .long("deb")
+                        .help("Maintainer scripts, systemd unit and assets for `cargo deb`")
+                        .action(ArgAction::SetTrue),
+                )
+                .arg(
+                    Arg::new("esxi-vib")
+                        .long("esxi-vib")
+                        .help("A VIB with this executable, its configuration and an init script")
+                        .action(ArgAction::SetTrue),
+                )
+                .group(
+                    ArgGroup::new("format")
+                        .args(["deb", "esxi-vib"])
+                        .required(true),
+                )
+                .arg(
+                    Arg::new("output")
+                        .long("output")
+                        .short('o')
+                        .help("Directory to write the files to (default: target/deb-assets or target/esxi-vib)")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    Arg::new("config")
+                        .long("config")
+                        .help("Configuration file to ship in the VIB (default: generated from --server and --interval)")
+                        .requires("esxi-vib")
+                        .value_parser(clap::value_parser!(String)),
+                )
+                .arg(
+                    Arg::new("acceptance-level")
+                        .long("acceptance-level")
+                        .help("Acceptance level declared by the VIB; unsigned VIBs need community")
+                        .requires("esxi-vib")
+                        .value_parser(["community", "partner", "accepted", "certified"])
+                        .default_value("community"),
+                ),
+        )
+        .subcommand(
+            Command::new("debug")
+                .about("Low-level diagnostics for bringing up new platforms")
+                .subcommand_required(true)
+                .subcommand(
+                    Command::new("msr")
+                        .about("Model-specific registers (Linux msr driver, requires root)")
+                        .subcommand_required(true)
+                        .subcommand(
+                            Command::new("read")
+                                .about("Read one register and decode its known fields")
+                                .arg(
+                                    Arg::new("cpu")
+                                        .help("Logical CPU number")
+                                        .required(true)
+                                        .value_parser(clap::value_parser!(usize)),
+                                )
+                                .arg(
+                                    Arg::new("address")
+                                        .help("Register address, e.g. 0x19c")
+                                        .required(true)
+                                        .value_parser(parse_address),
+                                ),
+                        ),
+                ),
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_completions_and_man_page() {
+        let matches = build_cli().get_matches_from(["gs", "completions", "bash"]);
+        assert!(matches!(
+            CliCommand::from_matches(&matches),
+            CliCommand::Completions(CompletionsOptions { shell: Shell::Bash })
+        ));
+        let matches = build_cli().get_matches_from(["gs", "man"]);
+        assert!(matches!(
+            CliCommand::from_matches(&matches),
+            CliCommand::Man
+        ));
+
+        let mut script = Vec::new();
+        clap_complete::generate(Shell::Bash, &mut build_cli(), "gs", &mut script);
+        let script = String::from_utf8(script).unwrap();
+        assert!(script.contains("--set-log-level"));
+
+        let mut page = Vec::new();
+        clap_mangen::Man::new(build_cli())
+            .render(&mut page)
+            .unwrap();
+        let page = String::from_utf8(page).unwrap();
+        assert!(page.contains(".TH Gilded-Sentinel-Client"));
+        assert!(page.contains("bundle"));
+    }
+}
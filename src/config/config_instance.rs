@@ -1,4 +1,7 @@
+use crate::config::config_loader::executable_dir;
+use crate::config::remote_config::RemoteConfig;
 use crate::config::AppConfig;
+use std::path::PathBuf;
 use std::sync::OnceLock;
 
 pub struct Config;
@@ -34,8 +37,29 @@ impl Config {
         &Config::get().server
     }
 
-    /// Convenience method for getting the interval.
+    /// Convenience method for getting the interval, as overridden by the server if it did.
     pub fn interval_secs() -> u64 {
-        Config::get().interval_secs
+        RemoteConfig::interval_secs()
+    }
+
+    /// Substitutes `{tenant_id}` and `{site_id}` in a topic or path template; unset ids
+    /// expand to an empty string.
+    pub fn expand_ids(template: &str) -> String {
+        let config = Config::get();
+        template
+            .replace(
+                "{tenant_id}",
+                config.tenant_id.as_deref().unwrap_or_default(),
+            )
+            .replace("{site_id}", config.site_id.as_deref().unwrap_or_default())
+    }
+
+    /// Resolves a file name inside the state directory (`state_dir`, or the executable's directory).
+    pub fn state_file(name: &str) -> PathBuf {
+        let dir = Config::get()
+            .state_dir
+            .clone()
+            .unwrap_or_else(executable_dir);
+        PathBuf::from(dir).join(name)
     }
 }
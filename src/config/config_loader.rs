@@ -1,30 +1,332 @@
-use clap::{Arg, Command};
+use clap::ArgMatches;
+use env_logger::{Env, WriteStyle};
 use log::{debug, error, info, warn};
+use std::collections::BTreeMap;
 use std::env;
 use std::fs;
+use std::net::IpAddr;
 use std::path::Path;
 
+use crate::alert::digest::DigestConfig;
+use crate::alert::gotify_notifier::GotifyConfig;
+use crate::alert::ntfy_notifier::NtfyConfig;
+use crate::alert::smtp_notifier::SmtpConfig;
+use crate::config::cli::{build_cli, CliCommand};
+use crate::config::embedded::EmbeddedConfig;
+use crate::config::role::Role;
+use crate::data::casing::FieldCasing;
+use crate::data::payload_format::PayloadFormat;
+use crate::data::transform::TransformConfig;
+use crate::data::units::Celsius;
+use crate::hardware::ipmi_util::IpmiHostConfig;
+use crate::hardware::liquidctl_util::LiquidctlConfig;
+use crate::hardware::msr_thermal::{MsrQuirk, ThermalSource};
+use crate::hardware::smart_util::SmartConfig;
+use crate::network::batch_util::BatchConfig;
+use crate::network::command_channel::CommandChannelConfig;
+use crate::network::ddns::DdnsConfig;
+use crate::network::grpc_sink::GrpcConfig;
+use crate::network::mqtt_sink::MqttConfig;
+use crate::network::nats_sink::NatsConfig;
+use crate::network::network_util::UdpConfig;
+use crate::network::otlp_sink::OtlpConfig;
+use crate::network::public_ip::PublicIpConfig;
+use crate::network::send_window::SendWindow;
+use crate::network::snmp_agent::SnmpConfig;
+use crate::network::snmp_poller::SnmpTargetConfig;
+use crate::network::socket_util::IpPreference;
+use crate::network::spool_util::SpoolConfig;
+use crate::network::statsd_sink::StatsdConfig;
+use crate::network::unix_socket_sink::UnixSocketConfig;
+use crate::sensor::check_util::CheckConfig;
+use crate::sensor::thermal_reconcile::ThermalReconcileConfig;
+use crate::system::log_level::{LogLevelUtil, RuntimeLevelLogger};
+use crate::system::output_style::OutputStyle;
+use crate::system::priority_util::IoniceClass;
+use crate::system::scheduler::CatchUpPolicy;
+
 /// Application configuration structure.
 ///
 /// This structure holds configuration values for the Gilded-Sentinel application,
 /// such as the server address, data collection interval, and execution method.
+///
+/// Fields missing from the configuration file fall back to their defaults.
 #[derive(Debug, serde::Deserialize)]
+#[serde(default)]
 pub struct AppConfig {
+    /// Host class whose preset supplies defaults for keys not set explicitly.
+    pub role: Option<Role>,
+    /// Tenant this host reports for, when one server ingests several tenants.
+    pub tenant_id: Option<String>,
+    /// Site this host reports for, when one server ingests several sites.
+    pub site_id: Option<String>,
+    /// Language of human-readable CLI output ("en", "de" or "fr"); taken from `LANG` when absent.
+    pub language: Option<String>,
+    /// Whether human-readable CLI output is always plain (no colors, one fact per line).
+    pub plain_output: bool,
     /// Server address to which the application will send data (e.g., `127.0.0.1:5000`).
     pub server: String,
+    /// Request path of the ingest endpoint; overrides any path given in `server`.
+    pub endpoint_path: Option<String>,
+    /// HTTP method used for uploads (e.g. `POST`, `PUT`).
+    pub endpoint_method: String,
+    /// Extra HTTP headers sent with every upload.
+    pub endpoint_headers: BTreeMap<String, String>,
+    /// Bearer token sent in the `Authorization` header of every upload.
+    pub auth_token: Option<String>,
+    /// File holding the bearer token; read on every send so rotated tokens are picked up.
+    pub auth_token_file: Option<String>,
     /// Interval in seconds between data collection.
     pub interval_secs: u64,
+    /// Maximum random delay in seconds before the first collection, to spread fleet load.
+    pub splay_secs: u64,
+    /// Maximum random delay in seconds added to each collection (capped at half the interval).
+    pub jitter_secs: u64,
+    /// What happens when collection deadlines were missed (overrun, load or suspend).
+    pub catch_up: CatchUpPolicy,
+    /// Interval in seconds between heartbeats, sent independently of collection; 0 disables them.
+    pub heartbeat_interval_secs: u64,
     /// Command execution method (e.g., "std_command", "execv").
     pub execution_method: String,
+    /// Nice level applied to the agent at startup (e.g. 10); unchanged when absent.
+    pub nice: Option<i32>,
+    /// I/O scheduling class applied at startup ("realtime", "best_effort" or "idle").
+    pub ionice_class: Option<IoniceClass>,
+    /// I/O priority level within the class, 0 (highest) to 7 (lowest).
+    pub ionice_level: u8,
+    /// CPU quota in percent of one CPU, enforced through a cgroup v2 child group.
+    pub cpu_quota_percent: Option<u32>,
+    /// Name of the cgroup created under the agent's own cgroup for the CPU quota.
+    pub cgroup_name: String,
+    /// Mount point of the host's root filesystem inside a container (e.g. "/host"), used
+    /// to report host totals instead of the container's view.
+    pub host_root: Option<String>,
+    /// Source of CPU temperatures ("sensors", "msr", "auto" or "hwmon").
+    pub thermal_source: ThermalSource,
+    /// Overrides of the thermal MSR addresses per CPU family or model (`[[msr_quirks]]`).
+    pub msr_quirks: Vec<MsrQuirk>,
+    /// Cross-checking of several thermal sources; replaces `thermal_source` when present.
+    pub thermal_reconcile: Option<ThermalReconcileConfig>,
+    /// Whether disk usage and I/O are collected.
+    pub collect_disks: bool,
+    /// Whether network interface counters are collected.
+    pub collect_network: bool,
+    /// Whether hwmon devices (fan controllers, pumps, flow sensors) are enumerated each cycle.
+    pub collect_hwmon: bool,
+    /// Whether AMD GPU temperatures, fan speed and power are collected.
+    pub collect_gpus: bool,
+    /// Whether ACPI power meters and the power of each RAPL domain are collected.
+    pub collect_power: bool,
+    /// liquidctl settings; when present, liquid cooling devices are reported.
+    pub liquidctl: Option<LiquidctlConfig>,
+    /// smartctl settings; when present, disk health is reported (needs root).
+    pub smart: Option<SmartConfig>,
+    /// Whether reports are submitted as one entry per host identity (own host, SNMP
+    /// devices, IPMI hosts), each with its own tags and sequence numbers.
+    pub group_hosts: bool,
+    /// Tags of the agent's own entry in grouped reports.
+    pub host_tags: BTreeMap<String, String>,
+    /// BMCs of headless hosts read with ipmitool and reported as remote hosts.
+    pub ipmi_hosts: Vec<IpmiHostConfig>,
+    /// Whether the ARP/NDP neighbor table is collected and reported (opt-in).
+    pub neighbor_discovery: bool,
+    /// Minimum interval in seconds between two neighbor table snapshots.
+    pub neighbor_interval_secs: u64,
+    /// Seconds after which the host inventory is resent even if unchanged; 0 sends it with
+    /// every report.
+    pub inventory_heartbeat_secs: u64,
+    /// Directory to which outgoing requests and server responses are traced (debugging aid).
+    pub trace_dir: Option<String>,
+    /// Number of seconds after startup during which tracing stays active.
+    pub trace_duration_secs: u64,
+    /// Field naming policy for serialized payloads ("snake_case" or "camelCase").
+    pub field_casing: FieldCasing,
+    /// Serialization format of payloads ("json", "msgpack", "cbor" or "protobuf").
+    pub payload_format: PayloadFormat,
+    /// Rhai script transforming payloads before they are encoded; disabled when absent.
+    pub transform: Option<TransformConfig>,
+    /// Directory for persisted agent state (defaults to the executable's directory).
+    pub state_dir: Option<String>,
+    /// Whether a gap declaration is sent when reporting resumes after missed intervals.
+    pub report_gaps: bool,
+    /// Whether the thermal "black box" recorder arms itself near critical temperature.
+    pub black_box_enabled: bool,
+    /// Distance in °C below a package's critical threshold at which the black box arms.
+    pub black_box_margin_celsius: f32,
+    /// Warning temperature used for alerts when a sensor reports no high threshold.
+    pub alert_warning_celsius: Option<Celsius>,
+    /// Critical temperature used for alerts when a sensor reports no critical threshold.
+    pub alert_critical_celsius: Option<Celsius>,
+    /// SMTP settings for emailing alerts; alert emails are disabled when absent.
+    pub smtp: Option<SmtpConfig>,
+    /// Gotify settings for pushing alerts; disabled when absent.
+    pub gotify: Option<GotifyConfig>,
+    /// ntfy settings for publishing alerts; disabled when absent.
+    pub ntfy: Option<NtfyConfig>,
+    /// Daily or weekly summary sent through the notifiers; disabled when absent.
+    pub digest: Option<DigestConfig>,
+    /// Path of the local control socket used by the `status` subcommand; disabled when absent.
+    pub control_socket: Option<String>,
+    /// Seconds after which a log level changed at runtime reverts to the startup level.
+    pub log_level_revert_secs: u64,
+    /// Address of the local read-only REST API (e.g. `127.0.0.1:9180`); disabled when absent.
+    pub api_listen: Option<String>,
+    /// Minutes of payload history kept in memory for the local API.
+    pub history_minutes: u64,
+    /// Number of recent send attempts kept for `/status` and the self-metrics.
+    pub send_log_size: usize,
+    /// MQTT broker settings; when present, payloads are published there instead of `server`.
+    pub mqtt: Option<MqttConfig>,
+    /// NATS settings; when present, payloads are published there instead of `server`.
+    pub nats: Option<NatsConfig>,
+    /// gRPC ingestion settings; when present, reports are sent there instead of `server`.
+    pub grpc: Option<GrpcConfig>,
+    /// UDP settings; when present, payloads are sent as fire-and-forget datagrams.
+    pub udp: Option<UdpConfig>,
+    /// Unix socket settings; when present, payloads are written to a local socket.
+    pub unix_socket: Option<UnixSocketConfig>,
+    /// StatsD settings; when present, gauges are emitted in addition to the reports.
+    pub statsd: Option<StatsdConfig>,
+    /// OTLP settings; when present, metrics are exported to an OpenTelemetry collector.
+    pub otlp: Option<OtlpConfig>,
+    /// Public IP lookup settings; when present, the host's public IP is reported.
+    pub public_ip: Option<PublicIpConfig>,
+    /// Dynamic DNS settings; when present, a record follows the public IP (needs `public_ip`).
+    pub ddns: Option<DdnsConfig>,
+    /// Remote command settings; when present, the server is polled for commands.
+    pub commands: Option<CommandChannelConfig>,
+    /// SNMP settings; when present, temperatures are served to SNMP pollers.
+    pub snmp: Option<SnmpConfig>,
+    /// LAN devices polled over SNMP and reported as proxied readings.
+    pub snmp_targets: Vec<SnmpTargetConfig>,
+    /// Default thresholds of the `check` subcommand.
+    pub check: Option<CheckConfig>,
+    /// Batching of sensor reports sent to `server` over HTTP; each report is sent alone when absent.
+    pub http_batch: Option<BatchConfig>,
+    /// Disk spool settings; when present, undeliverable payloads are kept and replayed.
+    pub spool: Option<SpoolConfig>,
+    /// Whether the HTTP connection to the server is kept open between reports.
+    pub keep_alive: bool,
+    /// Seconds after which an idle kept-alive connection is no longer reused.
+    pub keep_alive_idle_secs: u64,
+    /// Seconds to wait for the server's response before giving up on a request.
+    pub server_read_timeout_secs: u64,
+    /// Seconds a stalled write of a request to the server may block before it fails.
+    pub server_write_timeout_secs: u64,
+    /// Whether the server may change settings at runtime through its responses.
+    pub remote_config: bool,
+    /// Largest encoded report in bytes; larger reports are sent in chunks.
+    pub max_payload_bytes: Option<usize>,
+    /// Encoded payload size in bytes beyond which a warning is logged; no warning when absent.
+    pub payload_warn_bytes: Option<usize>,
+    /// Whether a payload only counts as delivered once the server acknowledges its sequence number.
+    pub require_ack: bool,
+    /// Local times during which payloads may be sent; always when empty.
+    pub send_windows: Vec<SendWindow>,
+    /// Whether payloads are sent to the server over TLS.
+    pub server_tls: bool,
+    /// PEM bundle of additional CA certificates trusted for the server connection.
+    pub server_ca_file: Option<String>,
+    /// Network interface connections to the server leave through, e.g. the management NIC.
+    pub bind_interface: Option<String>,
+    /// Local source address of connections to the server.
+    pub bind_address: Option<IpAddr>,
+    /// Address family tried first when the server resolves to both IPv4 and IPv6.
+    pub ip_preference: IpPreference,
 }
 
 impl Default for AppConfig {
     /// Provides default values for the application configuration.
     fn default() -> Self {
         Self {
+            role: None,
+            tenant_id: None,
+            site_id: None,
+            language: None,
+            plain_output: false,
             server: "127.0.0.1:5000".to_string(),
+            endpoint_path: None,
+            endpoint_method: "POST".to_string(),
+            endpoint_headers: BTreeMap::new(),
+            auth_token: None,
+            auth_token_file: None,
             interval_secs: 10,
+            splay_secs: 0,
+            jitter_secs: 0,
+            catch_up: CatchUpPolicy::Skip,
+            heartbeat_interval_secs: 0,
             execution_method: "std_command".to_string(),
+            nice: None,
+            ionice_class: None,
+            ionice_level: 7,
+            cpu_quota_percent: None,
+            cgroup_name: "gilded-sentinel".to_string(),
+            host_root: None,
+            thermal_source: ThermalSource::Sensors,
+            msr_quirks: Vec::new(),
+            thermal_reconcile: None,
+            collect_disks: true,
+            collect_network: true,
+            collect_hwmon: true,
+            collect_gpus: true,
+            collect_power: true,
+            liquidctl: None,
+            smart: None,
+            group_hosts: false,
+            host_tags: BTreeMap::new(),
+            ipmi_hosts: Vec::new(),
+            neighbor_discovery: false,
+            neighbor_interval_secs: 300,
+            inventory_heartbeat_secs: 86_400,
+            trace_dir: None,
+            trace_duration_secs: 600,
+            field_casing: FieldCasing::SnakeCase,
+            payload_format: PayloadFormat::Json,
+            transform: None,
+            state_dir: None,
+            report_gaps: false,
+            black_box_enabled: false,
+            black_box_margin_celsius: 5.0,
+            alert_warning_celsius: None,
+            alert_critical_celsius: None,
+            smtp: None,
+            gotify: None,
+            ntfy: None,
+            digest: None,
+            control_socket: None,
+            log_level_revert_secs: 900,
+            api_listen: None,
+            history_minutes: 60,
+            send_log_size: 50,
+            mqtt: None,
+            nats: None,
+            grpc: None,
+            udp: None,
+            unix_socket: None,
+            statsd: None,
+            otlp: None,
+            public_ip: None,
+            ddns: None,
+            commands: None,
+            snmp: None,
+            snmp_targets: Vec::new(),
+            http_batch: None,
+            spool: None,
+            keep_alive: true,
+            keep_alive_idle_secs: 30,
+            server_read_timeout_secs: 10,
+            server_write_timeout_secs: 10,
+            remote_config: false,
+            max_payload_bytes: None,
+            payload_warn_bytes: None,
+            require_ack: false,
+            send_windows: Vec::new(),
+            check: None,
+            server_tls: false,
+            server_ca_file: None,
+            bind_interface: None,
+            bind_address: None,
+            ip_preference: IpPreference::Any,
         }
     }
 }
@@ -37,19 +339,24 @@ impl Default for AppConfig {
 /// - Command-line arguments
 pub struct ConfigLoader {
     exe_dir: String,
+    matches: ArgMatches,
 }
 
 impl ConfigLoader {
     /// Creates a new `ConfigLoader` instance with the executable's directory.
     ///
     /// This ensures that configuration files can be loaded relative to the executable's location.
+    /// Command-line arguments are parsed once here and reused for overrides and subcommands.
     pub fn new() -> Self {
-        let exe_dir = env::current_exe()
-            .ok()
-            .and_then(|path| path.parent().map(|p| p.to_string_lossy().to_string()))
-            .unwrap_or_else(|| ".".to_string());
+        Self {
+            exe_dir: executable_dir(),
+            matches: build_cli().get_matches(),
+        }
+    }
 
-        Self { exe_dir }
+    /// Returns the command requested on the command line.
+    pub fn command(&self) -> CliCommand {
+        CliCommand::from_matches(&self.matches)
     }
 
     /// Loads the complete application configuration by combining:
@@ -74,8 +381,12 @@ impl ConfigLoader {
         let final_config = self.override_with_cli(env_config);
 
         info!(
-            "Final configuration: server = {}, interval_secs = {}, execution_method = {}",
-            final_config.server, final_config.interval_secs, final_config.execution_method
+            "Final configuration: role = {:?}, server = {}, interval_secs = {}, execution_method = {}, field_casing = {:?}",
+            final_config.role,
+            final_config.server,
+            final_config.interval_secs,
+            final_config.execution_method,
+            final_config.field_casing
         );
 
         final_config
@@ -83,29 +394,43 @@ impl ConfigLoader {
 
     /// Loads configuration from the `config.toml` file in the executable's directory.
     ///
-    /// If the file is not found or cannot be parsed, this function logs the error
-    /// and returns `None`.
+    /// The file's keys are layered over the configuration embedded at build time, if any;
+    /// without a file, the embedded configuration is used alone. If the result sets a
+    /// `role`, the role's preset fills in keys left unset.
+    /// If the file cannot be read or parsed, this function logs the error and returns `None`.
     fn load_from_file(&self) -> Option<AppConfig> {
         let config_path = Path::new(&self.exe_dir).join("config.toml");
 
-        if config_path.exists() {
+        let file = if config_path.exists() {
             info!("Found configuration file at: {}", config_path.display());
             match fs::read_to_string(&config_path) {
-                Ok(contents) => match toml::from_str(&contents) {
-                    Ok(config) => Some(config),
-                    Err(e) => {
-                        error!("Failed to parse configuration file: {}", e);
-                        None
-                    }
-                },
+                Ok(contents) => contents,
                 Err(e) => {
                     error!("Failed to read configuration file: {}", e);
-                    None
+                    return None;
                 }
             }
+        } else if EmbeddedConfig::is_present() {
+            info!(
+                "No configuration file found in {}; using the embedded defaults.",
+                self.exe_dir
+            );
+            String::new()
         } else {
             warn!("No configuration file found in: {}", self.exe_dir);
-            None
+            return None;
+        };
+
+        match toml::from_str::<toml::Table>(&file)
+            .map(EmbeddedConfig::apply)
+            .and_then(Role::apply_preset)
+            .and_then(|table| table.try_into())
+        {
+            Ok(config) => Some(config),
+            Err(e) => {
+                error!("Failed to parse configuration file: {}", e);
+                None
+            }
         }
     }
 
@@ -115,6 +440,9 @@ impl ConfigLoader {
     /// - `SENSOR_SERVER`: Overrides the `server` value.
     /// - `SENSOR_INTERVAL`: Overrides the `interval_secs` value.
     /// - `SENSOR_EXECUTION_METHOD`: Overrides the `execution_method` value.
+    /// - `SENSOR_AUTH_TOKEN`: Overrides the `auth_token` value.
+    /// - `SENSOR_TENANT_ID`: Overrides the `tenant_id` value.
+    /// - `SENSOR_SITE_ID`: Overrides the `site_id` value.
     ///
     /// Logs any overridden values for traceability.
     fn override_with_env(&self, config: AppConfig) -> AppConfig {
@@ -125,6 +453,15 @@ impl ConfigLoader {
             .unwrap_or(config.interval_secs);
         let execution_method =
             env::var("SENSOR_EXECUTION_METHOD").unwrap_or_else(|_| config.execution_method.clone());
+        let auth_token = env::var("SENSOR_AUTH_TOKEN")
+            .ok()
+            .or_else(|| config.auth_token.clone());
+        let tenant_id = env::var("SENSOR_TENANT_ID")
+            .ok()
+            .or_else(|| config.tenant_id.clone());
+        let site_id = env::var("SENSOR_SITE_ID")
+            .ok()
+            .or_else(|| config.site_id.clone());
 
         if server != config.server {
             info!("Server address overridden by environment variable.");
@@ -135,11 +472,24 @@ impl ConfigLoader {
         if execution_method != config.execution_method {
             info!("Execution method overridden by environment variable.");
         }
+        if auth_token != config.auth_token {
+            info!("Auth token overridden by environment variable.");
+        }
+        if tenant_id != config.tenant_id {
+            info!("Tenant id overridden by environment variable.");
+        }
+        if site_id != config.site_id {
+            info!("Site id overridden by environment variable.");
+        }
 
         AppConfig {
             server,
             interval_secs,
             execution_method,
+            auth_token,
+            tenant_id,
+            site_id,
+            ..config
         }
     }
 
@@ -149,29 +499,13 @@ impl ConfigLoader {
     /// - `--server`: Overrides the `server` value.
     /// - `--interval`: Overrides the `interval_secs` value.
     /// - `--execution-method`: Overrides the `execution_method` value.
+    /// - `--trace-dir`: Enables request/response tracing into the given directory.
+    /// - `--trace-duration`: Overrides the `trace_duration_secs` value.
+    /// - `--plain`: Sets `plain_output`.
     ///
     /// Logs any overridden values for traceability.
     fn override_with_cli(&self, config: AppConfig) -> AppConfig {
-        let matches = Command::new("Gilded-Sentinel-Client")
-            .arg(
-                Arg::new("server")
-                    .long("server")
-                    .help("Server address to send data (e.g., 127.0.0.1:5000)")
-                    .value_parser(clap::value_parser!(String)),
-            )
-            .arg(
-                Arg::new("interval")
-                    .long("interval")
-                    .help("Interval in seconds between data collection")
-                    .value_parser(clap::value_parser!(u64)),
-            )
-            .arg(
-                Arg::new("execution-method")
-                    .long("execution-method")
-                    .help("Command execution method: [std_command (default), no_fork, execv, libc, direct_check]")
-                    .value_parser(clap::value_parser!(String)),
-            )
-            .get_matches();
+        let matches = &self.matches;
 
         debug!("Command-line arguments parsed successfully.");
 
@@ -190,6 +524,18 @@ impl ConfigLoader {
             .unwrap_or(&config.execution_method)
             .to_string();
 
+        let trace_dir = matches
+            .get_one::<String>("trace-dir")
+            .cloned()
+            .or_else(|| config.trace_dir.clone());
+
+        let trace_duration_secs = matches
+            .get_one::<u64>("trace-duration")
+            .copied()
+            .unwrap_or(config.trace_duration_secs);
+
+        let plain_output = matches.get_flag("plain") || config.plain_output;
+
         if server != config.server {
             info!("Server address overridden by command-line argument.");
         }
@@ -199,29 +545,64 @@ impl ConfigLoader {
         if execution_method != config.execution_method {
             info!("Execution method overridden by command-line argument.");
         }
+        if trace_dir != config.trace_dir {
+            info!("Trace directory overridden by command-line argument.");
+        }
 
         AppConfig {
             server,
             interval_secs,
             execution_method,
+            trace_dir,
+            trace_duration_secs,
+            plain_output,
+            ..config
         }
     }
 }
 
+/// Returns the directory containing the running executable, or `.` if it cannot be determined.
+pub fn executable_dir() -> String {
+    env::current_exe()
+        .ok()
+        .and_then(|path| path.parent().map(|p| p.to_string_lossy().to_string()))
+        .unwrap_or_else(|| ".".to_string())
+}
+
 /// Initializes the logger for the application.
 ///
 /// This function sets up the `env_logger` backend to handle logging, allowing
 /// log levels to be dynamically adjusted via environment variables (e.g., `RUST_LOG`).
 pub fn initialize_logger() {
-    env_logger::Builder::from_default_env()
-        .filter_level(log::LevelFilter::Info)
-        .init();
+    let write_style = if OutputStyle::plain_requested_early() {
+        WriteStyle::Never
+    } else {
+        WriteStyle::Auto
+    };
+    let env = || Env::default().default_filter_or("info");
+    let builder = || {
+        let mut builder = env_logger::Builder::from_env(env());
+        builder.write_style(write_style);
+        builder
+    };
+    // The startup level is the most verbose one `RUST_LOG` enables, and it is in effect
+    // before the logger is installed. The second logger only handles the agent's own
+    // records while `LogLevelUtil` has raised the level above it.
+    let configured = builder().build();
+    let raised = builder()
+        .filter_module(env!("CARGO_CRATE_NAME"), log::LevelFilter::Trace)
+        .build();
+    LogLevelUtil::set_startup_level(configured.filter());
+    log::set_boxed_logger(Box::new(RuntimeLevelLogger::new(configured, raised)))
+        .expect("the logger is initialized only once");
 }
 
 /// Loads the application configuration by using the `ConfigLoader`.
 ///
 /// This function acts as a simple entry point for loading the configuration,
 /// combining values from files, environment variables, and command-line arguments.
-pub fn load_application_config() -> AppConfig {
-    ConfigLoader::new().load_config()
+/// The command requested on the command line is returned alongside the configuration.
+pub fn load_application_config() -> (AppConfig, CliCommand) {
+    let loader = ConfigLoader::new();
+    (loader.load_config(), loader.command())
 }
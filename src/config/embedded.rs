@@ -0,0 +1,51 @@
+//! Build-Time Default Configuration
+//!
+//! Preconfigured images (e.g. for a relative's NAS) can embed a configuration at build time
+//! by pointing `SENTINEL_DEFAULT_CONFIG` at a TOML file (see `build.rs`). The embedded keys
+//! sit between the role preset and the configuration file: a local `config.toml`, the
+//! environment and the command line still override them, and sections are merged key by key.
+
+use log::error;
+use toml::{Table, Value};
+
+/// The configuration embedded at build time; empty when none was given.
+const EMBEDDED_CONFIG: &str = include_str!(concat!(env!("OUT_DIR"), "/default_config.toml"));
+
+/// Access to the configuration embedded at build time.
+pub struct EmbeddedConfig;
+
+impl EmbeddedConfig {
+    /// Whether a configuration was embedded at build time.
+    pub fn is_present() -> bool {
+        !EMBEDDED_CONFIG.trim().is_empty()
+    }
+
+    /// Returns the embedded keys; empty if none were embedded or they cannot be parsed.
+    pub fn table() -> Table {
+        toml::from_str(EMBEDDED_CONFIG).unwrap_or_else(|e| {
+            error!("Ignoring unparsable embedded default configuration: {}", e);
+            Table::new()
+        })
+    }
+
+    /// Layers the keys of `file` over the embedded configuration.
+    pub fn apply(file: Table) -> Table {
+        let mut merged = Self::table();
+        Self::merge(&mut merged, file);
+        merged
+    }
+
+    /// Recursively overwrites `base` with `overlay`, merging tables present in both.
+    fn merge(base: &mut Table, overlay: Table) {
+        for (key, value) in overlay {
+            match (base.get_mut(&key), value) {
+                (Some(Value::Table(base_table)), Value::Table(overlay_table)) => {
+                    Self::merge(base_table, overlay_table)
+                }
+                (_, value) => {
+                    base.insert(key, value);
+                }
+            }
+        }
+    }
+}
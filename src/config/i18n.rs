@@ -0,0 +1,131 @@
+//! Localized CLI Output
+//!
+//! This module translates the human-readable output of the subcommands (English, German
+//! and French). The language is taken from the `language` configuration key, else from
+//! `LC_ALL`, `LC_MESSAGES` or `LANG`, falling back to English. Machine-readable output
+//! (JSON, plugin status keywords, perfdata, control socket replies) is never translated.
+
+use std::env;
+use std::sync::OnceLock;
+
+use crate::config::config_instance::Config;
+
+/// A supported output language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Locale {
+    En,
+    De,
+    Fr,
+}
+
+impl Locale {
+    /// Parses a language tag or POSIX locale such as `de`, `fr-CA` or `de_DE.UTF-8`.
+    pub fn parse(value: &str) -> Option<Self> {
+        let language = value
+            .split(['_', '-', '.', '@'])
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match language.as_str() {
+            "en" => Some(Locale::En),
+            "de" => Some(Locale::De),
+            "fr" => Some(Locale::Fr),
+            _ => None,
+        }
+    }
+
+    /// The configured language, else the one of the environment, else English.
+    pub fn current() -> Self {
+        static LOCALE: OnceLock<Locale> = OnceLock::new();
+        *LOCALE.get_or_init(|| {
+            let configured = Config::get().language.clone();
+            let environment = ["LC_ALL", "LC_MESSAGES", "LANG"]
+                .iter()
+                .filter_map(|name| env::var(name).ok())
+                .find(|value| !value.is_empty());
+            configured
+                .or(environment)
+                .and_then(|value| Self::parse(&value))
+                .unwrap_or(Locale::En)
+        })
+    }
+}
+
+/// A translatable message with its arguments.
+pub enum Text<'a> {
+    /// Current log level of the agent.
+    LogLevel(&'a str),
+    /// Seconds until a runtime log level change reverts.
+    RevertsIn(u64),
+    /// The `check` subcommand found no readings at all.
+    NoReadings,
+    /// The sensor requested with `check --sensor` does not exist.
+    SensorNotFound(&'a str),
+    /// A subcommand failed.
+    Error(&'a str),
+}
+
+impl Text<'_> {
+    /// Renders the message in the current locale.
+    pub fn render(&self) -> String {
+        self.render_in(Locale::current())
+    }
+
+    /// Renders the message in `locale`.
+    pub fn render_in(&self, locale: Locale) -> String {
+        match (self, locale) {
+            (Text::LogLevel(level), Locale::En) => format!("Log level: {}", level),
+            (Text::LogLevel(level), Locale::De) => format!("Log-Level: {}", level),
+            (Text::LogLevel(level), Locale::Fr) => format!("Niveau de journalisation : {}", level),
+            (Text::RevertsIn(secs), Locale::En) => {
+                format!("reverts to the startup level in {} s", secs)
+            }
+            (Text::RevertsIn(secs), Locale::De) => {
+                format!("wird in {} s auf den Startwert zurückgesetzt", secs)
+            }
+            (Text::RevertsIn(secs), Locale::Fr) => {
+                format!("retour au niveau initial dans {} s", secs)
+            }
+            (Text::NoReadings, Locale::En) => "no readings found".to_string(),
+            (Text::NoReadings, Locale::De) => "keine Messwerte gefunden".to_string(),
+            (Text::NoReadings, Locale::Fr) => "aucune mesure trouvée".to_string(),
+            (Text::SensorNotFound(sensor), Locale::En) => format!("sensor '{}' not found", sensor),
+            (Text::SensorNotFound(sensor), Locale::De) => {
+                format!("Sensor '{}' nicht gefunden", sensor)
+            }
+            (Text::SensorNotFound(sensor), Locale::Fr) => {
+                format!("capteur '{}' introuvable", sensor)
+            }
+            (Text::Error(message), Locale::En) => format!("Error: {}", message),
+            (Text::Error(message), Locale::De) => format!("Fehler: {}", message),
+            (Text::Error(message), Locale::Fr) => format!("Erreur : {}", message),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn posix_locales_select_the_language() {
+        assert_eq!(Locale::parse("de_DE.UTF-8"), Some(Locale::De));
+        assert_eq!(Locale::parse("fr-CA"), Some(Locale::Fr));
+        assert_eq!(Locale::parse("EN"), Some(Locale::En));
+        assert_eq!(Locale::parse("C.UTF-8"), None);
+        assert_eq!(Locale::parse(""), None);
+    }
+
+    #[test]
+    fn messages_render_per_locale() {
+        assert_eq!(
+            Text::RevertsIn(5).render_in(Locale::En),
+            "reverts to the startup level in 5 s"
+        );
+        assert_eq!(
+            Text::NoReadings.render_in(Locale::De),
+            "keine Messwerte gefunden"
+        );
+        assert_eq!(Text::Error("x").render_in(Locale::Fr), "Erreur : x");
+    }
+}
@@ -1,3 +1,8 @@
+pub mod cli;
 pub mod config_instance;
 pub mod config_loader;
+pub mod embedded;
+pub mod i18n;
+pub mod remote_config;
+pub mod role;
 pub use config_loader::AppConfig;
@@ -0,0 +1,147 @@
+//! Remote Configuration
+//!
+//! With `remote_config = true`, the server can change a few settings at runtime by
+//! answering a report with a JSON body carrying a `config` object, e.g.
+//! `{"ack": 42, "config": {"interval_secs": 60, "collect_disks": false}}`. Only the keys of
+//! `RemoteOverrides` can be changed; a key set to `null` reverts to the configured value.
+//! Overrides are kept in memory and are lost on restart.
+
+use log::{info, warn};
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::RwLock;
+
+use crate::config::config_instance::Config;
+use crate::data::units::Celsius;
+
+/// Settings the server may override at runtime; `None` keeps the configured value.
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct RemoteOverrides {
+    pub interval_secs: Option<u64>,
+    pub collect_disks: Option<bool>,
+    pub collect_network: Option<bool>,
+    pub alert_warning_celsius: Option<Celsius>,
+    pub alert_critical_celsius: Option<Celsius>,
+}
+
+/// Overrides received from the server so far.
+static OVERRIDES: RwLock<RemoteOverrides> = RwLock::new(RemoteOverrides {
+    interval_secs: None,
+    collect_disks: None,
+    collect_network: None,
+    alert_warning_celsius: None,
+    alert_critical_celsius: None,
+});
+
+/// Static utility class for settings changed by the server.
+pub struct RemoteConfig;
+
+impl RemoteConfig {
+    /// Applies the `config` object of a server response body, if remote configuration is
+    /// enabled and the body carries one.
+    pub fn apply_from_response(body: &[u8]) {
+        if !Config::get().remote_config {
+            return;
+        }
+        let Ok(Value::Object(mut response)) = serde_json::from_slice::<Value>(body) else {
+            return;
+        };
+        let Some(update) = response.remove("config") else {
+            return;
+        };
+        match Self::merge(&Self::overrides(), update) {
+            Ok(merged) => {
+                let mut overrides = OVERRIDES.write().unwrap_or_else(|e| e.into_inner());
+                if *overrides != merged {
+                    info!("Applying configuration from the server: {:?}", merged);
+                    *overrides = merged;
+                }
+            }
+            Err(e) => warn!("Ignoring configuration from the server: {}", e),
+        }
+    }
+
+    /// The overrides currently in effect.
+    pub fn overrides() -> RemoteOverrides {
+        OVERRIDES.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    pub fn interval_secs() -> u64 {
+        Self::overrides()
+            .interval_secs
+            .unwrap_or(Config::get().interval_secs)
+            .max(1)
+    }
+
+    pub fn collect_disks() -> bool {
+        Self::overrides()
+            .collect_disks
+            .unwrap_or(Config::get().collect_disks)
+    }
+
+    pub fn collect_network() -> bool {
+        Self::overrides()
+            .collect_network
+            .unwrap_or(Config::get().collect_network)
+    }
+
+    pub fn alert_warning_celsius() -> Option<Celsius> {
+        Self::overrides()
+            .alert_warning_celsius
+            .or(Config::get().alert_warning_celsius)
+    }
+
+    pub fn alert_critical_celsius() -> Option<Celsius> {
+        Self::overrides()
+            .alert_critical_celsius
+            .or(Config::get().alert_critical_celsius)
+    }
+
+    /// Merges an update object into `current`: present keys replace the current value,
+    /// `null` clears it, absent keys are kept.
+    fn merge(current: &RemoteOverrides, update: Value) -> Result<RemoteOverrides, String> {
+        let Value::Object(update) = update else {
+            return Err("`config` is not an object".to_string());
+        };
+        let mut merged = serde_json::json!({
+            "interval_secs": current.interval_secs,
+            "collect_disks": current.collect_disks,
+            "collect_network": current.collect_network,
+            "alert_warning_celsius": current.alert_warning_celsius,
+            "alert_critical_celsius": current.alert_critical_celsius,
+        });
+        for (key, value) in update {
+            match merged.get_mut(&key) {
+                Some(slot) => *slot = value,
+                None => warn!("Ignoring unsupported remote setting {}.", key),
+            }
+        }
+        serde_json::from_value(merged).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn merges_updates_and_clears_nulls() {
+        let current = RemoteOverrides {
+            interval_secs: Some(30),
+            collect_disks: Some(false),
+            ..RemoteOverrides::default()
+        };
+        let merged = RemoteConfig::merge(
+            &current,
+            json!({"collect_disks": null, "alert_warning_celsius": 75.0, "nice": 5}),
+        )
+        .unwrap();
+        assert_eq!(merged.interval_secs, Some(30));
+        assert_eq!(merged.collect_disks, None);
+        assert_eq!(merged.alert_warning_celsius, Some(Celsius(75.0)));
+
+        assert!(RemoteConfig::merge(&current, json!({"interval_secs": "soon"})).is_err());
+    }
+}
@@ -0,0 +1,77 @@
+//! Host Role Presets
+//!
+//! A role selects a bundle of collectors and intervals suited to a class of host, so fleet
+//! configuration management only has to set `role` per host class. Preset values sit below
+//! the configuration file: any key set explicitly in `config.toml` (or through the
+//! environment or command line) overrides the preset.
+
+use serde::Deserialize;
+use toml::{Table, Value};
+
+/// Class of host the agent runs on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Role {
+    /// Virtualization hosts: frequent reports, neighbor tracking and the thermal black box.
+    Hypervisor,
+    /// Storage boxes: disk-focused, slower cadence and a longer local history.
+    Nas,
+    /// Workstations: thermal focus, no neighbor tracking.
+    Desktop,
+    /// Low-power edge devices: infrequent, lightweight reports.
+    Edge,
+}
+
+impl Role {
+    /// Returns the configuration keys preset by this role.
+    pub fn preset(self) -> Table {
+        let entries: &[(&str, Value)] = match self {
+            Role::Hypervisor => &[
+                ("interval_secs", Value::Integer(10)),
+                ("neighbor_discovery", Value::Boolean(true)),
+                ("black_box_enabled", Value::Boolean(true)),
+                ("collect_disks", Value::Boolean(true)),
+                ("collect_network", Value::Boolean(true)),
+            ],
+            Role::Nas => &[
+                ("interval_secs", Value::Integer(30)),
+                ("neighbor_discovery", Value::Boolean(false)),
+                ("collect_disks", Value::Boolean(true)),
+                ("collect_network", Value::Boolean(true)),
+                ("history_minutes", Value::Integer(120)),
+            ],
+            Role::Desktop => &[
+                ("interval_secs", Value::Integer(15)),
+                ("neighbor_discovery", Value::Boolean(false)),
+                ("black_box_enabled", Value::Boolean(true)),
+                ("collect_disks", Value::Boolean(true)),
+                ("collect_network", Value::Boolean(false)),
+            ],
+            Role::Edge => &[
+                ("interval_secs", Value::Integer(60)),
+                ("neighbor_discovery", Value::Boolean(true)),
+                ("neighbor_interval_secs", Value::Integer(900)),
+                ("black_box_enabled", Value::Boolean(false)),
+                ("collect_disks", Value::Boolean(false)),
+                ("collect_network", Value::Boolean(true)),
+                ("history_minutes", Value::Integer(30)),
+            ],
+        };
+
+        entries
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.clone()))
+            .collect()
+    }
+
+    /// Layers the keys of `file` over the preset of the role it names, if any.
+    pub fn apply_preset(file: Table) -> Result<Table, toml::de::Error> {
+        let Some(role) = file.get("role").cloned() else {
+            return Ok(file);
+        };
+
+        let mut merged = role.try_into::<Role>()?.preset();
+        merged.extend(file);
+        Ok(merged)
+    }
+}
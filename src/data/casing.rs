@@ -0,0 +1,147 @@
+//! Wire Field Casing
+//!
+//! The DTOs in `data::models` serialize their fields in snake_case. Some server
+//! deployments expect camelCase instead, so the casing is applied at serialization
+//! time according to the configured policy rather than baked into each DTO.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+
+/// Field naming policy applied to serialized payloads.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum FieldCasing {
+    /// Field names as declared on the DTOs (e.g. `package_temperature`).
+    #[default]
+    #[serde(rename = "snake_case")]
+    SnakeCase,
+    /// Field names converted to camelCase (e.g. `packageTemperature`).
+    #[serde(rename = "camelCase")]
+    CamelCase,
+}
+
+impl FieldCasing {
+    /// Serializes `data` into a JSON value with this casing policy applied.
+    ///
+    /// `serde_json::to_value` would widen every `f32` to `f64` (`45.1` becoming
+    /// `45.099998474121094`), so `data` is serialized to text first, where floats keep
+    /// their shortest representation, and parsed back.
+    pub fn to_value<T: Serialize>(self, data: &T) -> serde_json::Result<Value> {
+        let value = serde_json::from_str(&serde_json::to_string(data)?)?;
+        Ok(self.apply(value))
+    }
+
+    /// Rewrites all object keys of `value` (recursively) according to this policy.
+    pub fn apply(self, value: Value) -> Value {
+        match self {
+            FieldCasing::SnakeCase => value,
+            FieldCasing::CamelCase => Self::rename_keys(value, &Self::snake_to_camel),
+        }
+    }
+
+    /// Returns the wire name of a single DTO field under this policy.
+    pub fn rename(self, key: &str) -> String {
+        match self {
+            FieldCasing::SnakeCase => key.to_string(),
+            FieldCasing::CamelCase => Self::snake_to_camel(key),
+        }
+    }
+
+    fn rename_keys(value: Value, rename: &dyn Fn(&str) -> String) -> Value {
+        match value {
+            Value::Object(map) => Value::Object(
+                map.into_iter()
+                    .map(|(key, value)| (rename(&key), Self::rename_keys(value, rename)))
+                    .collect::<Map<String, Value>>(),
+            ),
+            Value::Array(values) => Value::Array(
+                values
+                    .into_iter()
+                    .map(|value| Self::rename_keys(value, rename))
+                    .collect(),
+            ),
+            other => other,
+        }
+    }
+
+    /// Converts a snake_case identifier to camelCase; other keys are left untouched.
+    fn snake_to_camel(key: &str) -> String {
+        let is_snake_case = key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+        if !is_snake_case {
+            return key.to_string();
+        }
+
+        let mut result = String::with_capacity(key.len());
+        let mut upper_next = false;
+        for c in key.chars() {
+            if c == '_' {
+                upper_next = !result.is_empty();
+            } else if upper_next {
+                result.push(c.to_ascii_uppercase());
+                upper_next = false;
+            } else {
+                result.push(c);
+            }
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::models::{CpuCoreData, CpuPackageData, Uptime};
+    use crate::data::units::Celsius;
+
+    fn sample_package() -> CpuPackageData {
+        CpuPackageData {
+            package_id: "0".to_string(),
+            adapter_name: "coretemp-isa-0000".to_string(),
+            package_temperature: Celsius(45.1),
+            high_threshold: Celsius(80.0),
+            critical_threshold: Celsius(100.0),
+            cores: vec![CpuCoreData {
+                core_name: "Core".to_string(),
+                temperature: Celsius(42.0),
+                high_threshold: Celsius(80.0),
+                critical_threshold: Celsius(100.0),
+            }],
+        }
+    }
+
+    #[test]
+    fn snake_case_wire_format_is_pinned() {
+        let value = FieldCasing::SnakeCase.to_value(&sample_package()).unwrap();
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"package_id":"0","adapter_name":"coretemp-isa-0000","package_temperature":45.1,"high_threshold":80.0,"critical_threshold":100.0,"cores":[{"core_name":"Core","temperature":42.0,"high_threshold":80.0,"critical_threshold":100.0}]}"#
+        );
+    }
+
+    #[test]
+    fn camel_case_wire_format_is_pinned() {
+        let value = FieldCasing::CamelCase.to_value(&sample_package()).unwrap();
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"packageId":"0","adapterName":"coretemp-isa-0000","packageTemperature":45.1,"highThreshold":80.0,"criticalThreshold":100.0,"cores":[{"coreName":"Core","temperature":42.0,"highThreshold":80.0,"criticalThreshold":100.0}]}"#
+        );
+    }
+
+    #[test]
+    fn camel_case_keeps_nested_values_and_non_identifier_keys() {
+        let value = FieldCasing::CamelCase.apply(serde_json::json!({
+            "total_seconds": 1,
+            "Package id 0": { "high_threshold": 80 },
+        }));
+        assert_eq!(
+            value,
+            serde_json::json!({ "totalSeconds": 1, "Package id 0": { "highThreshold": 80 } })
+        );
+
+        let uptime = FieldCasing::CamelCase
+            .to_value(&Uptime::new(90061, 1_700_000_000))
+            .unwrap();
+        assert_eq!(uptime["totalSeconds"], 90061);
+    }
+}
@@ -0,0 +1,269 @@
+//! collectd Plain-Text Protocol
+//!
+//! This module renders a `SensorData` snapshot as `PUTVAL` commands of collectd's plain-text
+//! protocol, the format read by collectd's `exec` plugin. Identifiers have the form
+//! `<host>/sentinel-<instance>/<type>-<type instance>`, using types from collectd's default
+//! `types.db` so no custom types are needed:
+//! - `uptime`, `count`, `percent`, `temperature`, `memory`, `df_complex`: gauges.
+//! - `disk_octets` (`read:write`) and `if_octets` (`rx:tx`): derives from the raw counters.
+
+use std::fmt::Write;
+
+use crate::data::models::SensorData;
+use crate::data::units::Percent;
+
+/// Plugin name of every identifier.
+const PLUGIN: &str = "sentinel";
+
+/// Static encoder for `PUTVAL` commands.
+pub struct PutVal;
+
+impl PutVal {
+    /// Encodes `data` as newline-terminated `PUTVAL` commands for `host`, stamped with
+    /// `timestamp_secs` and announcing `interval_secs` as the collection interval.
+    pub fn encode(
+        data: &SensorData,
+        host: &str,
+        interval_secs: f64,
+        timestamp_secs: f64,
+    ) -> String {
+        let mut lines = String::new();
+        let mut putval = |instance: &str, type_: &str, type_instance: &str, values: &[Value]| {
+            Self::write_line(
+                &mut lines,
+                host,
+                instance,
+                type_,
+                type_instance,
+                values,
+                interval_secs,
+                timestamp_secs,
+            )
+        };
+
+        putval(
+            "system",
+            "uptime",
+            "",
+            &[Value::Int(data.system_info.uptime.total_seconds)],
+        );
+
+        if let Some(Percent(usage_average)) = data.cpu_info.average_usage() {
+            putval(
+                "cpu",
+                "percent",
+                "usage",
+                &[Value::Float(usage_average as f64)],
+            );
+        }
+        putval(
+            "cpu",
+            "count",
+            "cores",
+            &[Value::Int(data.cpu_info.core_count as u64)],
+        );
+
+        for package in &data.cpu_packages {
+            let instance = format!("{}_{}", package.adapter_name, package.package_id);
+            putval(
+                &instance,
+                "temperature",
+                "package",
+                &[Value::Float(package.package_temperature.0 as f64)],
+            );
+            for core in &package.cores {
+                putval(
+                    &instance,
+                    "temperature",
+                    &core.core_name,
+                    &[Value::Float(core.temperature.0 as f64)],
+                );
+            }
+        }
+
+        let memory = &data.memory_info;
+        putval("memory", "memory", "used", &[Value::Int(memory.used.0)]);
+        putval(
+            "memory",
+            "memory",
+            "free",
+            &[Value::Int(memory.total.saturating_sub(memory.used).0)],
+        );
+        putval(
+            "memory",
+            "memory",
+            "swap_used",
+            &[Value::Int(memory.used_swap.0)],
+        );
+        putval(
+            "memory",
+            "memory",
+            "swap_free",
+            &[Value::Int(
+                memory.total_swap.saturating_sub(memory.used_swap).0,
+            )],
+        );
+
+        for disk in &data.disks {
+            let instance = format!("disk_{}", disk.name);
+            putval(
+                &instance,
+                "df_complex",
+                "free",
+                &[Value::Int(disk.available_space.0)],
+            );
+            putval(
+                &instance,
+                "df_complex",
+                "used",
+                &[Value::Int(
+                    disk.total_space.saturating_sub(disk.available_space).0,
+                )],
+            );
+            putval(
+                &instance,
+                "disk_octets",
+                "",
+                &[
+                    Value::Int(disk.read_bytes.0),
+                    Value::Int(disk.written_bytes.0),
+                ],
+            );
+        }
+
+        for interface in &data.network_interfaces {
+            putval(
+                &format!("net_{}", interface.interface_name),
+                "if_octets",
+                "",
+                &[
+                    Value::Int(interface.received.0),
+                    Value::Int(interface.transmitted.0),
+                ],
+            );
+        }
+
+        for component in &data.components {
+            if let Some(temperature) = component.temperature {
+                putval(
+                    "component",
+                    "temperature",
+                    &component.label,
+                    &[Value::Float(temperature.0 as f64)],
+                );
+            }
+        }
+
+        lines
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn write_line(
+        out: &mut String,
+        host: &str,
+        instance: &str,
+        type_: &str,
+        type_instance: &str,
+        values: &[Value],
+        interval_secs: f64,
+        timestamp_secs: f64,
+    ) {
+        // collectd reads NaN as "unknown", but a skipped value keeps graphs from dropping to 0.
+        if values
+            .iter()
+            .any(|value| matches!(value, Value::Float(v) if !v.is_finite()))
+        {
+            return;
+        }
+
+        let _ = write!(
+            out,
+            "PUTVAL \"{}/{}-{}/{}",
+            Self::sanitize(host),
+            PLUGIN,
+            Self::sanitize(instance),
+            type_
+        );
+        if !type_instance.is_empty() {
+            let _ = write!(out, "-{}", Self::sanitize(type_instance));
+        }
+        let _ = write!(
+            out,
+            "\" interval={:.3} {:.3}",
+            interval_secs, timestamp_secs
+        );
+        for value in values {
+            let _ = match value {
+                Value::Int(v) => write!(out, ":{}", v),
+                Value::Float(v) => write!(out, ":{}", v),
+            };
+        }
+        out.push('\n');
+    }
+
+    /// Replaces characters that would end an identifier part or the quoted identifier.
+    fn sanitize(value: &str) -> String {
+        value
+            .chars()
+            .map(|c| match c {
+                '/' | '"' | '\\' | ' ' => '_',
+                c if c.is_control() => '_',
+                c => c,
+            })
+            .collect()
+    }
+}
+
+/// A value of a data source.
+enum Value {
+    Int(u64),
+    Float(f64),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::units::Celsius;
+    use crate::test_util::sample_sensor_data;
+
+    #[test]
+    fn encodes_putval_commands() {
+        let lines = PutVal::encode(
+            &sample_sensor_data(),
+            "sentinel-test",
+            10.0,
+            1_700_000_000.5,
+        );
+        let lines: Vec<&str> = lines.lines().collect();
+        assert_eq!(
+            lines[..5],
+            [
+                "PUTVAL \"sentinel-test/sentinel-system/uptime\" interval=10.000 1700000000.500:93784",
+                "PUTVAL \"sentinel-test/sentinel-cpu/percent-usage\" interval=10.000 1700000000.500:7.75",
+                "PUTVAL \"sentinel-test/sentinel-cpu/count-cores\" interval=10.000 1700000000.500:2",
+                "PUTVAL \"sentinel-test/sentinel-coretemp-isa-0000_0/temperature-package\" interval=10.000 1700000000.500:45",
+                "PUTVAL \"sentinel-test/sentinel-coretemp-isa-0000_0/temperature-Core_0\" interval=10.000 1700000000.500:42",
+            ]
+        );
+        assert!(lines.contains(
+            &"PUTVAL \"sentinel-test/sentinel-memory/memory-free\" interval=10.000 1700000000.500:12000000000"
+        ));
+        assert!(lines.contains(
+            &"PUTVAL \"sentinel-test/sentinel-disk__dev_sda1/disk_octets\" interval=10.000 1700000000.500:4096:8192"
+        ));
+        assert!(lines.contains(
+            &"PUTVAL \"sentinel-test/sentinel-net_eth0/if_octets\" interval=10.000 1700000000.500:1024:2048"
+        ));
+    }
+
+    #[test]
+    fn sanitizes_identifiers_and_skips_unknown_values() {
+        let mut data = sample_sensor_data();
+        data.components[0].label = "a/b \"c\"\\d".to_string();
+        data.cpu_packages[0].package_temperature = Celsius(f32::NAN);
+
+        let lines = PutVal::encode(&data, "host one", 10.0, 1.0);
+        assert!(lines.contains("PUTVAL \"host_one/sentinel-component/temperature-a_b__c__d\""));
+        assert!(!lines.contains("temperature-package"));
+    }
+}
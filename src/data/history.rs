@@ -0,0 +1,74 @@
+//! Local Payload History
+//!
+//! This module keeps a time-bounded ring buffer of recently collected payloads in memory,
+//! so local consumers (such as the REST API) can read recent data without the server.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::config_instance::Config;
+
+/// Recorded payloads, oldest first.
+static HISTORY: Mutex<VecDeque<HistoryEntry>> = Mutex::new(VecDeque::new());
+
+/// A payload recorded at a point in time.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct HistoryEntry {
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+    pub data: Value,
+}
+
+/// Static accessor for the payload ring buffer.
+pub struct History;
+
+impl History {
+    /// Records a payload and evicts entries older than `history_minutes`.
+    pub fn record<T: Serialize>(data: &T) {
+        let Ok(data) = serde_json::to_value(data) else {
+            return;
+        };
+
+        let timestamp = Self::now();
+        let retention_secs = Config::get().history_minutes * 60;
+        let mut history = HISTORY.lock().unwrap_or_else(|e| e.into_inner());
+        history.push_back(HistoryEntry { timestamp, data });
+        while history
+            .front()
+            .is_some_and(|entry| timestamp.saturating_sub(entry.timestamp) > retention_secs)
+        {
+            history.pop_front();
+        }
+    }
+
+    /// Returns the most recently recorded payload.
+    pub fn latest() -> Option<HistoryEntry> {
+        HISTORY
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .back()
+            .cloned()
+    }
+
+    /// Returns all payloads recorded within the last `minutes` minutes, oldest first.
+    pub fn since_minutes(minutes: u64) -> Vec<HistoryEntry> {
+        let cutoff = Self::now().saturating_sub(minutes * 60);
+        HISTORY
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|entry| entry.timestamp >= cutoff)
+            .cloned()
+            .collect()
+    }
+
+    fn now() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,247 @@
+//! InfluxDB Line Protocol
+//!
+//! This module renders a `SensorData` snapshot as InfluxDB line protocol, the format read
+//! by Telegraf's `exec`/`execd` inputs. Every measurement is prefixed with `sentinel_` and
+//! tagged with the host name.
+
+use std::fmt::Write;
+
+use crate::data::models::SensorData;
+
+/// Prefix of every measurement name.
+const MEASUREMENT_PREFIX: &str = "sentinel_";
+
+/// Static encoder for line protocol.
+pub struct LineProtocol;
+
+impl LineProtocol {
+    /// Encodes `data` as newline-terminated lines stamped with `timestamp_ns`.
+    pub fn encode(data: &SensorData, timestamp_ns: u128) -> String {
+        let host = data.system_info.hostname.as_str();
+        let mut lines = String::new();
+        let mut line = |measurement: &str, tags: &[(&str, &str)], fields: &[(&str, Field)]| {
+            Self::write_line(&mut lines, measurement, host, tags, fields, timestamp_ns)
+        };
+
+        line(
+            "system",
+            &[],
+            &[
+                ("uptime", Field::Int(data.system_info.uptime.total_seconds)),
+                ("boot_time", Field::Int(data.system_info.uptime.boot_time)),
+            ],
+        );
+
+        let usage_average = data.cpu_info.average_usage().unwrap_or_default().0;
+        let mut cpu_fields = vec![
+            ("usage_average", Field::Float(usage_average as f64)),
+            ("core_count", Field::Int(data.cpu_info.core_count as u64)),
+        ];
+        if let Some(limit) = data.cpu_info.container_cpu_limit {
+            cpu_fields.push(("container_cpu_limit", Field::Float(limit)));
+        }
+        line("cpu", &[], &cpu_fields);
+
+        for package in &data.cpu_packages {
+            line(
+                "cpu_package",
+                &[
+                    ("adapter", &package.adapter_name),
+                    ("package", &package.package_id),
+                ],
+                &[
+                    (
+                        "temperature",
+                        Field::Float(package.package_temperature.0 as f64),
+                    ),
+                    ("high", Field::Float(package.high_threshold.0 as f64)),
+                    (
+                        "critical",
+                        Field::Float(package.critical_threshold.0 as f64),
+                    ),
+                ],
+            );
+            for core in &package.cores {
+                line(
+                    "cpu_core",
+                    &[
+                        ("adapter", &package.adapter_name),
+                        ("package", &package.package_id),
+                        ("core", &core.core_name),
+                    ],
+                    &[
+                        ("temperature", Field::Float(core.temperature.0 as f64)),
+                        ("high", Field::Float(core.high_threshold.0 as f64)),
+                        ("critical", Field::Float(core.critical_threshold.0 as f64)),
+                    ],
+                );
+            }
+        }
+
+        let memory = &data.memory_info;
+        let mut memory_fields = vec![
+            ("total", Field::Int(memory.total.0)),
+            ("used", Field::Int(memory.used.0)),
+            ("total_swap", Field::Int(memory.total_swap.0)),
+            ("used_swap", Field::Int(memory.used_swap.0)),
+        ];
+        if let Some(limit) = memory.container_limit {
+            memory_fields.push(("container_limit", Field::Int(limit.0)));
+        }
+        if let Some(used) = memory.container_used {
+            memory_fields.push(("container_used", Field::Int(used.0)));
+        }
+        line("memory", &[], &memory_fields);
+
+        for disk in &data.disks {
+            let mut disk_fields = vec![
+                ("total_space", Field::Int(disk.total_space.0)),
+                ("available_space", Field::Int(disk.available_space.0)),
+                ("read_bytes", Field::Int(disk.read_bytes.0)),
+                ("written_bytes", Field::Int(disk.written_bytes.0)),
+            ];
+            if let Some(rate) = disk.read_bytes_per_sec {
+                disk_fields.push(("read_bytes_per_sec", Field::Float(rate)));
+            }
+            if let Some(rate) = disk.written_bytes_per_sec {
+                disk_fields.push(("written_bytes_per_sec", Field::Float(rate)));
+            }
+            line("disk", &[("name", &disk.name)], &disk_fields);
+        }
+
+        for interface in &data.network_interfaces {
+            let mut net_fields = vec![
+                ("received", Field::Int(interface.received.0)),
+                ("transmitted", Field::Int(interface.transmitted.0)),
+            ];
+            if let Some(rate) = interface.received_per_sec {
+                net_fields.push(("received_per_sec", Field::Float(rate)));
+            }
+            if let Some(rate) = interface.transmitted_per_sec {
+                net_fields.push(("transmitted_per_sec", Field::Float(rate)));
+            }
+            line(
+                "net",
+                &[("interface", &interface.interface_name)],
+                &net_fields,
+            );
+        }
+
+        for component in &data.components {
+            if let Some(temperature) = component.temperature {
+                line(
+                    "component",
+                    &[("label", &component.label)],
+                    &[("temperature", Field::Float(temperature.0 as f64))],
+                );
+            }
+        }
+
+        lines
+    }
+
+    fn write_line(
+        out: &mut String,
+        measurement: &str,
+        host: &str,
+        tags: &[(&str, &str)],
+        fields: &[(&str, Field)],
+        timestamp_ns: u128,
+    ) {
+        // NaN and infinities cannot be represented; a line needs at least one field.
+        let fields: Vec<&(&str, Field)> = fields
+            .iter()
+            .filter(|(_, value)| !matches!(value, Field::Float(v) if !v.is_finite()))
+            .collect();
+        if fields.is_empty() {
+            return;
+        }
+
+        out.push_str(MEASUREMENT_PREFIX);
+        out.push_str(&Self::escape(measurement, &[',', ' ']));
+        for (key, value) in std::iter::once(&("host", host)).chain(tags) {
+            if value.is_empty() {
+                continue; // Empty tag values are invalid in line protocol.
+            }
+            let _ = write!(
+                out,
+                ",{}={}",
+                Self::escape(key, &[',', '=', ' ']),
+                Self::escape(value, &[',', '=', ' '])
+            );
+        }
+
+        for (i, (key, value)) in fields.iter().enumerate() {
+            let separator = if i == 0 { ' ' } else { ',' };
+            let _ = match value {
+                Field::Int(v) => write!(out, "{}{}={}i", separator, key, v),
+                Field::Float(v) => write!(out, "{}{}={}", separator, key, v),
+            };
+        }
+        let _ = writeln!(out, " {}", timestamp_ns);
+    }
+
+    /// Backslash-escapes `special` characters (and backslashes) in a name or tag.
+    fn escape(value: &str, special: &[char]) -> String {
+        let mut escaped = String::with_capacity(value.len());
+        for c in value.chars() {
+            if c == '\\' || special.contains(&c) {
+                escaped.push('\\');
+            }
+            escaped.push(c);
+        }
+        escaped
+    }
+}
+
+/// A field value; integers carry the `i` suffix.
+enum Field {
+    Int(u64),
+    Float(f64),
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::units::Celsius;
+    use crate::test_util::sample_sensor_data;
+
+    #[test]
+    fn encodes_one_line_per_measurement() {
+        let lines = LineProtocol::encode(&sample_sensor_data(), 1_700_000_000_000_000_000);
+        let lines: Vec<&str> = lines.lines().collect();
+        assert_eq!(
+            lines[..4],
+            [
+                "sentinel_system,host=sentinel-test uptime=93784i,boot_time=1700000000i 1700000000000000000",
+                "sentinel_cpu,host=sentinel-test usage_average=7.75,core_count=2i 1700000000000000000",
+                "sentinel_cpu_package,host=sentinel-test,adapter=coretemp-isa-0000,package=0 temperature=45,high=80,critical=100 1700000000000000000",
+                "sentinel_cpu_core,host=sentinel-test,adapter=coretemp-isa-0000,package=0,core=Core\\ 0 temperature=42,high=80,critical=100 1700000000000000000",
+            ]
+        );
+        assert_eq!(
+            lines.last(),
+            Some(&"sentinel_component,host=sentinel-test,label=acpitz\\ temp1 temperature=27.5 1700000000000000000")
+        );
+    }
+
+    #[test]
+    fn escapes_tags_and_skips_unrepresentable_fields() {
+        let mut data = sample_sensor_data();
+        data.system_info.hostname = "rack 1,a=b\\c".to_string();
+        data.cpu_packages[0].package_id = String::new();
+        data.cpu_packages[0].package_temperature = Celsius(f32::NAN);
+        data.components[0].temperature = Some(Celsius(f32::INFINITY));
+
+        let lines = LineProtocol::encode(&data, 1);
+        let package = lines
+            .lines()
+            .find(|line| line.starts_with("sentinel_cpu_package"))
+            .unwrap();
+        assert_eq!(
+            package,
+            "sentinel_cpu_package,host=rack\\ 1\\,a\\=b\\\\c,adapter=coretemp-isa-0000 high=80,critical=100 1"
+        );
+        assert!(!lines.contains("sentinel_component"));
+    }
+}
@@ -1 +1,13 @@
+pub mod casing;
+pub mod collectd;
+pub mod history;
+pub mod line_protocol;
 pub mod models;
+pub mod parse;
+pub mod payload_format;
+mod property_tests;
+pub mod proto;
+pub mod rate;
+pub mod transform;
+pub mod units;
+mod wire_contract;
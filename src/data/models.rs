@@ -1,56 +1,355 @@
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::fmt;
+use std::net::IpAddr;
 use sysinfo::Component;
 
+use crate::data::units::{Bytes, Celsius, Percent};
+
+/// Version of the `facts` document schema. Bump on any incompatible change.
+pub const FACTS_VERSION: u32 = 1;
+
+/// Version of the `SensorData` wire schema. Bump whenever a field is renamed,
+/// removed or changes meaning, and add golden files for the new version.
+///
+/// Version 2 reports `package_id` as the bare package number and `core_name` as the full
+/// `sensors` label.
+pub const SCHEMA_VERSION: u32 = 2;
+
 // General System DTOs
 #[derive(Serialize, Debug)]
 pub struct CpuCoreData {
+    /// Label of the core as printed by `sensors`, e.g. `Core 0`.
     pub core_name: String,
-    pub temperature: f32,
-    pub high_threshold: f32,
-    pub critical_threshold: f32,
+    pub temperature: Celsius,
+    pub high_threshold: Celsius,
+    pub critical_threshold: Celsius,
 }
 
 #[derive(Serialize, Debug)]
 pub struct CpuPackageData {
+    /// Number of the package, e.g. `0` for `Package id 0`.
     pub package_id: String,
     pub adapter_name: String,
-    pub package_temperature: f32,
-    pub high_threshold: f32,
-    pub critical_threshold: f32,
+    pub package_temperature: Celsius,
+    pub high_threshold: Celsius,
+    pub critical_threshold: Celsius,
     pub cores: Vec<CpuCoreData>,
 }
 
+/// A fan speed read from the `sensors` command or hwmon.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct FanInfo {
+    /// Chip the fan is connected to, e.g. `nct6798-isa-0290`.
+    pub adapter_name: String,
+    /// `fanN`, or the label configured in `sensors.conf` (e.g. `CPU Fan`).
+    pub label: String,
+    /// Speed in RPM.
+    pub speed: f32,
+    /// Minimum speed in RPM before the chip raises an alarm.
+    pub min_speed: Option<f32>,
+}
+
+/// A voltage rail read from the `sensors` command.
+#[derive(Serialize, Debug)]
+pub struct VoltageInfo {
+    /// Chip measuring the rail, e.g. `nct6798-isa-0290`.
+    pub adapter_name: String,
+    /// `inN`, or the label configured in `sensors.conf` (e.g. `Vcore`, `+12V`).
+    pub label: String,
+    /// Voltage in volts.
+    pub voltage: f32,
+    /// Alarm thresholds in volts.
+    pub min_voltage: Option<f32>,
+    pub max_voltage: Option<f32>,
+}
+
+/// Power drawn by the platform or one of its domains.
+#[derive(Serialize, Debug)]
+pub struct PowerInfo {
+    /// Chip or powercap zone, e.g. `power_meter-acpi-0` or `intel-rapl:0`.
+    pub adapter_name: String,
+    /// Channel or RAPL domain, e.g. `power1`, `package-0` or `psys`.
+    pub label: String,
+    /// Power in watts; averaged since the previous report for RAPL domains.
+    pub power: f32,
+    /// Energy counter of RAPL domains in joules, which wraps around; absent for meters.
+    pub energy: Option<f64>,
+}
+
+/// Memory totals of the host. When the agent runs in a container, the container's own
+/// cgroup limit and usage are reported separately in the `container_*` fields.
 #[derive(Serialize, Debug)]
 pub struct MemoryInfo {
-    pub total: u64,
-    pub used: u64,
-    pub total_swap: u64,
-    pub used_swap: u64,
+    pub total: Bytes,
+    pub used: Bytes,
+    pub total_swap: Bytes,
+    pub used_swap: Bytes,
+    /// Memory limit of the agent's container in bytes; absent outside containers or when unlimited.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_limit: Option<Bytes>,
+    /// Memory charged to the agent's container in bytes; absent outside containers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_used: Option<Bytes>,
 }
 
+/// CPU usage and core count of the host. When the agent runs in a container, the
+/// container's CPU quota is reported separately in `container_cpu_limit`.
 #[derive(Serialize, Debug)]
 pub struct CpuInfo {
-    pub usage_per_core: Vec<f32>,
+    pub usage_per_core: Vec<Percent>,
     pub core_count: usize,
     pub cpu_arch: String,
+    /// CPU quota of the agent's container in cores (e.g. 1.5); absent outside containers
+    /// or when unlimited.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_cpu_limit: Option<f64>,
+}
+
+impl CpuInfo {
+    /// Usage averaged over all cores; `None` when no cores were read.
+    pub fn average_usage(&self) -> Option<Percent> {
+        if self.usage_per_core.is_empty() {
+            return None;
+        }
+        let total: f32 = self.usage_per_core.iter().map(|usage| usage.0).sum();
+        Some(Percent(total / self.usage_per_core.len() as f32))
+    }
 }
 
 #[derive(Serialize, Debug)]
 pub struct DiskInfo {
     pub name: String,
-    pub total_space: u64,
-    pub available_space: u64,
-    pub read_bytes: u64,
-    pub written_bytes: u64,
+    pub total_space: Bytes,
+    pub available_space: Bytes,
+    pub read_bytes: Bytes,
+    pub written_bytes: Bytes,
+    /// Cumulative counters the rates are computed from; not serialized.
+    #[serde(skip)]
+    pub total_read_bytes: u64,
+    #[serde(skip)]
+    pub total_written_bytes: u64,
+    /// Bytes read per second; absent on the first sample and after a counter reset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub read_bytes_per_sec: Option<f64>,
+    /// Bytes written per second; absent on the first sample and after a counter reset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub written_bytes_per_sec: Option<f64>,
+    /// Set on the first sample after the disk's counters reset.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub counter_reset: bool,
 }
 
 #[derive(Serialize, Debug)]
 pub struct NetworkInfo {
     pub interface_name: String,
-    pub received: u64,
-    pub transmitted: u64,
+    pub received: Bytes,
+    pub transmitted: Bytes,
     pub mtu: Option<u64>,
+    /// Hardware address (`aa:bb:cc:dd:ee:ff`); absent for interfaces without one.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mac_address: Option<String>,
+    /// IPv4 and IPv6 addresses assigned to the interface.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub addresses: Vec<InterfaceAddress>,
+    /// Cumulative counters the rates are computed from; not serialized.
+    #[serde(skip)]
+    pub total_received: u64,
+    #[serde(skip)]
+    pub total_transmitted: u64,
+    /// Bytes received per second; absent on the first sample and after a counter reset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub received_per_sec: Option<f64>,
+    /// Bytes transmitted per second; absent on the first sample and after a counter reset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transmitted_per_sec: Option<f64>,
+    /// Set on the first sample after the interface's counters reset.
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub counter_reset: bool,
+}
+
+/// An IP address assigned to a network interface.
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct InterfaceAddress {
+    pub address: IpAddr,
+    /// Length of the network prefix, e.g. 24 for a /24.
+    pub prefix_length: u8,
+}
+
+#[derive(Serialize, Debug)]
+pub struct NeighborInfo {
+    pub ip_address: String,
+    pub mac_address: Option<String>,
+    pub state: String,
+    pub interface_name: String,
+}
+
+/// A device found under `/sys/class/hwmon`, such as a USB fan controller or pump.
+#[derive(Serialize, Debug)]
+pub struct HwmonDevice {
+    /// Driver name, e.g. `d5next` or `corsaircpro`.
+    pub name: String,
+    /// Identity of the underlying sysfs device, which survives replugging unlike `hwmonN`.
+    pub device: String,
+    pub readings: Vec<HwmonReading>,
+}
+
+/// One channel of a hwmon device.
+#[derive(Serialize, Debug)]
+pub struct HwmonReading {
+    pub kind: HwmonReadingKind,
+    pub label: String,
+    /// °C for temperatures, RPM for fans and pumps, the driver's unit (named in the
+    /// label) for flow.
+    pub value: f32,
+}
+
+/// What a hwmon channel measures.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum HwmonReadingKind {
+    Temperature,
+    Fan,
+    Pump,
+    Flow,
+}
+
+impl HwmonReadingKind {
+    /// The serialized name of the kind.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HwmonReadingKind::Temperature => "temperature",
+            HwmonReadingKind::Fan => "fan",
+            HwmonReadingKind::Pump => "pump",
+            HwmonReadingKind::Flow => "flow",
+        }
+    }
+}
+
+/// An AIO or custom-loop cooling device reported by liquidctl.
+#[derive(Serialize, Debug)]
+pub struct CoolingDeviceInfo {
+    pub description: String,
+    /// Bus and address of the device (e.g. `hid`, `/dev/hidraw3`).
+    pub bus: String,
+    pub address: String,
+    pub coolant_temperature: Option<Celsius>,
+    /// Pump speed in RPM.
+    pub pump_speed: Option<f32>,
+    pub pump_duty: Option<Percent>,
+    /// Flow in the unit reported by the device (usually l/h).
+    pub flow: Option<f32>,
+    pub fans: Vec<CoolingFanInfo>,
+}
+
+/// A fan channel of a cooling device.
+#[derive(Serialize, Debug)]
+pub struct CoolingFanInfo {
+    pub name: String,
+    /// Speed in RPM.
+    pub speed: Option<f32>,
+    pub duty: Option<Percent>,
+}
+
+/// A LAN device polled over SNMP on behalf of the server.
+#[derive(Serialize, Debug)]
+pub struct ProxiedDevice {
+    pub name: String,
+    pub address: String,
+    /// Whether the device answered; `readings` is empty when it did not.
+    pub reachable: bool,
+    pub readings: Vec<ProxiedReading>,
+}
+
+/// A variable read from a polled device, scaled as configured.
+#[derive(Serialize, Debug)]
+pub struct ProxiedReading {
+    pub name: String,
+    pub oid: String,
+    /// Absent when the device does not have the variable or it is not numeric.
+    pub value: Option<f64>,
+    pub unit: Option<String>,
+}
+
+/// A host that cannot run the client, read remotely (e.g. through its BMC).
+#[derive(Serialize, Debug)]
+pub struct RemoteHostInfo {
+    pub name: String,
+    pub address: String,
+    /// How the host was read, e.g. `ipmi`.
+    pub source: String,
+    /// Whether the host answered; `sensors` is empty when it did not.
+    pub reachable: bool,
+    pub sensors: Vec<RemoteSensorReading>,
+}
+
+/// A sensor of a remote host with its upper thresholds.
+#[derive(Serialize, Debug)]
+pub struct RemoteSensorReading {
+    pub name: String,
+    /// Absent when the sensor has no reading.
+    pub value: Option<f64>,
+    pub unit: String,
+    /// Status as reported by the host, e.g. `ok`, `nc`, `cr` or `na`.
+    pub status: String,
+    pub upper_non_critical: Option<f64>,
+    pub upper_critical: Option<f64>,
+}
+
+/// Temperatures, fan speed and power draw of a GPU.
+#[derive(Serialize, Debug)]
+pub struct GpuInfo {
+    /// DRM card name, e.g. `card0`.
+    pub card: String,
+    /// `amd`.
+    pub vendor: String,
+    pub name: Option<String>,
+    /// PCI address, e.g. `0000:03:00.0`.
+    pub pci_address: Option<String>,
+    /// Edge temperature.
+    pub temperature: Option<Celsius>,
+    /// Hotspot temperature.
+    pub junction_temperature: Option<Celsius>,
+    /// Fan speed in RPM.
+    pub fan_speed: Option<f32>,
+    /// Power draw in watts.
+    pub power: Option<f32>,
+}
+
+/// Health of a disk as read through smartctl.
+#[derive(Serialize, Debug)]
+pub struct SmartInfo {
+    /// Device path, e.g. `/dev/sda`.
+    pub device: String,
+    pub model: Option<String>,
+    pub serial_number: Option<String>,
+    pub health: SmartHealth,
+    /// Reallocated sectors (ATA) or grown defects (SCSI); absent when the disk has no such
+    /// counter, e.g. NVMe.
+    pub reallocated_sectors: Option<u64>,
+    pub power_on_hours: Option<u64>,
+    pub temperature: Option<Celsius>,
+}
+
+/// The overall SMART verdict of a disk.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SmartHealth {
+    Passed,
+    Failed,
+    /// No verdict, e.g. because the disk was in standby.
+    Unknown,
+}
+
+impl SmartHealth {
+    /// The serialized name of the verdict.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SmartHealth::Passed => "passed",
+            SmartHealth::Failed => "failed",
+            SmartHealth::Unknown => "unknown",
+        }
+    }
 }
 
 #[derive(Serialize, Debug)]
@@ -63,18 +362,18 @@ pub struct ProcessInfo {
 #[derive(Serialize, Debug)]
 pub struct ComponentInfo {
     pub label: String,
-    pub temperature: Option<f32>,
-    pub max_temperature: Option<f32>,
-    pub critical_temperature: Option<f32>,
+    pub temperature: Option<Celsius>,
+    pub max_temperature: Option<Celsius>,
+    pub critical_temperature: Option<Celsius>,
 }
 
 impl From<&Component> for ComponentInfo {
     fn from(component: &Component) -> Self {
         Self {
             label: component.label().to_string(),
-            temperature: component.temperature(),
-            max_temperature: component.max(),
-            critical_temperature: component.critical(),
+            temperature: component.temperature().map(Celsius),
+            max_temperature: component.max().map(Celsius),
+            critical_temperature: component.critical().map(Celsius),
         }
     }
 }
@@ -86,10 +385,12 @@ pub struct Uptime {
     pub minutes: u64,
     pub seconds: u64,
     pub total_seconds: u64,
+    /// Absolute boot time as seconds since the Unix epoch (UTC).
+    pub boot_time: u64,
 }
 
 impl Uptime {
-    pub fn from_seconds(total_seconds: u64) -> Self {
+    pub fn new(total_seconds: u64, boot_time: u64) -> Self {
         let days = total_seconds / 86400;
         let hours = (total_seconds % 86400) / 3600;
         let minutes = (total_seconds % 3600) / 60;
@@ -101,6 +402,7 @@ impl Uptime {
             minutes,
             seconds,
             total_seconds,
+            boot_time,
         }
     }
 }
@@ -119,10 +421,84 @@ pub struct SystemInfo {
     pub hostname: String,
     pub uptime: Uptime,
     pub management_ip: String,
+    /// Address the host reaches the internet from; absent unless `[public_ip]` is configured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub public_ip: Option<String>,
+    /// Whether the host booted since the last successfully delivered report.
+    pub rebooted_since_last_report: bool,
+}
+
+/// One 1 Hz sample of the thermal black box.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ThermalSample {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp: u64,
+    pub package_temperatures: Vec<Celsius>,
+    pub max_core_temperature: Celsius,
+    pub load_average_one: f64,
+    /// Fan speeds, if the thermal source reads fans (`sensors` or hwmon).
+    #[serde(default)]
+    pub fans: Vec<FanInfo>,
+}
+
+/// Rolling record of the last samples before a (possible) thermal shutdown.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ThermalForensicRecord {
+    pub hostname: String,
+    pub boot_time: u64,
+    /// Milliseconds since the Unix epoch at which recording was triggered.
+    pub triggered_at: u64,
+    pub samples: Vec<ThermalSample>,
+}
+
+/// Declaration of a period without delivered reports, sent once reporting resumes so the
+/// server can annotate the gap instead of drawing a flat line across it.
+#[derive(Serialize, Debug)]
+pub struct GapDeclaration {
+    pub hostname: String,
+    /// Seconds since the Unix epoch of the last report delivered before the gap.
+    pub gap_start: u64,
+    /// Seconds since the Unix epoch of the first report delivered after the gap.
+    pub gap_end: u64,
+    /// Number of scheduled reports that were not delivered.
+    pub missed_samples: u64,
+    /// Number of locally stored samples (e.g. black box records) delivered after the gap.
+    pub recovered_samples: u64,
+    /// Whether the host rebooted during the gap.
+    pub rebooted: bool,
+}
+
+/// Why a `SensorData` section holds no readings, as opposed to the host having none.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionStatus {
+    /// The platform cannot provide this data.
+    Unsupported,
+    /// The collector is turned off in the configuration.
+    Disabled,
+}
+
+impl SectionStatus {
+    /// The wire name of the status.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            SectionStatus::Unsupported => "unsupported",
+            SectionStatus::Disabled => "disabled",
+        }
+    }
 }
 
 #[derive(Serialize, Debug)]
 pub struct SensorData {
+    pub schema_version: u32,
+    /// Persistent ID of the reporting agent; absent only in tests.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub agent_id: Option<String>,
+    /// Tenant and site the host reports for (`tenant_id`/`site_id`); absent when unset.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tenant_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub site_id: Option<String>,
     pub system_info: SystemInfo,
     pub cpu_info: CpuInfo,
     pub cpu_packages: Vec<CpuPackageData>,
@@ -130,4 +506,251 @@ pub struct SensorData {
     pub disks: Vec<DiskInfo>,
     pub network_interfaces: Vec<NetworkInfo>,
     pub components: Vec<ComponentInfo>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub neighbors: Option<Vec<NeighborInfo>>,
+    /// Fan speeds read from `sensors` or hwmon; absent when temperatures came from another
+    /// source (`thermal_source = "msr"` or `[thermal_reconcile]`).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fans: Option<Vec<FanInfo>>,
+    /// Voltage rails read from `sensors` or hwmon; absent when `fans` is.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voltages: Option<Vec<VoltageInfo>>,
+    /// Devices enumerated under `/sys/class/hwmon` this cycle; absent when `collect_hwmon`
+    /// is disabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hwmon_devices: Option<Vec<HwmonDevice>>,
+    /// Liquid cooling devices; absent unless `[liquidctl]` is configured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cooling_devices: Option<Vec<CoolingDeviceInfo>>,
+    /// Adjacent devices polled over SNMP; absent unless `[[snmp_targets]]` are configured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub proxied_devices: Option<Vec<ProxiedDevice>>,
+    /// Headless hosts read through their BMC; absent unless `[[ipmi_hosts]]` are configured.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub remote_hosts: Option<Vec<RemoteHostInfo>>,
+    /// Disk health read through smartctl; absent unless `[smart]` is configured, and
+    /// between its snapshots.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub smart_devices: Option<Vec<SmartInfo>>,
+    /// GPU sensors; absent when `collect_gpus` is disabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gpus: Option<Vec<GpuInfo>>,
+    /// Power meters and RAPL domains; absent when `collect_power` is disabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub power: Option<Vec<PowerInfo>>,
+    /// Packages whose temperature differs between thermal sources by more than the
+    /// configured tolerance; absent when sources agree or reconciliation is disabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub thermal_disagreements: Option<Vec<ThermalDisagreement>>,
+    /// Slow-changing description of the host; only sent when it changed since the last
+    /// delivered report or the inventory heartbeat is due.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inventory: Option<HostInventory>,
+    /// Hash of the host's current inventory, sent with every report so the server can tell
+    /// whether the inventory it holds is current.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub inventory_hash: Option<String>,
+    /// Sections (keyed by field name) that are empty because they could not or were not
+    /// read; absent when every section was read.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub section_status: BTreeMap<String, SectionStatus>,
+}
+
+/// Reports of every host identity an agent speaks for, sent instead of `SensorData` when
+/// `group_hosts` is enabled.
+#[derive(Serialize, Debug)]
+pub struct HostGroupReport {
+    pub schema_version: u32,
+    /// Host name of the submitting agent.
+    pub agent: String,
+    /// Persistent ID of the submitting agent.
+    pub agent_id: String,
+    pub hosts: Vec<HostEntry>,
+}
+
+/// The report of one host identity within a `HostGroupReport`.
+#[derive(Serialize, Debug)]
+pub struct HostEntry {
+    pub host: String,
+    /// How the host was read: `agent` (the agent's own host), `snmp` or `ipmi`.
+    pub source: String,
+    /// Number of this report among the reports of this identity, starting at 1.
+    pub sequence: u64,
+    pub tags: Vec<HostTag>,
+    pub report: HostReportData,
+}
+
+/// A configured key/value tag of a host identity.
+#[derive(Serialize, Debug)]
+pub struct HostTag {
+    pub key: String,
+    pub value: String,
+}
+
+/// The readings of a host identity, keyed by their type.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum HostReportData {
+    Sensors(Box<SensorData>),
+    Snmp(ProxiedDevice),
+    Ipmi(RemoteHostInfo),
+}
+
+/// Several `SensorData` samples sent as one request when batching is configured.
+#[derive(Serialize, Debug)]
+pub struct SensorDataBatch {
+    pub schema_version: u32,
+    /// Samples in collection order.
+    pub samples: Vec<TimestampedSensorData>,
+}
+
+/// Small liveness message sent every `heartbeat_interval_secs`, independently of reports.
+#[derive(Serialize, Debug)]
+pub struct Heartbeat {
+    pub schema_version: u32,
+    pub agent_id: String,
+    pub hostname: String,
+    pub agent_version: String,
+    /// Seconds since the host booted.
+    pub uptime_secs: u64,
+    /// Seconds since the agent started.
+    pub agent_uptime_secs: u64,
+    /// Seconds since the Unix epoch at which the heartbeat was sent.
+    pub timestamp: u64,
+}
+
+/// Outcome of pointing the `[ddns]` record at a changed public IP address.
+#[derive(Serialize, Debug)]
+pub struct DdnsUpdateEvent {
+    pub agent_id: String,
+    pub hostname: String,
+    /// `duckdns` or `cloudflare`.
+    pub provider: String,
+    pub domain: String,
+    pub address: String,
+    /// Public address before the change; absent on the first lookup after startup.
+    pub previous_address: Option<String>,
+    pub success: bool,
+    /// Why the update failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Seconds since the Unix epoch of the update.
+    pub timestamp: u64,
+}
+
+/// Outcome of a command received over the `[commands]` channel.
+#[derive(Serialize, Debug)]
+pub struct CommandResult {
+    pub agent_id: String,
+    pub hostname: String,
+    /// Id the server gave the command.
+    pub command_id: String,
+    /// `collect_now`, `send_processes` or `log_level`.
+    pub command: String,
+    pub success: bool,
+    /// What was done, or why the command failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Running processes, for `send_processes`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub processes: Option<Vec<ProcessInfo>>,
+    /// Seconds since the Unix epoch at which the command was executed.
+    pub timestamp: u64,
+}
+
+/// Sent once per agent ID so the server can track the agent across host name and
+/// address changes.
+#[derive(Serialize, Debug)]
+pub struct AgentRegistration {
+    pub schema_version: u32,
+    pub agent_id: String,
+    pub hostname: String,
+    pub agent_version: String,
+    pub os_name: String,
+    pub os_version: String,
+    pub kernel_version: String,
+    pub cpu_arch: String,
+}
+
+/// A part of a `SensorData` report that exceeded `max_payload_bytes`.
+#[derive(Serialize, Debug)]
+pub struct SensorDataChunk {
+    pub schema_version: u32,
+    /// Shared by every chunk of one report.
+    pub correlation_id: String,
+    /// Position of this chunk among the report's chunks, starting at 0.
+    pub chunk_index: usize,
+    pub chunk_count: usize,
+    pub agent_id: String,
+    pub hostname: String,
+    #[serde(flatten)]
+    pub part: ChunkPart,
+}
+
+/// The contents of a `SensorDataChunk`, keyed by their type.
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "snake_case")]
+pub enum ChunkPart {
+    /// The report without its disks, network interfaces and components.
+    Report(Box<SensorData>),
+    Disks(Vec<DiskInfo>),
+    NetworkInterfaces(Vec<NetworkInfo>),
+    Components(Vec<ComponentInfo>),
+}
+
+/// A `SensorData` sample with the time it was collected.
+#[derive(Serialize, Debug)]
+pub struct TimestampedSensorData {
+    /// Milliseconds since the Unix epoch.
+    pub timestamp_ms: u64,
+    pub data: SensorData,
+}
+
+/// A package temperature as reported by one thermal source.
+#[derive(Serialize, Debug)]
+pub struct ThermalReading {
+    pub source: String,
+    pub temperature: Celsius,
+}
+
+/// Data-quality warning raised when thermal sources disagree on a package temperature.
+#[derive(Serialize, Debug)]
+pub struct ThermalDisagreement {
+    pub package_id: String,
+    /// Source whose reading is reported in `cpu_packages`.
+    pub primary_source: String,
+    /// Difference between the highest and lowest reading in °C.
+    pub spread: f32,
+    pub readings: Vec<ThermalReading>,
+}
+
+/// Slow-changing description of a host, as reported by the `facts` subcommand.
+#[derive(Serialize, Debug)]
+pub struct HostInventory {
+    pub hostname: String,
+    pub management_ip: String,
+    pub os_name: String,
+    pub os_version: String,
+    pub kernel_version: String,
+    pub boot_time: u64,
+    pub cpu_arch: String,
+    pub core_count: usize,
+    pub cpu_package_count: usize,
+    pub memory_total: Bytes,
+    pub swap_total: Bytes,
+    pub disk_names: Vec<String>,
+    pub network_interface_names: Vec<String>,
+}
+
+/// Single-document facts output (inventory plus current readings) for CM tools.
+///
+/// Always serialized in snake_case regardless of `field_casing`, so the schema is stable.
+#[derive(Serialize, Debug)]
+pub struct FactsDocument {
+    pub facts_version: u32,
+    pub agent_version: String,
+    /// Seconds since the Unix epoch at which the readings were taken.
+    pub collected_at: u64,
+    pub inventory: HostInventory,
+    pub readings: SensorData,
 }
@@ -0,0 +1,188 @@
+//! Parsing Helpers
+//!
+//! Command-line tools format numbers according to the locale they run in: `sensors` prints
+//! `+45,0°C` under a German locale, and hypervisor tools group digits as `1 234,5` or
+//! `1.234,5`. The helpers here read such values regardless of the locale the output was
+//! produced in, so collectors do not silently drop readings on non-English hosts.
+//!
+//! Typed helpers such as `parse_temp` return a `ParseError` naming the offending text,
+//! which the caller extends with the line and label it was reading.
+
+use std::fmt;
+
+use crate::data::units::Celsius;
+
+/// A value that could not be parsed, with where it was found.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    /// 1-based line of the input, when known.
+    pub line: Option<usize>,
+    /// What was being read, e.g. the sensor label.
+    pub context: Option<String>,
+    pub message: String,
+}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+impl ParseError {
+    pub fn new(message: impl Into<String>) -> Self {
+        ParseError {
+            line: None,
+            context: None,
+            message: message.into(),
+        }
+    }
+
+    /// Records the line the error occurred on.
+    pub fn at_line(mut self, line: usize) -> Self {
+        self.line = Some(line);
+        self
+    }
+
+    /// Records what was being read, e.g. `Core 0`.
+    pub fn context(mut self, context: impl Into<String>) -> Self {
+        self.context = Some(context.into());
+        self
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(line) = self.line {
+            write!(f, "line {}: ", line)?;
+        }
+        if let Some(context) = &self.context {
+            write!(f, "{}: ", context)?;
+        }
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Splits a `label: reading` line, trimming both parts.
+pub fn split_label(line: &str) -> ParseResult<(&str, &str)> {
+    line.split_once(':')
+        .map(|(label, reading)| (label.trim(), reading.trim()))
+        .ok_or_else(|| ParseError::new(format!("expected `label: value`, found `{}`", line.trim())))
+}
+
+/// The first whitespace-separated token of `text`.
+pub fn first_token(text: &str) -> Option<&str> {
+    text.split_whitespace().next()
+}
+
+/// The token following `name =` in `text`, e.g. `+100.0°C)` for `crit` in
+/// `(high = +80.0°C, crit = +100.0°C)`.
+pub fn named_token<'a>(text: &'a str, name: &str) -> Option<&'a str> {
+    let (_, rest) = text.split_once(&format!("{} =", name))?;
+    first_token(rest)
+}
+
+/// Parses a temperature token such as `+45.0°C`, `45,0 C` or `+80.0°C,`.
+pub fn parse_temp(text: &str) -> ParseResult<Celsius> {
+    let value = text
+        .trim()
+        .trim_end_matches([',', ')'])
+        .trim_end_matches("°C")
+        .trim_end_matches(['C', '°']);
+    parse_number(value)
+        .map(|value| Celsius(value as f32))
+        .ok_or_else(|| ParseError::new(format!("expected a temperature, found `{}`", text.trim())))
+}
+
+/// Parses a decimal number written in any common locale convention.
+///
+/// Accepts a leading sign, `.` or `,` as the decimal separator, digit grouping with `.`,
+/// `,`, `'` or (non-breaking) spaces, and scientific notation such as `1,5E+03`. A single
+/// separator is taken as the decimal separator; when both appear, the last one is. Returns
+/// `None` for anything else, including `NaN` and infinities.
+pub fn parse_number(text: &str) -> Option<f64> {
+    let compact: String = text
+        .trim()
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != '\'')
+        .collect();
+    let (mantissa, exponent) = match compact.find(['e', 'E']) {
+        Some(index) => (&compact[..index], Some(&compact[index + 1..])),
+        None => (compact.as_str(), None),
+    };
+
+    let decimal = match (mantissa.rfind('.'), mantissa.rfind(',')) {
+        (Some(dot), Some(comma)) => Some(dot.max(comma)),
+        (Some(index), None) if mantissa.matches('.').count() == 1 => Some(index),
+        (None, Some(index)) if mantissa.matches(',').count() == 1 => Some(index),
+        _ => None,
+    };
+    let mut normalized: String = mantissa
+        .char_indices()
+        .filter_map(|(index, c)| match c {
+            '.' | ',' if Some(index) == decimal => Some('.'),
+            '.' | ',' => None,
+            c => Some(c),
+        })
+        .collect();
+    if let Some(exponent) = exponent {
+        normalized.push('e');
+        normalized.push_str(exponent);
+    }
+
+    let starts_with_digit = normalized
+        .trim_start_matches(['+', '-'])
+        .starts_with(|c: char| c.is_ascii_digit() || c == '.');
+    normalized
+        .parse::<f64>()
+        .ok()
+        .filter(|value| starts_with_digit && value.is_finite())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_comma_decimals() {
+        assert_eq!(parse_number("45,0"), Some(45.0));
+        assert_eq!(parse_number("+45,5"), Some(45.5));
+        assert_eq!(parse_number("-0,25"), Some(-0.25));
+        assert_eq!(parse_number("1.234,5"), Some(1234.5));
+        assert_eq!(parse_number("1,234.5"), Some(1234.5));
+        assert_eq!(parse_number("1.234.567"), Some(1_234_567.0));
+    }
+
+    #[test]
+    fn parses_whitespace_variants() {
+        assert_eq!(parse_number("  42.5\t"), Some(42.5));
+        assert_eq!(parse_number("1 234,5"), Some(1234.5));
+        assert_eq!(parse_number("1\u{a0}234,5"), Some(1234.5));
+        assert_eq!(parse_number("1\u{202f}234"), Some(1234.0));
+        assert_eq!(parse_number("1'234.5"), Some(1234.5));
+    }
+
+    #[test]
+    fn parses_scientific_notation() {
+        assert_eq!(parse_number("1.5e3"), Some(1500.0));
+        assert_eq!(parse_number("1,5E+03"), Some(1500.0));
+        assert_eq!(parse_number("2.5e-2"), Some(0.025));
+    }
+
+    #[test]
+    fn temperature_errors_carry_context() {
+        assert_eq!(parse_temp("+80,0°C,"), Ok(Celsius(80.0)));
+        assert_eq!(parse_temp("45.5 C"), Ok(Celsius(45.5)));
+        let error = parse_temp("N/A").unwrap_err().context("Core 3").at_line(7);
+        assert_eq!(
+            error.to_string(),
+            "line 7: Core 3: expected a temperature, found `N/A`"
+        );
+    }
+
+    #[test]
+    fn rejects_non_numbers() {
+        assert_eq!(parse_number(""), None);
+        assert_eq!(parse_number("N/A"), None);
+        assert_eq!(parse_number("NaN"), None);
+        assert_eq!(parse_number("inf"), None);
+        assert_eq!(parse_number("0x1f"), None);
+    }
+}
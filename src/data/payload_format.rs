@@ -0,0 +1,227 @@
+//! Wire Payload Format
+//!
+//! Payloads are JSON by default. Hosts reporting at short intervals or over constrained
+//! links can switch to a binary encoding instead; the field casing policy applies to
+//! every self-describing format, since encoding happens after casing has been applied.
+//! Protobuf field names come from the schema in `proto/`, so casing does not apply to it,
+//! and payloads without a protobuf schema fall back to JSON.
+
+use serde::{Deserialize, Serialize};
+use std::io;
+
+use crate::data::casing::FieldCasing;
+use crate::data::models::{
+    AgentRegistration, CommandResult, DdnsUpdateEvent, GapDeclaration, Heartbeat, HostGroupReport,
+    SensorData, SensorDataBatch, SensorDataChunk, ThermalForensicRecord,
+};
+use crate::data::proto;
+
+/// Serialization format of payloads sent to the server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PayloadFormat {
+    /// JSON text (`application/json`).
+    #[default]
+    Json,
+    /// MessagePack with named fields (`application/msgpack`).
+    Msgpack,
+    /// CBOR, RFC 8949 (`application/cbor`).
+    Cbor,
+    /// Protocol Buffers, `proto/sentinel/v1` (`application/x-protobuf`).
+    Protobuf,
+}
+
+/// A payload that can be sent to the server in any `PayloadFormat`.
+pub trait WirePayload: Serialize {
+    /// Short name of the payload type, used e.g. in MQTT topics and sent over HTTP in the
+    /// `X-Sentinel-Kind` header.
+    const KIND: &'static str;
+
+    /// Encodes the payload as protobuf, or `None` if it has no protobuf schema.
+    fn to_protobuf(&self) -> Option<Vec<u8>> {
+        None
+    }
+}
+
+impl WirePayload for SensorData {
+    const KIND: &'static str = "sensors";
+
+    fn to_protobuf(&self) -> Option<Vec<u8>> {
+        Some(proto::encode_sensor_data(self))
+    }
+}
+
+impl WirePayload for SensorDataBatch {
+    const KIND: &'static str = "sensors_batch";
+
+    fn to_protobuf(&self) -> Option<Vec<u8>> {
+        Some(proto::encode_sensor_data_batch(self))
+    }
+}
+
+impl WirePayload for Heartbeat {
+    const KIND: &'static str = "heartbeat";
+}
+
+impl WirePayload for DdnsUpdateEvent {
+    const KIND: &'static str = "ddns_update";
+}
+
+impl WirePayload for CommandResult {
+    const KIND: &'static str = "command_result";
+}
+
+impl WirePayload for AgentRegistration {
+    const KIND: &'static str = "registration";
+}
+
+impl WirePayload for SensorDataChunk {
+    const KIND: &'static str = "sensors_chunk";
+}
+
+impl WirePayload for HostGroupReport {
+    const KIND: &'static str = "host_group";
+}
+
+impl WirePayload for ThermalForensicRecord {
+    const KIND: &'static str = "black_box";
+}
+
+impl WirePayload for GapDeclaration {
+    const KIND: &'static str = "gap";
+}
+
+impl PayloadFormat {
+    /// Serializes `data` in this format, returning the body and its MIME type.
+    ///
+    /// The MIME type differs from `content_type()` when `data` has no protobuf schema
+    /// and was sent as JSON instead.
+    pub fn encode<T: WirePayload>(
+        self,
+        data: &T,
+        casing: FieldCasing,
+    ) -> io::Result<(Vec<u8>, &'static str)> {
+        if self == PayloadFormat::Protobuf {
+            if let Some(body) = data.to_protobuf() {
+                return Ok((body, self.content_type()));
+            }
+            return PayloadFormat::Json.encode(data, casing);
+        }
+
+        // Field names are already snake_case, so the DTO is serialized directly.
+        if casing == FieldCasing::SnakeCase {
+            let body = match self {
+                PayloadFormat::Json | PayloadFormat::Protobuf => serde_json::to_vec(data)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+                PayloadFormat::Msgpack => rmp_serde::to_vec_named(data)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+                PayloadFormat::Cbor => serde_cbor::to_vec(data)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            }?;
+            return Ok((body, self.content_type()));
+        }
+
+        let value = casing
+            .to_value(data)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        self.encode_value(&value)
+    }
+
+    /// Encodes an already cased JSON value in this format; protobuf falls back to JSON,
+    /// since a value carries no schema.
+    pub fn encode_value(self, value: &serde_json::Value) -> io::Result<(Vec<u8>, &'static str)> {
+        let body =
+            match self {
+                PayloadFormat::Json | PayloadFormat::Protobuf => serde_json::to_vec(value)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+                PayloadFormat::Msgpack => rmp_serde::to_vec_named(value)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+                PayloadFormat::Cbor => serde_cbor::to_vec(value)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)),
+            }?;
+        let content_type = match self {
+            PayloadFormat::Protobuf => PayloadFormat::Json.content_type(),
+            _ => self.content_type(),
+        };
+        Ok((body, content_type))
+    }
+
+    /// MIME type sent in the `Content-Type` header.
+    pub fn content_type(self) -> &'static str {
+        match self {
+            PayloadFormat::Json => "application/json",
+            PayloadFormat::Msgpack => "application/msgpack",
+            PayloadFormat::Cbor => "application/cbor",
+            PayloadFormat::Protobuf => "application/x-protobuf",
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::units::Celsius;
+    use crate::test_util::sample_sensor_data;
+    use serde_json::Value;
+
+    #[test]
+    fn json_keeps_the_shortest_float_representation() {
+        let mut data = sample_sensor_data();
+        data.cpu_packages[0].package_temperature = Celsius(45.1);
+        for (casing, field) in [
+            (FieldCasing::SnakeCase, r#""package_temperature":45.1,"#),
+            (FieldCasing::CamelCase, r#""packageTemperature":45.1,"#),
+        ] {
+            let (body, _) = PayloadFormat::Json.encode(&data, casing).unwrap();
+            let body = String::from_utf8(body).unwrap();
+            assert!(body.contains(field), "{}", body);
+        }
+    }
+
+    #[test]
+    fn msgpack_round_trips_the_cased_value() {
+        let data = sample_sensor_data();
+        for casing in [FieldCasing::SnakeCase, FieldCasing::CamelCase] {
+            let (body, content_type) = PayloadFormat::Msgpack.encode(&data, casing).unwrap();
+            assert_eq!(content_type, "application/msgpack");
+            let expected = casing.to_value(&data).unwrap();
+            assert_eq!(rmp_serde::from_slice::<Value>(&body).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn cbor_round_trips_the_cased_value() {
+        let data = sample_sensor_data();
+        for casing in [FieldCasing::SnakeCase, FieldCasing::CamelCase] {
+            let (body, content_type) = PayloadFormat::Cbor.encode(&data, casing).unwrap();
+            assert_eq!(content_type, "application/cbor");
+            let expected = casing.to_value(&data).unwrap();
+            assert_eq!(serde_cbor::from_slice::<Value>(&body).unwrap(), expected);
+        }
+    }
+
+    #[test]
+    fn protobuf_uses_the_schema_or_falls_back_to_json() {
+        let (body, content_type) = PayloadFormat::Protobuf
+            .encode(&sample_sensor_data(), FieldCasing::SnakeCase)
+            .unwrap();
+        assert_eq!(content_type, "application/x-protobuf");
+        assert_eq!(body, proto::encode_sensor_data(&sample_sensor_data()));
+
+        let heartbeat = Heartbeat {
+            schema_version: 1,
+            agent_id: "agent".to_string(),
+            hostname: "sentinel-test".to_string(),
+            agent_version: "1.0.0".to_string(),
+            uptime_secs: 60,
+            agent_uptime_secs: 30,
+            timestamp: 1_700_000_000,
+        };
+        let (body, content_type) = PayloadFormat::Protobuf
+            .encode(&heartbeat, FieldCasing::SnakeCase)
+            .unwrap();
+        assert_eq!(content_type, "application/json");
+        let value: Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(value["uptime_secs"], 60);
+    }
+}
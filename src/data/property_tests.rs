@@ -0,0 +1,177 @@
+//! Property-Based Tests
+//!
+//! Checks the arithmetic helpers alerting decisions rely on (uptime breakdown, threshold
+//! classification, gap detection, counter rates) against invariants over arbitrary inputs,
+//! including extreme values, counter wraparound and clocks that went backwards.
+#![cfg(test)]
+
+use proptest::prelude::*;
+
+use crate::alert::alert_util::{AlertSeverity, AlertUtil};
+use crate::data::models::Uptime;
+use crate::data::rate::{CounterDelta, RateUtil};
+use crate::sensor::gap_util::GapUtil;
+
+/// Thresholds as read from sensors: unknown (non-positive) or a plausible temperature.
+fn threshold() -> impl Strategy<Value = f32> {
+    prop_oneof![Just(0.0f32), Just(-1.0f32), 1.0f32..150.0]
+}
+
+fn fallback() -> impl Strategy<Value = Option<f32>> {
+    proptest::option::of(1.0f32..150.0)
+}
+
+proptest! {
+    #[test]
+    fn uptime_components_recombine(total in any::<u64>(), boot_time in any::<u64>()) {
+        let uptime = Uptime::new(total, boot_time);
+        prop_assert!(uptime.hours < 24);
+        prop_assert!(uptime.minutes < 60);
+        prop_assert!(uptime.seconds < 60);
+        prop_assert_eq!(uptime.total_seconds, total);
+        prop_assert_eq!(uptime.boot_time, boot_time);
+
+        let recombined = u128::from(uptime.days) * 86_400
+            + u128::from(uptime.hours) * 3_600
+            + u128::from(uptime.minutes) * 60
+            + u128::from(uptime.seconds);
+        prop_assert_eq!(recombined, u128::from(total));
+    }
+
+    #[test]
+    fn classification_is_monotonic_in_temperature(
+        a in -50.0f32..200.0,
+        b in -50.0f32..200.0,
+        high in threshold(),
+        critical in threshold(),
+        fallback_high in fallback(),
+        fallback_critical in fallback(),
+    ) {
+        let (low, high_temperature) = if a <= b { (a, b) } else { (b, a) };
+        let classify = |t| AlertUtil::classify_with(t, high, critical, fallback_high, fallback_critical);
+        prop_assert!(classify(low) <= classify(high_temperature));
+    }
+
+    #[test]
+    fn reaching_the_critical_threshold_is_critical(
+        critical in 1.0f32..150.0,
+        excess in 0.0f32..100.0,
+        high in threshold(),
+    ) {
+        prop_assert_eq!(
+            AlertUtil::classify_with(critical + excess, high, critical, None, None),
+            AlertSeverity::Critical
+        );
+    }
+
+    #[test]
+    fn below_all_thresholds_is_normal(
+        high in 1.0f32..150.0,
+        critical in 1.0f32..150.0,
+        below in 0.001f32..50.0,
+    ) {
+        let temperature = high.min(critical) - below;
+        prop_assert_eq!(
+            AlertUtil::classify_with(temperature, high, critical, Some(200.0), Some(200.0)),
+            AlertSeverity::Resolved
+        );
+    }
+
+    #[test]
+    fn unknown_thresholds_use_fallbacks(
+        temperature in -50.0f32..200.0,
+        fallback_high in fallback(),
+        fallback_critical in fallback(),
+    ) {
+        prop_assert_eq!(
+            AlertUtil::classify_with(temperature, 0.0, -1.0, fallback_high, fallback_critical),
+            AlertUtil::classify_with(
+                temperature,
+                fallback_high.unwrap_or(0.0),
+                fallback_critical.unwrap_or(0.0),
+                None,
+                None,
+            )
+        );
+    }
+
+    #[test]
+    fn clock_going_backwards_is_never_a_gap(
+        now in any::<u64>(),
+        back in 0u64..=u64::MAX,
+        interval in any::<u64>(),
+    ) {
+        let last = now.saturating_add(back);
+        prop_assert_eq!(GapUtil::missed_samples(last, now, interval), None);
+    }
+
+    #[test]
+    fn gaps_start_after_two_intervals(
+        last in any::<u64>(),
+        elapsed in any::<u64>(),
+        interval in 0u64..100_000,
+    ) {
+        let now = last.saturating_add(elapsed);
+        let elapsed = now - last;
+        let effective = interval.max(1);
+        match GapUtil::missed_samples(last, now, interval) {
+            None => prop_assert!(elapsed <= effective.saturating_mul(2)),
+            Some(missed) => {
+                prop_assert!(elapsed > effective * 2);
+                prop_assert!(missed >= 1);
+                prop_assert_eq!(missed, elapsed / effective - 1);
+            }
+        }
+    }
+
+    #[test]
+    fn increasing_counters_yield_their_difference(previous in any::<u64>(), increase in any::<u64>()) {
+        let current = previous.saturating_add(increase);
+        prop_assert_eq!(
+            RateUtil::counter_delta(previous, current),
+            CounterDelta::Increase(current - previous)
+        );
+    }
+
+    #[test]
+    fn wrapped_32_bit_counters_are_corrected(
+        before_wrap in 1u64..(1 << 30),
+        after_wrap in 0u64..(1 << 30),
+    ) {
+        let previous = (1u64 << 32) - before_wrap;
+        prop_assert_eq!(
+            RateUtil::counter_delta(previous, after_wrap),
+            CounterDelta::Wrapped(before_wrap + after_wrap)
+        );
+    }
+
+    #[test]
+    fn decreasing_counters_never_yield_huge_increases(previous in any::<u64>(), current in any::<u64>()) {
+        prop_assume!(current < previous);
+        match RateUtil::counter_delta(previous, current) {
+            CounterDelta::Wrapped(delta) => prop_assert!(delta <= 1 << 31),
+            CounterDelta::Reset => {}
+            CounterDelta::Increase(_) => prop_assert!(false, "a decrease is not an increase"),
+        }
+    }
+
+    #[test]
+    fn resets_have_no_rate(previous in (1u64 << 32)..u64::MAX, current in any::<u64>(), elapsed in 0.001f64..1e6) {
+        prop_assume!(current < previous);
+        prop_assert_eq!(RateUtil::rate(previous, current, elapsed), None);
+    }
+
+    #[test]
+    fn rates_need_elapsed_time(previous in any::<u64>(), current in any::<u64>(), elapsed in -1e6f64..=0.0) {
+        prop_assert_eq!(RateUtil::rate(previous, current, elapsed), None);
+        prop_assert_eq!(RateUtil::rate(previous, current, f64::NAN), None);
+    }
+
+    #[test]
+    fn rates_are_finite_and_non_negative(previous in any::<u64>(), current in any::<u64>(), elapsed in 0.001f64..1e6) {
+        if let Some(rate) = RateUtil::rate(previous, current, elapsed) {
+            prop_assert!(rate.is_finite());
+            prop_assert!(rate >= 0.0);
+        }
+    }
+}
@@ -0,0 +1,919 @@
+//! Protobuf Messages
+//!
+//! Hand-written `prost` messages mirroring `proto/sentinel/v1/sensor_data.proto`, so the
+//! build does not need `protoc`. Keep field numbers and types in sync with that file.
+//! Conversions from the DTOs in `data::models` are provided for every message.
+
+use prost::Message;
+use std::collections::BTreeMap;
+
+use crate::data::models;
+
+#[derive(Clone, PartialEq, Message)]
+pub struct SensorData {
+    #[prost(uint32, tag = "1")]
+    pub schema_version: u32,
+    #[prost(message, optional, tag = "2")]
+    pub system_info: Option<SystemInfo>,
+    #[prost(message, optional, tag = "3")]
+    pub cpu_info: Option<CpuInfo>,
+    #[prost(message, repeated, tag = "4")]
+    pub cpu_packages: Vec<CpuPackageData>,
+    #[prost(message, optional, tag = "5")]
+    pub memory_info: Option<MemoryInfo>,
+    #[prost(message, repeated, tag = "6")]
+    pub disks: Vec<DiskInfo>,
+    #[prost(message, repeated, tag = "7")]
+    pub network_interfaces: Vec<NetworkInfo>,
+    #[prost(message, repeated, tag = "8")]
+    pub components: Vec<ComponentInfo>,
+    #[prost(message, repeated, tag = "9")]
+    pub neighbors: Vec<NeighborInfo>,
+    #[prost(message, repeated, tag = "10")]
+    pub thermal_disagreements: Vec<ThermalDisagreement>,
+    #[prost(message, optional, tag = "11")]
+    pub inventory: Option<HostInventory>,
+    #[prost(string, optional, tag = "12")]
+    pub inventory_hash: Option<String>,
+    #[prost(string, optional, tag = "13")]
+    pub tenant_id: Option<String>,
+    #[prost(string, optional, tag = "14")]
+    pub site_id: Option<String>,
+    #[prost(message, repeated, tag = "15")]
+    pub hwmon_devices: Vec<HwmonDevice>,
+    #[prost(message, repeated, tag = "16")]
+    pub cooling_devices: Vec<CoolingDeviceInfo>,
+    #[prost(message, repeated, tag = "17")]
+    pub proxied_devices: Vec<ProxiedDevice>,
+    #[prost(message, repeated, tag = "18")]
+    pub remote_hosts: Vec<RemoteHostInfo>,
+    #[prost(btree_map = "string, string", tag = "19")]
+    pub section_status: BTreeMap<String, String>,
+    #[prost(string, optional, tag = "20")]
+    pub agent_id: Option<String>,
+    #[prost(message, repeated, tag = "21")]
+    pub smart_devices: Vec<SmartInfo>,
+    #[prost(message, repeated, tag = "22")]
+    pub gpus: Vec<GpuInfo>,
+    #[prost(message, repeated, tag = "23")]
+    pub fans: Vec<FanInfo>,
+    #[prost(message, repeated, tag = "24")]
+    pub voltages: Vec<VoltageInfo>,
+    #[prost(message, repeated, tag = "25")]
+    pub power: Vec<PowerInfo>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct SensorDataBatch {
+    #[prost(uint32, tag = "1")]
+    pub schema_version: u32,
+    #[prost(message, repeated, tag = "2")]
+    pub samples: Vec<TimestampedSensorData>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct TimestampedSensorData {
+    #[prost(uint64, tag = "1")]
+    pub timestamp_ms: u64,
+    #[prost(message, optional, tag = "2")]
+    pub data: Option<SensorData>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct SystemInfo {
+    #[prost(string, tag = "1")]
+    pub hostname: String,
+    #[prost(message, optional, tag = "2")]
+    pub uptime: Option<Uptime>,
+    #[prost(string, tag = "3")]
+    pub management_ip: String,
+    #[prost(bool, tag = "4")]
+    pub rebooted_since_last_report: bool,
+    #[prost(string, optional, tag = "5")]
+    pub public_ip: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct Uptime {
+    #[prost(uint64, tag = "1")]
+    pub days: u64,
+    #[prost(uint64, tag = "2")]
+    pub hours: u64,
+    #[prost(uint64, tag = "3")]
+    pub minutes: u64,
+    #[prost(uint64, tag = "4")]
+    pub seconds: u64,
+    #[prost(uint64, tag = "5")]
+    pub total_seconds: u64,
+    #[prost(uint64, tag = "6")]
+    pub boot_time: u64,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct CpuInfo {
+    #[prost(float, repeated, tag = "1")]
+    pub usage_per_core: Vec<f32>,
+    #[prost(uint64, tag = "2")]
+    pub core_count: u64,
+    #[prost(string, tag = "3")]
+    pub cpu_arch: String,
+    #[prost(double, optional, tag = "4")]
+    pub container_cpu_limit: Option<f64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct CpuPackageData {
+    #[prost(string, tag = "1")]
+    pub package_id: String,
+    #[prost(string, tag = "2")]
+    pub adapter_name: String,
+    #[prost(float, tag = "3")]
+    pub package_temperature: f32,
+    #[prost(float, tag = "4")]
+    pub high_threshold: f32,
+    #[prost(float, tag = "5")]
+    pub critical_threshold: f32,
+    #[prost(message, repeated, tag = "6")]
+    pub cores: Vec<CpuCoreData>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct CpuCoreData {
+    #[prost(string, tag = "1")]
+    pub core_name: String,
+    #[prost(float, tag = "2")]
+    pub temperature: f32,
+    #[prost(float, tag = "3")]
+    pub high_threshold: f32,
+    #[prost(float, tag = "4")]
+    pub critical_threshold: f32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct MemoryInfo {
+    #[prost(uint64, tag = "1")]
+    pub total: u64,
+    #[prost(uint64, tag = "2")]
+    pub used: u64,
+    #[prost(uint64, tag = "3")]
+    pub total_swap: u64,
+    #[prost(uint64, tag = "4")]
+    pub used_swap: u64,
+    #[prost(uint64, optional, tag = "5")]
+    pub container_limit: Option<u64>,
+    #[prost(uint64, optional, tag = "6")]
+    pub container_used: Option<u64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct DiskInfo {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(uint64, tag = "2")]
+    pub total_space: u64,
+    #[prost(uint64, tag = "3")]
+    pub available_space: u64,
+    #[prost(uint64, tag = "4")]
+    pub read_bytes: u64,
+    #[prost(uint64, tag = "5")]
+    pub written_bytes: u64,
+    #[prost(double, optional, tag = "6")]
+    pub read_bytes_per_sec: Option<f64>,
+    #[prost(double, optional, tag = "7")]
+    pub written_bytes_per_sec: Option<f64>,
+    #[prost(bool, tag = "8")]
+    pub counter_reset: bool,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct NetworkInfo {
+    #[prost(string, tag = "1")]
+    pub interface_name: String,
+    #[prost(uint64, tag = "2")]
+    pub received: u64,
+    #[prost(uint64, tag = "3")]
+    pub transmitted: u64,
+    #[prost(uint64, optional, tag = "4")]
+    pub mtu: Option<u64>,
+    #[prost(double, optional, tag = "5")]
+    pub received_per_sec: Option<f64>,
+    #[prost(double, optional, tag = "6")]
+    pub transmitted_per_sec: Option<f64>,
+    #[prost(bool, tag = "7")]
+    pub counter_reset: bool,
+    #[prost(string, optional, tag = "8")]
+    pub mac_address: Option<String>,
+    #[prost(message, repeated, tag = "9")]
+    pub addresses: Vec<InterfaceAddress>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct InterfaceAddress {
+    #[prost(string, tag = "1")]
+    pub address: String,
+    #[prost(uint32, tag = "2")]
+    pub prefix_length: u32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ComponentInfo {
+    #[prost(string, tag = "1")]
+    pub label: String,
+    #[prost(float, optional, tag = "2")]
+    pub temperature: Option<f32>,
+    #[prost(float, optional, tag = "3")]
+    pub max_temperature: Option<f32>,
+    #[prost(float, optional, tag = "4")]
+    pub critical_temperature: Option<f32>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct NeighborInfo {
+    #[prost(string, tag = "1")]
+    pub ip_address: String,
+    #[prost(string, optional, tag = "2")]
+    pub mac_address: Option<String>,
+    #[prost(string, tag = "3")]
+    pub state: String,
+    #[prost(string, tag = "4")]
+    pub interface_name: String,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct HwmonDevice {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub device: String,
+    #[prost(message, repeated, tag = "3")]
+    pub readings: Vec<HwmonReading>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct HwmonReading {
+    #[prost(string, tag = "1")]
+    pub kind: String,
+    #[prost(string, tag = "2")]
+    pub label: String,
+    #[prost(float, tag = "3")]
+    pub value: f32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct CoolingDeviceInfo {
+    #[prost(string, tag = "1")]
+    pub description: String,
+    #[prost(string, tag = "2")]
+    pub bus: String,
+    #[prost(string, tag = "3")]
+    pub address: String,
+    #[prost(float, optional, tag = "4")]
+    pub coolant_temperature: Option<f32>,
+    #[prost(float, optional, tag = "5")]
+    pub pump_speed: Option<f32>,
+    #[prost(float, optional, tag = "6")]
+    pub pump_duty: Option<f32>,
+    #[prost(float, optional, tag = "7")]
+    pub flow: Option<f32>,
+    #[prost(message, repeated, tag = "8")]
+    pub fans: Vec<CoolingFanInfo>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct CoolingFanInfo {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(float, optional, tag = "2")]
+    pub speed: Option<f32>,
+    #[prost(float, optional, tag = "3")]
+    pub duty: Option<f32>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProxiedDevice {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub address: String,
+    #[prost(bool, tag = "3")]
+    pub reachable: bool,
+    #[prost(message, repeated, tag = "4")]
+    pub readings: Vec<ProxiedReading>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ProxiedReading {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub oid: String,
+    #[prost(double, optional, tag = "3")]
+    pub value: Option<f64>,
+    #[prost(string, optional, tag = "4")]
+    pub unit: Option<String>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct RemoteHostInfo {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(string, tag = "2")]
+    pub address: String,
+    #[prost(string, tag = "3")]
+    pub source: String,
+    #[prost(bool, tag = "4")]
+    pub reachable: bool,
+    #[prost(message, repeated, tag = "5")]
+    pub sensors: Vec<RemoteSensorReading>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct FanInfo {
+    #[prost(string, tag = "1")]
+    pub adapter_name: String,
+    #[prost(string, tag = "2")]
+    pub label: String,
+    #[prost(float, tag = "3")]
+    pub speed: f32,
+    #[prost(float, optional, tag = "4")]
+    pub min_speed: Option<f32>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct VoltageInfo {
+    #[prost(string, tag = "1")]
+    pub adapter_name: String,
+    #[prost(string, tag = "2")]
+    pub label: String,
+    #[prost(float, tag = "3")]
+    pub voltage: f32,
+    #[prost(float, optional, tag = "4")]
+    pub min_voltage: Option<f32>,
+    #[prost(float, optional, tag = "5")]
+    pub max_voltage: Option<f32>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct PowerInfo {
+    #[prost(string, tag = "1")]
+    pub adapter_name: String,
+    #[prost(string, tag = "2")]
+    pub label: String,
+    #[prost(float, tag = "3")]
+    pub power: f32,
+    #[prost(double, optional, tag = "4")]
+    pub energy: Option<f64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct GpuInfo {
+    #[prost(string, tag = "1")]
+    pub card: String,
+    #[prost(string, tag = "2")]
+    pub vendor: String,
+    #[prost(string, optional, tag = "3")]
+    pub name: Option<String>,
+    #[prost(string, optional, tag = "4")]
+    pub pci_address: Option<String>,
+    #[prost(float, optional, tag = "5")]
+    pub temperature: Option<f32>,
+    #[prost(float, optional, tag = "6")]
+    pub junction_temperature: Option<f32>,
+    #[prost(float, optional, tag = "7")]
+    pub fan_speed: Option<f32>,
+    #[prost(float, optional, tag = "8")]
+    pub power: Option<f32>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct SmartInfo {
+    #[prost(string, tag = "1")]
+    pub device: String,
+    #[prost(string, optional, tag = "2")]
+    pub model: Option<String>,
+    #[prost(string, optional, tag = "3")]
+    pub serial_number: Option<String>,
+    #[prost(string, tag = "4")]
+    pub health: String,
+    #[prost(uint64, optional, tag = "5")]
+    pub reallocated_sectors: Option<u64>,
+    #[prost(uint64, optional, tag = "6")]
+    pub power_on_hours: Option<u64>,
+    #[prost(float, optional, tag = "7")]
+    pub temperature: Option<f32>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct RemoteSensorReading {
+    #[prost(string, tag = "1")]
+    pub name: String,
+    #[prost(double, optional, tag = "2")]
+    pub value: Option<f64>,
+    #[prost(string, tag = "3")]
+    pub unit: String,
+    #[prost(string, tag = "4")]
+    pub status: String,
+    #[prost(double, optional, tag = "5")]
+    pub upper_non_critical: Option<f64>,
+    #[prost(double, optional, tag = "6")]
+    pub upper_critical: Option<f64>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ThermalReading {
+    #[prost(string, tag = "1")]
+    pub source: String,
+    #[prost(float, tag = "2")]
+    pub temperature: f32,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct ThermalDisagreement {
+    #[prost(string, tag = "1")]
+    pub package_id: String,
+    #[prost(string, tag = "2")]
+    pub primary_source: String,
+    #[prost(float, tag = "3")]
+    pub spread: f32,
+    #[prost(message, repeated, tag = "4")]
+    pub readings: Vec<ThermalReading>,
+}
+
+#[derive(Clone, PartialEq, Message)]
+pub struct HostInventory {
+    #[prost(string, tag = "1")]
+    pub hostname: String,
+    #[prost(string, tag = "2")]
+    pub management_ip: String,
+    #[prost(string, tag = "3")]
+    pub os_name: String,
+    #[prost(string, tag = "4")]
+    pub os_version: String,
+    #[prost(string, tag = "5")]
+    pub kernel_version: String,
+    #[prost(uint64, tag = "6")]
+    pub boot_time: u64,
+    #[prost(string, tag = "7")]
+    pub cpu_arch: String,
+    #[prost(uint64, tag = "8")]
+    pub core_count: u64,
+    #[prost(uint64, tag = "9")]
+    pub cpu_package_count: u64,
+    #[prost(uint64, tag = "10")]
+    pub memory_total: u64,
+    #[prost(uint64, tag = "11")]
+    pub swap_total: u64,
+    #[prost(string, repeated, tag = "12")]
+    pub disk_names: Vec<String>,
+    #[prost(string, repeated, tag = "13")]
+    pub network_interface_names: Vec<String>,
+}
+
+impl From<&models::SensorData> for SensorData {
+    fn from(data: &models::SensorData) -> Self {
+        Self {
+            schema_version: data.schema_version,
+            system_info: Some(SystemInfo {
+                hostname: data.system_info.hostname.clone(),
+                uptime: Some((&data.system_info.uptime).into()),
+                management_ip: data.system_info.management_ip.clone(),
+                rebooted_since_last_report: data.system_info.rebooted_since_last_report,
+                public_ip: data.system_info.public_ip.clone(),
+            }),
+            cpu_info: Some(CpuInfo {
+                usage_per_core: data
+                    .cpu_info
+                    .usage_per_core
+                    .iter()
+                    .map(|usage| usage.0)
+                    .collect(),
+                core_count: data.cpu_info.core_count as u64,
+                cpu_arch: data.cpu_info.cpu_arch.clone(),
+                container_cpu_limit: data.cpu_info.container_cpu_limit,
+            }),
+            cpu_packages: data.cpu_packages.iter().map(Into::into).collect(),
+            memory_info: Some(MemoryInfo {
+                total: data.memory_info.total.0,
+                used: data.memory_info.used.0,
+                total_swap: data.memory_info.total_swap.0,
+                used_swap: data.memory_info.used_swap.0,
+                container_limit: data.memory_info.container_limit.map(|value| value.0),
+                container_used: data.memory_info.container_used.map(|value| value.0),
+            }),
+            disks: data.disks.iter().map(Into::into).collect(),
+            network_interfaces: data.network_interfaces.iter().map(Into::into).collect(),
+            components: data.components.iter().map(Into::into).collect(),
+            neighbors: data.neighbors.iter().flatten().map(Into::into).collect(),
+            thermal_disagreements: data
+                .thermal_disagreements
+                .iter()
+                .flatten()
+                .map(Into::into)
+                .collect(),
+            inventory: data.inventory.as_ref().map(Into::into),
+            inventory_hash: data.inventory_hash.clone(),
+            agent_id: data.agent_id.clone(),
+            tenant_id: data.tenant_id.clone(),
+            site_id: data.site_id.clone(),
+            hwmon_devices: data
+                .hwmon_devices
+                .iter()
+                .flatten()
+                .map(Into::into)
+                .collect(),
+            cooling_devices: data
+                .cooling_devices
+                .iter()
+                .flatten()
+                .map(Into::into)
+                .collect(),
+            proxied_devices: data
+                .proxied_devices
+                .iter()
+                .flatten()
+                .map(Into::into)
+                .collect(),
+            remote_hosts: data.remote_hosts.iter().flatten().map(Into::into).collect(),
+            smart_devices: data
+                .smart_devices
+                .iter()
+                .flatten()
+                .map(Into::into)
+                .collect(),
+            gpus: data.gpus.iter().flatten().map(Into::into).collect(),
+            fans: data.fans.iter().flatten().map(Into::into).collect(),
+            voltages: data.voltages.iter().flatten().map(Into::into).collect(),
+            power: data.power.iter().flatten().map(Into::into).collect(),
+            section_status: data
+                .section_status
+                .iter()
+                .map(|(section, status)| (section.clone(), status.as_str().to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl From<&models::Uptime> for Uptime {
+    fn from(uptime: &models::Uptime) -> Self {
+        Self {
+            days: uptime.days,
+            hours: uptime.hours,
+            minutes: uptime.minutes,
+            seconds: uptime.seconds,
+            total_seconds: uptime.total_seconds,
+            boot_time: uptime.boot_time,
+        }
+    }
+}
+
+impl From<&models::CpuPackageData> for CpuPackageData {
+    fn from(package: &models::CpuPackageData) -> Self {
+        Self {
+            package_id: package.package_id.clone(),
+            adapter_name: package.adapter_name.clone(),
+            package_temperature: package.package_temperature.0,
+            high_threshold: package.high_threshold.0,
+            critical_threshold: package.critical_threshold.0,
+            cores: package.cores.iter().map(Into::into).collect(),
+        }
+    }
+}
+
+impl From<&models::CpuCoreData> for CpuCoreData {
+    fn from(core: &models::CpuCoreData) -> Self {
+        Self {
+            core_name: core.core_name.clone(),
+            temperature: core.temperature.0,
+            high_threshold: core.high_threshold.0,
+            critical_threshold: core.critical_threshold.0,
+        }
+    }
+}
+
+impl From<&models::DiskInfo> for DiskInfo {
+    fn from(disk: &models::DiskInfo) -> Self {
+        Self {
+            name: disk.name.clone(),
+            total_space: disk.total_space.0,
+            available_space: disk.available_space.0,
+            read_bytes: disk.read_bytes.0,
+            written_bytes: disk.written_bytes.0,
+            read_bytes_per_sec: disk.read_bytes_per_sec,
+            written_bytes_per_sec: disk.written_bytes_per_sec,
+            counter_reset: disk.counter_reset,
+        }
+    }
+}
+
+impl From<&models::NetworkInfo> for NetworkInfo {
+    fn from(network: &models::NetworkInfo) -> Self {
+        Self {
+            interface_name: network.interface_name.clone(),
+            received: network.received.0,
+            transmitted: network.transmitted.0,
+            mtu: network.mtu,
+            received_per_sec: network.received_per_sec,
+            transmitted_per_sec: network.transmitted_per_sec,
+            counter_reset: network.counter_reset,
+            mac_address: network.mac_address.clone(),
+            addresses: network
+                .addresses
+                .iter()
+                .map(|address| InterfaceAddress {
+                    address: address.address.to_string(),
+                    prefix_length: address.prefix_length.into(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&models::ComponentInfo> for ComponentInfo {
+    fn from(component: &models::ComponentInfo) -> Self {
+        Self {
+            label: component.label.clone(),
+            temperature: component.temperature.map(|value| value.0),
+            max_temperature: component.max_temperature.map(|value| value.0),
+            critical_temperature: component.critical_temperature.map(|value| value.0),
+        }
+    }
+}
+
+impl From<&models::NeighborInfo> for NeighborInfo {
+    fn from(neighbor: &models::NeighborInfo) -> Self {
+        Self {
+            ip_address: neighbor.ip_address.clone(),
+            mac_address: neighbor.mac_address.clone(),
+            state: neighbor.state.clone(),
+            interface_name: neighbor.interface_name.clone(),
+        }
+    }
+}
+
+impl From<&models::HwmonDevice> for HwmonDevice {
+    fn from(device: &models::HwmonDevice) -> Self {
+        Self {
+            name: device.name.clone(),
+            device: device.device.clone(),
+            readings: device
+                .readings
+                .iter()
+                .map(|reading| HwmonReading {
+                    kind: reading.kind.as_str().to_string(),
+                    label: reading.label.clone(),
+                    value: reading.value,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&models::CoolingDeviceInfo> for CoolingDeviceInfo {
+    fn from(device: &models::CoolingDeviceInfo) -> Self {
+        Self {
+            description: device.description.clone(),
+            bus: device.bus.clone(),
+            address: device.address.clone(),
+            coolant_temperature: device.coolant_temperature.map(|value| value.0),
+            pump_speed: device.pump_speed,
+            pump_duty: device.pump_duty.map(|value| value.0),
+            flow: device.flow,
+            fans: device
+                .fans
+                .iter()
+                .map(|fan| CoolingFanInfo {
+                    name: fan.name.clone(),
+                    speed: fan.speed,
+                    duty: fan.duty.map(|value| value.0),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&models::ProxiedDevice> for ProxiedDevice {
+    fn from(device: &models::ProxiedDevice) -> Self {
+        Self {
+            name: device.name.clone(),
+            address: device.address.clone(),
+            reachable: device.reachable,
+            readings: device
+                .readings
+                .iter()
+                .map(|reading| ProxiedReading {
+                    name: reading.name.clone(),
+                    oid: reading.oid.clone(),
+                    value: reading.value,
+                    unit: reading.unit.clone(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&models::RemoteHostInfo> for RemoteHostInfo {
+    fn from(host: &models::RemoteHostInfo) -> Self {
+        Self {
+            name: host.name.clone(),
+            address: host.address.clone(),
+            source: host.source.clone(),
+            reachable: host.reachable,
+            sensors: host
+                .sensors
+                .iter()
+                .map(|sensor| RemoteSensorReading {
+                    name: sensor.name.clone(),
+                    value: sensor.value,
+                    unit: sensor.unit.clone(),
+                    status: sensor.status.clone(),
+                    upper_non_critical: sensor.upper_non_critical,
+                    upper_critical: sensor.upper_critical,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&models::FanInfo> for FanInfo {
+    fn from(fan: &models::FanInfo) -> Self {
+        Self {
+            adapter_name: fan.adapter_name.clone(),
+            label: fan.label.clone(),
+            speed: fan.speed,
+            min_speed: fan.min_speed,
+        }
+    }
+}
+
+impl From<&models::VoltageInfo> for VoltageInfo {
+    fn from(rail: &models::VoltageInfo) -> Self {
+        Self {
+            adapter_name: rail.adapter_name.clone(),
+            label: rail.label.clone(),
+            voltage: rail.voltage,
+            min_voltage: rail.min_voltage,
+            max_voltage: rail.max_voltage,
+        }
+    }
+}
+
+impl From<&models::PowerInfo> for PowerInfo {
+    fn from(reading: &models::PowerInfo) -> Self {
+        Self {
+            adapter_name: reading.adapter_name.clone(),
+            label: reading.label.clone(),
+            power: reading.power,
+            energy: reading.energy,
+        }
+    }
+}
+
+impl From<&models::GpuInfo> for GpuInfo {
+    fn from(gpu: &models::GpuInfo) -> Self {
+        Self {
+            card: gpu.card.clone(),
+            vendor: gpu.vendor.clone(),
+            name: gpu.name.clone(),
+            pci_address: gpu.pci_address.clone(),
+            temperature: gpu.temperature.map(|value| value.0),
+            junction_temperature: gpu.junction_temperature.map(|value| value.0),
+            fan_speed: gpu.fan_speed,
+            power: gpu.power,
+        }
+    }
+}
+
+impl From<&models::SmartInfo> for SmartInfo {
+    fn from(info: &models::SmartInfo) -> Self {
+        Self {
+            device: info.device.clone(),
+            model: info.model.clone(),
+            serial_number: info.serial_number.clone(),
+            health: info.health.as_str().to_string(),
+            reallocated_sectors: info.reallocated_sectors,
+            power_on_hours: info.power_on_hours,
+            temperature: info.temperature.map(|value| value.0),
+        }
+    }
+}
+
+impl From<&models::ThermalDisagreement> for ThermalDisagreement {
+    fn from(disagreement: &models::ThermalDisagreement) -> Self {
+        Self {
+            package_id: disagreement.package_id.clone(),
+            primary_source: disagreement.primary_source.clone(),
+            spread: disagreement.spread,
+            readings: disagreement
+                .readings
+                .iter()
+                .map(|reading| ThermalReading {
+                    source: reading.source.clone(),
+                    temperature: reading.temperature.0,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&models::SensorDataBatch> for SensorDataBatch {
+    fn from(batch: &models::SensorDataBatch) -> Self {
+        Self {
+            schema_version: batch.schema_version,
+            samples: batch
+                .samples
+                .iter()
+                .map(|sample| TimestampedSensorData {
+                    timestamp_ms: sample.timestamp_ms,
+                    data: Some(SensorData::from(&sample.data)),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl From<&models::HostInventory> for HostInventory {
+    fn from(inventory: &models::HostInventory) -> Self {
+        Self {
+            hostname: inventory.hostname.clone(),
+            management_ip: inventory.management_ip.clone(),
+            os_name: inventory.os_name.clone(),
+            os_version: inventory.os_version.clone(),
+            kernel_version: inventory.kernel_version.clone(),
+            boot_time: inventory.boot_time,
+            cpu_arch: inventory.cpu_arch.clone(),
+            core_count: inventory.core_count as u64,
+            cpu_package_count: inventory.cpu_package_count as u64,
+            memory_total: inventory.memory_total.0,
+            swap_total: inventory.swap_total.0,
+            disk_names: inventory.disk_names.clone(),
+            network_interface_names: inventory.network_interface_names.clone(),
+        }
+    }
+}
+
+/// Encodes a `SensorData` DTO as a protobuf message.
+pub fn encode_sensor_data(data: &models::SensorData) -> Vec<u8> {
+    SensorData::from(data).encode_to_vec()
+}
+
+/// Encodes a `SensorDataBatch` DTO as a protobuf message.
+pub fn encode_sensor_data_batch(batch: &models::SensorDataBatch) -> Vec<u8> {
+    SensorDataBatch::from(batch).encode_to_vec()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::sample_sensor_data;
+
+    #[test]
+    fn sensor_data_decodes_back_from_the_wire() {
+        let mut data = sample_sensor_data();
+        data.fans = Some(vec![models::FanInfo {
+            adapter_name: "nct6798-isa-0290".to_string(),
+            label: "CPU Fan".to_string(),
+            speed: 1200.0,
+            min_speed: None,
+        }]);
+
+        let decoded = SensorData::decode(encode_sensor_data(&data).as_slice()).unwrap();
+        assert_eq!(decoded, SensorData::from(&data));
+
+        let system_info = decoded.system_info.unwrap();
+        assert_eq!(system_info.hostname, "sentinel-test");
+        assert_eq!(system_info.uptime.unwrap().total_seconds, 93784);
+        assert_eq!(decoded.cpu_info.unwrap().usage_per_core, [12.5, 3.0]);
+        let package = &decoded.cpu_packages[0];
+        assert_eq!(
+            (package.package_id.as_str(), package.package_temperature),
+            ("0", 45.0)
+        );
+        assert_eq!(package.cores[0].core_name, "Core 0");
+        assert_eq!(decoded.memory_info.unwrap().total, 16_000_000_000);
+        assert_eq!(decoded.neighbors[0].ip_address, "192.168.1.1");
+        assert_eq!(decoded.fans[0].speed, 1200.0);
+        assert_eq!(decoded.fans[0].min_speed, None);
+        // Absent optional sections encode as empty lists.
+        assert!(decoded.gpus.is_empty());
+        assert_eq!(decoded.agent_id, None);
+    }
+
+    #[test]
+    fn batches_keep_sample_timestamps() {
+        let batch = models::SensorDataBatch {
+            schema_version: models::SCHEMA_VERSION,
+            samples: vec![models::TimestampedSensorData {
+                timestamp_ms: 1_700_000_000_000,
+                data: sample_sensor_data(),
+            }],
+        };
+
+        let decoded = SensorDataBatch::decode(encode_sensor_data_batch(&batch).as_slice()).unwrap();
+        assert_eq!(decoded.schema_version, models::SCHEMA_VERSION);
+        assert_eq!(decoded.samples[0].timestamp_ms, 1_700_000_000_000);
+        assert_eq!(
+            decoded.samples[0].data,
+            Some(SensorData::from(&sample_sensor_data()))
+        );
+    }
+}
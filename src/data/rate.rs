@@ -0,0 +1,225 @@
+//! Counter Rates
+//!
+//! Network and disk counters are cumulative, but they reset when a driver is reloaded or an
+//! interface is recreated, and wrap at 2^32 on some 32-bit kernels. A naive difference
+//! turns either into an absurd spike. This module computes per-second rates from
+//! consecutive readings, corrects 32-bit wraps, and marks the first sample after a reset
+//! as invalid (no rate, `counter_reset` set) instead of emitting a garbage value. Counters
+//! not read in a cycle (a removed interface or disk) are forgotten.
+
+use log::debug;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::data::models::{DiskInfo, NetworkInfo};
+
+/// Range of a 32-bit counter.
+const U32_RANGE: u64 = 1 << 32;
+/// A drop is a 32-bit wrap only if the previous reading was in the top quarter of the
+/// 32-bit range and the current one is in the bottom quarter; anything else is a reset.
+const WRAP_WINDOW: u64 = U32_RANGE / 4;
+
+/// Previous reading of every counter, by key.
+static PREVIOUS: Mutex<Option<HashMap<String, (Instant, u64)>>> = Mutex::new(None);
+
+/// Change of a counter between two readings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterDelta {
+    /// The counter increased by this amount.
+    Increase(u64),
+    /// The counter wrapped at 2^32; the amount is the corrected increase.
+    Wrapped(u64),
+    /// The counter restarted; the increase is unknown.
+    Reset,
+}
+
+/// The rate of one counter at one sample.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateSample {
+    /// Increase per second; `None` for the first sample and after a reset.
+    pub per_sec: Option<f64>,
+    /// Whether the counter reset since the previous sample.
+    pub reset: bool,
+}
+
+/// Static utility class for counter rate computation.
+pub struct RateUtil;
+
+impl RateUtil {
+    /// Classifies the change from `previous` to `current`.
+    pub fn counter_delta(previous: u64, current: u64) -> CounterDelta {
+        if current >= previous {
+            CounterDelta::Increase(current - previous)
+        } else if (U32_RANGE - WRAP_WINDOW..U32_RANGE).contains(&previous) && current < WRAP_WINDOW
+        {
+            CounterDelta::Wrapped(U32_RANGE - previous + current)
+        } else {
+            CounterDelta::Reset
+        }
+    }
+
+    /// Computes the per-second rate between two readings `elapsed_secs` apart.
+    ///
+    /// Returns `None` after a reset, or when no time (or negative time) has passed.
+    pub fn rate(previous: u64, current: u64, elapsed_secs: f64) -> Option<f64> {
+        if elapsed_secs.is_nan() || elapsed_secs <= 0.0 {
+            return None;
+        }
+        match Self::counter_delta(previous, current) {
+            CounterDelta::Increase(delta) | CounterDelta::Wrapped(delta) => {
+                Some(delta as f64 / elapsed_secs)
+            }
+            CounterDelta::Reset => None,
+        }
+    }
+
+    /// Records a reading of the counter `key` and returns its rate since the last reading.
+    pub fn sample(key: &str, value: u64, now: Instant) -> RateSample {
+        let mut guard = PREVIOUS.lock().unwrap_or_else(|e| e.into_inner());
+        let previous = guard
+            .get_or_insert_with(HashMap::new)
+            .insert(key.to_string(), (now, value));
+
+        let Some((at, previous)) = previous else {
+            return RateSample {
+                per_sec: None,
+                reset: false,
+            };
+        };
+        let elapsed = now.saturating_duration_since(at).as_secs_f64();
+        match Self::counter_delta(previous, value) {
+            CounterDelta::Reset => {
+                debug!(
+                    "Counter {} reset ({} -> {}); skipping its rate for this sample.",
+                    key, previous, value
+                );
+                RateSample {
+                    per_sec: None,
+                    reset: true,
+                }
+            }
+            CounterDelta::Wrapped(_) => {
+                debug!("Counter {} wrapped at 2^32.", key);
+                RateSample {
+                    per_sec: Self::rate(previous, value, elapsed),
+                    reset: false,
+                }
+            }
+            CounterDelta::Increase(_) => RateSample {
+                per_sec: Self::rate(previous, value, elapsed),
+                reset: false,
+            },
+        }
+    }
+
+    /// Fills in the per-second rates of network interfaces and disks from their
+    /// cumulative counters.
+    pub fn apply(networks: &mut [NetworkInfo], disks: &mut [DiskInfo]) {
+        Self::apply_at(networks, disks, Instant::now());
+    }
+
+    fn apply_at(networks: &mut [NetworkInfo], disks: &mut [DiskInfo], now: Instant) {
+        for network in networks {
+            let name = &network.interface_name;
+            let received = Self::sample(&format!("net/{}/rx", name), network.total_received, now);
+            let transmitted =
+                Self::sample(&format!("net/{}/tx", name), network.total_transmitted, now);
+            network.received_per_sec = received.per_sec;
+            network.transmitted_per_sec = transmitted.per_sec;
+            network.counter_reset = received.reset || transmitted.reset;
+        }
+        for disk in disks {
+            let name = &disk.name;
+            let read = Self::sample(&format!("disk/{}/read", name), disk.total_read_bytes, now);
+            let written = Self::sample(
+                &format!("disk/{}/written", name),
+                disk.total_written_bytes,
+                now,
+            );
+            disk.read_bytes_per_sec = read.per_sec;
+            disk.written_bytes_per_sec = written.per_sec;
+            disk.counter_reset = read.reset || written.reset;
+        }
+
+        // Forget the counters of interfaces and disks that are gone.
+        let mut guard = PREVIOUS.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some(previous) = guard.as_mut() {
+            previous.retain(|_, (at, _)| *at == now);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn network(name: &str, received: u64) -> NetworkInfo {
+        NetworkInfo {
+            mac_address: None,
+            addresses: Vec::new(),
+            interface_name: name.to_string(),
+            received: Default::default(),
+            transmitted: Default::default(),
+            mtu: None,
+            total_received: received,
+            total_transmitted: 0,
+            received_per_sec: None,
+            transmitted_per_sec: None,
+            counter_reset: false,
+        }
+    }
+
+    #[test]
+    fn drops_in_the_wrap_window_are_wraps() {
+        assert_eq!(
+            RateUtil::counter_delta(U32_RANGE - 100, 50),
+            CounterDelta::Wrapped(150)
+        );
+        assert_eq!(
+            RateUtil::counter_delta(U32_RANGE - WRAP_WINDOW, WRAP_WINDOW - 1),
+            CounterDelta::Wrapped(2 * WRAP_WINDOW - 1)
+        );
+        assert_eq!(RateUtil::rate(U32_RANGE - 100, 900, 10.0), Some(100.0));
+    }
+
+    #[test]
+    fn other_drops_are_resets() {
+        // The previous reading was below the top quarter of the 32-bit range.
+        assert_eq!(
+            RateUtil::counter_delta(U32_RANGE - WRAP_WINDOW - 1, 50),
+            CounterDelta::Reset
+        );
+        // The current reading is above the bottom quarter.
+        assert_eq!(
+            RateUtil::counter_delta(U32_RANGE - 100, WRAP_WINDOW),
+            CounterDelta::Reset
+        );
+        // 64-bit counters never wrap at 2^32.
+        assert_eq!(
+            RateUtil::counter_delta(U32_RANGE + 100, 50),
+            CounterDelta::Reset
+        );
+        assert_eq!(RateUtil::rate(1_000, 10, 10.0), None);
+    }
+
+    #[test]
+    fn forgets_counters_that_were_not_read() {
+        let start = Instant::now();
+        let at = |secs| start + Duration::from_secs(secs);
+
+        let mut networks = [network("rate-test0", 1_000)];
+        RateUtil::apply_at(&mut networks, &mut [], at(0));
+        networks[0].total_received = 2_000;
+        RateUtil::apply_at(&mut networks, &mut [], at(10));
+        assert_eq!(networks[0].received_per_sec, Some(100.0));
+
+        // The interface disappears for a cycle and comes back.
+        RateUtil::apply_at(&mut [], &mut [], at(20));
+        networks[0].total_received = 5_000;
+        RateUtil::apply_at(&mut networks, &mut [], at(30));
+        assert_eq!(networks[0].received_per_sec, None);
+        assert!(!networks[0].counter_reset);
+    }
+}
@@ -0,0 +1,212 @@
+//! Payload Transformation Hooks
+//!
+//! Sites with quirks (a dashboard expecting other field names, a derived metric, a section
+//! that must never leave the network) can change payloads with a Rhai script instead of
+//! patching the agent. The script configured in `[transform]` must define
+//!
+//! ```rhai
+//! fn transform(payload, kind) {
+//!     payload.remove("network_interfaces");
+//!     payload.site = "attic";
+//!     payload
+//! }
+//! ```
+//!
+//! which receives every payload as a map, after field casing has been applied, together
+//! with its kind (`"sensors"`, `"heartbeat"`, ...), and returns the map to send. The hook
+//! runs for the self-describing formats only; protobuf and gRPC payloads follow a fixed
+//! schema and are sent unchanged. If the script cannot be loaded or fails on a payload,
+//! the payload is dropped, so a redaction never fails open; with `on_error = "send"` the
+//! original payload is sent instead.
+
+use log::{error, info, warn};
+use rhai::{Dynamic, Engine, Scope, AST};
+use serde::Deserialize;
+use serde_json::Value;
+use std::fs;
+use std::io;
+use std::sync::OnceLock;
+
+use crate::config::config_instance::Config;
+
+/// Configuration of the `[transform]` section; payloads are transformed when present.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct TransformConfig {
+    /// Path of the Rhai script defining `fn transform(payload, kind)`.
+    pub script: String,
+    /// Upper bound on the operations a single call may run, so a runaway loop cannot stall
+    /// reporting.
+    pub max_operations: u64,
+    /// What happens to a payload the script could not transform.
+    pub on_error: TransformFailure,
+}
+
+impl Default for TransformConfig {
+    fn default() -> Self {
+        Self {
+            script: String::new(),
+            max_operations: 100_000,
+            on_error: TransformFailure::Drop,
+        }
+    }
+}
+
+/// Handling of payloads the script could not transform.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TransformFailure {
+    /// Drop the payload (fail closed).
+    #[default]
+    Drop,
+    /// Send the original payload (fail open).
+    Send,
+}
+
+/// A compiled transformation script.
+pub struct PayloadTransform {
+    engine: Engine,
+    ast: AST,
+}
+
+/// The configured script, compiled on first use, or why it could not be loaded.
+static TRANSFORM: OnceLock<Result<PayloadTransform, String>> = OnceLock::new();
+
+impl PayloadTransform {
+    /// Whether a `[transform]` section is configured.
+    pub fn is_configured() -> bool {
+        Config::get().transform.is_some()
+    }
+
+    /// Runs the configured script on a payload of the given kind, compiling it on first
+    /// use. Fails with `InvalidData` if the payload is to be dropped (see `apply`), which
+    /// includes every payload when the script cannot be loaded.
+    pub fn run(kind: &str, payload: Value) -> io::Result<Value> {
+        let Some(config) = &Config::get().transform else {
+            return Ok(payload);
+        };
+        let transform = TRANSFORM.get_or_init(|| {
+            let transform = fs::read_to_string(&config.script)
+                .map_err(|e| format!("Failed to read transform script {}: {}", config.script, e))
+                .and_then(|source| {
+                    Self::compile(&source, config.max_operations)
+                        .map_err(|e| format!("Invalid transform script {}: {}", config.script, e))
+                });
+            match &transform {
+                Ok(_) => info!("Transforming payloads with {}.", config.script),
+                Err(e) => error!("{}", e),
+            }
+            transform
+        });
+        match transform {
+            Ok(transform) => transform.apply(kind, payload, config.on_error),
+            Err(e) => Self::fail(kind, e, payload, config.on_error),
+        }
+    }
+
+    /// Compiles a script, which must define `transform(payload, kind)`.
+    pub fn compile(source: &str, max_operations: u64) -> Result<Self, String> {
+        let mut engine = Engine::new();
+        engine.set_max_operations(max_operations);
+        let ast = engine.compile(source).map_err(|e| e.to_string())?;
+        if !ast
+            .iter_functions()
+            .any(|f| f.name == "transform" && f.params.len() == 2)
+        {
+            return Err("script does not define fn transform(payload, kind)".to_string());
+        }
+        Ok(Self { engine, ast })
+    }
+
+    /// Runs the script on a payload of the given kind. If the script fails, the payload is
+    /// dropped with an `InvalidData` error, or returned unchanged with `TransformFailure::Send`.
+    pub fn apply(
+        &self,
+        kind: &str,
+        payload: Value,
+        on_error: TransformFailure,
+    ) -> io::Result<Value> {
+        match self.call(kind, &payload) {
+            Ok(transformed) => Ok(transformed),
+            Err(e) => Self::fail(kind, &e, payload, on_error),
+        }
+    }
+
+    fn fail(
+        kind: &str,
+        error: &str,
+        payload: Value,
+        on_error: TransformFailure,
+    ) -> io::Result<Value> {
+        match on_error {
+            TransformFailure::Send => {
+                warn!(
+                    "Transform failed on a {} payload: {}; sending it unchanged.",
+                    kind, error
+                );
+                Ok(payload)
+            }
+            TransformFailure::Drop => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "Transform failed on a {} payload: {}; dropping it",
+                    kind, error
+                ),
+            )),
+        }
+    }
+
+    fn call(&self, kind: &str, payload: &Value) -> Result<Value, String> {
+        let payload = rhai::serde::to_dynamic(payload).map_err(|e| e.to_string())?;
+        let result: Dynamic = self
+            .engine
+            .call_fn(
+                &mut Scope::new(),
+                &self.ast,
+                "transform",
+                (payload, kind.to_string()),
+            )
+            .map_err(|e| e.to_string())?;
+        rhai::serde::from_dynamic(&result).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn transforms_payloads_and_handles_failures() {
+        let transform = PayloadTransform::compile(
+            r#"
+            fn transform(payload, kind) {
+                if kind != "sensors" { throw "unexpected kind"; }
+                payload.remove("disks");
+                payload.max_temp = payload.cpu_packages.reduce(|max, p| max.max(p.temp), 0.0);
+                payload
+            }
+            "#,
+            10_000,
+        )
+        .unwrap();
+
+        let payload = json!({"disks": [], "cpu_packages": [{"temp": 51.5}, {"temp": 63.0}]});
+        assert_eq!(
+            transform
+                .apply("sensors", payload.clone(), TransformFailure::Drop)
+                .unwrap(),
+            json!({"cpu_packages": [{"temp": 51.5}, {"temp": 63.0}], "max_temp": 63.0})
+        );
+        let dropped = transform.apply("heartbeat", payload.clone(), TransformFailure::Drop);
+        assert_eq!(dropped.unwrap_err().kind(), io::ErrorKind::InvalidData);
+        assert_eq!(
+            transform
+                .apply("heartbeat", payload.clone(), TransformFailure::Send)
+                .unwrap(),
+            payload
+        );
+
+        assert!(PayloadTransform::compile("fn other(x) { x }", 10_000).is_err());
+    }
+}
@@ -0,0 +1,70 @@
+//! Units
+//!
+//! Newtypes for measured quantities, so a value cannot be mixed up with a number of a
+//! different unit: a threshold in °C cannot be compared to a raw digital thermal readout,
+//! and a byte count cannot be passed where a percentage is expected. They serialize as the
+//! bare number, so the wire format is unchanged.
+
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// A temperature in degrees Celsius.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Celsius(pub f32);
+
+/// A size or amount of data in bytes.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Bytes(pub u64);
+
+/// A percentage, 0 to 100.
+#[derive(Debug, Clone, Copy, Default, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Percent(pub f32);
+
+impl Celsius {
+    /// Whether the value is a usable threshold; sensors report 0 for unknown thresholds.
+    pub fn is_known(self) -> bool {
+        self.0 > 0.0
+    }
+}
+
+impl Bytes {
+    /// `self` as a percentage of `total`; 0 when `total` is 0.
+    pub fn percent_of(self, total: Bytes) -> Percent {
+        if total.0 == 0 {
+            return Percent(0.0);
+        }
+        Percent((self.0 as f64 / total.0 as f64 * 100.0) as f32)
+    }
+
+    pub fn saturating_sub(self, other: Bytes) -> Bytes {
+        Bytes(self.0.saturating_sub(other.0))
+    }
+}
+
+// Display formats the number with the caller's options (e.g. `{:.1}`) and appends the unit.
+
+impl fmt::Display for Celsius {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)?;
+        f.write_str("°C")
+    }
+}
+
+impl fmt::Display for Bytes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)?;
+        f.write_str(" B")
+    }
+}
+
+impl fmt::Display for Percent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)?;
+        f.write_str("%")
+    }
+}
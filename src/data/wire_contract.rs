@@ -0,0 +1,87 @@
+//! Wire-Format Contract Tests
+//!
+//! Serializes representative DTOs and compares them against the checked-in golden
+//! JSON under `tests/golden/v<schema_version>/`, so refactors cannot silently change
+//! what the server receives. When a change to the wire format is intentional, bump
+//! `SCHEMA_VERSION` and add golden files for the new version instead of editing old ones.
+#![cfg(test)]
+
+use serde_json::Value;
+
+use crate::data::casing::FieldCasing;
+use crate::data::models::{SensorDataBatch, TimestampedSensorData, SCHEMA_VERSION};
+use crate::test_util::sample_sensor_data;
+
+/// Golden payloads per supported schema version: (version, snake_case, camelCase).
+const GOLDEN: &[(u32, &str, &str)] = &[
+    (
+        1,
+        include_str!("../../tests/golden/v1/sensor_data.snake_case.json"),
+        include_str!("../../tests/golden/v1/sensor_data.camelCase.json"),
+    ),
+    (
+        2,
+        include_str!("../../tests/golden/v2/sensor_data.snake_case.json"),
+        include_str!("../../tests/golden/v2/sensor_data.camelCase.json"),
+    ),
+];
+
+fn golden_for(version: u32) -> (Value, Value) {
+    let (_, snake, camel) = GOLDEN
+        .iter()
+        .find(|(golden_version, _, _)| *golden_version == version)
+        .unwrap_or_else(|| panic!("no golden files for schema version {}", version));
+    (
+        serde_json::from_str(snake).expect("invalid snake_case golden JSON"),
+        serde_json::from_str(camel).expect("invalid camelCase golden JSON"),
+    )
+}
+
+#[test]
+fn golden_files_exist_for_every_supported_version() {
+    for (version, snake, camel) in GOLDEN {
+        let snake: Value = serde_json::from_str(snake).unwrap();
+        let camel: Value = serde_json::from_str(camel).unwrap();
+        assert_eq!(snake["schema_version"], *version);
+        assert_eq!(camel["schemaVersion"], *version);
+    }
+}
+
+#[test]
+fn sensor_data_matches_snake_case_golden() {
+    let (expected, _) = golden_for(SCHEMA_VERSION);
+    let actual = FieldCasing::SnakeCase
+        .to_value(&sample_sensor_data())
+        .unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn sensor_data_matches_camel_case_golden() {
+    let (_, expected) = golden_for(SCHEMA_VERSION);
+    let actual = FieldCasing::CamelCase
+        .to_value(&sample_sensor_data())
+        .unwrap();
+    assert_eq!(actual, expected);
+}
+
+#[test]
+fn batched_samples_match_the_golden() {
+    let (snake, camel) = golden_for(SCHEMA_VERSION);
+    let batch = SensorDataBatch {
+        schema_version: SCHEMA_VERSION,
+        samples: vec![TimestampedSensorData {
+            timestamp_ms: 1_700_000_000_000,
+            data: sample_sensor_data(),
+        }],
+    };
+
+    let actual = FieldCasing::SnakeCase.to_value(&batch).unwrap();
+    assert_eq!(actual["schema_version"], SCHEMA_VERSION);
+    assert_eq!(actual["samples"][0]["timestamp_ms"], 1_700_000_000_000u64);
+    assert_eq!(actual["samples"][0]["data"], snake);
+
+    let actual = FieldCasing::CamelCase.to_value(&batch).unwrap();
+    assert_eq!(actual["samples"][0]["timestampMs"], 1_700_000_000_000u64);
+    assert_eq!(actual["samples"][0]["data"], camel);
+}
@@ -0,0 +1,272 @@
+//! Fake System Source
+//!
+//! A `SystemSource` returning canned readings, for testing DTO building and filtering
+//! without a real host. Refreshes are counted so tests can check that readings are
+//! refreshed before use.
+#![cfg(test)]
+
+use crate::data::models::{
+    ComponentInfo, CpuCoreData, CpuInfo, CpuPackageData, DiskInfo, InterfaceAddress, MemoryInfo,
+    NetworkInfo, ProcessInfo, SectionStatus, Uptime,
+};
+use crate::data::units::{Bytes, Celsius, Percent};
+use crate::hardware::system_information_monitor::SysInfoMonitor;
+use crate::hardware::system_source::SystemSource;
+use crate::sensor::sensor_util::SensorUtils;
+use std::net::IpAddr;
+
+/// Canned system readings.
+pub struct FakeSystem {
+    pub host_name: String,
+    pub total_memory: u64,
+    pub used_memory: u64,
+    pub usage_per_core: Vec<f32>,
+    pub uptime_secs: u64,
+    pub boot_time: u64,
+    pub disks: Vec<(String, u64, u64)>,
+    pub networks: Vec<(String, u64, u64)>,
+    pub supported: bool,
+    pub system_refreshes: u32,
+    pub disk_refreshes: u32,
+    pub network_refreshes: u32,
+    pub component_refreshes: u32,
+}
+
+impl Default for FakeSystem {
+    fn default() -> Self {
+        Self {
+            host_name: "fake-host".to_string(),
+            total_memory: 16 * 1024 * 1024 * 1024,
+            used_memory: 4 * 1024 * 1024 * 1024,
+            usage_per_core: vec![12.5, 50.0],
+            uptime_secs: 93_784,
+            boot_time: 1_700_000_000,
+            disks: vec![("/dev/sda1".to_string(), 500, 200)],
+            networks: vec![("eth0".to_string(), 1_000, 2_000)],
+            supported: true,
+            system_refreshes: 0,
+            disk_refreshes: 0,
+            network_refreshes: 0,
+            component_refreshes: 0,
+        }
+    }
+}
+
+impl SystemSource for FakeSystem {
+    fn refresh_system(&mut self) {
+        self.system_refreshes += 1;
+    }
+
+    fn refresh_disks(&mut self) {
+        self.disk_refreshes += 1;
+    }
+
+    fn refresh_networks(&mut self) {
+        self.network_refreshes += 1;
+    }
+
+    fn refresh_components(&mut self) {
+        self.component_refreshes += 1;
+    }
+
+    fn memory_info(&self) -> MemoryInfo {
+        MemoryInfo {
+            total: Bytes(self.total_memory),
+            used: Bytes(self.used_memory),
+            total_swap: Bytes(0),
+            used_swap: Bytes(0),
+            container_limit: None,
+            container_used: None,
+        }
+    }
+
+    fn cpu_info(&self) -> CpuInfo {
+        CpuInfo {
+            usage_per_core: self.usage_per_core.iter().copied().map(Percent).collect(),
+            core_count: self.usage_per_core.len(),
+            cpu_arch: "x86_64".to_string(),
+            container_cpu_limit: None,
+        }
+    }
+
+    fn disk_info(&self) -> Vec<DiskInfo> {
+        self.disks
+            .iter()
+            .map(|(name, total, available)| DiskInfo {
+                name: name.clone(),
+                total_space: Bytes(*total),
+                available_space: Bytes(*available),
+                read_bytes: Bytes(0),
+                written_bytes: Bytes(0),
+                total_read_bytes: 0,
+                total_written_bytes: 0,
+                read_bytes_per_sec: None,
+                written_bytes_per_sec: None,
+                counter_reset: false,
+            })
+            .collect()
+    }
+
+    fn network_info(&self) -> Vec<NetworkInfo> {
+        self.networks
+            .iter()
+            .map(|(name, received, transmitted)| NetworkInfo {
+                interface_name: name.clone(),
+                received: Bytes(*received),
+                transmitted: Bytes(*transmitted),
+                mtu: Some(1500),
+                mac_address: Some("52:54:00:12:34:56".to_string()),
+                addresses: vec![InterfaceAddress {
+                    address: IpAddr::from([192, 168, 1, 20]),
+                    prefix_length: 24,
+                }],
+                total_received: *received,
+                total_transmitted: *transmitted,
+                received_per_sec: None,
+                transmitted_per_sec: None,
+                counter_reset: false,
+            })
+            .collect()
+    }
+
+    fn process_info(&self) -> Vec<ProcessInfo> {
+        vec![ProcessInfo {
+            name: "init".to_string(),
+            pid: 1,
+            memory: 1024,
+        }]
+    }
+
+    fn components_info(&self) -> Vec<ComponentInfo> {
+        vec![ComponentInfo {
+            label: "acpitz temp1".to_string(),
+            temperature: Some(Celsius(27.5)),
+            max_temperature: None,
+            critical_temperature: Some(Celsius(119.0)),
+        }]
+    }
+
+    fn uptime(&self) -> Uptime {
+        Uptime::new(self.uptime_secs, self.boot_time)
+    }
+
+    fn os_name(&self) -> String {
+        "FakeOS".to_string()
+    }
+
+    fn os_version(&self) -> String {
+        "1.0".to_string()
+    }
+
+    fn kernel_version(&self) -> String {
+        "6.0.0-fake".to_string()
+    }
+
+    fn host_name(&self) -> String {
+        self.host_name.clone()
+    }
+
+    fn is_supported(&self) -> bool {
+        self.supported
+    }
+}
+
+fn sample_package() -> CpuPackageData {
+    CpuPackageData {
+        package_id: "0".to_string(),
+        adapter_name: "coretemp-isa-0000".to_string(),
+        package_temperature: Celsius(48.0),
+        high_threshold: Celsius(80.0),
+        critical_threshold: Celsius(100.0),
+        cores: vec![CpuCoreData {
+            core_name: "Core 0".to_string(),
+            temperature: Celsius(45.0),
+            high_threshold: Celsius(80.0),
+            critical_threshold: Celsius(100.0),
+        }],
+    }
+}
+
+#[test]
+fn monitor_refreshes_before_reading() {
+    let mut monitor = SysInfoMonitor::with_source(FakeSystem::default());
+    monitor.get_memory_info();
+    monitor.get_cpu_info();
+    monitor.get_disk_info();
+    monitor.get_network_info();
+    monitor.get_components_info();
+
+    let source = monitor.source();
+    assert_eq!(source.system_refreshes, 2);
+    assert_eq!(source.disk_refreshes, 1);
+    assert_eq!(source.network_refreshes, 1);
+    assert_eq!(source.component_refreshes, 1);
+}
+
+#[test]
+fn build_sensor_data_copies_readings() {
+    let mut monitor = SysInfoMonitor::with_source(FakeSystem::default());
+    let data = SensorUtils::build_sensor_data(&mut monitor, true, true, vec![sample_package()]);
+
+    assert_eq!(data.system_info.hostname, "fake-host");
+    assert_eq!(data.system_info.uptime.total_seconds, 93_784);
+    assert_eq!(data.system_info.uptime.days, 1);
+    assert_eq!(data.memory_info.total, Bytes(16 * 1024 * 1024 * 1024));
+    assert_eq!(data.cpu_info.core_count, 2);
+    assert_eq!(data.disks.len(), 1);
+    assert_eq!(data.disks[0].available_space, Bytes(200));
+    assert_eq!(data.network_interfaces[0].interface_name, "eth0");
+    assert_eq!(data.network_interfaces[0].addresses[0].prefix_length, 24);
+    assert_eq!(data.cpu_packages[0].cores[0].temperature, Celsius(45.0));
+    assert!(data.neighbors.is_none());
+    assert!(data.thermal_disagreements.is_none());
+}
+
+#[test]
+fn build_sensor_data_skips_disabled_collectors() {
+    let mut monitor = SysInfoMonitor::with_source(FakeSystem::default());
+    let data = SensorUtils::build_sensor_data(&mut monitor, false, false, Vec::new());
+
+    assert!(data.disks.is_empty());
+    assert!(data.network_interfaces.is_empty());
+    assert_eq!(monitor.source().disk_refreshes, 0);
+    assert_eq!(monitor.source().network_refreshes, 0);
+    assert_eq!(data.section_status["disks"], SectionStatus::Disabled);
+}
+
+#[test]
+fn build_sensor_data_marks_unreadable_sections_unsupported() {
+    let mut monitor = SysInfoMonitor::with_source(FakeSystem {
+        total_memory: 0,
+        ..FakeSystem::default()
+    });
+    let data = SensorUtils::build_sensor_data(&mut monitor, true, true, Vec::new());
+    assert_eq!(
+        data.section_status["memory_info"],
+        SectionStatus::Unsupported
+    );
+    assert!(!data.section_status.contains_key("disks"));
+
+    let mut monitor = SysInfoMonitor::with_source(FakeSystem {
+        supported: false,
+        ..FakeSystem::default()
+    });
+    let data = SensorUtils::build_sensor_data(&mut monitor, false, true, Vec::new());
+    assert_eq!(data.section_status["disks"], SectionStatus::Disabled);
+    assert_eq!(
+        data.section_status["network_interfaces"],
+        SectionStatus::Unsupported
+    );
+    assert!(data.network_interfaces.is_empty());
+}
+
+#[test]
+fn host_details_come_from_source() {
+    let monitor = SysInfoMonitor::with_source(FakeSystem {
+        host_name: "nas-01".to_string(),
+        ..FakeSystem::default()
+    });
+    assert_eq!(monitor.get_host_name(), "nas-01");
+    assert_eq!(monitor.get_os_name(), "FakeOS");
+    assert_eq!(monitor.get_kernel_version(), "6.0.0-fake");
+}
@@ -0,0 +1,250 @@
+//! GPU Sensors
+//!
+//! This module reports the temperature, fan speed and power draw of AMD GPUs. They are read
+//! from the hwmon directory the amdgpu driver exposes under `/sys/class/drm/cardN/device`,
+//! which needs no extra tooling. When no card can be read there (e.g. in a container without
+//! sysfs), `rocm-smi --json` is used instead if it is installed.
+
+use log::debug;
+use serde_json::Value;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::config::config_instance::Config;
+use crate::data::models::GpuInfo;
+use crate::data::units::Celsius;
+use crate::hardware::hwmon_util::HwmonUtil;
+
+/// Root of the DRM class in sysfs.
+const DRM_ROOT: &str = "/sys/class/drm";
+
+/// PCI vendor id of AMD.
+const AMD_VENDOR_ID: &str = "0x1002";
+
+/// The rocm-smi executable used as a fallback.
+const ROCM_SMI: &str = "rocm-smi";
+
+/// Set once rocm-smi turned out not to be installed, so it is not looked up every cycle.
+static ROCM_SMI_MISSING: AtomicBool = AtomicBool::new(false);
+
+/// A utility class for reading GPU sensors.
+pub struct GpuUtil;
+
+impl GpuUtil {
+    /// Reads every AMD GPU if `collect_gpus` is enabled.
+    ///
+    /// Returns `None` when the collector is disabled.
+    pub fn collect_if_enabled() -> Option<Vec<GpuInfo>> {
+        if !Config::get().collect_gpus {
+            return None;
+        }
+        let gpus = Self::discover_amd(Path::new(DRM_ROOT));
+        if !gpus.is_empty() || ROCM_SMI_MISSING.load(Ordering::Relaxed) {
+            return Some(gpus);
+        }
+        match Self::run_rocm_smi() {
+            Ok(output) => Some(Self::parse_rocm_smi(&output)),
+            Err(e) => {
+                if e.kind() == io::ErrorKind::NotFound {
+                    ROCM_SMI_MISSING.store(true, Ordering::Relaxed);
+                }
+                debug!("Cannot read AMD GPUs with {}: {}", ROCM_SMI, e);
+                Some(gpus)
+            }
+        }
+    }
+
+    /// Reads every AMD card under `root` (the DRM class directory), ordered by card.
+    pub fn discover_amd(root: &Path) -> Vec<GpuInfo> {
+        let entries = match fs::read_dir(root) {
+            Ok(entries) => entries,
+            Err(e) => {
+                debug!("Cannot enumerate {}: {}", root.display(), e);
+                return Vec::new();
+            }
+        };
+        let mut gpus: Vec<GpuInfo> = entries
+            .flatten()
+            .filter(|entry| {
+                // Skip the connectors (`card0-DP-1`) and render nodes.
+                let name = entry.file_name().to_string_lossy().into_owned();
+                name.strip_prefix("card")
+                    .is_some_and(|number| number.parse::<u32>().is_ok())
+            })
+            .filter_map(|entry| Self::read_amd_card(&entry.path()))
+            .collect();
+        gpus.sort_by(|a, b| a.card.cmp(&b.card));
+        gpus
+    }
+
+    fn read_amd_card(dir: &Path) -> Option<GpuInfo> {
+        let device = dir.join("device");
+        if HwmonUtil::read_trimmed(&device.join("vendor")).as_deref() != Some(AMD_VENDOR_ID) {
+            return None;
+        }
+        // The card has a single hwmon directory, `hwmon/hwmonN`.
+        let hwmon = fs::read_dir(device.join("hwmon"))
+            .ok()?
+            .flatten()
+            .next()?
+            .path();
+
+        let mut gpu = GpuInfo {
+            card: dir.file_name()?.to_string_lossy().into_owned(),
+            vendor: "amd".to_string(),
+            name: HwmonUtil::read_trimmed(&device.join("product_name")),
+            pci_address: fs::canonicalize(&device)
+                .ok()
+                .and_then(|path| path.file_name().map(|n| n.to_string_lossy().into_owned())),
+            temperature: None,
+            junction_temperature: None,
+            fan_speed: None,
+            power: None,
+        };
+        for number in 1..=3 {
+            let Some(value) = HwmonUtil::read_number(&hwmon.join(format!("temp{}_input", number)))
+            else {
+                continue;
+            };
+            let celsius = Some(Celsius((value / 1000.0) as f32));
+            match HwmonUtil::read_trimmed(&hwmon.join(format!("temp{}_label", number))).as_deref() {
+                Some("junction") => gpu.junction_temperature = celsius,
+                Some("edge") | None => gpu.temperature = celsius,
+                _ => {}
+            }
+        }
+        gpu.fan_speed = HwmonUtil::read_number(&hwmon.join("fan1_input")).map(|rpm| rpm as f32);
+        // Power is in microwatts; older kernels only expose the average.
+        gpu.power = HwmonUtil::read_number(&hwmon.join("power1_input"))
+            .or_else(|| HwmonUtil::read_number(&hwmon.join("power1_average")))
+            .map(|microwatts| (microwatts / 1_000_000.0) as f32);
+
+        if gpu.temperature.is_none() && gpu.fan_speed.is_none() && gpu.power.is_none() {
+            return None;
+        }
+        Some(gpu)
+    }
+
+    fn run_rocm_smi() -> io::Result<String> {
+        let output = Command::new(ROCM_SMI)
+            .args([
+                "--showtemp",
+                "--showfan",
+                "--showpower",
+                "--showproductname",
+                "--showbus",
+                "--json",
+            ])
+            .stdin(Stdio::null())
+            .output()?;
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+            return Err(io::Error::other(format!(
+                "exited with {}: {}",
+                output.status, stderr
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Parses `rocm-smi --json`, whose keys differ slightly between ROCm releases.
+    pub fn parse_rocm_smi(output: &str) -> Vec<GpuInfo> {
+        let Ok(Value::Object(cards)) = serde_json::from_str::<Value>(output) else {
+            return Vec::new();
+        };
+        let mut gpus: Vec<GpuInfo> = cards
+            .iter()
+            .filter(|(card, _)| card.starts_with("card"))
+            .map(|(card, fields)| {
+                let field = |matches: &dyn Fn(&str) -> bool| {
+                    fields.as_object().and_then(|fields| {
+                        fields
+                            .iter()
+                            .find(|(key, _)| matches(key))
+                            .and_then(|(_, value)| match value {
+                                Value::String(text) => Some(text.trim().to_string()),
+                                Value::Number(number) => Some(number.to_string()),
+                                _ => None,
+                            })
+                    })
+                };
+                let number = |matches: &dyn Fn(&str) -> bool| {
+                    field(matches).and_then(|value| value.parse::<f32>().ok())
+                };
+                GpuInfo {
+                    card: card.clone(),
+                    vendor: "amd".to_string(),
+                    name: field(&|key| key == "Card series" || key == "Card Series"),
+                    pci_address: field(&|key| key == "PCI Bus"),
+                    temperature: number(&|key| key.starts_with("Temperature (Sensor edge)"))
+                        .map(Celsius),
+                    junction_temperature: number(&|key| {
+                        key.starts_with("Temperature (Sensor junction)")
+                    })
+                    .map(Celsius),
+                    fan_speed: number(&|key| key == "Fan RPM"),
+                    power: number(&|key| key.contains("Graphics Package Power (W)")),
+                }
+            })
+            .collect();
+        gpus.sort_by(|a, b| a.card.cmp(&b.card));
+        gpus
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn reads_amd_cards_from_sysfs_and_rocm_smi() {
+        let root = TempDir::new("drm");
+        let hwmon = root.join("card1/device/hwmon/hwmon5");
+        fs::create_dir_all(&hwmon).unwrap();
+        fs::create_dir_all(root.join("card1-DP-1")).unwrap();
+        fs::write(root.join("card1/device/vendor"), "0x1002\n").unwrap();
+        for (file, contents) in [
+            ("temp1_input", "43000\n"),
+            ("temp1_label", "edge\n"),
+            ("temp2_input", "51000\n"),
+            ("temp2_label", "junction\n"),
+            ("fan1_input", "1250\n"),
+            ("power1_average", "35000000\n"),
+        ] {
+            fs::write(hwmon.join(file), contents).unwrap();
+        }
+
+        let gpus = GpuUtil::discover_amd(&root);
+
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].card, "card1");
+        assert_eq!(gpus[0].temperature, Some(Celsius(43.0)));
+        assert_eq!(gpus[0].junction_temperature, Some(Celsius(51.0)));
+        assert_eq!(gpus[0].fan_speed, Some(1250.0));
+        assert_eq!(gpus[0].power, Some(35.0));
+
+        let rocm_smi = r#"{
+            "card0": {
+                "Temperature (Sensor edge) (C)": "40.0",
+                "Temperature (Sensor junction) (C)": "44.0",
+                "Fan RPM": "900",
+                "Current Socket Graphics Package Power (W)": "18.5",
+                "Card series": "Navi 21 [Radeon RX 6800]",
+                "PCI Bus": "0000:03:00.0"
+            },
+            "system": {"Driver version": "6.8.0"}
+        }"#;
+        let gpus = GpuUtil::parse_rocm_smi(rocm_smi);
+        assert_eq!(gpus.len(), 1);
+        assert_eq!(gpus[0].name.as_deref(), Some("Navi 21 [Radeon RX 6800]"));
+        assert_eq!(gpus[0].pci_address.as_deref(), Some("0000:03:00.0"));
+        assert_eq!(gpus[0].temperature, Some(Celsius(40.0)));
+        assert_eq!(gpus[0].junction_temperature, Some(Celsius(44.0)));
+        assert_eq!(gpus[0].fan_speed, Some(900.0));
+        assert_eq!(gpus[0].power, Some(18.5));
+    }
+}
@@ -0,0 +1,343 @@
+//! Hwmon Thermal Source
+//!
+//! Reads CPU temperatures, and the fan, voltage and power channels of every other chip,
+//! straight from `/sys/class/hwmon`: the same files libsensors reads, so hosts without
+//! lm-sensors (minimal installs, containers) are covered without spawning `sensors` on
+//! every cycle. Packages come from `coretemp` (Intel) and `k10temp` (AMD). Chips are named
+//! the way `sensors` names them (e.g. `coretemp-isa-0000`, `nct6798-isa-0290`), so readings
+//! keep their identity when a host switches sources. Chips are enumerated by
+//! [`HwmonUtil`], whose own device list then leaves out the fans reported here.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::data::models::{CpuCoreData, CpuPackageData, FanInfo, PowerInfo, VoltageInfo};
+use crate::data::units::Celsius;
+use crate::hardware::hwmon_util::{HwmonChip, HwmonUtil, HWMON_ROOT};
+
+/// Fan, voltage and power readings of every chip, read along with the CPU packages.
+#[derive(Debug, Default)]
+pub struct ChipReadings {
+    pub fans: Vec<FanInfo>,
+    pub voltages: Vec<VoltageInfo>,
+    pub power: Vec<PowerInfo>,
+}
+
+/// Static utility class for reading temperatures from hwmon.
+pub struct HwmonThermal;
+
+impl HwmonThermal {
+    /// Reads the CPU packages and chip readings of this host.
+    pub fn collect() -> io::Result<(Vec<CpuPackageData>, ChipReadings)> {
+        Self::read(Path::new(HWMON_ROOT))
+    }
+
+    /// Reads the CPU packages and chip readings under `root` (the hwmon class directory),
+    /// ordered by chip name.
+    pub fn read(root: &Path) -> io::Result<(Vec<CpuPackageData>, ChipReadings)> {
+        let mut chips: Vec<(String, HwmonChip)> = HwmonUtil::chips(root)?
+            .into_iter()
+            .map(|chip| (Self::chip_name(&chip.name, &chip.dir), chip))
+            .collect();
+        chips.sort();
+
+        let mut packages: Vec<CpuPackageData> = Vec::new();
+        let mut readings = ChipReadings::default();
+        for (chip, HwmonChip { dir, name, .. }) in chips {
+            match name.as_str() {
+                "coretemp" | "k10temp" => {
+                    let mut package = Self::read_package(&dir, &chip);
+                    if name == "k10temp" {
+                        // k10temp has no package label; number the packages in order.
+                        package.package_id = packages
+                            .iter()
+                            .filter(|p| p.adapter_name.starts_with("k10temp"))
+                            .count()
+                            .to_string();
+                    }
+                    packages.push(package);
+                }
+                _ => Self::read_chip(&dir, &chip, &mut readings),
+            }
+        }
+        Ok((packages, readings))
+    }
+
+    /// Reads the temperature channels of a `coretemp` or `k10temp` chip: `Package id N` and
+    /// `Core N` on Intel, `Tctl`/`Tdie` and `Tccd N` on AMD.
+    fn read_package(dir: &Path, chip: &str) -> CpuPackageData {
+        let mut package = CpuPackageData {
+            package_id: String::new(),
+            adapter_name: chip.to_string(),
+            package_temperature: Celsius::default(),
+            high_threshold: Celsius::default(),
+            critical_threshold: Celsius::default(),
+            cores: Vec::new(),
+        };
+        let mut has_tdie = false;
+        for number in HwmonUtil::channels(dir, "temp") {
+            let Some(temperature) = Self::read_celsius(dir, number, "input") else {
+                continue;
+            };
+            let label = HwmonUtil::read_trimmed(&dir.join(format!("temp{}_label", number)))
+                .unwrap_or_else(|| format!("temp{}", number));
+            let high_threshold = Self::read_celsius(dir, number, "max").unwrap_or_default();
+            let critical_threshold = Self::read_celsius(dir, number, "crit").unwrap_or_default();
+
+            // Tdie is Tctl without the offset some CPUs add for fan control.
+            let is_package = match label.as_str() {
+                "Tdie" => {
+                    has_tdie = true;
+                    true
+                }
+                "Tctl" => !has_tdie,
+                _ => label.starts_with("Package id"),
+            };
+            if is_package {
+                if let Some(id) = label.strip_prefix("Package id") {
+                    package.package_id = id.trim().to_string();
+                }
+                package.package_temperature = temperature;
+                package.high_threshold = high_threshold;
+                package.critical_threshold = critical_threshold;
+            } else if label.starts_with("Core") || label.starts_with("Tccd") {
+                package.cores.push(CpuCoreData {
+                    core_name: label,
+                    temperature,
+                    high_threshold,
+                    critical_threshold,
+                });
+            }
+        }
+        package
+    }
+
+    /// Reads the fan, voltage and power channels of a chip into `readings`.
+    fn read_chip(dir: &Path, chip: &str, readings: &mut ChipReadings) {
+        let label = |prefix: &str, number: u32| {
+            HwmonUtil::read_trimmed(&dir.join(format!("{}{}_label", prefix, number)))
+                .unwrap_or_else(|| format!("{}{}", prefix, number))
+        };
+        let value = |prefix: &str, number: u32, attribute: &str| {
+            HwmonUtil::read_number(&dir.join(format!("{}{}_{}", prefix, number, attribute)))
+        };
+
+        for number in HwmonUtil::channels(dir, "fan") {
+            if let Some(speed) = value("fan", number, "input") {
+                readings.fans.push(FanInfo {
+                    adapter_name: chip.to_string(),
+                    label: label("fan", number),
+                    speed: speed as f32,
+                    min_speed: value("fan", number, "min").map(|rpm| rpm as f32),
+                });
+            }
+        }
+        // Voltages are in millivolts.
+        let volts = |millivolts: f64| (millivolts / 1000.0) as f32;
+        for number in HwmonUtil::channels(dir, "in") {
+            if let Some(voltage) = value("in", number, "input") {
+                readings.voltages.push(VoltageInfo {
+                    adapter_name: chip.to_string(),
+                    label: label("in", number),
+                    voltage: volts(voltage),
+                    min_voltage: value("in", number, "min").map(volts),
+                    max_voltage: value("in", number, "max").map(volts),
+                });
+            }
+        }
+        // Power is in microwatts; meters may only expose the average.
+        for number in HwmonUtil::channels(dir, "power") {
+            if let Some(power) =
+                value("power", number, "input").or_else(|| value("power", number, "average"))
+            {
+                readings.power.push(PowerInfo {
+                    adapter_name: chip.to_string(),
+                    label: label("power", number),
+                    power: (power / 1_000_000.0) as f32,
+                    energy: None,
+                });
+            }
+        }
+    }
+
+    /// Names a chip like libsensors: `<name>-<bus>-<address>`, e.g. `coretemp-isa-0000`,
+    /// `k10temp-pci-00c3` or `w83793-i2c-1-2f`.
+    fn chip_name(name: &str, dir: &Path) -> String {
+        let Ok(device) = fs::canonicalize(dir.join("device")) else {
+            return format!("{}-virtual-0", name);
+        };
+        let id = device
+            .file_name()
+            .map(|id| id.to_string_lossy().into_owned())
+            .unwrap_or_default();
+        let subsystem = fs::canonicalize(device.join("subsystem"))
+            .ok()
+            .and_then(|path| path.file_name().map(|n| n.to_string_lossy().into_owned()));
+        let hex = |value: &str| u32::from_str_radix(value, 16).ok();
+        let named = match subsystem.as_deref() {
+            // `coretemp.0`, `nct6775.656`.
+            Some("platform") => id
+                .rsplit_once('.')
+                .and_then(|(_, address)| address.parse::<u32>().ok())
+                .map(|address| format!("{}-isa-{:04x}", name, address)),
+            // `0000:00:18.3`.
+            Some("pci") => id.split_once(':').and_then(|(domain, rest)| {
+                let (bus, slot) = rest.split_once(':')?;
+                let (device, function) = slot.split_once('.')?;
+                let address =
+                    (hex(domain)? << 16) + (hex(bus)? << 8) + (hex(device)? << 3) + hex(function)?;
+                Some(format!("{}-pci-{:04x}", name, address))
+            }),
+            // `1-002f`.
+            Some("i2c") => id.split_once('-').and_then(|(bus, address)| {
+                Some(format!(
+                    "{}-i2c-{}-{:02x}",
+                    name,
+                    bus.parse::<u32>().ok()?,
+                    hex(address)?
+                ))
+            }),
+            // `ACPI000D:00`.
+            Some("acpi") => id
+                .rsplit_once(':')
+                .and_then(|(_, number)| hex(number))
+                .map(|number| format!("{}-acpi-{:x}", name, number)),
+            _ => None,
+        };
+        named.unwrap_or_else(|| format!("{}-{}", name, id))
+    }
+
+    /// Reads `temp<number>_<attribute>`, in millidegrees.
+    fn read_celsius(dir: &Path, number: u32, attribute: &str) -> Option<Celsius> {
+        HwmonUtil::read_number(&dir.join(format!("temp{}_{}", number, attribute)))
+            .map(|millidegrees| Celsius((millidegrees / 1000.0) as f32))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn reads_packages_and_chips_like_sensors() {
+        let root = TempDir::new("hwthermal");
+        let platform = root.join("devices/platform");
+        let pci = root.join("devices/pci0000:00");
+        let class = root.join("class/hwmon");
+        fs::create_dir_all(&class).unwrap();
+        fs::create_dir_all(root.join("bus/platform")).unwrap();
+        fs::create_dir_all(root.join("bus/pci")).unwrap();
+        for (hwmon, device, bus, files) in [
+            (
+                "hwmon3",
+                platform.join("coretemp.0"),
+                "platform",
+                vec![
+                    ("name", "coretemp"),
+                    ("temp1_input", "48000"),
+                    ("temp1_label", "Package id 0"),
+                    ("temp1_max", "80000"),
+                    ("temp1_crit", "100000"),
+                    ("temp2_input", "45000"),
+                    ("temp2_label", "Core 0"),
+                    ("temp2_max", "80000"),
+                    ("temp2_crit", "100000"),
+                ],
+            ),
+            (
+                "hwmon2",
+                platform.join("nct6775.656"),
+                "platform",
+                vec![
+                    ("name", "nct6798"),
+                    ("in0_input", "304"),
+                    ("in0_min", "0"),
+                    ("in0_max", "1744"),
+                    ("fan1_input", "1200"),
+                    ("fan1_min", "300"),
+                    ("fan1_label", "CPU Fan"),
+                    ("temp1_input", "32000"),
+                ],
+            ),
+            (
+                "hwmon1",
+                pci.join("0000:00:18.3"),
+                "pci",
+                vec![
+                    ("name", "k10temp"),
+                    ("temp1_input", "61500"),
+                    ("temp1_label", "Tctl"),
+                    ("temp3_input", "52250"),
+                    ("temp3_label", "Tccd1"),
+                ],
+            ),
+        ] {
+            let dir = class.join(hwmon);
+            fs::create_dir_all(&dir).unwrap();
+            fs::create_dir_all(&device).unwrap();
+            symlink(&device, dir.join("device")).unwrap();
+            symlink(root.join("bus").join(bus), device.join("subsystem")).unwrap();
+            for (file, contents) in files {
+                fs::write(dir.join(file), format!("{}\n", contents)).unwrap();
+            }
+        }
+        let meter = class.join("hwmon0");
+        fs::create_dir_all(&meter).unwrap();
+        fs::write(meter.join("name"), "power_meter\n").unwrap();
+        fs::write(meter.join("power1_average"), "130000000\n").unwrap();
+
+        let (packages, chips) = HwmonThermal::read(&class).unwrap();
+
+        let packages: Vec<_> = packages
+            .iter()
+            .map(|p| {
+                let cores: Vec<_> = p
+                    .cores
+                    .iter()
+                    .map(|c| (c.core_name.as_str(), c.temperature))
+                    .collect();
+                (
+                    p.adapter_name.as_str(),
+                    p.package_id.as_str(),
+                    p.package_temperature,
+                    p.critical_threshold,
+                    cores,
+                )
+            })
+            .collect();
+        assert_eq!(
+            packages,
+            [
+                (
+                    "coretemp-isa-0000",
+                    "0",
+                    Celsius(48.0),
+                    Celsius(100.0),
+                    vec![("Core 0", Celsius(45.0))]
+                ),
+                (
+                    "k10temp-pci-00c3",
+                    "0",
+                    Celsius(61.5),
+                    Celsius(0.0),
+                    vec![("Tccd1", Celsius(52.25))]
+                ),
+            ]
+        );
+
+        assert_eq!(chips.fans.len(), 1);
+        assert_eq!(chips.fans[0].adapter_name, "nct6798-isa-0290");
+        assert_eq!(chips.fans[0].label, "CPU Fan");
+        assert_eq!(chips.fans[0].min_speed, Some(300.0));
+        assert_eq!(chips.voltages.len(), 1);
+        assert_eq!(chips.voltages[0].label, "in0");
+        assert_eq!(chips.voltages[0].voltage, 0.304);
+        assert_eq!(chips.voltages[0].max_voltage, Some(1.744));
+        assert_eq!(chips.power.len(), 1);
+        assert_eq!(chips.power[0].adapter_name, "power_meter-virtual-0");
+        assert_eq!(chips.power[0].power, 130.0);
+    }
+}
@@ -0,0 +1,260 @@
+//! Hwmon Device Discovery
+//!
+//! This module enumerates `/sys/class/hwmon` on every collection cycle instead of relying
+//! on a fixed parse of `sensors`, so devices that appear after boot (USB fan controllers
+//! such as the Corsair Commander or Aquacomputer pumps and flow sensors) are reported
+//! without restarting the agent. Devices are identified by their underlying sysfs device
+//! rather than the `hwmonN` index, which the kernel reassigns when a device is replugged.
+//!
+//! The enumeration and the sysfs readers here are shared by every collector reading
+//! hwmon (the hwmon thermal source, power meters and GPUs).
+
+use log::{debug, info};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::config::config_instance::Config;
+use crate::data::models::{HwmonDevice, HwmonReading, HwmonReadingKind};
+
+/// Root of the hwmon class in sysfs.
+pub const HWMON_ROOT: &str = "/sys/class/hwmon";
+
+/// Devices (identity to driver name) seen in the previous cycle; `None` before the first.
+static KNOWN_DEVICES: Mutex<Option<BTreeMap<String, String>>> = Mutex::new(None);
+
+/// A chip under the hwmon class, i.e. one `hwmonN` directory.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct HwmonChip {
+    /// The `hwmonN` directory.
+    pub dir: PathBuf,
+    /// Driver name, e.g. `coretemp` or `d5next`; the directory name if the chip has none.
+    pub name: String,
+    /// Identity of the underlying sysfs device, which survives replugging unlike `hwmonN`.
+    pub device: String,
+}
+
+/// A utility class for enumerating hwmon devices.
+pub struct HwmonUtil;
+
+impl HwmonUtil {
+    /// Enumerates the hwmon devices present now if `collect_hwmon` is enabled, logging
+    /// devices that were plugged in or removed since the previous cycle. Fans are left out
+    /// unless `include_fans` is set, for when the thermal source already reports them.
+    ///
+    /// Returns `None` when the collector is disabled.
+    pub fn collect_if_enabled(include_fans: bool) -> Option<Vec<HwmonDevice>> {
+        if !Config::get().collect_hwmon {
+            return None;
+        }
+        let devices = Self::discover(Path::new(HWMON_ROOT), include_fans);
+        Self::track_hotplug(&devices);
+        Some(devices)
+    }
+
+    /// Lists the chips under `root` (the hwmon class directory), ordered by directory.
+    pub fn chips(root: &Path) -> io::Result<Vec<HwmonChip>> {
+        let mut chips: Vec<HwmonChip> = fs::read_dir(root)?
+            .flatten()
+            .filter_map(|entry| {
+                let dir = entry.path();
+                let index = dir.file_name()?.to_string_lossy().into_owned();
+                let name = Self::read_trimmed(&dir.join("name")).unwrap_or_else(|| index.clone());
+                let device = fs::canonicalize(dir.join("device"))
+                    .ok()
+                    .and_then(|path| path.file_name().map(|n| n.to_string_lossy().into_owned()))
+                    .unwrap_or(index);
+                Some(HwmonChip { dir, name, device })
+            })
+            .collect();
+        chips.sort();
+        Ok(chips)
+    }
+
+    /// Reads every device under `root` that has at least one temperature, fan, pump or
+    /// flow reading, ordered by device identity. Fan channels that are neither pumps nor
+    /// flow sensors are skipped unless `include_fans` is set.
+    pub fn discover(root: &Path, include_fans: bool) -> Vec<HwmonDevice> {
+        let chips = match Self::chips(root) {
+            Ok(chips) => chips,
+            Err(e) => {
+                debug!("Cannot enumerate {}: {}", root.display(), e);
+                return Vec::new();
+            }
+        };
+        let mut devices: Vec<HwmonDevice> = chips
+            .into_iter()
+            .filter_map(|chip| Self::read_device(chip, include_fans))
+            .collect();
+        devices.sort_by(|a, b| a.device.cmp(&b.device));
+        devices
+    }
+
+    fn read_device(chip: HwmonChip, include_fans: bool) -> Option<HwmonDevice> {
+        let channels = Self::channels(&chip.dir, "fan")
+            .into_iter()
+            .map(|number| ("fan", number))
+            .chain(
+                Self::channels(&chip.dir, "temp")
+                    .into_iter()
+                    .map(|number| ("temp", number)),
+            );
+        let readings: Vec<HwmonReading> = channels
+            .filter_map(|(prefix, number)| Self::read_channel(&chip.dir, prefix, number))
+            .filter(|reading| include_fans || reading.kind != HwmonReadingKind::Fan)
+            .collect();
+        if readings.is_empty() {
+            return None;
+        }
+        Some(HwmonDevice {
+            name: chip.name,
+            device: chip.device,
+            readings,
+        })
+    }
+
+    /// Numbers of the `prefix` channels of a chip with a reading (`temp1_input`,
+    /// `power1_average`, ...), in ascending order.
+    pub fn channels(dir: &Path, prefix: &str) -> Vec<u32> {
+        let mut numbers: Vec<u32> = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .filter_map(|entry| {
+                let file = entry.file_name().to_string_lossy().into_owned();
+                let channel = file
+                    .strip_suffix("_input")
+                    .or_else(|| file.strip_suffix("_average"))?;
+                channel.strip_prefix(prefix)?.parse().ok()
+            })
+            .collect();
+        numbers.sort_unstable();
+        numbers.dedup();
+        numbers
+    }
+
+    /// Reads one channel. Temperatures are in millidegrees; fan channels report RPM, except
+    /// flow sensors, which drivers expose as fan channels labelled with the flow unit.
+    fn read_channel(dir: &Path, prefix: &str, number: u32) -> Option<HwmonReading> {
+        let raw: f64 = Self::read_trimmed(&dir.join(format!("{}{}_input", prefix, number)))?
+            .parse()
+            .ok()?;
+        let label = Self::read_trimmed(&dir.join(format!("{}{}_label", prefix, number)))
+            .unwrap_or_else(|| format!("{}{}", prefix, number));
+        let (kind, value) = if prefix == "temp" {
+            (HwmonReadingKind::Temperature, raw / 1000.0)
+        } else {
+            (Self::fan_kind(&label), raw)
+        };
+        Some(HwmonReading {
+            kind,
+            label,
+            value: value as f32,
+        })
+    }
+
+    /// Tells pumps and flow sensors apart from fans by their channel label.
+    fn fan_kind(label: &str) -> HwmonReadingKind {
+        let label = label.to_ascii_lowercase();
+        if label.contains("flow") {
+            HwmonReadingKind::Flow
+        } else if label.contains("pump") {
+            HwmonReadingKind::Pump
+        } else {
+            HwmonReadingKind::Fan
+        }
+    }
+
+    /// Reads a numeric sysfs attribute.
+    pub fn read_number(path: &Path) -> Option<f64> {
+        Self::read_trimmed(path)?.parse().ok()
+    }
+
+    /// Reads a sysfs attribute without its trailing newline; `None` if it is empty.
+    pub fn read_trimmed(path: &Path) -> Option<String> {
+        fs::read_to_string(path)
+            .ok()
+            .map(|value| value.trim().to_string())
+            .filter(|value| !value.is_empty())
+    }
+
+    fn track_hotplug(devices: &[HwmonDevice]) {
+        let current: BTreeMap<String, String> = devices
+            .iter()
+            .map(|device| (device.device.clone(), device.name.clone()))
+            .collect();
+        let mut known = KNOWN_DEVICES.lock().unwrap_or_else(|e| e.into_inner());
+        match known.as_ref() {
+            None => debug!("Found {} hwmon device(s).", current.len()),
+            Some(previous) => {
+                for (device, name) in current.iter().filter(|(d, _)| !previous.contains_key(*d)) {
+                    info!("Hwmon device {} ({}) appeared.", name, device);
+                }
+                for (device, name) in previous.iter().filter(|(d, _)| !current.contains_key(*d)) {
+                    info!("Hwmon device {} ({}) disappeared.", name, device);
+                }
+            }
+        }
+        *known = Some(current);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn discovers_fans_pumps_and_flow_sensors() {
+        let root = TempDir::new("hwmon");
+        let dir = root.join("hwmon3");
+        fs::create_dir_all(&dir).unwrap();
+        for (file, contents) in [
+            ("name", "d5next\n"),
+            ("temp1_input", "31250\n"),
+            ("temp1_label", "Coolant temp\n"),
+            ("fan1_input", "2400\n"),
+            ("fan1_label", "Pump speed\n"),
+            ("fan2_input", "812\n"),
+            ("fan2_label", "Flow [dL/h]\n"),
+            ("fan3_input", "1150\n"),
+        ] {
+            fs::write(dir.join(file), contents).unwrap();
+        }
+        fs::create_dir_all(root.join("hwmon4")).unwrap();
+
+        let devices = HwmonUtil::discover(&root, true);
+
+        assert_eq!(devices.len(), 1);
+        assert_eq!(devices[0].name, "d5next");
+        assert_eq!(devices[0].device, "hwmon3");
+        let readings: Vec<_> = devices[0]
+            .readings
+            .iter()
+            .map(|r| (r.kind, r.label.as_str(), r.value))
+            .collect();
+        assert_eq!(
+            readings,
+            [
+                (HwmonReadingKind::Pump, "Pump speed", 2400.0),
+                (HwmonReadingKind::Flow, "Flow [dL/h]", 812.0),
+                (HwmonReadingKind::Fan, "fan3", 1150.0),
+                (HwmonReadingKind::Temperature, "Coolant temp", 31.25),
+            ]
+        );
+
+        // Without fans, pumps and flow sensors are still reported.
+        let devices = HwmonUtil::discover(&root, false);
+        let kinds: Vec<_> = devices[0].readings.iter().map(|r| r.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                HwmonReadingKind::Pump,
+                HwmonReadingKind::Flow,
+                HwmonReadingKind::Temperature
+            ]
+        );
+    }
+}
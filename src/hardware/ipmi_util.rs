@@ -0,0 +1,184 @@
+//! Remote IPMI Collection
+//!
+//! This module gathers chassis sensors from the BMCs of headless hosts that cannot run
+//! the client themselves, by running `ipmitool -I lanplus -H <bmc> sensor` for every
+//! configured `[[ipmi_hosts]]` entry. Each BMC is reported as a separate remote host entry
+//! of the payload. Passwords are handed to ipmitool through `IPMI_PASSWORD` (`-E`), so
+//! they never appear in the process list.
+
+use log::warn;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::process::{Command, Stdio};
+use std::thread;
+
+use crate::config::config_instance::Config;
+use crate::data::models::{RemoteHostInfo, RemoteSensorReading};
+use crate::data::parse::parse_number;
+
+/// A BMC polled over the network, one `[[ipmi_hosts]]` entry.
+#[derive(Debug, Clone, Deserialize)]
+pub struct IpmiHostConfig {
+    /// Name the host is reported under.
+    pub name: String,
+    /// Address of the BMC.
+    pub address: String,
+    /// BMC user name.
+    pub username: String,
+    /// BMC password; prefer `password_file`.
+    pub password: Option<String>,
+    /// File whose first line is the BMC password.
+    pub password_file: Option<String>,
+    /// ipmitool interface, `lanplus` (IPMI 2.0) or `lan` (IPMI 1.5).
+    #[serde(default = "default_interface")]
+    pub interface: String,
+    /// Seconds ipmitool waits for each response.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Tags of the host's entry in grouped reports (`group_hosts`).
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+}
+
+fn default_interface() -> String {
+    "lanplus".to_string()
+}
+
+fn default_timeout_secs() -> u64 {
+    5
+}
+
+/// A utility class for reading sensors from remote BMCs with ipmitool.
+pub struct IpmiUtil;
+
+impl IpmiUtil {
+    /// Polls every configured BMC in parallel.
+    ///
+    /// Returns `None` when no `[[ipmi_hosts]]` are configured.
+    pub fn collect_remote_if_configured() -> Option<Vec<RemoteHostInfo>> {
+        let hosts = &Config::get().ipmi_hosts;
+        if hosts.is_empty() {
+            return None;
+        }
+        let remote_hosts = thread::scope(|scope| {
+            let handles: Vec<_> = hosts
+                .iter()
+                .map(|host| scope.spawn(move || Self::poll(host)))
+                .collect();
+            handles
+                .into_iter()
+                .zip(hosts)
+                .map(|(handle, host)| {
+                    handle
+                        .join()
+                        .unwrap_or_else(|_| Self::host_entry(host, false, Vec::new()))
+                })
+                .collect()
+        });
+        Some(remote_hosts)
+    }
+
+    fn poll(host: &IpmiHostConfig) -> RemoteHostInfo {
+        match Self::read_sensors(host) {
+            Ok(output) => Self::host_entry(host, true, Self::parse_sensor_list(&output)),
+            Err(e) => {
+                warn!(
+                    "IPMI poll of {} ({}) failed: {}",
+                    host.name, host.address, e
+                );
+                Self::host_entry(host, false, Vec::new())
+            }
+        }
+    }
+
+    fn host_entry(
+        host: &IpmiHostConfig,
+        reachable: bool,
+        sensors: Vec<RemoteSensorReading>,
+    ) -> RemoteHostInfo {
+        RemoteHostInfo {
+            name: host.name.clone(),
+            address: host.address.clone(),
+            source: "ipmi".to_string(),
+            reachable,
+            sensors,
+        }
+    }
+
+    /// Runs `ipmitool sensor` against the BMC and returns its output.
+    fn read_sensors(host: &IpmiHostConfig) -> Result<String, String> {
+        let password = match (&host.password_file, &host.password) {
+            (Some(file), _) => fs::read_to_string(file)
+                .map_err(|e| format!("cannot read {}: {}", file, e))?
+                .lines()
+                .next()
+                .unwrap_or_default()
+                .to_string(),
+            (None, Some(password)) => password.clone(),
+            (None, None) => String::new(),
+        };
+        let timeout = host.timeout_secs.max(1).to_string();
+        let output = Command::new("ipmitool")
+            .args(["-I", &host.interface, "-H", &host.address])
+            .args(["-U", &host.username, "-E", "-N", &timeout, "-R", "1"])
+            .arg("sensor")
+            .env("IPMI_PASSWORD", password)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("cannot run ipmitool: {}", e))?;
+        if !output.status.success() {
+            return Err(String::from_utf8_lossy(&output.stderr).trim().to_string());
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+
+    /// Parses `ipmitool sensor` output: `name | value | unit | status | lnr | lcr | lnc |
+    /// unc | ucr | unr`, where unavailable values read `na`. Discrete sensors (value in
+    /// hex, unit `discrete`) are skipped.
+    pub fn parse_sensor_list(output: &str) -> Vec<RemoteSensorReading> {
+        output
+            .lines()
+            .filter_map(|line| {
+                let fields: Vec<&str> = line.split('|').map(str::trim).collect();
+                if fields.len() < 4 || fields[0].is_empty() || fields[2] == "discrete" {
+                    return None;
+                }
+                let number = |index: usize| fields.get(index).and_then(|f| parse_number(f));
+                Some(RemoteSensorReading {
+                    name: fields[0].to_string(),
+                    value: number(1),
+                    unit: fields[2].to_string(),
+                    status: fields[3].to_string(),
+                    upper_non_critical: number(7),
+                    upper_critical: number(8),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_threshold_sensors() {
+        let output = "\
+CPU1 Temp        | 41.000     | degrees C  | ok    | 5.000     | 5.000     | 10.000    | 85.000    | 90.000    | 90.000
+FAN1             | 5600.000   | RPM        | ok    | 300.000   | 500.000   | 700.000   | 25300.000 | 25400.000 | 25500.000
+PS2 Input Power  | na         | Watts      | na    | na        | na        | na        | na        | na        | na
+Chassis Intru    | 0x0        | discrete   | 0x0000| na        | na        | na        | na        | na        | na
+";
+        let sensors = IpmiUtil::parse_sensor_list(output);
+        assert_eq!(sensors.len(), 3);
+        assert_eq!(sensors[0].name, "CPU1 Temp");
+        assert_eq!(sensors[0].value, Some(41.0));
+        assert_eq!(sensors[0].unit, "degrees C");
+        assert_eq!(sensors[0].upper_non_critical, Some(85.0));
+        assert_eq!(sensors[0].upper_critical, Some(90.0));
+        assert_eq!(sensors[1].value, Some(5600.0));
+        assert_eq!(sensors[2].value, None);
+        assert_eq!(sensors[2].status, "na");
+    }
+}
@@ -0,0 +1,171 @@
+//! Liquid Cooling Telemetry
+//!
+//! This module reports AIO and custom-loop cooling devices (NZXT Kraken, Corsair
+//! Hydro/Commander, Aquacomputer, EVGA CLC, ...) through `liquidctl status --json`. Each
+//! device's status items are mapped onto a `CoolingDeviceInfo` with its coolant
+//! temperature, pump and flow readings and fan channels. Devices are not initialized by
+//! the agent; run `liquidctl initialize` at boot as liquidctl recommends.
+
+use log::{debug, warn};
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::config::config_instance::Config;
+use crate::data::models::{CoolingDeviceInfo, CoolingFanInfo};
+use crate::data::units::{Celsius, Percent};
+use crate::system::execution_util::ExecutionUtil;
+
+/// Configuration of the `[liquidctl]` section. When present, cooling devices are queried
+/// every cycle.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LiquidctlConfig {
+    /// The liquidctl executable.
+    pub command: String,
+    /// Only devices whose description contains this text (liquidctl `--match`).
+    pub match_device: Option<String>,
+}
+
+impl Default for LiquidctlConfig {
+    fn default() -> Self {
+        Self {
+            command: "liquidctl".to_string(),
+            match_device: None,
+        }
+    }
+}
+
+/// A utility class for reading cooling devices through liquidctl.
+pub struct LiquidctlUtil;
+
+impl LiquidctlUtil {
+    /// Queries the cooling devices if `[liquidctl]` is configured.
+    ///
+    /// Returns `None` when it is not configured or liquidctl cannot be run.
+    pub fn collect_if_configured() -> Option<Vec<CoolingDeviceInfo>> {
+        let config = Config::get().liquidctl.as_ref()?;
+        let mut args = vec!["status", "--json"];
+        if let Some(pattern) = &config.match_device {
+            args.extend(["--match", pattern]);
+        }
+        match ExecutionUtil::execute_with_method("direct", &config.command, &args) {
+            Ok(output) => match Self::parse_status(&output) {
+                Ok(devices) => Some(devices),
+                Err(e) => {
+                    warn!("Failed to parse liquidctl output: {}", e);
+                    None
+                }
+            },
+            Err(e) => {
+                debug!("liquidctl failed: {}", e.trim());
+                None
+            }
+        }
+    }
+
+    /// Parses the output of `liquidctl status --json`.
+    pub fn parse_status(output: &str) -> Result<Vec<CoolingDeviceInfo>, serde_json::Error> {
+        let devices: Vec<Value> = serde_json::from_str(output)?;
+        Ok(devices.iter().map(Self::parse_device).collect())
+    }
+
+    fn parse_device(device: &Value) -> CoolingDeviceInfo {
+        let text = |key: &str| device[key].as_str().unwrap_or_default().to_string();
+        let mut info = CoolingDeviceInfo {
+            description: text("description"),
+            bus: text("bus"),
+            address: text("address"),
+            coolant_temperature: None,
+            pump_speed: None,
+            pump_duty: None,
+            flow: None,
+            fans: Vec::new(),
+        };
+
+        for item in device["status"].as_array().into_iter().flatten() {
+            let (Some(key), Some(value)) = (item["key"].as_str(), item["value"].as_f64()) else {
+                continue;
+            };
+            let value = value as f32;
+            let lower = key.to_ascii_lowercase();
+            if lower.starts_with("fan ") {
+                Self::apply_fan_item(&mut info.fans, key, &lower, value);
+            } else if lower.contains("flow") {
+                info.flow.get_or_insert(value);
+            } else if lower.contains("pump") && lower.ends_with("speed") {
+                info.pump_speed.get_or_insert(value);
+            } else if lower.contains("pump") && lower.ends_with("duty") {
+                info.pump_duty.get_or_insert(Percent(value));
+            } else if ["liquid", "coolant", "water"]
+                .iter()
+                .any(|medium| lower.starts_with(medium) && lower.contains("temp"))
+            {
+                info.coolant_temperature.get_or_insert(Celsius(value));
+            }
+        }
+        info
+    }
+
+    /// Adds a `Fan <n> speed` or `Fan <n> duty` item to the fan channel `Fan <n>`.
+    fn apply_fan_item(fans: &mut Vec<CoolingFanInfo>, key: &str, lower: &str, value: f32) {
+        let (name, is_speed) = if lower.ends_with(" speed") {
+            (&key[..key.len() - " speed".len()], true)
+        } else if lower.ends_with(" duty") {
+            (&key[..key.len() - " duty".len()], false)
+        } else {
+            return;
+        };
+        let index = match fans.iter().position(|fan| fan.name == name) {
+            Some(index) => index,
+            None => {
+                fans.push(CoolingFanInfo {
+                    name: name.to_string(),
+                    speed: None,
+                    duty: None,
+                });
+                fans.len() - 1
+            }
+        };
+        if is_speed {
+            fans[index].speed = Some(value);
+        } else {
+            fans[index].duty = Some(Percent(value));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_liquidctl_status() {
+        let output = r#"[
+            {"bus": "hid", "address": "/dev/hidraw3",
+             "description": "NZXT Kraken X (X53, X63 or X73)",
+             "status": [
+                {"key": "Liquid temperature", "value": 31.2, "unit": "°C"},
+                {"key": "Pump speed", "value": 1860, "unit": "rpm"},
+                {"key": "Pump duty", "value": 60, "unit": "%"},
+                {"key": "Fan 1 speed", "value": 950, "unit": "rpm"},
+                {"key": "Fan 1 duty", "value": 40, "unit": "%"},
+                {"key": "Fan 2 speed", "value": 0, "unit": "rpm"},
+                {"key": "Firmware version", "value": "2.1.0", "unit": ""}
+             ]}
+        ]"#;
+
+        let devices = LiquidctlUtil::parse_status(output).unwrap();
+        assert_eq!(devices.len(), 1);
+        let kraken = &devices[0];
+        assert_eq!(kraken.address, "/dev/hidraw3");
+        assert_eq!(kraken.coolant_temperature, Some(Celsius(31.2)));
+        assert_eq!(kraken.pump_speed, Some(1860.0));
+        assert_eq!(kraken.pump_duty, Some(Percent(60.0)));
+        assert_eq!(kraken.flow, None);
+        assert_eq!(kraken.fans.len(), 2);
+        assert_eq!(kraken.fans[0].name, "Fan 1");
+        assert_eq!(kraken.fans[0].speed, Some(950.0));
+        assert_eq!(kraken.fans[0].duty, Some(Percent(40.0)));
+        assert_eq!(kraken.fans[1].duty, None);
+    }
+}
@@ -1,2 +1,13 @@
+pub mod fake_system;
+pub mod gpu_util;
+pub mod hwmon_thermal;
+pub mod hwmon_util;
+pub mod ipmi_util;
+pub mod liquidctl_util;
+pub mod msr_thermal;
+pub mod msr_util;
+pub mod power_util;
+pub mod smart_util;
 pub mod system_information;
 pub mod system_information_monitor;
+pub mod system_source;
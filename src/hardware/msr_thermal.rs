@@ -0,0 +1,397 @@
+#![cfg(unix)]
+
+//! MSR Thermal Source
+//!
+//! Reads CPU temperatures straight from the digital thermal sensors of Intel CPUs through
+//! the `msr` driver, for systems without lm-sensors. The computation matches the ESXi
+//! agent's: each sensor reports `DIGITAL_READOUT`, the distance in °C below TjMax, so the
+//! temperature is `TjMax - DIGITAL_READOUT`, with TjMax taken from
+//! `MSR_TEMPERATURE_TARGET`. Thresholds follow the same register: `critical_threshold` is
+//! TjMax and `high_threshold` is the point where the TCC starts throttling.
+//!
+//! The register addresses are looked up by CPU family and model in `THERMAL_QUIRKS`, and
+//! can be overridden with `[[msr_quirks]]` entries for CPUs whose thermal registers live
+//! elsewhere. CPUs without package thermal management (before Sandy Bridge) report the
+//! hottest core as the package temperature.
+
+use log::debug;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::config::config_instance::Config;
+use crate::data::models::{CpuCoreData, CpuPackageData};
+use crate::data::units::Celsius;
+use crate::hardware::msr_util::MsrUtil;
+
+// Architectural addresses; they also select the field layouts in `KNOWN_REGISTERS`.
+const IA32_THERM_STATUS: u32 = 0x19C;
+const MSR_TEMPERATURE_TARGET: u32 = 0x1A2;
+const IA32_PACKAGE_THERM_STATUS: u32 = 0x1B1;
+
+/// Thermal registers at their architectural addresses.
+const ARCHITECTURAL: ThermalRegisters = ThermalRegisters {
+    therm_status: IA32_THERM_STATUS,
+    temperature_target: MSR_TEMPERATURE_TARGET,
+    package_therm_status: Some(IA32_PACKAGE_THERM_STATUS),
+};
+
+/// Nehalem and Westmere have the per-core registers but no package thermal status.
+const NO_PACKAGE_STATUS: ThermalRegisters = ThermalRegisters {
+    package_therm_status: None,
+    ..ARCHITECTURAL
+};
+
+/// Thermal registers per CPU family and model (`None` for every model of the family);
+/// an entry for the model takes precedence over one for the whole family.
+const THERMAL_QUIRKS: &[(u32, Option<u32>, ThermalRegisters)] = &[
+    (6, None, ARCHITECTURAL),
+    (6, Some(0x1A), NO_PACKAGE_STATUS),
+    (6, Some(0x1E), NO_PACKAGE_STATUS),
+    (6, Some(0x1F), NO_PACKAGE_STATUS),
+    (6, Some(0x2E), NO_PACKAGE_STATUS),
+    (6, Some(0x25), NO_PACKAGE_STATUS),
+    (6, Some(0x2C), NO_PACKAGE_STATUS),
+    (6, Some(0x2F), NO_PACKAGE_STATUS),
+];
+
+/// Adapter name reported for packages read from MSRs.
+const ADAPTER_NAME: &str = "msr";
+/// Directory listing the logical CPUs.
+const CPU_ROOT: &str = "/sys/devices/system/cpu";
+
+/// Source of CPU package and core temperatures.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThermalSource {
+    /// Parse the output of the lm-sensors `sensors` command.
+    #[default]
+    Sensors,
+    /// Read the digital thermal sensors through `/dev/cpu/*/msr`.
+    Msr,
+    /// Use `sensors`, falling back to MSRs when it fails or reports no packages.
+    Auto,
+    /// Read `/sys/class/hwmon` directly, without lm-sensors.
+    Hwmon,
+}
+
+impl ThermalSource {
+    /// Name of the source as written in the configuration.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ThermalSource::Sensors => "sensors",
+            ThermalSource::Msr => "msr",
+            ThermalSource::Auto => "auto",
+            ThermalSource::Hwmon => "hwmon",
+        }
+    }
+
+    /// Whether the source runs the `sensors` command, which lm-sensors provides.
+    pub fn uses_sensors(self) -> bool {
+        matches!(self, ThermalSource::Sensors | ThermalSource::Auto)
+    }
+}
+
+/// Addresses of the thermal registers of a CPU.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ThermalRegisters {
+    /// Per-core thermal status (`IA32_THERM_STATUS` layout).
+    pub therm_status: u32,
+    /// TjMax and TCC offset (`MSR_TEMPERATURE_TARGET` layout).
+    pub temperature_target: u32,
+    /// Package thermal status (`IA32_PACKAGE_THERM_STATUS` layout), if the CPU has one.
+    pub package_therm_status: Option<u32>,
+}
+
+/// Overrides the thermal register addresses of a CPU family or model, one
+/// `[[msr_quirks]]` entry. Unset addresses keep their built-in value.
+#[derive(Debug, Clone, Deserialize)]
+pub struct MsrQuirk {
+    /// CPU family, as shown in `/proc/cpuinfo`.
+    pub family: u32,
+    /// CPU model, as shown in `/proc/cpuinfo`; every model of the family when unset.
+    pub model: Option<u32>,
+    pub therm_status: Option<u32>,
+    pub temperature_target: Option<u32>,
+    /// `0` for CPUs without a package thermal status register.
+    pub package_therm_status: Option<u32>,
+}
+
+/// Position of a logical CPU in the package/core topology.
+struct CpuTopology {
+    cpu: usize,
+    package_id: u32,
+    core_id: u32,
+}
+
+/// A utility class for reading CPU temperatures from MSRs.
+pub struct MsrThermal;
+
+impl MsrThermal {
+    /// Reads package and core temperatures of all online CPUs.
+    ///
+    /// Fails on non-Intel CPUs, when the `msr` driver is not loaded or without root.
+    pub fn collect_cpu_packages() -> io::Result<Vec<CpuPackageData>> {
+        let cpuinfo = fs::read_to_string("/proc/cpuinfo")?;
+        if !cpuinfo.contains("GenuineIntel") {
+            return Err(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "The MSR thermal source requires an Intel CPU",
+            ));
+        }
+        let (family, model) = Self::family_and_model(&cpuinfo).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "No CPU family and model in /proc/cpuinfo",
+            )
+        })?;
+        let registers = Self::registers(family, model, &Config::get().msr_quirks);
+
+        // First logical CPU of every package and of every physical core within it.
+        let mut packages: BTreeMap<u32, BTreeMap<u32, usize>> = BTreeMap::new();
+        for topology in Self::topology()? {
+            packages
+                .entry(topology.package_id)
+                .or_default()
+                .entry(topology.core_id)
+                .or_insert(topology.cpu);
+        }
+
+        packages
+            .into_iter()
+            .map(|(package_id, cores)| Self::read_package(package_id, &cores, registers))
+            .collect()
+    }
+
+    /// Looks up the thermal registers of a CPU in `THERMAL_QUIRKS`, then applies the
+    /// matching `overrides`, family-wide entries before model-specific ones.
+    pub fn registers(family: u32, model: u32, overrides: &[MsrQuirk]) -> ThermalRegisters {
+        let specificity = |quirk_model: Option<u32>| match quirk_model {
+            None => Some(0),
+            Some(m) if m == model => Some(1),
+            Some(_) => None,
+        };
+        let mut registers = THERMAL_QUIRKS
+            .iter()
+            .filter(|(f, _, _)| *f == family)
+            .filter_map(|(_, m, registers)| Some((specificity(*m)?, *registers)))
+            .max_by_key(|(specificity, _)| *specificity)
+            .map_or(ARCHITECTURAL, |(_, registers)| registers);
+
+        let mut matching: Vec<(u8, &MsrQuirk)> = overrides
+            .iter()
+            .filter(|quirk| quirk.family == family)
+            .filter_map(|quirk| Some((specificity(quirk.model)?, quirk)))
+            .collect();
+        matching.sort_by_key(|(specificity, _)| *specificity);
+        for (_, quirk) in matching {
+            registers.therm_status = quirk.therm_status.unwrap_or(registers.therm_status);
+            registers.temperature_target = quirk
+                .temperature_target
+                .unwrap_or(registers.temperature_target);
+            if let Some(address) = quirk.package_therm_status {
+                registers.package_therm_status = Some(address).filter(|&address| address != 0);
+            }
+        }
+        registers
+    }
+
+    /// The `cpu family` and `model` of the first CPU in `/proc/cpuinfo`.
+    fn family_and_model(cpuinfo: &str) -> Option<(u32, u32)> {
+        let value = |key: &str| {
+            cpuinfo.lines().find_map(|line| {
+                let (name, value) = line.split_once(':')?;
+                (name.trim() == key).then(|| value.trim().parse().ok())?
+            })
+        };
+        Some((value("cpu family")?, value("model")?))
+    }
+
+    fn read_package(
+        package_id: u32,
+        cores: &BTreeMap<u32, usize>,
+        registers: ThermalRegisters,
+    ) -> io::Result<CpuPackageData> {
+        let cpu = *cores.values().next().ok_or_else(|| {
+            io::Error::new(io::ErrorKind::NotFound, "Package without online CPUs")
+        })?;
+
+        let target = MsrUtil::read(cpu, registers.temperature_target)?;
+        let tj_max = MsrUtil::field(MSR_TEMPERATURE_TARGET, "tj_max")?.extract(target);
+        let tcc_offset = MsrUtil::field(MSR_TEMPERATURE_TARGET, "tcc_offset")?.extract(target);
+        let high_threshold = Self::below_tj_max(tj_max, tcc_offset);
+
+        let package_temperature = match registers.package_therm_status {
+            Some(address) => {
+                let status = MsrUtil::read(cpu, address)?;
+                let readout =
+                    MsrUtil::field(IA32_PACKAGE_THERM_STATUS, "digital_readout")?.extract(status);
+                Some(Self::below_tj_max(tj_max, readout))
+            }
+            None => None,
+        };
+
+        let mut package = CpuPackageData {
+            package_id: package_id.to_string(),
+            adapter_name: ADAPTER_NAME.to_string(),
+            package_temperature: package_temperature.unwrap_or_default(),
+            high_threshold,
+            critical_threshold: Self::below_tj_max(tj_max, 0),
+            cores: Vec::new(),
+        };
+
+        let readout_field = MsrUtil::field(IA32_THERM_STATUS, "digital_readout")?;
+        let valid_field = MsrUtil::field(IA32_THERM_STATUS, "reading_valid")?;
+        for (&core_id, &cpu) in cores {
+            let status = MsrUtil::read(cpu, registers.therm_status)?;
+            if valid_field.extract(status) == 0 {
+                debug!(
+                    "No valid thermal reading for core {} (CPU {}).",
+                    core_id, cpu
+                );
+                continue;
+            }
+            package.cores.push(CpuCoreData {
+                core_name: format!("Core {}", core_id),
+                temperature: Self::below_tj_max(tj_max, readout_field.extract(status)),
+                high_threshold,
+                critical_threshold: Self::below_tj_max(tj_max, 0),
+            });
+        }
+        if package_temperature.is_none() {
+            package.package_temperature = package
+                .cores
+                .iter()
+                .map(|core| core.temperature)
+                .fold(Celsius::default(), |a, b| if b.0 > a.0 { b } else { a });
+        }
+        Ok(package)
+    }
+
+    /// Converts a digital readout, which counts degrees below TjMax, to a temperature.
+    fn below_tj_max(tj_max: u64, readout: u64) -> Celsius {
+        Celsius(tj_max as f32 - readout as f32)
+    }
+
+    /// Lists online logical CPUs with their package and core ids.
+    fn topology() -> io::Result<Vec<CpuTopology>> {
+        let mut cpus = Vec::new();
+        for entry in fs::read_dir(CPU_ROOT)? {
+            let entry = entry?;
+            let name = entry.file_name();
+            let Some(cpu) = name
+                .to_str()
+                .and_then(|name| name.strip_prefix("cpu"))
+                .and_then(|index| index.parse::<usize>().ok())
+            else {
+                continue;
+            };
+
+            // Offline CPUs have no topology directory.
+            let topology = entry.path().join("topology");
+            let (Some(package_id), Some(core_id)) = (
+                Self::read_id(&topology.join("physical_package_id")),
+                Self::read_id(&topology.join("core_id")),
+            ) else {
+                continue;
+            };
+            cpus.push(CpuTopology {
+                cpu,
+                package_id,
+                core_id,
+            });
+        }
+        cpus.sort_by_key(|topology| topology.cpu);
+        Ok(cpus)
+    }
+
+    fn read_id(path: &Path) -> Option<u32> {
+        fs::read_to_string(path).ok()?.trim().parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quirk(family: u32, model: Option<u32>) -> MsrQuirk {
+        MsrQuirk {
+            family,
+            model,
+            therm_status: None,
+            temperature_target: None,
+            package_therm_status: None,
+        }
+    }
+
+    #[test]
+    fn converts_readouts_below_tj_max() {
+        let field = |address, name| MsrUtil::field(address, name).unwrap();
+        // TjMax 100 °C with a TCC offset of 5 °C.
+        let target: u64 = (5 << 24) | (100 << 16);
+        let tj_max = field(MSR_TEMPERATURE_TARGET, "tj_max").extract(target);
+        let tcc_offset = field(MSR_TEMPERATURE_TARGET, "tcc_offset").extract(target);
+        assert_eq!(MsrThermal::below_tj_max(tj_max, tcc_offset), Celsius(95.0));
+        assert_eq!(MsrThermal::below_tj_max(tj_max, 0), Celsius(100.0));
+
+        // A valid reading 58 °C below TjMax.
+        let status: u64 = (1 << 31) | (58 << 16) | 0x1;
+        assert_eq!(field(IA32_THERM_STATUS, "reading_valid").extract(status), 1);
+        let readout = field(IA32_THERM_STATUS, "digital_readout").extract(status);
+        assert_eq!(MsrThermal::below_tj_max(tj_max, readout), Celsius(42.0));
+    }
+
+    #[test]
+    fn looks_up_registers_by_family_and_model() {
+        assert_eq!(MsrThermal::registers(6, 0x55, &[]), ARCHITECTURAL);
+        assert_eq!(MsrThermal::registers(6, 0x2C, &[]), NO_PACKAGE_STATUS);
+        assert_eq!(MsrThermal::registers(0x13, 1, &[]), ARCHITECTURAL);
+    }
+
+    #[test]
+    fn overrides_apply_family_wide_then_per_model() {
+        let overrides = [
+            MsrQuirk {
+                therm_status: Some(0x2000),
+                package_therm_status: Some(0),
+                ..quirk(6, Some(0x55))
+            },
+            MsrQuirk {
+                therm_status: Some(0x1000),
+                temperature_target: Some(0x1001),
+                ..quirk(6, None)
+            },
+            MsrQuirk {
+                therm_status: Some(0x3000),
+                ..quirk(6, Some(0x56))
+            },
+        ];
+        assert_eq!(
+            MsrThermal::registers(6, 0x55, &overrides),
+            ThermalRegisters {
+                therm_status: 0x2000,
+                temperature_target: 0x1001,
+                package_therm_status: None,
+            }
+        );
+        assert_eq!(
+            MsrThermal::registers(6, 0x2C, &overrides).package_therm_status,
+            None
+        );
+        assert_eq!(
+            MsrThermal::registers(6, 0x8F, &overrides).package_therm_status,
+            Some(IA32_PACKAGE_THERM_STATUS)
+        );
+    }
+
+    #[test]
+    fn reads_family_and_model_of_the_first_cpu() {
+        let cpuinfo = "processor\t: 0\nvendor_id\t: GenuineIntel\ncpu family\t: 6\n\
+                       model\t\t: 85\nmodel name\t: Intel(R) Xeon(R)\n\n\
+                       processor\t: 1\ncpu family\t: 6\nmodel\t\t: 85\n";
+        assert_eq!(MsrThermal::family_and_model(cpuinfo), Some((6, 85)));
+        assert_eq!(MsrThermal::family_and_model("processor\t: 0\n"), None);
+    }
+}
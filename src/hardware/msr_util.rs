@@ -0,0 +1,173 @@
+#![cfg(unix)]
+
+//! Model-Specific Registers
+//!
+//! This module reads raw MSRs through the Linux `msr` driver (`/dev/cpu/<n>/msr`, which
+//! needs root and `modprobe msr`) and decodes the registers it knows field by field. It
+//! backs the `debug msr read` subcommand used to bring up new platforms and the MSR
+//! thermal source.
+
+use std::fs::File;
+use std::io;
+use std::os::unix::fs::FileExt;
+
+use crate::config::cli::MsrReadOptions;
+
+/// A bit field of a register, `low..=high`.
+pub struct MsrField {
+    pub name: &'static str,
+    pub low: u32,
+    pub high: u32,
+}
+
+impl MsrField {
+    const fn new(name: &'static str, low: u32, high: u32) -> Self {
+        Self { name, low, high }
+    }
+
+    /// Extracts this field from a raw register value.
+    pub fn extract(&self, value: u64) -> u64 {
+        let width = self.high - self.low + 1;
+        let mask = if width >= 64 {
+            u64::MAX
+        } else {
+            (1 << width) - 1
+        };
+        (value >> self.low) & mask
+    }
+}
+
+/// A register with a known layout.
+pub struct MsrRegister {
+    pub address: u32,
+    pub name: &'static str,
+    pub fields: &'static [MsrField],
+}
+
+/// Registers decoded by `debug msr read` and read by the MSR thermal source.
+pub const KNOWN_REGISTERS: &[MsrRegister] = &[
+    MsrRegister {
+        address: 0x198,
+        name: "IA32_PERF_STATUS",
+        fields: &[MsrField::new("current_ratio", 8, 15)],
+    },
+    MsrRegister {
+        address: 0x19C,
+        name: "IA32_THERM_STATUS",
+        fields: &[
+            MsrField::new("thermal_status", 0, 0),
+            MsrField::new("thermal_status_log", 1, 1),
+            MsrField::new("prochot", 2, 2),
+            MsrField::new("critical_temperature", 4, 4),
+            MsrField::new("digital_readout", 16, 22),
+            MsrField::new("resolution_celsius", 27, 30),
+            MsrField::new("reading_valid", 31, 31),
+        ],
+    },
+    MsrRegister {
+        address: 0x1A2,
+        name: "MSR_TEMPERATURE_TARGET",
+        fields: &[
+            MsrField::new("tj_max", 16, 23),
+            MsrField::new("tcc_offset", 24, 29),
+        ],
+    },
+    MsrRegister {
+        address: 0x1B1,
+        name: "IA32_PACKAGE_THERM_STATUS",
+        fields: &[
+            MsrField::new("thermal_status", 0, 0),
+            MsrField::new("thermal_status_log", 1, 1),
+            MsrField::new("prochot", 2, 2),
+            MsrField::new("critical_temperature", 4, 4),
+            MsrField::new("digital_readout", 16, 22),
+        ],
+    },
+    MsrRegister {
+        address: 0x606,
+        name: "MSR_RAPL_POWER_UNIT",
+        fields: &[
+            MsrField::new("power_units", 0, 3),
+            MsrField::new("energy_units", 8, 12),
+            MsrField::new("time_units", 16, 19),
+        ],
+    },
+    MsrRegister {
+        address: 0x611,
+        name: "MSR_PKG_ENERGY_STATUS",
+        fields: &[MsrField::new("energy_counter", 0, 31)],
+    },
+];
+
+/// A utility class for reading model-specific registers.
+pub struct MsrUtil;
+
+impl MsrUtil {
+    /// Reads the 64-bit register at `address` on logical CPU `cpu`.
+    pub fn read(cpu: usize, address: u32) -> io::Result<u64> {
+        let path = format!("/dev/cpu/{}/msr", cpu);
+        let file = File::open(&path).map_err(|e| {
+            let hint = match e.kind() {
+                io::ErrorKind::NotFound => " (is the msr kernel module loaded? try `modprobe msr`)",
+                io::ErrorKind::PermissionDenied => " (reading MSRs requires root)",
+                _ => "",
+            };
+            io::Error::new(e.kind(), format!("Cannot open {}: {}{}", path, e, hint))
+        })?;
+
+        let mut buffer = [0u8; 8];
+        file.read_exact_at(&mut buffer, u64::from(address))
+            .map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!("Cannot read MSR {:#x} on CPU {}: {}", address, cpu, e),
+                )
+            })?;
+        Ok(u64::from_le_bytes(buffer))
+    }
+
+    /// Returns the known layout of the register at `address`, if any.
+    pub fn register(address: u32) -> Option<&'static MsrRegister> {
+        KNOWN_REGISTERS
+            .iter()
+            .find(|register| register.address == address)
+    }
+
+    /// Returns the named field of the known register at `address`.
+    pub fn field(address: u32, name: &str) -> io::Result<&'static MsrField> {
+        Self::register(address)
+            .and_then(|register| register.fields.iter().find(|field| field.name == name))
+            .ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Unknown field {} of MSR {:#x}", name, address),
+                )
+            })
+    }
+
+    /// Reads one register and prints its raw value and any known fields.
+    pub fn print_read(options: &MsrReadOptions) -> io::Result<()> {
+        let value = Self::read(options.cpu, options.address)?;
+        let register = Self::register(options.address);
+
+        println!(
+            "CPU {} MSR {:#x}{} = {:#018x}",
+            options.cpu,
+            options.address,
+            register
+                .map(|r| format!(" ({})", r.name))
+                .unwrap_or_default(),
+            value
+        );
+        for field in register.map(|r| r.fields).unwrap_or_default() {
+            println!(
+                "  {:<22} [{:>2}:{:<2}] = {}",
+                field.name,
+                field.high,
+                field.low,
+                field.extract(value)
+            );
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,214 @@
+//! Power Draw
+//!
+//! This module reports how much power the host draws: the platform total measured by ACPI
+//! power meters (`power_meter-acpi`), and every domain of Intel RAPL under
+//! `/sys/class/powercap`: each package with its `core`, `uncore` and `dram` subdomains, and
+//! the platform (`psys`). RAPL only exposes energy counters, so its watts are averaged
+//! between two cycles and first reported on the second one; the counters are reported too,
+//! for servers averaging over longer periods. Since the PLATYPUS fixes, the counters can
+//! only be read as root.
+
+use log::debug;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use crate::config::config_instance::Config;
+use crate::data::models::PowerInfo;
+use crate::hardware::hwmon_util::{HwmonUtil, HWMON_ROOT};
+
+/// Root of the powercap class in sysfs.
+const POWERCAP_ROOT: &str = "/sys/class/powercap";
+
+/// Driver name of ACPI power meters in hwmon.
+const ACPI_POWER_METER: &str = "power_meter";
+
+/// Previous energy counter (µJ) of each RAPL zone, keyed by its path.
+static RAPL_SAMPLES: Mutex<BTreeMap<String, (u64, Instant)>> = Mutex::new(BTreeMap::new());
+
+/// A utility class for reading power meters and RAPL energy counters.
+pub struct PowerUtil;
+
+impl PowerUtil {
+    /// Reads the power meters and RAPL domains if `collect_power` is enabled.
+    ///
+    /// `meters` are the power readings parsed from `sensors`, which already include the
+    /// ACPI power meters; they are read from sysfs when `sensors` was not run. Returns
+    /// `None` when the collector is disabled.
+    pub fn collect_if_enabled(meters: Option<Vec<PowerInfo>>) -> Option<Vec<PowerInfo>> {
+        if !Config::get().collect_power {
+            return None;
+        }
+        let mut power = meters.unwrap_or_else(|| Self::read_acpi_meters(Path::new(HWMON_ROOT)));
+        power.extend(Self::read_rapl(Path::new(POWERCAP_ROOT), Instant::now()));
+        Some(power)
+    }
+
+    /// Reads every ACPI power meter under `root` (the hwmon class directory).
+    pub fn read_acpi_meters(root: &Path) -> Vec<PowerInfo> {
+        let chips = match HwmonUtil::chips(root) {
+            Ok(chips) => chips,
+            Err(e) => {
+                debug!("Cannot enumerate {}: {}", root.display(), e);
+                return Vec::new();
+            }
+        };
+        let mut meters: Vec<PowerInfo> = chips
+            .into_iter()
+            .filter(|chip| chip.name == ACPI_POWER_METER)
+            .filter_map(|chip| {
+                // Power is in microwatts, averaged over the meter's interval.
+                let microwatts = HwmonUtil::read_number(&chip.dir.join("power1_average"))?;
+                Some(PowerInfo {
+                    adapter_name: chip.device,
+                    label: "power1".to_string(),
+                    power: (microwatts / 1_000_000.0) as f32,
+                    energy: None,
+                })
+            })
+            .collect();
+        meters.sort_by(|a, b| a.adapter_name.cmp(&b.adapter_name));
+        meters
+    }
+
+    /// Reads the RAPL zones under `root` (the powercap class directory), e.g. `intel-rapl:0`
+    /// (`package-0`), its subzone `intel-rapl:0:2` (`dram`) or `intel-rapl:1` (`psys`), and
+    /// returns their average power since the previous call.
+    pub fn read_rapl(root: &Path, now: Instant) -> Vec<PowerInfo> {
+        let Ok(entries) = fs::read_dir(root) else {
+            return Vec::new();
+        };
+        let mut zones: Vec<String> = entries
+            .flatten()
+            .map(|entry| entry.file_name().to_string_lossy().into_owned())
+            .filter(|zone| {
+                // `intel-rapl:<zone>` or `intel-rapl:<zone>:<subzone>`; the `intel-rapl`
+                // control type itself has no counter.
+                zone.strip_prefix("intel-rapl:").is_some_and(|numbers| {
+                    numbers
+                        .split(':')
+                        .all(|number| number.parse::<u32>().is_ok())
+                })
+            })
+            .collect();
+        zones.sort();
+
+        let mut samples = RAPL_SAMPLES.lock().unwrap_or_else(|e| e.into_inner());
+        zones
+            .into_iter()
+            .filter_map(|zone| {
+                let dir = root.join(&zone);
+                let energy = match fs::read_to_string(dir.join("energy_uj")) {
+                    Ok(value) => value.trim().parse::<u64>().ok()?,
+                    Err(e) => {
+                        debug!("Cannot read the RAPL counter of {}: {}", zone, e);
+                        return None;
+                    }
+                };
+                let (previous, taken) = samples.insert(dir.display().to_string(), (energy, now))?;
+                let elapsed = now.checked_duration_since(taken)?.as_secs_f64();
+                if elapsed <= 0.0 {
+                    return None;
+                }
+                // The counter wraps around to 0 after `max_energy_range_uj`, its largest
+                // value; a previous value above it means the counter was reset instead.
+                let consumed = match energy.checked_sub(previous) {
+                    Some(consumed) => consumed,
+                    None => {
+                        let max = HwmonUtil::read_trimmed(&dir.join("max_energy_range_uj"))?
+                            .parse::<u64>()
+                            .ok()?;
+                        let Some(remaining) = max.checked_sub(previous) else {
+                            debug!("The RAPL counter of {} was reset.", zone);
+                            return None;
+                        };
+                        remaining + energy + 1
+                    }
+                };
+                Some(PowerInfo {
+                    label: HwmonUtil::read_trimmed(&dir.join("name"))
+                        .unwrap_or_else(|| zone.clone()),
+                    adapter_name: zone,
+                    power: (consumed as f64 / 1_000_000.0 / elapsed) as f32,
+                    energy: Some(energy as f64 / 1_000_000.0),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+    use std::time::Duration;
+
+    #[test]
+    fn reads_acpi_meters_and_averages_rapl_counters() {
+        let root = TempDir::new("power");
+        let hwmon = root.join("hwmon");
+        fs::create_dir_all(hwmon.join("hwmon2")).unwrap();
+        fs::create_dir_all(hwmon.join("hwmon3")).unwrap();
+        fs::write(hwmon.join("hwmon2/name"), "power_meter\n").unwrap();
+        fs::write(hwmon.join("hwmon2/power1_average"), "130000000\n").unwrap();
+        fs::write(hwmon.join("hwmon3/name"), "coretemp\n").unwrap();
+
+        let meters = PowerUtil::read_acpi_meters(&hwmon);
+        assert_eq!(meters.len(), 1);
+        assert_eq!(meters[0].adapter_name, "hwmon2");
+        assert_eq!(meters[0].power, 130.0);
+
+        let powercap = root.join("powercap");
+        for (zone, name) in [
+            ("intel-rapl", ""),
+            ("intel-rapl:0", "package-0"),
+            ("intel-rapl:0:2", "dram"),
+        ] {
+            fs::create_dir_all(powercap.join(zone)).unwrap();
+            fs::write(powercap.join(zone).join("name"), name).unwrap();
+            fs::write(powercap.join(zone).join("energy_uj"), "262000000\n").unwrap();
+        }
+        let package = powercap.join("intel-rapl:0");
+        fs::write(package.join("max_energy_range_uj"), "262143328850\n").unwrap();
+
+        let start = Instant::now();
+        assert!(PowerUtil::read_rapl(&powercap, start).is_empty());
+        fs::write(package.join("energy_uj"), "712000000\n").unwrap();
+        fs::write(powercap.join("intel-rapl:0:2/energy_uj"), "312000000\n").unwrap();
+        let power: Vec<_> = PowerUtil::read_rapl(&powercap, start + Duration::from_secs(10))
+            .into_iter()
+            .map(|power| (power.adapter_name, power.label, power.power, power.energy))
+            .collect();
+        assert_eq!(
+            power,
+            [
+                (
+                    "intel-rapl:0".to_string(),
+                    "package-0".to_string(),
+                    45.0,
+                    Some(712.0)
+                ),
+                (
+                    "intel-rapl:0:2".to_string(),
+                    "dram".to_string(),
+                    5.0,
+                    Some(312.0)
+                ),
+            ]
+        );
+
+        // 100 J until the counter wraps, then 50 J.
+        fs::write(package.join("energy_uj"), "50000000\n").unwrap();
+        fs::write(package.join("max_energy_range_uj"), "811999999\n").unwrap();
+        let power = PowerUtil::read_rapl(&powercap, start + Duration::from_secs(20));
+        assert_eq!(power[0].power, 15.0);
+
+        // A counter that went back below its previous value without wrapping was reset.
+        fs::write(package.join("energy_uj"), "20000000\n").unwrap();
+        fs::write(package.join("max_energy_range_uj"), "40000000\n").unwrap();
+        let power = PowerUtil::read_rapl(&powercap, start + Duration::from_secs(30));
+        assert_eq!(power[0].adapter_name, "intel-rapl:0:2");
+    }
+}
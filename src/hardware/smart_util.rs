@@ -0,0 +1,216 @@
+//! SMART Disk Health
+//!
+//! This module reports the health of each disk through `smartctl --json`: the overall
+//! SMART verdict, reallocated sectors (the grown defect list on SCSI disks), power-on
+//! hours and the drive temperature. Disks are found with `smartctl --scan` unless listed
+//! in `devices`. Reading SMART data needs root, so the collector only runs when `[smart]`
+//! is configured, and at most every `interval_secs` since the values change slowly.
+//! Disks in standby are not woken up; they are reported with an `unknown` verdict.
+
+use log::{debug, warn};
+use serde::Deserialize;
+use serde_json::Value;
+use std::process::{Command, Stdio};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::config_instance::Config;
+use crate::data::models::{SmartHealth, SmartInfo};
+use crate::data::units::Celsius;
+
+/// SMART attribute id of the reallocated sector count on ATA disks.
+const REALLOCATED_SECTOR_COUNT: u64 = 5;
+
+/// smartctl exit status bits meaning the command line was invalid or the device could
+/// not be opened; the higher bits only describe the disk's condition.
+const SMARTCTL_FATAL_BITS: i32 = 0b11;
+
+/// Time of the previous snapshot.
+static LAST_SNAPSHOT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Configuration of the `[smart]` section. When present, disk health is read with smartctl.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SmartConfig {
+    /// The smartctl executable.
+    pub command: String,
+    /// Devices to read (e.g. `/dev/sda`); every device found by `smartctl --scan` when empty.
+    pub devices: Vec<String>,
+    /// Minimum seconds between two snapshots.
+    pub interval_secs: u64,
+}
+
+impl Default for SmartConfig {
+    fn default() -> Self {
+        Self {
+            command: "smartctl".to_string(),
+            devices: Vec::new(),
+            interval_secs: 300,
+        }
+    }
+}
+
+/// A utility class for reading disk health through smartctl.
+pub struct SmartUtil;
+
+impl SmartUtil {
+    /// Reads every disk if `[smart]` is configured and a snapshot is due.
+    ///
+    /// Returns `None` when it is not configured or no snapshot is due.
+    pub fn collect_if_due() -> Option<Vec<SmartInfo>> {
+        let config = Config::get().smart.as_ref()?;
+        let mut last = LAST_SNAPSHOT.lock().unwrap_or_else(|e| e.into_inner());
+        let interval = Duration::from_secs(config.interval_secs);
+        if last.is_some_and(|taken| taken.elapsed() < interval) {
+            return None;
+        }
+        *last = Some(Instant::now());
+
+        let devices = if config.devices.is_empty() {
+            match Self::run(config, &["--scan", "--json"]) {
+                Ok(output) => Self::parse_scan(&output),
+                Err(e) => {
+                    warn!("Failed to list disks with smartctl: {}", e);
+                    return None;
+                }
+            }
+        } else {
+            config
+                .devices
+                .iter()
+                .map(|device| (device.clone(), None))
+                .collect()
+        };
+
+        Some(
+            devices
+                .iter()
+                .filter_map(|(device, device_type)| {
+                    let mut args = vec!["--json", "--info", "--health", "--attributes"];
+                    args.extend(["--nocheck", "standby,0"]);
+                    if let Some(device_type) = device_type {
+                        args.extend(["--device", device_type]);
+                    }
+                    args.push(device);
+                    match Self::run(config, &args).and_then(|output| {
+                        Self::parse_device(device, &output).map_err(|e| e.to_string())
+                    }) {
+                        Ok(info) => Some(info),
+                        Err(e) => {
+                            debug!("Skipping SMART data of {}: {}", device, e);
+                            None
+                        }
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    /// Runs smartctl, accepting exit statuses that only report the disk's condition.
+    fn run(config: &SmartConfig, args: &[&str]) -> Result<String, String> {
+        let output = Command::new(&config.command)
+            .args(args)
+            .stdin(Stdio::null())
+            .output()
+            .map_err(|e| format!("cannot run {}: {}", config.command, e))?;
+        match output.status.code() {
+            Some(code) if code & SMARTCTL_FATAL_BITS == 0 => {
+                Ok(String::from_utf8_lossy(&output.stdout).to_string())
+            }
+            _ => {
+                let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+                Err(format!("smartctl failed ({}): {}", output.status, stderr))
+            }
+        }
+    }
+
+    /// Parses `smartctl --scan --json` into device names and their `--device` types.
+    pub fn parse_scan(output: &str) -> Vec<(String, Option<String>)> {
+        let Ok(scan) = serde_json::from_str::<Value>(output) else {
+            return Vec::new();
+        };
+        scan["devices"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|device| {
+                let name = device["name"].as_str()?.to_string();
+                let device_type = device["type"].as_str().map(str::to_string);
+                Some((name, device_type))
+            })
+            .collect()
+    }
+
+    /// Parses the `smartctl --json` output of one device.
+    pub fn parse_device(device: &str, output: &str) -> Result<SmartInfo, serde_json::Error> {
+        let report: Value = serde_json::from_str(output)?;
+        let text = |key: &str| report[key].as_str().map(|value| value.trim().to_string());
+
+        let health = match report["smart_status"]["passed"].as_bool() {
+            Some(true) => SmartHealth::Passed,
+            Some(false) => SmartHealth::Failed,
+            None => SmartHealth::Unknown,
+        };
+        let reallocated_sectors = report["ata_smart_attributes"]["table"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .find(|attribute| attribute["id"].as_u64() == Some(REALLOCATED_SECTOR_COUNT))
+            .and_then(|attribute| attribute["raw"]["value"].as_u64())
+            .or_else(|| report["scsi_grown_defect_list"].as_u64());
+
+        Ok(SmartInfo {
+            device: device.to_string(),
+            model: text("model_name").or_else(|| text("scsi_model_name")),
+            serial_number: text("serial_number"),
+            health,
+            reallocated_sectors,
+            power_on_hours: report["power_on_time"]["hours"].as_u64(),
+            temperature: report["temperature"]["current"]
+                .as_f64()
+                .map(|value| Celsius(value as f32)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_smartctl_reports() {
+        let scan = r#"{"devices": [
+            {"name": "/dev/sda", "info_name": "/dev/sda [SAT]", "type": "sat", "protocol": "ATA"},
+            {"name": "/dev/nvme0", "type": "nvme", "protocol": "NVMe"}
+        ]}"#;
+        assert_eq!(
+            SmartUtil::parse_scan(scan),
+            vec![
+                ("/dev/sda".to_string(), Some("sat".to_string())),
+                ("/dev/nvme0".to_string(), Some("nvme".to_string())),
+            ]
+        );
+
+        let ata = r#"{
+            "model_name": "WDC WD40EFRX-68N32N0", "serial_number": "WD-WCC7K1234567",
+            "smart_status": {"passed": false},
+            "ata_smart_attributes": {"table": [
+                {"id": 1, "name": "Raw_Read_Error_Rate", "raw": {"value": 0}},
+                {"id": 5, "name": "Reallocated_Sector_Ct", "raw": {"value": 24}}
+            ]},
+            "power_on_time": {"hours": 35012},
+            "temperature": {"current": 34}
+        }"#;
+        let info = SmartUtil::parse_device("/dev/sda", ata).unwrap();
+        assert_eq!(info.model.as_deref(), Some("WDC WD40EFRX-68N32N0"));
+        assert_eq!(info.health, SmartHealth::Failed);
+        assert_eq!(info.reallocated_sectors, Some(24));
+        assert_eq!(info.power_on_hours, Some(35012));
+        assert_eq!(info.temperature, Some(Celsius(34.0)));
+
+        let standby = r#"{"device": {"name": "/dev/sdb"}, "power_mode": "STANDBY"}"#;
+        let info = SmartUtil::parse_device("/dev/sdb", standby).unwrap();
+        assert_eq!(info.health, SmartHealth::Unknown);
+        assert_eq!(info.temperature, None);
+    }
+}
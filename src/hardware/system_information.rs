@@ -1,6 +1,11 @@
 use sysinfo::{Components, Disks, Networks, System, Users};
 
-use crate::data::models::{CpuInfo, DiskInfo, MemoryInfo, NetworkInfo, ProcessInfo, Uptime};
+use crate::data::models::{
+    ComponentInfo, CpuInfo, DiskInfo, InterfaceAddress, MemoryInfo, NetworkInfo, ProcessInfo,
+    Uptime,
+};
+use crate::data::units::{Bytes, Percent};
+use crate::hardware::system_source::SystemSource;
 
 pub struct SystemInfo {
     system: System,
@@ -30,26 +35,6 @@ impl SystemInfo {
         }
     }
 
-    /// Refreshes only the system-related data.
-    pub fn refresh_system(&mut self) {
-        self.system.refresh_all();
-    }
-
-    /// Refreshes only the network-related data.
-    pub fn refresh_networks(&mut self) {
-        self.networks.refresh(false);
-    }
-
-    /// Refreshes only the disk-related data.
-    pub fn refresh_disks(&mut self) {
-        self.disks.refresh(false);
-    }
-
-    /// Refreshes only the components data.
-    pub fn refresh_components(&mut self) {
-        self.components.refresh(false);
-    }
-
     /// Refreshes only the users data.
     pub fn refresh_users(&mut self) {
         self.users.refresh();
@@ -74,62 +59,109 @@ impl SystemInfo {
         &self.components
     }
 
-    /// Retrieves memory information.
-    pub fn memory_info(&self) -> MemoryInfo {
+    /// Retrieves system details such as OS name, version, kernel, hostname.
+    pub fn system_details(&self) -> (String, String, String, String) {
+        (
+            self.os_name(),
+            self.os_version(),
+            self.kernel_version(),
+            self.host_name(),
+        )
+    }
+}
+
+impl SystemSource for SystemInfo {
+    fn refresh_system(&mut self) {
+        self.system.refresh_all();
+    }
+
+    fn refresh_networks(&mut self) {
+        self.networks.refresh(false);
+    }
+
+    fn refresh_disks(&mut self) {
+        self.disks.refresh(false);
+    }
+
+    fn refresh_components(&mut self) {
+        self.components.refresh(false);
+    }
+
+    fn memory_info(&self) -> MemoryInfo {
         MemoryInfo {
-            total: self.system.total_memory(),
-            used: self.system.used_memory(),
-            total_swap: self.system.total_swap(),
-            used_swap: self.system.used_swap(),
+            total: Bytes(self.system.total_memory()),
+            used: Bytes(self.system.used_memory()),
+            total_swap: Bytes(self.system.total_swap()),
+            used_swap: Bytes(self.system.used_swap()),
+            container_limit: None,
+            container_used: None,
         }
     }
 
-    /// Retrieves CPU information.
-    pub fn cpu_info(&self) -> CpuInfo {
+    fn cpu_info(&self) -> CpuInfo {
         CpuInfo {
             usage_per_core: self
                 .system
                 .cpus()
                 .iter()
-                .map(|cpu| cpu.cpu_usage())
+                .map(|cpu| Percent(cpu.cpu_usage()))
                 .collect(),
             core_count: self.system.cpus().len(),
             cpu_arch: sysinfo::System::cpu_arch(),
+            container_cpu_limit: None,
         }
     }
 
-    /// Retrieves disk information as a vector of `DiskInfo`.
-    pub fn disk_info(&self) -> Vec<DiskInfo> {
+    fn disk_info(&self) -> Vec<DiskInfo> {
         self.disks
             .iter()
             .map(|disk| {
                 let usage = disk.usage();
                 DiskInfo {
                     name: disk.name().to_string_lossy().to_string(),
-                    total_space: disk.total_space(),
-                    available_space: disk.available_space(),
-                    read_bytes: usage.read_bytes,
-                    written_bytes: usage.written_bytes,
+                    total_space: Bytes(disk.total_space()),
+                    available_space: Bytes(disk.available_space()),
+                    read_bytes: Bytes(usage.read_bytes),
+                    written_bytes: Bytes(usage.written_bytes),
+                    total_read_bytes: usage.total_read_bytes,
+                    total_written_bytes: usage.total_written_bytes,
+                    read_bytes_per_sec: None,
+                    written_bytes_per_sec: None,
+                    counter_reset: false,
                 }
             })
             .collect()
     }
 
-    /// Retrieves network information as a vector of `NetworkInfo`.
-    pub fn network_info(&self) -> Vec<NetworkInfo> {
+    fn network_info(&self) -> Vec<NetworkInfo> {
         self.networks
             .iter()
             .map(|(name, data)| NetworkInfo {
                 interface_name: name.clone(),
-                received: data.received(),
-                transmitted: data.transmitted(),
+                received: Bytes(data.received()),
+                transmitted: Bytes(data.transmitted()),
                 mtu: Some(data.mtu()),
+                mac_address: Some(data.mac_address())
+                    .filter(|mac| !mac.is_unspecified())
+                    .map(|mac| mac.to_string()),
+                addresses: data
+                    .ip_networks()
+                    .iter()
+                    .map(|network| InterfaceAddress {
+                        address: network.addr,
+                        prefix_length: network.prefix,
+                    })
+                    .collect(),
+                total_received: data.total_received(),
+                total_transmitted: data.total_transmitted(),
+                received_per_sec: None,
+                transmitted_per_sec: None,
+                counter_reset: false,
             })
             .collect()
     }
 
-    /// Retrieves process information as a vector of `ProcessInfo`.
-    pub fn process_info(&self) -> Vec<ProcessInfo> {
+    fn process_info(&self) -> Vec<ProcessInfo> {
         self.system
             .processes()
             .iter()
@@ -141,38 +173,31 @@ impl SystemInfo {
             .collect()
     }
 
-    /// Retrieves system uptime.
-    pub fn uptime(&self) -> Uptime {
-        Uptime::from_seconds(sysinfo::System::uptime())
+    fn components_info(&self) -> Vec<ComponentInfo> {
+        self.components.iter().map(ComponentInfo::from).collect()
+    }
+
+    fn uptime(&self) -> Uptime {
+        Uptime::new(sysinfo::System::uptime(), sysinfo::System::boot_time())
     }
 
-    /// Retrieves the operating system name.
-    pub fn os_name(&self) -> String {
+    fn os_name(&self) -> String {
         sysinfo::System::name().unwrap_or_else(|| "<unknown>".to_string())
     }
 
-    /// Retrieves the operating system version.
-    pub fn os_version(&self) -> String {
+    fn os_version(&self) -> String {
         sysinfo::System::os_version().unwrap_or_else(|| "<unknown>".to_string())
     }
 
-    /// Retrieves the kernel version.
-    pub fn kernel_version(&self) -> String {
+    fn kernel_version(&self) -> String {
         sysinfo::System::kernel_version().unwrap_or_else(|| "<unknown>".to_string())
     }
 
-    /// Retrieves the hostname of the system.
-    pub fn host_name(&self) -> String {
+    fn host_name(&self) -> String {
         sysinfo::System::host_name().unwrap_or_else(|| "<unknown>".to_string())
     }
 
-    /// Retrieves system details such as OS name, version, kernel, hostname.
-    pub fn system_details(&self) -> (String, String, String, String) {
-        (
-            Self::os_name(self),
-            Self::os_version(self),
-            Self::kernel_version(self),
-            Self::host_name(self),
-        )
+    fn is_supported(&self) -> bool {
+        sysinfo::IS_SUPPORTED_SYSTEM
     }
 }
@@ -2,15 +2,18 @@ use crate::{
     data::models::{
         ComponentInfo, CpuInfo, DiskInfo, MemoryInfo, NetworkInfo, ProcessInfo, Uptime,
     },
-    hardware::system_information::SystemInfo,
+    hardware::{system_information::SystemInfo, system_source::SystemSource},
 };
 use log::info;
 use sysinfo::{Components, Users};
 
-pub struct SysInfoMonitor {
-    system_info: SystemInfo,
+/// Builds DTOs from a `SystemSource`, refreshing the relevant data before each read.
+/// Defaults to the live host (`SystemInfo`).
+pub struct SysInfoMonitor<S = SystemInfo> {
+    system_info: S,
 }
 
+/// Operations only available on the live host, as they expose sysinfo types.
 #[allow(dead_code)] // Suppress warnings for unused functions.
 impl SysInfoMonitor {
     /// Creates a new instance of `SysInfoMonitor`.
@@ -25,6 +28,53 @@ impl SysInfoMonitor {
         self.system_info.refresh_all();
     }
 
+    /// Refreshes user-related data.
+    pub fn refresh_users(&mut self) {
+        self.system_info.refresh_users();
+    }
+
+    /// Returns user information.
+    pub fn get_user_info(&mut self) -> &Users {
+        self.refresh_users();
+        self.system_info.get_users()
+    }
+
+    /// Logs user information.
+    pub fn log_user_info(&mut self) {
+        let users = self.get_user_info();
+        info!("Users:");
+        for user in users.iter() {
+            info!("Name: {}", user.name());
+        }
+    }
+
+    /// Returns system details.
+    pub fn get_system_details(&mut self) -> (String, String, String, String) {
+        self.refresh_system();
+        self.system_info.system_details()
+    }
+
+    /// Returns system components as a read-only reference.
+    pub fn get_components(&mut self) -> &Components {
+        self.refresh_components();
+        self.system_info.get_components()
+    }
+}
+
+#[allow(dead_code)] // Suppress warnings for unused functions.
+impl<S: SystemSource> SysInfoMonitor<S> {
+    /// Creates a monitor reading from `source`, e.g. canned data in tests.
+    pub fn with_source(source: S) -> Self {
+        Self {
+            system_info: source,
+        }
+    }
+
+    /// Returns the underlying system source.
+    pub fn source(&self) -> &S {
+        &self.system_info
+    }
+
     /// Refreshes system-related data.
     pub fn refresh_system(&mut self) {
         self.system_info.refresh_system();
@@ -45,11 +95,6 @@ impl SysInfoMonitor {
         self.system_info.refresh_networks();
     }
 
-    /// Refreshes user-related data.
-    pub fn refresh_users(&mut self) {
-        self.system_info.refresh_users();
-    }
-
     /// Returns memory information.
     pub fn get_memory_info(&mut self) -> MemoryInfo {
         self.refresh_system();
@@ -78,22 +123,7 @@ impl SysInfoMonitor {
         info!("CPU Architecture: {}", cpu_info.cpu_arch);
         info!("CPU Usage per Core:");
         for (i, usage) in cpu_info.usage_per_core.iter().enumerate() {
-            info!("Core {}: {:.2}%", i, usage);
-        }
-    }
-
-    /// Returns user information.
-    pub fn get_user_info(&mut self) -> &Users {
-        self.refresh_users();
-        self.system_info.get_users()
-    }
-
-    /// Logs user information.
-    pub fn log_user_info(&mut self) {
-        let users = self.get_user_info();
-        info!("Users:");
-        for user in users.iter() {
-            info!("Name: {}", user.name());
+            info!("Core {}: {:.2}", i, usage);
         }
     }
 
@@ -153,7 +183,7 @@ impl SysInfoMonitor {
 
     /// Retrieves the operating system name.
     pub fn get_os_name(&self) -> String {
-        sysinfo::System::name().unwrap_or_else(|| "<unknown>".to_string())
+        self.system_info.os_name()
     }
 
     /// Logs the operating system name.
@@ -164,7 +194,7 @@ impl SysInfoMonitor {
 
     /// Retrieves the operating system version.
     pub fn get_os_version(&self) -> String {
-        sysinfo::System::os_version().unwrap_or_else(|| "<unknown>".to_string())
+        self.system_info.os_version()
     }
 
     /// Logs the operating system version.
@@ -175,7 +205,7 @@ impl SysInfoMonitor {
 
     /// Retrieves the kernel version.
     pub fn get_kernel_version(&self) -> String {
-        sysinfo::System::kernel_version().unwrap_or_else(|| "<unknown>".to_string())
+        self.system_info.kernel_version()
     }
 
     /// Logs the kernel version.
@@ -186,7 +216,7 @@ impl SysInfoMonitor {
 
     /// Retrieves the hostname.
     pub fn get_host_name(&self) -> String {
-        sysinfo::System::host_name().unwrap_or_else(|| "<unknown>".to_string())
+        self.system_info.host_name()
     }
 
     /// Logs the hostname.
@@ -195,12 +225,6 @@ impl SysInfoMonitor {
         info!("Hostname: {}", host_name);
     }
 
-    /// Returns system details.
-    pub fn get_system_details(&mut self) -> (String, String, String, String) {
-        self.refresh_system();
-        self.system_info.system_details()
-    }
-
     /// Logs system details.
     pub fn log_system_details(&mut self) {
         info!("OS Name: {}", self.get_os_name());
@@ -221,19 +245,10 @@ impl SysInfoMonitor {
         info!("System Uptime: {}", uptime.to_string());
     }
 
-    /// Returns system components as a read-only reference.
-    pub fn get_components(&mut self) -> &Components {
-        self.refresh_components();
-        self.system_info.get_components()
-    }
-
     /// Returns a vector of `ComponentInfo` DTOs representing system components.
     pub fn get_components_info(&mut self) -> Vec<ComponentInfo> {
         self.refresh_components();
-        self.get_components()
-            .iter()
-            .map(ComponentInfo::from)
-            .collect()
+        self.system_info.components_info()
     }
 
     /// Logs detailed information about all system components.
@@ -246,15 +261,15 @@ impl SysInfoMonitor {
                 component.label,
                 component
                     .temperature
-                    .map(|t| format!("{:.2}°C", t))
+                    .map(|t| format!("{:.2}", t))
                     .unwrap_or_else(|| "Unavailable".to_string()),
                 component
                     .max_temperature
-                    .map(|t| format!("{:.2}°C", t))
+                    .map(|t| format!("{:.2}", t))
                     .unwrap_or_else(|| "Unavailable".to_string()),
                 component
                     .critical_temperature
-                    .map(|t| format!("{:.2}°C", t))
+                    .map(|t| format!("{:.2}", t))
                     .unwrap_or_else(|| "Unavailable".to_string()),
             );
         }
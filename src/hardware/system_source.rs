@@ -0,0 +1,49 @@
+//! System Source
+//!
+//! The readings `SysInfoMonitor` builds its DTOs from. `SystemInfo` reads the live host
+//! through sysinfo; tests implement this trait with canned data, so DTO building, filtering
+//! and thresholds can be exercised without a real Linux host.
+
+use crate::data::models::{
+    ComponentInfo, CpuInfo, DiskInfo, MemoryInfo, NetworkInfo, ProcessInfo, Uptime,
+};
+
+/// A provider of system readings.
+///
+/// Readings reflect the state as of the last matching `refresh_*` call.
+pub trait SystemSource {
+    /// Refreshes memory, CPU and process data.
+    fn refresh_system(&mut self);
+    /// Refreshes disk data.
+    fn refresh_disks(&mut self);
+    /// Refreshes network data.
+    fn refresh_networks(&mut self);
+    /// Refreshes component (temperature sensor) data.
+    fn refresh_components(&mut self);
+
+    /// Retrieves memory information.
+    fn memory_info(&self) -> MemoryInfo;
+    /// Retrieves CPU information.
+    fn cpu_info(&self) -> CpuInfo;
+    /// Retrieves disk information.
+    fn disk_info(&self) -> Vec<DiskInfo>;
+    /// Retrieves network information.
+    fn network_info(&self) -> Vec<NetworkInfo>;
+    /// Retrieves process information.
+    fn process_info(&self) -> Vec<ProcessInfo>;
+    /// Retrieves component information.
+    fn components_info(&self) -> Vec<ComponentInfo>;
+    /// Retrieves system uptime.
+    fn uptime(&self) -> Uptime;
+
+    /// Retrieves the operating system name.
+    fn os_name(&self) -> String;
+    /// Retrieves the operating system version.
+    fn os_version(&self) -> String;
+    /// Retrieves the kernel version.
+    fn kernel_version(&self) -> String;
+    /// Retrieves the hostname of the system.
+    fn host_name(&self) -> String;
+    /// Whether the platform can be read at all; every section is unsupported otherwise.
+    fn is_supported(&self) -> bool;
+}
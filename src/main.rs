@@ -4,6 +4,7 @@
 //! the application, sets up signal handling, and delegates execution to the appropriate main loop
 //! based on the environment (e.g., ESXi or Linux).
 
+mod alert;
 mod config;
 mod data;
 mod hardware;
@@ -11,9 +12,13 @@ mod main_loop;
 mod network;
 mod sensor;
 mod system;
+#[cfg(test)]
+mod test_util;
 
+use config::cli::CliCommand;
 use config::config_instance::Config;
 use config::config_loader::{initialize_logger, load_application_config};
+use config::i18n::Text;
 
 use log::{info, warn};
 use std::sync::{atomic::AtomicBool, Arc};
@@ -24,7 +29,16 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
     initialize_logger();
 
     // Set the global configuration
-    Config::initialize(load_application_config());
+    let (config, command) = load_application_config();
+    Config::initialize(config);
+
+    if !matches!(command, CliCommand::Run) {
+        if let Err(e) = run_command(command) {
+            eprintln!("{}", Text::Error(&e.to_string()).render());
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
 
     SystemUtil::redirect_to_null();
     let is_tty: bool = SystemUtil::is_tty();
@@ -58,3 +72,39 @@ fn setup(running: &Arc<AtomicBool>) {
 }
 #[cfg(not(unix))]
 fn setup(_running: &Arc<AtomicBool>) {}
+
+/// Executes a one-shot subcommand instead of the monitoring loop.
+#[cfg(unix)]
+fn run_command(command: CliCommand) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        CliCommand::Run => Ok(()),
+        CliCommand::Replay(options) => Ok(network::replay_util::ReplayUtil::replay(&options)?),
+        CliCommand::Facts(options) => Ok(system::facts_util::FactsUtil::print(&options)?),
+        CliCommand::Telegraf(options) => Ok(system::telegraf_util::TelegrafUtil::run(&options)?),
+        CliCommand::Collectd(options) => Ok(system::collectd_util::CollectdUtil::run(&options)?),
+        CliCommand::DebugMsrRead(options) => Ok(hardware::msr_util::MsrUtil::print_read(&options)?),
+        CliCommand::BundleExport(options) => Ok(system::bundle_util::BundleUtil::export(&options)?),
+        CliCommand::BundleImport(options) => Ok(system::bundle_util::BundleUtil::import(&options)?),
+        CliCommand::Status(options) => Ok(system::control_socket::ControlSocket::print_status(
+            &options,
+        )?),
+        CliCommand::Check(options) => {
+            std::process::exit(sensor::check_util::CheckUtil::run(&options))
+        }
+        CliCommand::Completions(options) => {
+            config::cli::print_completions(&options);
+            Ok(())
+        }
+        CliCommand::Man => Ok(config::cli::print_man_page()?),
+        CliCommand::Package(options) => Ok(system::package_util::PackageUtil::run(&options)?),
+    }
+}
+#[cfg(not(unix))]
+fn run_command(command: CliCommand) -> Result<(), Box<dyn std::error::Error>> {
+    match command {
+        CliCommand::Completions(options) => config::cli::print_completions(&options),
+        CliCommand::Man => config::cli::print_man_page()?,
+        _ => warn!("Subcommands are only supported on Unix platforms."),
+    }
+    Ok(())
+}
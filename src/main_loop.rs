@@ -7,13 +7,23 @@
 use crate::config::config_instance::Config;
 use crate::config::AppConfig;
 use crate::hardware::system_information_monitor::SysInfoMonitor;
+use crate::network::api_server::ApiServer;
+use crate::network::command_channel::CommandChannel;
+use crate::network::heartbeat::HeartbeatSender;
+use crate::network::snmp_agent::SnmpAgent;
+use crate::sensor::black_box::BlackBox;
 use crate::sensor::sensor_util::SensorUtils;
+use crate::system::agent_id::AgentId;
+use crate::system::control_socket::ControlSocket;
 use crate::system::installer::InstallerUtil;
-use log::{error, info};
+use crate::system::log_level::LogLevelUtil;
+use crate::system::priority_util::PriorityUtil;
+use crate::system::scheduler::Scheduler;
+use crate::system::self_metrics::SelfMetrics;
+use log::{error, info, warn};
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
-use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 /// Detects the environment and delegates execution to the appropriate loop.
 pub fn run_main_loop(running: &Arc<AtomicBool>) {
@@ -23,7 +33,14 @@ pub fn run_main_loop(running: &Arc<AtomicBool>) {
 
 /// Main loop for Linux/Dev systems.
 fn run_linux_main_loop(running: &Arc<AtomicBool>, config: &AppConfig) {
-    if !InstallerUtil::ensure_sensors_installed() {
+    SelfMetrics::mark_started();
+    PriorityUtil::apply();
+
+    let uses_sensors = match &config.thermal_reconcile {
+        Some(reconcile) => reconcile.sources.iter().any(|source| source.uses_sensors()),
+        None => config.thermal_source.uses_sensors(),
+    };
+    if uses_sensors && !InstallerUtil::ensure_sensors_installed() {
         error!("Failed to ensure lm-sensors is installed.");
         return;
     }
@@ -31,8 +48,38 @@ fn run_linux_main_loop(running: &Arc<AtomicBool>, config: &AppConfig) {
     let mut monitor = SysInfoMonitor::new();
     monitor.setup_monitoring();
 
+    AgentId::register_if_needed(&config.server);
+    HeartbeatSender::start_if_configured(&config.server, running);
+    CommandChannel::start_if_configured(&config.server, running);
+    BlackBox::upload_pending(&config.server);
+    ApiServer::start_if_configured();
+    SnmpAgent::start_if_configured();
+    ControlSocket::start_if_configured();
+    LogLevelUtil::listen_for_signal();
+
+    let mut scheduler = Scheduler::new(Duration::from_secs(config.interval_secs))
+        .with_jitter(Duration::from_secs(config.jitter_secs))
+        .with_catch_up(config.catch_up);
+    if config.splay_secs > 0 {
+        info!(
+            "Delaying first collection by up to {} s of splay.",
+            config.splay_secs
+        );
+        scheduler.splay(Duration::from_secs(config.splay_secs));
+    }
     while running.load(Ordering::Relaxed) {
+        let started = Instant::now();
         SensorUtils::process_sensor_data(&config.server, &mut monitor);
-        thread::sleep(Duration::from_secs(config.interval_secs));
+        SelfMetrics::record_cycle(started.elapsed());
+        scheduler.set_interval(Duration::from_secs(Config::interval_secs()));
+        let skipped = scheduler.wait_next();
+        if skipped > 0 {
+            SelfMetrics::record_skipped(skipped);
+            warn!(
+                "Collection fell behind schedule; skipped {} sample(s).",
+                skipped
+            );
+        }
     }
+    SensorUtils::flush_pending_batch(&config.server);
 }
@@ -0,0 +1,190 @@
+//! Delivery Acknowledgements
+//!
+//! Every encoded payload is numbered with a sequence number that increases by one per
+//! payload and is never reused, even across agent restarts; over HTTP it is sent in the `X-Sentinel-Sequence`
+//! header, and a spooled payload keeps its number when it is replayed, so the server can
+//! discard duplicates. With `require_ack = true`, a 2xx response only counts as delivered
+//! if the server acknowledges that number, either in an `X-Sentinel-Ack` header or as
+//! `{"ack": <sequence>}` in a JSON body. Unacknowledged payloads take the retry and spool
+//! path like failed ones, which gives at-least-once delivery.
+//!
+//! Numbers are reserved in blocks of `SEQUENCE_BLOCK`, whose end is persisted in its own
+//! file before any number of the block is handed out, so the agent writes to disk once per
+//! block instead of once per payload. A restart skips the rest of the current block.
+
+use log::{error, warn};
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::config::config_instance::Config;
+use crate::network::http_client::HttpResponse;
+use crate::system::state::AgentState;
+
+/// Request header carrying the payload's sequence number.
+pub const SEQUENCE_HEADER: &str = "X-Sentinel-Sequence";
+
+/// Response header carrying the acknowledged sequence number.
+pub const ACK_HEADER: &str = "X-Sentinel-Ack";
+
+/// File name of the persisted sequence reservation inside the state directory.
+const SEQUENCE_FILE_NAME: &str = "sentinel-sequence";
+
+/// Number of sequence numbers reserved with each write of the sequence file.
+const SEQUENCE_BLOCK: u64 = 1000;
+
+/// Sequence numbers handed out since the last reservation, loaded on first use.
+static SEQUENCES: Mutex<Option<Reservation>> = Mutex::new(None);
+
+/// The last sequence number handed out and the last one reserved on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Reservation {
+    last: u64,
+    reserved: u64,
+}
+
+/// JSON acknowledgement body.
+#[derive(Deserialize)]
+struct AckBody {
+    ack: u64,
+}
+
+/// A utility class for numbering payloads and checking acknowledgements.
+pub struct AckUtil;
+
+impl AckUtil {
+    /// Returns the next sequence number, reserving a new block on disk when needed.
+    pub fn next_sequence() -> u64 {
+        let mut sequences = SEQUENCES.lock().unwrap_or_else(|e| e.into_inner());
+        Self::next_in(&mut sequences, &Config::state_file(SEQUENCE_FILE_NAME))
+    }
+
+    /// Hands out the number after `reservation`, loading it from `path` on first use and
+    /// reserving the next block in `path` once the current one is used up.
+    fn next_in(reservation: &mut Option<Reservation>, path: &Path) -> u64 {
+        let current = *reservation.get_or_insert_with(|| {
+            let reserved = Self::read_reservation(path);
+            Reservation {
+                last: reserved,
+                reserved,
+            }
+        });
+        let sequence = current.last + 1;
+        let reserved = if sequence > current.reserved {
+            let reserved = current.reserved + SEQUENCE_BLOCK;
+            if let Err(e) = fs::write(path, reserved.to_string()) {
+                error!("Failed to write sequence file {}: {}", path.display(), e);
+            }
+            reserved
+        } else {
+            current.reserved
+        };
+        *reservation = Some(Reservation {
+            last: sequence,
+            reserved,
+        });
+        sequence
+    }
+
+    /// The end of the last reserved block, or the last sequence recorded in the agent
+    /// state by versions that persisted it there.
+    fn read_reservation(path: &Path) -> u64 {
+        match fs::read_to_string(path) {
+            Ok(contents) => contents.trim().parse().unwrap_or_else(|e| {
+                warn!(
+                    "Ignoring unreadable sequence file {}: {}",
+                    path.display(),
+                    e
+                );
+                AgentState::load().payload_sequence
+            }),
+            Err(_) => AgentState::load().payload_sequence,
+        }
+    }
+
+    /// Checks that `response` acknowledges `sequence` when `require_ack` is set.
+    pub fn verify(response: &HttpResponse, sequence: u64) -> io::Result<()> {
+        if !Config::get().require_ack {
+            return Ok(());
+        }
+        match Self::acknowledged(response) {
+            Some(ack) if ack == sequence => Ok(()),
+            Some(ack) => Err(io::Error::other(format!(
+                "Server acknowledged sequence {} instead of {}",
+                ack, sequence
+            ))),
+            None => Err(io::Error::other(format!(
+                "Server did not acknowledge sequence {}",
+                sequence
+            ))),
+        }
+    }
+
+    /// The sequence number acknowledged by `response`, from the header or a JSON body.
+    fn acknowledged(response: &HttpResponse) -> Option<u64> {
+        if let Some(ack) = response.header(ACK_HEADER) {
+            return ack.trim().parse().ok();
+        }
+        serde_json::from_slice::<AckBody>(&response.body)
+            .ok()
+            .map(|body| body.ack)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn response(headers: &[(&str, &str)], body: &str) -> HttpResponse {
+        HttpResponse {
+            version: "HTTP/1.1".to_string(),
+            status_code: 200,
+            reason: "OK".to_string(),
+            headers: headers
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            body: body.as_bytes().to_vec(),
+            truncated: false,
+            complete: true,
+            raw: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn reads_acknowledgement_from_header_or_body() {
+        assert_eq!(
+            AckUtil::acknowledged(&response(&[("x-sentinel-ack", " 42 ")], "")),
+            Some(42)
+        );
+        assert_eq!(
+            AckUtil::acknowledged(&response(&[], r#"{"ack": 7, "stored": true}"#)),
+            Some(7)
+        );
+        assert_eq!(AckUtil::acknowledged(&response(&[], "OK")), None);
+    }
+
+    #[test]
+    fn reserves_sequence_numbers_in_blocks() {
+        let dir = TempDir::new("sequence");
+        let path = dir.join(SEQUENCE_FILE_NAME);
+        fs::write(&path, "41").unwrap();
+
+        let mut reservation = None;
+        assert_eq!(AckUtil::next_in(&mut reservation, &path), 42);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1041");
+        for expected in 43..=1041 {
+            assert_eq!(AckUtil::next_in(&mut reservation, &path), expected);
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "1041");
+        assert_eq!(AckUtil::next_in(&mut reservation, &path), 1042);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "2041");
+
+        // A restart continues after the reserved block, never reusing a number.
+        let mut restarted = None;
+        assert_eq!(AckUtil::next_in(&mut restarted, &path), 2042);
+    }
+}
@@ -0,0 +1,246 @@
+#![cfg(unix)]
+
+//! Local REST API
+//!
+//! This module runs a small read-only HTTP listener that exposes the most recent payload
+//! and the local history buffer, so dashboards and scripts on the LAN can consume data
+//! directly from the agent. Endpoints:
+//! - `GET /`: an embedded single-page dashboard polling the history endpoint.
+//! - `GET /api/v1/latest`: the most recent payload.
+//! - `GET /api/v1/sensors/{name}`: entries of the latest payload whose name or label matches.
+//! - `GET /api/v1/history?minutes=N`: payloads recorded in the last N minutes.
+//! - `GET /metrics`: the agent's own metrics in the Prometheus text format.
+//! - `GET /status`: the spool depth and the last send attempts with their outcomes.
+
+use log::{debug, error, info, warn};
+use serde::Serialize;
+use serde_json::{json, Value};
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use crate::config::config_instance::Config;
+use crate::data::history::History;
+use crate::network::send_log::SendLog;
+use crate::network::spool_util::SpoolUtil;
+use crate::system::self_metrics::SelfMetrics;
+
+/// Object keys that identify a named reading inside a payload.
+const NAME_KEYS: &[&str] = &[
+    "label",
+    "core_name",
+    "adapter_name",
+    "package_id",
+    "name",
+    "interface_name",
+];
+
+/// Self-contained dashboard page (inline styles and script, no external assets).
+const DASHBOARD_HTML: &str = include_str!("dashboard.html");
+
+/// A parsed HTTP response, ready to be written.
+struct ApiResponse {
+    status: &'static str,
+    content_type: &'static str,
+    body: Vec<u8>,
+}
+
+impl ApiResponse {
+    fn json<T: Serialize>(status: &'static str, body: &T) -> Self {
+        Self {
+            status,
+            content_type: "application/json",
+            body: serde_json::to_vec(body).unwrap_or_default(),
+        }
+    }
+
+    fn html(body: &'static str) -> Self {
+        Self {
+            status: "200 OK",
+            content_type: "text/html; charset=utf-8",
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    fn prometheus(body: String) -> Self {
+        Self {
+            status: "200 OK",
+            content_type: "text/plain; version=0.0.4; charset=utf-8",
+            body: body.into_bytes(),
+        }
+    }
+
+    fn error(status: &'static str, message: &str) -> Self {
+        Self::json(status, &json!({ "error": message }))
+    }
+}
+
+/// The local HTTP listener.
+pub struct ApiServer;
+
+impl ApiServer {
+    /// Starts the listener on a background thread if `api_listen` is configured.
+    pub fn start_if_configured() {
+        let Some(address) = Config::get().api_listen.clone() else {
+            return;
+        };
+
+        match TcpListener::bind(&address) {
+            Ok(listener) => {
+                info!("Local API listening on http://{}", address);
+                thread::spawn(move || Self::serve(listener));
+            }
+            Err(e) => error!("Failed to bind local API to {}: {}", address, e),
+        }
+    }
+
+    fn serve(listener: TcpListener) {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if let Err(e) = Self::handle_connection(stream) {
+                        debug!("Local API connection failed: {}", e);
+                    }
+                }
+                Err(e) => warn!("Failed to accept local API connection: {}", e),
+            }
+        }
+    }
+
+    fn handle_connection(mut stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(Duration::from_secs(5)))?;
+        stream.set_write_timeout(Some(Duration::from_secs(5)))?;
+
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut request_line = String::new();
+        reader.read_line(&mut request_line)?;
+
+        // Drain the request headers; the API does not use them.
+        let mut header = String::new();
+        while reader.read_line(&mut header)? > 2 {
+            header.clear();
+        }
+
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or_default();
+        let target = parts.next().unwrap_or_default();
+        let response = if method == "GET" {
+            Self::route(target)
+        } else {
+            ApiResponse::error("405 Method Not Allowed", "only GET is supported")
+        };
+
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+            response.status,
+            response.content_type,
+            response.body.len()
+        )?;
+        stream.write_all(&response.body)?;
+        stream.flush()
+    }
+
+    /// Dispatches a request target (path and query) to its handler.
+    fn route(target: &str) -> ApiResponse {
+        let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+        match path.trim_end_matches('/') {
+            "" | "/index.html" => ApiResponse::html(DASHBOARD_HTML),
+            "/api/v1/latest" => match History::latest() {
+                Some(entry) => ApiResponse::json("200 OK", &entry),
+                None => ApiResponse::error("503 Service Unavailable", "no data collected yet"),
+            },
+            "/metrics" => ApiResponse::prometheus(SelfMetrics::render(SpoolUtil::depth())),
+            "/status" => ApiResponse::json(
+                "200 OK",
+                &json!({
+                    "spool_depth": SpoolUtil::depth(),
+                    "send_attempts": SendLog::recent(),
+                }),
+            ),
+            "/api/v1/history" => {
+                let minutes = Self::query_param(query, "minutes")
+                    .and_then(|value| value.parse().ok())
+                    .unwrap_or(Config::get().history_minutes);
+                ApiResponse::json("200 OK", &History::since_minutes(minutes))
+            }
+            sensor_path if sensor_path.starts_with("/api/v1/sensors/") => {
+                let name = Self::percent_decode(&sensor_path["/api/v1/sensors/".len()..]);
+                Self::sensor(&name)
+            }
+            _ => ApiResponse::error("404 Not Found", "unknown endpoint"),
+        }
+    }
+
+    /// Finds all readings in the latest payload whose name or label matches `name`.
+    fn sensor(name: &str) -> ApiResponse {
+        let Some(entry) = History::latest() else {
+            return ApiResponse::error("503 Service Unavailable", "no data collected yet");
+        };
+
+        let mut matches = Vec::new();
+        Self::find_named(&entry.data, name, &mut matches);
+        if matches.is_empty() {
+            return ApiResponse::error("404 Not Found", "no sensor with that name");
+        }
+        ApiResponse::json(
+            "200 OK",
+            &json!({ "timestamp": entry.timestamp, "readings": matches }),
+        )
+    }
+
+    fn find_named(value: &Value, name: &str, matches: &mut Vec<Value>) {
+        match value {
+            Value::Object(map) => {
+                let is_match = NAME_KEYS.iter().any(|key| {
+                    map.get(*key)
+                        .and_then(Value::as_str)
+                        .is_some_and(|candidate| candidate.eq_ignore_ascii_case(name))
+                });
+                if is_match {
+                    matches.push(value.clone());
+                }
+                map.values()
+                    .for_each(|child| Self::find_named(child, name, matches));
+            }
+            Value::Array(values) => values
+                .iter()
+                .for_each(|child| Self::find_named(child, name, matches)),
+            _ => {}
+        }
+    }
+
+    fn query_param<'a>(query: &'a str, key: &str) -> Option<&'a str> {
+        query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(k, _)| *k == key)
+            .map(|(_, v)| v)
+    }
+
+    /// Decodes `%XX` escapes and `+` in a URL path segment.
+    fn percent_decode(input: &str) -> String {
+        let bytes = input.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            let escaped = (bytes[i] == b'%')
+                .then(|| input.get(i + 1..i + 3))
+                .flatten()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+            match (escaped, bytes[i]) {
+                (Some(byte), _) => {
+                    decoded.push(byte);
+                    i += 3;
+                    continue;
+                }
+                (None, b'+') => decoded.push(b' '),
+                (None, byte) => decoded.push(byte),
+            }
+            i += 1;
+        }
+        String::from_utf8_lossy(&decoded).to_string()
+    }
+}
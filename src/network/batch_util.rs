@@ -0,0 +1,97 @@
+//! Report Batching
+//!
+//! Fleets reporting at short intervals can accumulate samples and send them as one
+//! `SensorDataBatch` request instead of one request per sample. Batching is configured per
+//! sink (`[http_batch]`, `[mqtt.batch]`, `[udp.batch]`, `[unix_socket.batch]`); the
+//! settings of the sink in use apply. gRPC always ingests one sample per call.
+//!
+//! A batch is sent once it holds `max_samples` samples or its oldest sample is
+//! `max_secs` old, whichever comes first. Both limits are checked as samples arrive.
+
+use serde::Deserialize;
+use std::sync::Mutex;
+
+use crate::config::config_instance::Config;
+use crate::data::models::{SensorData, SensorDataBatch, TimestampedSensorData, SCHEMA_VERSION};
+use crate::network::grpc_sink::GrpcSink;
+use crate::network::mqtt_sink::MqttSink;
+use crate::network::nats_sink::NatsSink;
+use crate::network::unix_socket_sink::UnixSocketSink;
+
+/// Batching settings of one sink.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct BatchConfig {
+    /// Number of samples sent together.
+    pub max_samples: usize,
+    /// Age in seconds of the oldest sample at which a batch is sent early; 0 disables the limit.
+    pub max_secs: u64,
+}
+
+impl Default for BatchConfig {
+    fn default() -> Self {
+        Self {
+            max_samples: 10,
+            max_secs: 300,
+        }
+    }
+}
+
+/// Samples collected since the last batch was sent.
+static PENDING: Mutex<Vec<TimestampedSensorData>> = Mutex::new(Vec::new());
+
+/// Static utility class for accumulating samples into batches.
+pub struct BatchUtil;
+
+impl BatchUtil {
+    /// Returns the batching settings of the sink reports are sent through, or `None` if
+    /// reports are sent one at a time.
+    pub fn config() -> Option<&'static BatchConfig> {
+        let config = Config::get();
+        if GrpcSink::is_enabled() {
+            return None;
+        }
+        if MqttSink::is_enabled() {
+            return config.mqtt.as_ref()?.batch.as_ref();
+        }
+        if NatsSink::is_enabled() {
+            return config.nats.as_ref()?.batch.as_ref();
+        }
+        if UnixSocketSink::is_enabled() {
+            return config.unix_socket.as_ref()?.batch.as_ref();
+        }
+        if let Some(udp) = &config.udp {
+            return udp.batch.as_ref();
+        }
+        config.http_batch.as_ref()
+    }
+
+    /// Adds a sample collected at `timestamp_ms`, returning the batch to send if it is full
+    /// or old enough.
+    pub fn push(
+        data: SensorData,
+        timestamp_ms: u64,
+        config: &BatchConfig,
+    ) -> Option<SensorDataBatch> {
+        let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+        pending.push(TimestampedSensorData { timestamp_ms, data });
+
+        let oldest_age_ms = timestamp_ms.saturating_sub(pending[0].timestamp_ms);
+        let full = pending.len() >= config.max_samples.max(1);
+        let expired = config.max_secs > 0 && oldest_age_ms >= config.max_secs * 1000;
+        (full || expired).then(|| Self::batch(std::mem::take(&mut *pending)))
+    }
+
+    /// Takes the samples not sent yet, e.g. on shutdown.
+    pub fn take_pending() -> Option<SensorDataBatch> {
+        let mut pending = PENDING.lock().unwrap_or_else(|e| e.into_inner());
+        (!pending.is_empty()).then(|| Self::batch(std::mem::take(&mut *pending)))
+    }
+
+    fn batch(samples: Vec<TimestampedSensorData>) -> SensorDataBatch {
+        SensorDataBatch {
+            schema_version: SCHEMA_VERSION,
+            samples,
+        }
+    }
+}
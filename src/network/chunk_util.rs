@@ -0,0 +1,198 @@
+//! Payload Chunking
+//!
+//! Hosts with many disks or interfaces can produce reports larger than the server's ingest
+//! limit. With `max_payload_bytes` set, a report whose encoded size exceeds it is sent as
+//! several `SensorDataChunk` messages sharing a correlation id: the first carries the report
+//! without its disks, network interfaces and components, the others carry those lists in
+//! parts small enough to fit. Batches are not split.
+
+use log::{debug, warn};
+use std::io;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::config_instance::Config;
+use crate::data::casing::FieldCasing;
+use crate::data::models::{ChunkPart, SensorData, SensorDataChunk, SCHEMA_VERSION};
+use crate::data::payload_format::{PayloadFormat, WirePayload};
+
+/// Static utility class for splitting oversized reports.
+pub struct ChunkUtil;
+
+impl ChunkUtil {
+    /// Whether `data` exceeds `max_payload_bytes` and should be sent with `split`.
+    pub fn is_oversized(data: &SensorData) -> bool {
+        let config = Config::get();
+        let Some(max_bytes) = config.max_payload_bytes else {
+            return false;
+        };
+        Self::encoded_len(data, config.payload_format, config.field_casing)
+            .is_ok_and(|len| len > max_bytes)
+    }
+
+    /// Splits `data` into chunks of at most `max_bytes` each in the configured format where
+    /// possible; a single disk, interface or component larger than that is sent on its own.
+    pub fn split(data: SensorData, max_bytes: usize) -> Vec<SensorDataChunk> {
+        let config = Config::get();
+        Self::split_with(data, max_bytes, config.payload_format, config.field_casing)
+    }
+
+    fn split_with(
+        mut data: SensorData,
+        max_bytes: usize,
+        format: PayloadFormat,
+        casing: FieldCasing,
+    ) -> Vec<SensorDataChunk> {
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let hostname = data.system_info.hostname.clone();
+        let agent_id = data.agent_id.clone().unwrap_or_default();
+        let correlation_id = format!("{}-{}", hostname, millis);
+        let chunk = |chunk_index, chunk_count, part| SensorDataChunk {
+            schema_version: SCHEMA_VERSION,
+            correlation_id: correlation_id.clone(),
+            chunk_index,
+            chunk_count,
+            agent_id: agent_id.clone(),
+            hostname: hostname.clone(),
+            part,
+        };
+
+        let lists = [
+            ChunkPart::Disks(std::mem::take(&mut data.disks)),
+            ChunkPart::NetworkInterfaces(std::mem::take(&mut data.network_interfaces)),
+            ChunkPart::Components(std::mem::take(&mut data.components)),
+        ];
+        let mut parts = vec![ChunkPart::Report(Box::new(data))];
+        for list in lists {
+            Self::split_part(list, &mut parts, &|part| {
+                // Measured in a chunk envelope, as it is sent.
+                let envelope = chunk(0, 0, part);
+                let fits = Self::encoded_len(&envelope, format, casing)
+                    .map_or(true, |len| len <= max_bytes);
+                (envelope.part, fits)
+            });
+        }
+
+        let chunk_count = parts.len();
+        debug!(
+            "Split report {} into {} chunk(s) of at most {} bytes.",
+            correlation_id, chunk_count, max_bytes
+        );
+        parts
+            .into_iter()
+            .enumerate()
+            .map(|(chunk_index, part)| chunk(chunk_index, chunk_count, part))
+            .collect()
+    }
+
+    /// Size of `data` encoded in `format`.
+    fn encoded_len<T: WirePayload>(
+        data: &T,
+        format: PayloadFormat,
+        casing: FieldCasing,
+    ) -> io::Result<usize> {
+        Ok(format.encode(data, casing)?.0.len())
+    }
+
+    /// Appends `part` to `parts`, halving it until each half fits.
+    fn split_part(
+        part: ChunkPart,
+        parts: &mut Vec<ChunkPart>,
+        fits: &dyn Fn(ChunkPart) -> (ChunkPart, bool),
+    ) {
+        if part.is_empty() {
+            return;
+        }
+        let (part, fit) = fits(part);
+        if fit {
+            parts.push(part);
+        } else if part.len() == 1 {
+            warn!("A single entry exceeds max_payload_bytes; sending it on its own.");
+            parts.push(part);
+        } else {
+            let (first, second) = part.halve();
+            Self::split_part(first, parts, fits);
+            Self::split_part(second, parts, fits);
+        }
+    }
+}
+
+impl ChunkPart {
+    fn len(&self) -> usize {
+        match self {
+            ChunkPart::Report(_) => 1,
+            ChunkPart::Disks(items) => items.len(),
+            ChunkPart::NetworkInterfaces(items) => items.len(),
+            ChunkPart::Components(items) => items.len(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Splits a list part into two parts of (nearly) equal length.
+    fn halve(self) -> (ChunkPart, ChunkPart) {
+        fn halve<T>(mut items: Vec<T>) -> (Vec<T>, Vec<T>) {
+            let second = items.split_off(items.len() / 2);
+            (items, second)
+        }
+        match self {
+            ChunkPart::Report(_) => unreachable!("a report part is never split"),
+            ChunkPart::Disks(items) => {
+                let (first, second) = halve(items);
+                (ChunkPart::Disks(first), ChunkPart::Disks(second))
+            }
+            ChunkPart::NetworkInterfaces(items) => {
+                let (first, second) = halve(items);
+                (
+                    ChunkPart::NetworkInterfaces(first),
+                    ChunkPart::NetworkInterfaces(second),
+                )
+            }
+            ChunkPart::Components(items) => {
+                let (first, second) = halve(items);
+                (ChunkPart::Components(first), ChunkPart::Components(second))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hardware::fake_system::FakeSystem;
+    use crate::hardware::system_information_monitor::SysInfoMonitor;
+    use crate::sensor::sensor_util::SensorUtils;
+
+    #[test]
+    fn splits_lists_into_chunks_that_fit() {
+        let disks = (0..40)
+            .map(|index| (format!("/dev/disk{}", index), 1_000, 500))
+            .collect();
+        let mut monitor = SysInfoMonitor::with_source(FakeSystem {
+            disks,
+            ..FakeSystem::default()
+        });
+        let data = SensorUtils::build_sensor_data(&mut monitor, true, true, Vec::new());
+        let chunks =
+            ChunkUtil::split_with(data, 2_000, PayloadFormat::Json, FieldCasing::SnakeCase);
+
+        assert!(chunks.len() > 2);
+        assert!(matches!(chunks[0].part, ChunkPart::Report(_)));
+        let mut disk_count = 0;
+        for (index, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.chunk_index, index);
+            assert_eq!(chunk.chunk_count, chunks.len());
+            assert_eq!(chunk.correlation_id, chunks[0].correlation_id);
+            let len = ChunkUtil::encoded_len(chunk, PayloadFormat::Json, FieldCasing::SnakeCase);
+            assert!(len.unwrap() <= 2_000);
+            if let ChunkPart::Disks(disks) = &chunk.part {
+                disk_count += disks.len();
+            }
+        }
+        assert_eq!(disk_count, 40);
+    }
+}
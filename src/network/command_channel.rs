@@ -0,0 +1,267 @@
+//! Remote Commands
+//!
+//! With a `[commands]` section, the agent polls the server every `poll_secs` for pending
+//! commands, so a host can be troubleshot from the Gilded Sentinel UI without a shell on
+//! it. The server answers `GET <path>` with `{"commands": [{"id": "7", "command": "..."}]}`
+//! (or an empty body); supported commands are:
+//! - `collect_now`: collects and sends a report right away, keeping the schedule.
+//! - `send_processes`: sends the running processes.
+//! - `log_level` with `level` and optional `duration_secs`: changes the log level,
+//!   reverting after `duration_secs` (default `log_level_revert_secs`, `0` for never).
+//!
+//! Every command is answered with a `command_result` payload over the transport in use.
+//! Polling always goes to `server` over HTTP and pauses outside the send windows.
+
+use log::{debug, info, warn};
+use serde::Deserialize;
+use serde_json::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::config_instance::Config;
+use crate::data::models::{CommandResult, ProcessInfo};
+use crate::hardware::system_information_monitor::SysInfoMonitor;
+use crate::network::network_util::NetworkUtil;
+use crate::network::send_window::SendWindow;
+use crate::system::agent_id::AgentId;
+use crate::system::log_level::LogLevelUtil;
+use crate::system::scheduler::WakeableClock;
+
+/// Configuration of the `[commands]` section; the server is polled for commands when present.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CommandChannelConfig {
+    /// Path polled for pending commands; may contain {agent_id}, {tenant_id} and {site_id}.
+    pub path: String,
+    /// Seconds between polls.
+    pub poll_secs: u64,
+}
+
+impl Default for CommandChannelConfig {
+    fn default() -> Self {
+        Self {
+            path: "/api/v1/agents/{agent_id}/commands".to_string(),
+            poll_secs: 15,
+        }
+    }
+}
+
+/// A command the server can send.
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum Command {
+    CollectNow,
+    SendProcesses,
+    LogLevel {
+        level: String,
+        duration_secs: Option<u64>,
+    },
+}
+
+impl Command {
+    fn name(&self) -> &'static str {
+        match self {
+            Command::CollectNow => "collect_now",
+            Command::SendProcesses => "send_processes",
+            Command::LogLevel { .. } => "log_level",
+        }
+    }
+}
+
+/// A command, or the name it was sent with and why it could not be parsed.
+type ParsedCommand = Result<Command, (String, String)>;
+
+/// Answer of the server to a poll.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PendingCommands {
+    commands: Vec<Value>,
+}
+
+/// Static utility class for the remote command channel.
+pub struct CommandChannel;
+
+impl CommandChannel {
+    /// Starts the polling thread if `[commands]` is configured; it stops once `running`
+    /// is cleared.
+    pub fn start_if_configured(server: &str, running: &Arc<AtomicBool>) {
+        let Some(config) = Config::get().commands.clone() else {
+            return;
+        };
+        let path = Config::expand_ids(&config.path.replace("{agent_id}", AgentId::get()));
+        let poll_secs = config.poll_secs.max(1);
+        info!("Polling {} for commands every {} s.", path, poll_secs);
+
+        let server = server.to_string();
+        let running = Arc::clone(running);
+        thread::spawn(move || {
+            // Created on the first `send_processes`, then kept for the next ones.
+            let mut monitor = None;
+            while running.load(Ordering::Relaxed) {
+                if SendWindow::is_open() {
+                    Self::poll(&server, &path, &mut monitor);
+                }
+                thread::sleep(Duration::from_secs(poll_secs));
+            }
+        });
+    }
+
+    fn poll(server: &str, path: &str, monitor: &mut Option<SysInfoMonitor>) {
+        let body = match NetworkUtil::fetch_from_server(path, server) {
+            Ok(body) => body,
+            Err(e) => {
+                debug!("Failed to poll for commands: {}", e);
+                return;
+            }
+        };
+        let commands = match Self::parse(&body) {
+            Ok(commands) => commands,
+            Err(e) => {
+                warn!("Ignoring malformed commands from the server: {}", e);
+                return;
+            }
+        };
+
+        for (id, command) in commands {
+            let name = match &command {
+                Ok(command) => command.name().to_string(),
+                Err((name, _)) => name.clone(),
+            };
+            info!("Executing command {} ({}) from the server.", id, name);
+            let outcome = command
+                .map_err(|(_, message)| message)
+                .and_then(|command| Self::execute(&command, monitor));
+            if let Err(message) = &outcome {
+                warn!("Command {} ({}) failed: {}", id, name, message);
+            }
+
+            let (success, message, processes) = match outcome {
+                Ok((message, processes)) => (true, message, processes),
+                Err(message) => (false, message, None),
+            };
+            let result = CommandResult {
+                agent_id: AgentId::get().to_string(),
+                hostname: sysinfo::System::host_name().unwrap_or_default(),
+                command_id: id,
+                command: name,
+                success,
+                message: Some(message),
+                processes,
+                timestamp: SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default(),
+            };
+            if let Err(e) = NetworkUtil::send_object_to_server(&result, server) {
+                warn!(
+                    "Failed to send the result of command {}: {}",
+                    result.command_id, e
+                );
+            }
+        }
+    }
+
+    /// Parses a poll answer into the ids and commands it carries. Commands that cannot be
+    /// parsed are kept as `(name, error)` so they are answered too; entries without an id
+    /// cannot be answered and are skipped.
+    fn parse(body: &[u8]) -> Result<Vec<(String, ParsedCommand)>, String> {
+        if body.iter().all(u8::is_ascii_whitespace) {
+            return Ok(Vec::new());
+        }
+        let pending: PendingCommands = serde_json::from_slice(body).map_err(|e| e.to_string())?;
+
+        let mut commands = Vec::new();
+        for entry in pending.commands {
+            let Some(id) = entry.get("id").and_then(Self::id_string) else {
+                warn!("Ignoring command without an id: {}", entry);
+                continue;
+            };
+            let name = entry
+                .get("command")
+                .and_then(Value::as_str)
+                .unwrap_or_default()
+                .to_string();
+            let command = serde_json::from_value::<Command>(entry)
+                .map_err(|e| (name, format!("Unsupported command: {}", e)));
+            commands.push((id, command));
+        }
+        Ok(commands)
+    }
+
+    /// Accepts string and numeric command ids.
+    fn id_string(id: &Value) -> Option<String> {
+        match id {
+            Value::String(id) => Some(id.clone()),
+            Value::Number(id) => Some(id.to_string()),
+            _ => None,
+        }
+    }
+
+    /// Executes a command, returning a description of what was done and, for
+    /// `send_processes`, the process list.
+    fn execute(
+        command: &Command,
+        monitor: &mut Option<SysInfoMonitor>,
+    ) -> Result<(String, Option<Vec<ProcessInfo>>), String> {
+        match command {
+            Command::CollectNow => {
+                WakeableClock::wake();
+                Ok(("Collection requested".to_string(), None))
+            }
+            Command::SendProcesses => {
+                let processes = monitor
+                    .get_or_insert_with(SysInfoMonitor::new)
+                    .get_process_info();
+                Ok((format!("{} processes", processes.len()), Some(processes)))
+            }
+            Command::LogLevel {
+                level,
+                duration_secs,
+            } => {
+                let Some(filter) = LogLevelUtil::parse(level) else {
+                    return Err(format!("Unknown log level '{}'", level));
+                };
+                match duration_secs {
+                    None => LogLevelUtil::set_temporarily(filter),
+                    Some(0) => LogLevelUtil::set(filter, None),
+                    Some(secs) => LogLevelUtil::set(filter, Some(Duration::from_secs(*secs))),
+                }
+                Ok((format!("Log level set to {}", level.trim()), None))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_known_unknown_and_anonymous_commands() {
+        let body = br#"{"commands": [
+            {"id": "a", "command": "collect_now"},
+            {"id": 7, "command": "log_level", "level": "debug", "duration_secs": 60},
+            {"id": "b", "command": "reboot"},
+            {"command": "send_processes"}
+        ]}"#;
+        let commands = CommandChannel::parse(body).unwrap();
+        assert_eq!(commands.len(), 3);
+        assert_eq!(commands[0], ("a".to_string(), Ok(Command::CollectNow)));
+        assert_eq!(
+            commands[1],
+            (
+                "7".to_string(),
+                Ok(Command::LogLevel {
+                    level: "debug".to_string(),
+                    duration_secs: Some(60),
+                })
+            )
+        );
+        assert!(matches!(&commands[2], (id, Err((name, _))) if id == "b" && name == "reboot"));
+
+        assert!(CommandChannel::parse(b"").unwrap().is_empty());
+        assert!(CommandChannel::parse(b"[1, 2]").is_err());
+    }
+}
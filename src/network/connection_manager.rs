@@ -0,0 +1,188 @@
+#![cfg(unix)]
+
+//! Server Connection Reuse
+//!
+//! This module keeps the HTTP connection to the server open between reports (HTTP/1.1
+//! keep-alive), so short reporting intervals do not pay for a TCP and TLS handshake every
+//! time. A connection is reused only while it is idle for less than
+//! `keep_alive_idle_secs` and the server has not asked to close it. If a reused
+//! connection turns out to have been closed by the server, the request is sent again on a
+//! fresh connection, transparently to the caller.
+//!
+//! Reads and writes on the connection are bounded by `server_read_timeout_secs` and
+//! `server_write_timeout_secs`, so a stalled server cannot hang the collection loop. A
+//! connection on which a request was only partially written is never reused; such
+//! failures are counted in the agent's self-metrics by reason.
+
+use log::{debug, info, warn};
+use std::io::{self, Read, Write};
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::config_instance::Config;
+use crate::network::http_client::{HttpClient, HttpResponse, WriteFailure};
+use crate::network::socket_util::SocketUtil;
+use crate::network::tls_util::TlsUtil;
+use crate::system::self_metrics::SelfMetrics;
+
+/// Timeout for connecting to the server.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// A connection to the server, either plain TCP or TLS.
+trait ServerStream: Read + Write + Send {}
+impl<T: Read + Write + Send> ServerStream for T {}
+
+/// An open connection waiting for the next request.
+struct IdleConnection {
+    address: SocketAddr,
+    tls: bool,
+    stream: Box<dyn ServerStream>,
+    idle_since: Instant,
+}
+
+/// The kept-alive connection, if any.
+static IDLE: Mutex<Option<IdleConnection>> = Mutex::new(None);
+
+/// Static utility class managing the connection to the server.
+pub struct ConnectionManager;
+
+impl ConnectionManager {
+    /// Whether requests ask the server to keep the connection open.
+    pub fn keep_alive() -> bool {
+        Config::get().keep_alive
+    }
+
+    /// Sends `request` to the first of `addresses` that accepts a connection (TLS-verified
+    /// against `host` when `server_tls` is set) and reads the response, reusing the
+    /// kept-alive connection when it leads to one of them.
+    pub fn exchange(
+        addresses: &[SocketAddr],
+        host: &str,
+        request: &[u8],
+    ) -> io::Result<HttpResponse> {
+        let config = Config::get();
+        let tls = config.server_tls;
+        let idle_limit = Duration::from_secs(config.keep_alive_idle_secs);
+        let mut idle = IDLE.lock().unwrap_or_else(|e| e.into_inner());
+
+        let reusable = idle.take().filter(|connection| {
+            addresses.contains(&connection.address)
+                && connection.tls == tls
+                && connection.idle_since.elapsed() < idle_limit
+        });
+        if let Some(mut connection) = reusable {
+            debug!("Reusing the connection to {}.", connection.address);
+            match Self::observe(HttpClient::exchange(connection.stream.as_mut(), request)) {
+                Ok(response) => {
+                    if Self::keep_alive() && Self::allows_reuse(&response) {
+                        connection.idle_since = Instant::now();
+                        *idle = Some(connection);
+                    }
+                    return Ok(response);
+                }
+                // The server closed the idle connection before reading the request.
+                Err(e) if Self::is_stale(&e) => {
+                    debug!("Kept-alive connection closed ({}); reconnecting.", e)
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        let (mut stream, address) = Self::connect(addresses, host, tls)?;
+        let response = Self::observe(HttpClient::exchange(stream.as_mut(), request))?;
+        if Self::keep_alive() && Self::allows_reuse(&response) {
+            *idle = Some(IdleConnection {
+                address,
+                tls,
+                stream,
+                idle_since: Instant::now(),
+            });
+        }
+        Ok(response)
+    }
+
+    /// Counts a failed request write in the self-metrics and reports oversized bodies.
+    fn observe(result: io::Result<HttpResponse>) -> io::Result<HttpResponse> {
+        if let Ok(response) = &result {
+            if !response.complete {
+                warn!("Server response body is too large; closing the connection.");
+            } else if response.truncated {
+                warn!(
+                    "Server response body exceeds {} byte(s); ignoring the rest.",
+                    response.body.len()
+                );
+            }
+        }
+        if let Some(failure) = result.as_ref().err().and_then(WriteFailure::of) {
+            if failure.is_partial() {
+                warn!("{}; dropping the connection.", failure);
+            }
+            SelfMetrics::record_write_failure(failure.reason(), failure.is_partial());
+        }
+        result
+    }
+
+    fn connect(
+        addresses: &[SocketAddr],
+        host: &str,
+        tls: bool,
+    ) -> io::Result<(Box<dyn ServerStream>, SocketAddr)> {
+        info!("Connecting to server at: {}", host);
+        let (tcp_stream, address) =
+            SocketUtil::connect_tcp(addresses, CONNECT_TIMEOUT).map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!("Failed to connect to server at {}: {}", host, e),
+                )
+            })?;
+        info!("Successfully connected to the server at {}", address);
+
+        // Bound reads and writes so a stalled server cannot hang the collection loop
+        let config = Config::get();
+        let read_timeout = Duration::from_secs(config.server_read_timeout_secs.max(1));
+        if let Err(e) = tcp_stream.set_read_timeout(Some(read_timeout)) {
+            debug!("Failed to set read timeout: {}", e);
+        }
+        let write_timeout = Duration::from_secs(config.server_write_timeout_secs.max(1));
+        if let Err(e) = tcp_stream.set_write_timeout(Some(write_timeout)) {
+            debug!("Failed to set write timeout: {}", e);
+        }
+
+        // Wrap the connection in TLS if enabled
+        let stream: Box<dyn ServerStream> = if tls {
+            Box::new(TlsUtil::connect(tcp_stream, host)?)
+        } else {
+            Box::new(tcp_stream)
+        };
+        Ok((stream, address))
+    }
+
+    /// Whether the connection can carry another request after `response`: the server did
+    /// not ask to close it (HTTP/1.0 closes unless it asks to keep it alive), the body had
+    /// a known length and was read to its end.
+    fn allows_reuse(response: &HttpResponse) -> bool {
+        let connection = response.header("Connection").unwrap_or_default();
+        let closes = if response.version == "HTTP/1.0" {
+            !connection.eq_ignore_ascii_case("keep-alive")
+        } else {
+            connection.eq_ignore_ascii_case("close")
+        };
+        let delimited = response.header("Content-Length").is_some()
+            || response.header("Transfer-Encoding").is_some()
+            || matches!(response.status_code, 204 | 304);
+        !closes && delimited && response.complete
+    }
+
+    /// Whether an error means the connection had been closed by the server, in which case
+    /// the request was not processed and can safely be sent again.
+    fn is_stale(error: &io::Error) -> bool {
+        matches!(
+            error.kind(),
+            io::ErrorKind::UnexpectedEof
+                | io::ErrorKind::ConnectionReset
+                | io::ErrorKind::ConnectionAborted
+                | io::ErrorKind::BrokenPipe
+        )
+    }
+}
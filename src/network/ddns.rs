@@ -0,0 +1,187 @@
+//! Dynamic DNS Updates
+//!
+//! With `[public_ip]` and `[ddns]` configured, the agent points a DNS record at the host's
+//! public address whenever it changes (and once after startup), so a home connection with
+//! a dynamic address stays reachable by name. Supported providers are DuckDNS and
+//! Cloudflare; each attempt is reported to the server as a `DdnsUpdateEvent`.
+
+use log::{error, info, warn};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::fs;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::config_instance::Config;
+use crate::data::models::DdnsUpdateEvent;
+use crate::network::network_util::NetworkUtil;
+use crate::system::agent_id::AgentId;
+
+/// DNS provider whose record is updated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DdnsProvider {
+    /// `https://www.duckdns.org`; `domain` is the subdomain without `.duckdns.org`.
+    #[default]
+    Duckdns,
+    /// Cloudflare DNS; `domain` is the record name, `zone_id` and `record_id` identify it.
+    Cloudflare,
+}
+
+/// Configuration of the `[ddns]` section; records are updated when present.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct DdnsConfig {
+    pub provider: DdnsProvider,
+    /// Name whose record is updated.
+    pub domain: String,
+    /// API token of the provider.
+    pub token: Option<String>,
+    /// File containing the API token; used when `token` is unset.
+    pub token_file: Option<String>,
+    /// Cloudflare zone of the record.
+    pub zone_id: Option<String>,
+    /// Cloudflare ID of the record.
+    pub record_id: Option<String>,
+    /// Seconds to wait for the provider before giving up on an update.
+    pub timeout_secs: Option<u64>,
+}
+
+/// HTTP agent used for updates, built from the configured timeout.
+static AGENT: OnceLock<ureq::Agent> = OnceLock::new();
+
+/// Static utility class for updating dynamic DNS records.
+pub struct Ddns;
+
+impl Ddns {
+    /// Points the configured record at `address` and reports the outcome to the server.
+    /// Does nothing unless `[ddns]` is configured.
+    pub fn update_if_configured(address: IpAddr, previous: Option<IpAddr>) {
+        let Some(config) = &Config::get().ddns else {
+            return;
+        };
+        let result = Self::update(config, address);
+        match &result {
+            Ok(_) => info!("Updated DNS record {} to {}.", config.domain, address),
+            Err(e) => error!(
+                "Failed to update DNS record {} to {}: {}",
+                config.domain, address, e
+            ),
+        }
+
+        let event = DdnsUpdateEvent {
+            agent_id: AgentId::get().to_string(),
+            hostname: sysinfo::System::host_name().unwrap_or_default(),
+            provider: format!("{:?}", config.provider).to_lowercase(),
+            domain: config.domain.clone(),
+            address: address.to_string(),
+            previous_address: previous.map(|previous| previous.to_string()),
+            success: result.is_ok(),
+            message: result.err(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        };
+        if let Err(e) = NetworkUtil::send_with_retries(&event, Config::server(), 3) {
+            warn!("Failed to report DNS update event: {}", e);
+        }
+    }
+
+    fn update(config: &DdnsConfig, address: IpAddr) -> Result<(), String> {
+        let token = Self::token(config)?;
+        let agent = AGENT.get_or_init(|| {
+            ureq::Agent::config_builder()
+                .timeout_global(Some(Duration::from_secs(
+                    config.timeout_secs.unwrap_or(10).max(1),
+                )))
+                .http_status_as_error(false)
+                .build()
+                .into()
+        });
+        match config.provider {
+            DdnsProvider::Duckdns => {
+                let family = if address.is_ipv4() { "ip" } else { "ipv6" };
+                let url = format!(
+                    "https://www.duckdns.org/update?domains={}&token={}&{}={}",
+                    config.domain, token, family, address
+                );
+                let body = agent
+                    .get(&url)
+                    .call()
+                    .and_then(|mut response| response.body_mut().read_to_string())
+                    .map_err(|e| e.to_string())?;
+                match body.trim() {
+                    "OK" => Ok(()),
+                    answer => Err(format!("DuckDNS answered {:?}", answer)),
+                }
+            }
+            DdnsProvider::Cloudflare => {
+                let (Some(zone_id), Some(record_id)) = (&config.zone_id, &config.record_id) else {
+                    return Err("Cloudflare updates need zone_id and record_id".to_string());
+                };
+                let url = format!(
+                    "https://api.cloudflare.com/client/v4/zones/{}/dns_records/{}",
+                    zone_id, record_id
+                );
+                let record = json!({
+                    "type": if address.is_ipv4() { "A" } else { "AAAA" },
+                    "name": config.domain,
+                    "content": address.to_string(),
+                });
+                let answer: Value = agent
+                    .patch(&url)
+                    .header("Authorization", &format!("Bearer {}", token))
+                    .send_json(&record)
+                    .and_then(|mut response| response.body_mut().read_json())
+                    .map_err(|e| e.to_string())?;
+                Self::cloudflare_result(&answer)
+            }
+        }
+    }
+
+    /// Interprets a Cloudflare API answer.
+    fn cloudflare_result(answer: &Value) -> Result<(), String> {
+        if answer["success"].as_bool() == Some(true) {
+            return Ok(());
+        }
+        let errors: Vec<String> = answer["errors"]
+            .as_array()
+            .into_iter()
+            .flatten()
+            .filter_map(|error| error["message"].as_str().map(str::to_string))
+            .collect();
+        Err(format!(
+            "Cloudflare rejected the update: {}",
+            errors.join("; ")
+        ))
+    }
+
+    fn token(config: &DdnsConfig) -> Result<String, String> {
+        if let Some(token) = &config.token {
+            return Ok(token.trim().to_string());
+        }
+        let Some(token_file) = &config.token_file else {
+            return Err("no token or token_file configured".to_string());
+        };
+        fs::read_to_string(token_file)
+            .map(|token| token.trim().to_string())
+            .map_err(|e| format!("Failed to read token file {}: {}", token_file, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_cloudflare_answers() {
+        assert!(Ddns::cloudflare_result(&json!({"success": true, "errors": []})).is_ok());
+        let rejected = json!({"success": false, "errors": [{"code": 9109, "message": "Invalid access token"}]});
+        assert_eq!(
+            Ddns::cloudflare_result(&rejected),
+            Err("Cloudflare rejected the update: Invalid access token".to_string())
+        );
+    }
+}
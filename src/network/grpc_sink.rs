@@ -0,0 +1,167 @@
+//! gRPC Sink
+//!
+//! This module sends reports to a gRPC ingestion service implementing
+//! `sentinel.v1.Ingest/Ingest` (see `proto/sentinel/v1/ingest.proto`) instead of the HTTP
+//! server. Requests carry the payload's protobuf encoding as-is, so no generated client code
+//! is needed. The channel is created lazily on a private single-threaded runtime and
+//! reconnects on demand. Only plaintext HTTP/2 (`http://`) endpoints are supported.
+
+use bytes::{Buf, BufMut};
+use http::uri::PathAndQuery;
+use log::debug;
+use serde::Deserialize;
+use std::io;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tokio::runtime::{Builder, Runtime};
+use tonic::client::Grpc;
+use tonic::codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder};
+use tonic::transport::{Channel, Endpoint};
+use tonic::{Request, Status};
+
+use crate::config::config_instance::Config;
+use crate::network::socket_util::SocketUtil;
+
+/// Full method path of the ingest RPC.
+const INGEST_PATH: &str = "/sentinel.v1.Ingest/Ingest";
+
+/// Configuration of the `[grpc]` section. When present, reports are sent to the gRPC
+/// endpoint instead of `server`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct GrpcConfig {
+    /// Endpoint URI of the ingestion service (e.g. `http://ingest.example.com:50051`).
+    pub endpoint: String,
+    /// Timeout in seconds for connecting and for each call.
+    pub timeout_secs: u64,
+}
+
+impl Default for GrpcConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "http://127.0.0.1:50051".to_string(),
+            timeout_secs: 10,
+        }
+    }
+}
+
+/// Runtime driving the channel; created on first use.
+static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+/// The shared channel; created on first use.
+static CHANNEL: OnceLock<Channel> = OnceLock::new();
+
+/// Static utility class for sending reports over gRPC.
+pub struct GrpcSink;
+
+impl GrpcSink {
+    /// Returns whether reports are sent over gRPC.
+    pub fn is_enabled() -> bool {
+        Config::get().grpc.is_some()
+    }
+
+    /// Calls `Ingest` with an already protobuf-encoded `SensorData` message.
+    pub fn ingest(message: Vec<u8>) -> io::Result<()> {
+        let config = Config::get()
+            .grpc
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "gRPC is not configured"))?;
+        let runtime = RUNTIME.get_or_init(|| {
+            Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to create gRPC runtime")
+        });
+        let channel = match CHANNEL.get() {
+            Some(channel) => channel.clone(),
+            None => {
+                let channel = Self::connect(config, runtime)?;
+                CHANNEL.get_or_init(|| channel).clone()
+            }
+        };
+
+        let size = message.len();
+        runtime.block_on(async {
+            let mut grpc = Grpc::new(channel);
+            grpc.ready().await.map_err(|e| {
+                io::Error::other(format!(
+                    "gRPC endpoint {} unavailable: {}",
+                    config.endpoint, e
+                ))
+            })?;
+            grpc.unary::<_, (), _>(
+                Request::new(message),
+                PathAndQuery::from_static(INGEST_PATH),
+                RawCodec,
+            )
+            .await
+            .map_err(|status| {
+                io::Error::other(format!(
+                    "gRPC Ingest failed: {:?}: {}",
+                    status.code(),
+                    status.message()
+                ))
+            })
+        })?;
+        debug!(
+            "Sent {} byte(s) to gRPC endpoint {}.",
+            size, config.endpoint
+        );
+        Ok(())
+    }
+
+    fn connect(config: &GrpcConfig, runtime: &Runtime) -> io::Result<Channel> {
+        let timeout = Duration::from_secs(config.timeout_secs.max(1));
+        let endpoint = Endpoint::from_shared(config.endpoint.clone())
+            .map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("Invalid gRPC endpoint {}: {}", config.endpoint, e),
+                )
+            })?
+            .connect_timeout(timeout)
+            .timeout(timeout)
+            .local_address(SocketUtil::source_address());
+
+        // The channel's background worker is spawned onto the runtime.
+        let _guard = runtime.enter();
+        Ok(endpoint.connect_lazy())
+    }
+}
+
+/// Codec passing pre-encoded protobuf messages through and discarding the (empty) response.
+struct RawCodec;
+
+impl Codec for RawCodec {
+    type Encode = Vec<u8>;
+    type Decode = ();
+    type Encoder = RawCodec;
+    type Decoder = RawCodec;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        RawCodec
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        RawCodec
+    }
+}
+
+impl Encoder for RawCodec {
+    type Item = Vec<u8>;
+    type Error = Status;
+
+    fn encode(&mut self, item: Vec<u8>, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
+        dst.put_slice(&item);
+        Ok(())
+    }
+}
+
+impl Decoder for RawCodec {
+    type Item = ();
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<()>, Status> {
+        src.advance(src.remaining());
+        Ok(Some(()))
+    }
+}
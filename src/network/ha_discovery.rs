@@ -0,0 +1,198 @@
+//! Home Assistant MQTT Discovery
+//!
+//! When enabled in the `[mqtt]` section, a retained discovery config is published for every
+//! CPU package, core, disk and component temperature the first time it is seen, so the
+//! sensors appear in Home Assistant without manual YAML. Each entity reads its value from
+//! the regular report topic with a template, which requires the JSON payload format.
+
+use log::{info, warn};
+use serde_json::{json, Value};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use crate::config::config_instance::Config;
+use crate::data::models::SensorData;
+use crate::data::payload_format::{PayloadFormat, WirePayload};
+use crate::network::mqtt_sink::{MqttConfig, MqttSink};
+
+/// Unique ids of entities whose discovery config has been published.
+static ANNOUNCED: OnceLock<Mutex<HashSet<String>>> = OnceLock::new();
+/// Whether the warning about a non-JSON payload format has been logged.
+static FORMAT_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// One Home Assistant entity derived from a report.
+struct Entity {
+    object_id: String,
+    name: String,
+    value_template: String,
+    device_class: &'static str,
+    unit: &'static str,
+}
+
+/// Static utility class for publishing Home Assistant discovery configs.
+pub struct HaDiscovery;
+
+impl HaDiscovery {
+    /// Publishes discovery configs for the sensors in `data` not announced before.
+    pub fn announce(data: &SensorData) {
+        let config = Config::get();
+        let Some(mqtt) = config.mqtt.as_ref().filter(|mqtt| mqtt.ha_discovery) else {
+            return;
+        };
+        if config.payload_format != PayloadFormat::Json {
+            if !FORMAT_WARNED.swap(true, Ordering::Relaxed) {
+                warn!("Home Assistant discovery requires payload_format = \"json\"; skipping.");
+            }
+            return;
+        }
+
+        let node_id = Self::object_id(&data.system_info.hostname);
+        let mut announced = ANNOUNCED
+            .get_or_init(|| Mutex::new(HashSet::new()))
+            .lock()
+            .unwrap_or_else(|e| e.into_inner());
+
+        for entity in Self::entities(data) {
+            let unique_id = format!("gilded_sentinel_{}_{}", node_id, entity.object_id);
+            if announced.contains(&unique_id) {
+                continue;
+            }
+
+            let topic = format!(
+                "{}/sensor/{}/{}/config",
+                mqtt.ha_discovery_prefix, node_id, entity.object_id
+            );
+            let body = Self::discovery_config(mqtt, data, &node_id, &unique_id, &entity);
+            match MqttSink::publish_to(&topic, body.to_string().as_bytes(), true) {
+                Ok(()) => {
+                    info!("Announced Home Assistant sensor {}.", unique_id);
+                    announced.insert(unique_id);
+                }
+                Err(e) => warn!("Failed to announce {}: {}", unique_id, e),
+            }
+        }
+    }
+
+    fn discovery_config(
+        mqtt: &MqttConfig,
+        data: &SensorData,
+        node_id: &str,
+        unique_id: &str,
+        entity: &Entity,
+    ) -> Value {
+        json!({
+            "name": entity.name,
+            "unique_id": unique_id,
+            "object_id": format!("{}_{}", node_id, entity.object_id),
+            "state_topic": MqttSink::topic_for(mqtt, SensorData::KIND),
+            "value_template": entity.value_template,
+            "device_class": entity.device_class,
+            "unit_of_measurement": entity.unit,
+            "state_class": "measurement",
+            "expire_after": Config::interval_secs().max(1) * 3,
+            "device": {
+                "identifiers": [format!("gilded_sentinel_{}", node_id)],
+                "name": data.system_info.hostname,
+                "manufacturer": "Gilded Sentinel",
+                "model": "Sensors Client",
+                "sw_version": env!("CARGO_PKG_VERSION"),
+            },
+        })
+    }
+
+    fn entities(data: &SensorData) -> Vec<Entity> {
+        let casing = Config::get().field_casing;
+        let key = |name: &str| casing.rename(name);
+        let mut entities = Vec::new();
+
+        for package in &data.cpu_packages {
+            let package_path = Self::select(
+                &format!("value_json.{}", key("cpu_packages")),
+                &key("package_id"),
+                &package.package_id,
+            );
+            entities.push(Entity {
+                object_id: format!("cpu_package_{}", Self::object_id(&package.package_id)),
+                name: format!("CPU Package {}", package.package_id),
+                value_template: Self::template(&package_path, &key("package_temperature")),
+                device_class: "temperature",
+                unit: "°C",
+            });
+
+            for core in &package.cores {
+                let core_path = Self::select(
+                    &format!("{}.{}", package_path, key("cores")),
+                    &key("core_name"),
+                    &core.core_name,
+                );
+                entities.push(Entity {
+                    object_id: format!(
+                        "cpu_package_{}_{}",
+                        Self::object_id(&package.package_id),
+                        Self::object_id(&core.core_name)
+                    ),
+                    name: format!("CPU Package {} {}", package.package_id, core.core_name),
+                    value_template: Self::template(&core_path, &key("temperature")),
+                    device_class: "temperature",
+                    unit: "°C",
+                });
+            }
+        }
+
+        for disk in &data.disks {
+            let disk_path = Self::select(
+                &format!("value_json.{}", key("disks")),
+                &key("name"),
+                &disk.name,
+            );
+            entities.push(Entity {
+                object_id: format!("disk_{}_available", Self::object_id(&disk.name)),
+                name: format!("Disk {} available", disk.name),
+                value_template: Self::template(&disk_path, &key("available_space")),
+                device_class: "data_size",
+                unit: "B",
+            });
+        }
+
+        for component in data.components.iter().filter(|c| c.temperature.is_some()) {
+            let component_path = Self::select(
+                &format!("value_json.{}", key("components")),
+                &key("label"),
+                &component.label,
+            );
+            entities.push(Entity {
+                object_id: format!("component_{}", Self::object_id(&component.label)),
+                name: component.label.clone(),
+                value_template: Self::template(&component_path, &key("temperature")),
+                device_class: "temperature",
+                unit: "°C",
+            });
+        }
+
+        entities
+    }
+
+    /// Jinja expression selecting the first element of `list` whose `key` equals `value`.
+    fn select(list: &str, key: &str, value: &str) -> String {
+        let value = value.replace('\\', "\\\\").replace('\'', "\\'");
+        format!(
+            "({} | selectattr('{}', 'eq', '{}') | first)",
+            list, key, value
+        )
+    }
+
+    fn template(path: &str, field: &str) -> String {
+        format!("{{{{ {}.{} }}}}", path, field)
+    }
+
+    /// Reduces a label to the characters Home Assistant accepts in ids (`[a-z0-9_]`).
+    fn object_id(label: &str) -> String {
+        let id: String = label
+            .to_ascii_lowercase()
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+        id.trim_matches('_').to_string()
+    }
+}
@@ -0,0 +1,64 @@
+//! Heartbeats
+//!
+//! A full collection can take a while (slow `sensors`, SNMP or IPMI targets), so a missing
+//! report does not tell the server whether the host is down or just slow. With
+//! `heartbeat_interval_secs` set, a small `Heartbeat` payload is sent from its own thread
+//! at that interval, independently of collection. Heartbeats are neither retried nor
+//! spooled, and are skipped outside the send windows.
+
+use log::{debug, info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::config::config_instance::Config;
+use crate::data::models::{Heartbeat, SCHEMA_VERSION};
+use crate::network::network_util::NetworkUtil;
+use crate::network::send_window::SendWindow;
+use crate::system::agent_id::AgentId;
+
+/// Static utility class for sending heartbeats.
+pub struct HeartbeatSender;
+
+impl HeartbeatSender {
+    /// Starts the heartbeat thread if `heartbeat_interval_secs` is set; it stops once
+    /// `running` is cleared.
+    pub fn start_if_configured(server: &str, running: &Arc<AtomicBool>) {
+        let interval_secs = Config::get().heartbeat_interval_secs;
+        if interval_secs == 0 {
+            return;
+        }
+        info!("Sending heartbeats every {} s.", interval_secs);
+        let server = server.to_string();
+        let running = Arc::clone(running);
+        let started = Instant::now();
+        thread::spawn(move || {
+            while running.load(Ordering::Relaxed) {
+                if SendWindow::is_open() {
+                    Self::send(&server, started);
+                }
+                thread::sleep(Duration::from_secs(interval_secs));
+            }
+        });
+    }
+
+    fn send(server: &str, started: Instant) {
+        let heartbeat = Heartbeat {
+            schema_version: SCHEMA_VERSION,
+            agent_id: AgentId::get().to_string(),
+            hostname: sysinfo::System::host_name().unwrap_or_default(),
+            agent_version: env!("CARGO_PKG_VERSION").to_string(),
+            uptime_secs: sysinfo::System::uptime(),
+            agent_uptime_secs: started.elapsed().as_secs(),
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+        };
+        match NetworkUtil::send_object_to_server(&heartbeat, server) {
+            Ok(_) => debug!("Heartbeat sent."),
+            Err(e) => warn!("Failed to send heartbeat: {}", e),
+        }
+    }
+}
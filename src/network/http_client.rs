@@ -0,0 +1,585 @@
+#![cfg(unix)]
+
+//! Minimal HTTP/1.1 Client
+//!
+//! This module writes requests to an established server connection and parses the
+//! response: the status line, headers, and a body delimited by `Content-Length`,
+//! chunked transfer encoding, or the end of the connection. Bodies are bounded: an
+//! oversized body is drained and cut short, and a server streaming without end cannot
+//! make the agent read or buffer more than a fixed amount. The raw bytes kept are
+//! recorded so responses can be traced. A request that cannot be written completely
+//! fails with a `WriteFailure` recording how much of it was sent, and a non-2xx response
+//! turns into a `StatusError` with `error_for_status`.
+
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+
+/// Upper bound on the size of a response body that is buffered.
+const MAX_BODY_BYTES: usize = 1024 * 1024;
+
+/// Upper bound on the size of a response body that is read (and, beyond
+/// `MAX_BODY_BYTES`, discarded) to keep the connection usable; reading stops there.
+const MAX_DRAIN_BYTES: usize = 8 * 1024 * 1024;
+
+/// Upper bound on the length of the status line or a single header line.
+const MAX_LINE_BYTES: usize = 8 * 1024;
+
+/// Upper bound on the number of headers (or trailers) of a response.
+const MAX_HEADERS: usize = 128;
+
+/// Upper bound on the number of interim (1xx) responses before the final one.
+const MAX_INTERIM_RESPONSES: usize = 8;
+
+/// A parsed HTTP response.
+#[derive(Debug, Clone)]
+pub struct HttpResponse {
+    /// Protocol version of the status line, e.g. `HTTP/1.1`.
+    pub version: String,
+    pub status_code: u16,
+    pub reason: String,
+    pub headers: Vec<(String, String)>,
+    /// The body, up to `MAX_BODY_BYTES`.
+    pub body: Vec<u8>,
+    /// Whether the body was longer than `MAX_BODY_BYTES` and cut short.
+    pub truncated: bool,
+    /// Whether the whole response was read, leaving the connection ready for another
+    /// request; unset when the body exceeded `MAX_DRAIN_BYTES`.
+    pub complete: bool,
+    /// The response as received (without discarded body bytes), for tracing.
+    pub raw: Vec<u8>,
+}
+
+/// A response body being read: the first `MAX_BODY_BYTES` are kept, the rest discarded.
+#[derive(Default)]
+struct Body {
+    kept: Vec<u8>,
+    /// Bytes read so far, including discarded ones.
+    total: usize,
+}
+
+impl Body {
+    fn append(&mut self, raw: &mut Vec<u8>, bytes: &[u8]) {
+        let room = MAX_BODY_BYTES.saturating_sub(self.kept.len());
+        let kept = &bytes[..bytes.len().min(room)];
+        self.kept.extend_from_slice(kept);
+        raw.extend_from_slice(kept);
+        self.total += bytes.len();
+    }
+
+    fn is_truncated(&self) -> bool {
+        self.total > self.kept.len()
+    }
+}
+
+impl HttpResponse {
+    /// Returns the value of the first header named `name` (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Whether the status code is in the 2xx range.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status_code)
+    }
+
+    /// Converts a non-2xx response into an error carrying the status and a body excerpt.
+    pub fn error_for_status(self) -> io::Result<Self> {
+        if self.is_success() {
+            return Ok(self);
+        }
+
+        let excerpt: String = String::from_utf8_lossy(&self.body)
+            .chars()
+            .take(200)
+            .collect();
+        Err(io::Error::other(StatusError {
+            status_code: self.status_code,
+            reason: self.reason,
+            excerpt: excerpt.trim().to_string(),
+        }))
+    }
+}
+
+/// A response with a non-2xx status. Carried inside the `io::Error` returned by
+/// `HttpResponse::error_for_status`.
+#[derive(Debug)]
+pub struct StatusError {
+    pub status_code: u16,
+    pub reason: String,
+    /// The start of the response body.
+    pub excerpt: String,
+}
+
+impl StatusError {
+    /// Returns the `StatusError` inside `error`, if it is one.
+    pub fn of(error: &io::Error) -> Option<&StatusError> {
+        error.get_ref()?.downcast_ref::<StatusError>()
+    }
+
+    /// Whether the server rejected the request itself, so sending it again cannot succeed:
+    /// any 4xx status except 408 (Request Timeout) and 429 (Too Many Requests).
+    pub fn is_permanent(&self) -> bool {
+        (400..500).contains(&self.status_code) && !matches!(self.status_code, 408 | 429)
+    }
+}
+
+impl fmt::Display for StatusError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Server responded with {} {}: {}",
+            self.status_code, self.reason, self.excerpt
+        )
+    }
+}
+
+impl Error for StatusError {}
+
+/// A request that could not be written completely, e.g. because the write timed out or
+/// the server reset the connection. Carried inside the `io::Error` returned by
+/// `HttpClient::exchange`, which keeps the kind of the underlying error.
+#[derive(Debug)]
+pub struct WriteFailure {
+    /// Bytes of the request written before the failure.
+    pub written: usize,
+    /// Size of the whole request.
+    pub total: usize,
+    pub source: io::Error,
+}
+
+impl WriteFailure {
+    /// Returns the `WriteFailure` inside `error`, if it is one.
+    pub fn of(error: &io::Error) -> Option<&WriteFailure> {
+        error.get_ref()?.downcast_ref::<WriteFailure>()
+    }
+
+    /// Classifies the failure as `timeout`, `reset` or `other`.
+    pub fn reason(&self) -> &'static str {
+        match self.source.kind() {
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut => "timeout",
+            io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::BrokenPipe => "reset",
+            _ => "other",
+        }
+    }
+
+    /// Whether part of the request reached the connection before the failure.
+    pub fn is_partial(&self) -> bool {
+        self.written > 0
+    }
+
+    fn into_error(self) -> io::Error {
+        io::Error::new(self.source.kind(), self)
+    }
+}
+
+impl fmt::Display for WriteFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let what = match self.reason() {
+            "timeout" => "Timed out writing the request",
+            "reset" => "Connection reset while writing the request",
+            _ => "Failed to write the request",
+        };
+        write!(
+            f,
+            "{} after {} of {} byte(s): {}",
+            what, self.written, self.total, self.source
+        )
+    }
+}
+
+impl Error for WriteFailure {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        Some(&self.source)
+    }
+}
+
+/// Static helpers for issuing requests over an existing stream.
+pub struct HttpClient;
+
+impl HttpClient {
+    /// Builds the bytes of a request with a body, asking the server to keep the connection
+    /// open for further requests when `keep_alive` is set.
+    pub fn build_request(
+        method: &str,
+        path: &str,
+        host: &str,
+        headers: &[(String, String)],
+        body: &[u8],
+        keep_alive: bool,
+    ) -> Vec<u8> {
+        let mut request = format!("{} {} HTTP/1.1\r\nHost: {}\r\n", method, path, host);
+        for (name, value) in headers {
+            request.push_str(&format!("{}: {}\r\n", name, value));
+        }
+        request.push_str(&format!(
+            "Content-Length: {}\r\nConnection: {}\r\n\r\n",
+            body.len(),
+            if keep_alive { "keep-alive" } else { "close" }
+        ));
+
+        let mut bytes = request.into_bytes();
+        bytes.extend_from_slice(body);
+        bytes
+    }
+
+    /// Writes `request` to `stream` and reads the response.
+    pub fn exchange<S: Read + Write + ?Sized>(
+        stream: &mut S,
+        request: &[u8],
+    ) -> io::Result<HttpResponse> {
+        Self::write_request(stream, request)?;
+        Self::read_response(stream)
+    }
+
+    /// Writes and flushes `request`, continuing after short and interrupted writes; a
+    /// failure is returned as a `WriteFailure`.
+    fn write_request<W: Write + ?Sized>(stream: &mut W, request: &[u8]) -> io::Result<()> {
+        let failure = |written, source| {
+            WriteFailure {
+                written,
+                total: request.len(),
+                source,
+            }
+            .into_error()
+        };
+
+        let mut written = 0;
+        while written < request.len() {
+            match stream.write(&request[written..]) {
+                Ok(0) => {
+                    return Err(failure(
+                        written,
+                        io::Error::new(io::ErrorKind::WriteZero, "connection accepted no data"),
+                    ))
+                }
+                Ok(count) => written += count,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                Err(e) => return Err(failure(written, e)),
+            }
+        }
+        stream.flush().map_err(|e| failure(written, e))
+    }
+
+    /// Reads and parses one HTTP response from `stream`.
+    ///
+    /// At most `MAX_BODY_BYTES` of the body are kept. A longer body is still read up to
+    /// `MAX_DRAIN_BYTES` so the connection stays usable, and is marked `truncated`; beyond
+    /// that, reading stops and the response is marked incomplete.
+    pub fn read_response<R: Read + ?Sized>(stream: &mut R) -> io::Result<HttpResponse> {
+        let mut reader = BufReader::new(stream);
+        let mut raw = Vec::new();
+
+        // Skip interim 1xx responses (e.g. `100 Continue`).
+        let mut interim = 0;
+        let (version, status_code, reason, headers) = loop {
+            let (version, status_code, reason) =
+                Self::parse_status_line(&Self::read_line(&mut reader, &mut raw)?)?;
+            let headers = Self::read_headers(&mut reader, &mut raw)?;
+            if !(100..200).contains(&status_code) {
+                break (version, status_code, reason, headers);
+            }
+            interim += 1;
+            if interim > MAX_INTERIM_RESPONSES {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Too many interim responses",
+                ));
+            }
+        };
+
+        let mut response = HttpResponse {
+            version,
+            status_code,
+            reason,
+            headers,
+            body: Vec::new(),
+            truncated: false,
+            complete: true,
+            raw: Vec::new(),
+        };
+
+        let chunked = response
+            .header("Transfer-Encoding")
+            .is_some_and(|value| value.to_ascii_lowercase().contains("chunked"));
+        let content_length = response
+            .header("Content-Length")
+            .map(|value| {
+                value.trim().parse::<usize>().map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidData, "Invalid Content-Length header")
+                })
+            })
+            .transpose()?;
+
+        let mut body = Body::default();
+        response.complete = if status_code == 204 || status_code == 304 {
+            true
+        } else if chunked {
+            Self::read_chunked_body(&mut reader, &mut raw, &mut body)?
+        } else if let Some(length) = content_length {
+            Self::read_sized_body(&mut reader, &mut raw, &mut body, length)?
+        } else {
+            Self::read_body_to_end(&mut reader, &mut raw, &mut body)?
+        };
+        response.truncated = body.is_truncated();
+        response.body = body.kept;
+        response.raw = raw;
+
+        Ok(response)
+    }
+
+    fn parse_status_line(line: &str) -> io::Result<(String, u16, String)> {
+        let invalid = || {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Malformed HTTP status line: {:?}", line),
+            )
+        };
+
+        let mut parts = line.splitn(3, ' ');
+        let version = parts.next().unwrap_or_default();
+        if !version.starts_with("HTTP/") {
+            return Err(invalid());
+        }
+        let status_code = parts
+            .next()
+            .and_then(|code| code.parse::<u16>().ok())
+            .ok_or_else(invalid)?;
+        let reason = parts.next().unwrap_or_default().to_string();
+
+        Ok((version.to_string(), status_code, reason))
+    }
+
+    fn read_headers<R: BufRead>(
+        reader: &mut R,
+        raw: &mut Vec<u8>,
+    ) -> io::Result<Vec<(String, String)>> {
+        let mut headers = Vec::new();
+        loop {
+            let line = Self::read_line(reader, raw)?;
+            if line.is_empty() {
+                return Ok(headers);
+            }
+            if headers.len() == MAX_HEADERS {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Response has too many headers",
+                ));
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_string(), value.trim().to_string()));
+            }
+        }
+    }
+
+    /// Reads a chunked body; returns whether it was read to its end.
+    fn read_chunked_body<R: BufRead>(
+        reader: &mut R,
+        raw: &mut Vec<u8>,
+        body: &mut Body,
+    ) -> io::Result<bool> {
+        loop {
+            let size_line = Self::read_line(reader, raw)?;
+            let size_hex = size_line.split(';').next().unwrap_or_default().trim();
+            let size = usize::from_str_radix(size_hex, 16)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid chunk size"))?;
+
+            if size == 0 {
+                // Consume optional trailers up to the terminating empty line.
+                Self::read_headers(reader, raw)?;
+                return Ok(true);
+            }
+            if !Self::read_sized_body(reader, raw, body, size)? {
+                return Ok(false);
+            }
+            Self::read_line(reader, raw)?;
+        }
+    }
+
+    /// Reads a body of `length` bytes; returns whether it was read to its end, i.e. did
+    /// not exceed `MAX_DRAIN_BYTES`.
+    fn read_sized_body<R: BufRead>(
+        reader: &mut R,
+        raw: &mut Vec<u8>,
+        body: &mut Body,
+        length: usize,
+    ) -> io::Result<bool> {
+        let allowed = MAX_DRAIN_BYTES.saturating_sub(body.total);
+        let mut remaining = length.min(allowed);
+        let mut buffer = [0u8; 8 * 1024];
+        let capacity = buffer.len();
+        while remaining > 0 {
+            let read = reader.read(&mut buffer[..remaining.min(capacity)])?;
+            if read == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "Connection closed before the response body was complete",
+                ));
+            }
+            body.append(raw, &buffer[..read]);
+            remaining -= read;
+        }
+        Ok(length <= allowed)
+    }
+
+    /// Reads a body delimited by the end of the connection; returns whether the end was
+    /// reached within `MAX_DRAIN_BYTES`.
+    fn read_body_to_end<R: BufRead>(
+        reader: &mut R,
+        raw: &mut Vec<u8>,
+        body: &mut Body,
+    ) -> io::Result<bool> {
+        let mut buffer = [0u8; 8 * 1024];
+        while body.total < MAX_DRAIN_BYTES {
+            let limit = (MAX_DRAIN_BYTES - body.total).min(buffer.len());
+            match reader.read(&mut buffer[..limit])? {
+                0 => return Ok(true),
+                read => body.append(raw, &buffer[..read]),
+            }
+        }
+        Ok(false)
+    }
+
+    /// Reads one CRLF- or LF-terminated line, without the terminator. Lines longer than
+    /// `MAX_LINE_BYTES` are rejected rather than split into two.
+    fn read_line<R: BufRead>(reader: &mut R, raw: &mut Vec<u8>) -> io::Result<String> {
+        let mut line = Vec::new();
+        let read = reader
+            .by_ref()
+            .take(MAX_LINE_BYTES as u64)
+            .read_until(b'\n', &mut line)?;
+        if read == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Connection closed before a complete response was received",
+            ));
+        }
+        if read == MAX_LINE_BYTES && line.last() != Some(&b'\n') {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Response line longer than {} bytes", MAX_LINE_BYTES),
+            ));
+        }
+        raw.extend_from_slice(&line);
+
+        while line
+            .last()
+            .is_some_and(|byte| *byte == b'\n' || *byte == b'\r')
+        {
+            line.pop();
+        }
+        Ok(String::from_utf8_lossy(&line).to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Accepts `capacity` bytes a few at a time, then fails with `error`.
+    struct StallingWriter {
+        capacity: usize,
+        written: usize,
+        error: io::ErrorKind,
+    }
+
+    impl Write for StallingWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            if self.written == self.capacity {
+                return Err(io::Error::from(self.error));
+            }
+            let count = buf.len().min(3).min(self.capacity - self.written);
+            self.written += count;
+            Ok(count)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn write_failures_record_progress_and_reason() {
+        let mut writer = StallingWriter {
+            capacity: 10,
+            written: 0,
+            error: io::ErrorKind::WouldBlock,
+        };
+        let error = HttpClient::write_request(&mut writer, &[0u8; 25]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::WouldBlock);
+        let failure = WriteFailure::of(&error).unwrap();
+        assert_eq!((failure.written, failure.total), (10, 25));
+        assert_eq!(failure.reason(), "timeout");
+        assert!(failure.is_partial());
+
+        let mut writer = StallingWriter {
+            capacity: 0,
+            written: 0,
+            error: io::ErrorKind::BrokenPipe,
+        };
+        let error = HttpClient::write_request(&mut writer, b"GET / HTTP/1.1").unwrap_err();
+        let failure = WriteFailure::of(&error).unwrap();
+        assert_eq!(failure.reason(), "reset");
+        assert!(!failure.is_partial());
+
+        let mut writer = StallingWriter {
+            capacity: 100,
+            written: 0,
+            error: io::ErrorKind::Other,
+        };
+        assert!(HttpClient::write_request(&mut writer, &[0u8; 25]).is_ok());
+        assert_eq!(writer.written, 25);
+    }
+
+    #[test]
+    fn oversized_bodies_are_drained_and_cut_short() {
+        let oversized = MAX_BODY_BYTES + 10;
+        let mut stream =
+            format!("HTTP/1.1 200 OK\r\nContent-Length: {}\r\n\r\n", oversized).into_bytes();
+        stream.extend(vec![b'x'; oversized]);
+        let mut stream = io::Cursor::new(stream);
+
+        let response = HttpClient::read_response(&mut stream).unwrap();
+        assert_eq!(response.body.len(), MAX_BODY_BYTES);
+        assert!(response.truncated);
+        assert!(response.complete);
+        // The whole body was consumed.
+        assert_eq!(stream.position() as usize, stream.get_ref().len());
+    }
+
+    #[test]
+    fn unbounded_bodies_stop_at_the_drain_limit() {
+        let mut stream = b"HTTP/1.0 200 OK\r\n\r\n".to_vec();
+        stream.extend(vec![b'x'; MAX_DRAIN_BYTES + 1]);
+        let response = HttpClient::read_response(&mut io::Cursor::new(stream)).unwrap();
+        assert_eq!(response.version, "HTTP/1.0");
+        assert_eq!(response.body.len(), MAX_BODY_BYTES);
+        assert!(!response.complete);
+
+        let mut stream = b"HTTP/1.1 200 OK\r\n".to_vec();
+        for index in 0..=MAX_HEADERS {
+            stream.extend(format!("X-Header-{}: 1\r\n", index).into_bytes());
+        }
+        assert!(HttpClient::read_response(&mut io::Cursor::new(stream)).is_err());
+    }
+
+    #[test]
+    fn overlong_lines_are_rejected() {
+        let mut stream = b"HTTP/1.1 200 OK\r\nX-Long: ".to_vec();
+        stream.extend(vec![b'x'; MAX_LINE_BYTES]);
+        stream.extend(b"\r\nContent-Length: 0\r\n\r\n");
+        let error = HttpClient::read_response(&mut io::Cursor::new(stream)).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+
+        // A line of exactly the limit, terminator included, is still accepted.
+        let mut stream = b"HTTP/1.1 200 OK\r\nX-Long: ".to_vec();
+        stream.extend(vec![b'x'; MAX_LINE_BYTES - "X-Long: \r\n".len()]);
+        stream.extend(b"\r\nContent-Length: 0\r\n\r\n");
+        assert!(HttpClient::read_response(&mut io::Cursor::new(stream)).is_ok());
+    }
+}
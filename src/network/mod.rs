@@ -1 +1,29 @@
+pub mod ack_util;
+pub mod api_server;
+pub mod batch_util;
+pub mod chunk_util;
+pub mod command_channel;
+pub mod connection_manager;
+pub mod ddns;
+pub mod grpc_sink;
+pub mod ha_discovery;
+pub mod heartbeat;
+pub mod http_client;
+pub mod mqtt_sink;
+pub mod nats_sink;
+pub mod neighbor_util;
 pub mod network_util;
+pub mod otlp_sink;
+pub mod public_ip;
+pub mod replay_util;
+pub mod send_log;
+pub mod send_window;
+pub mod snmp_agent;
+pub mod snmp_ber;
+pub mod snmp_poller;
+pub mod socket_util;
+pub mod spool_util;
+pub mod statsd_sink;
+pub mod tls_util;
+pub mod trace_util;
+pub mod unix_socket_sink;
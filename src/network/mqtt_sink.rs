@@ -0,0 +1,224 @@
+//! MQTT Sink
+//!
+//! This module publishes payloads to an MQTT broker instead of the HTTP server, so
+//! MQTT-based monitoring stacks can consume reports directly. A single client is
+//! connected lazily on the first publish; a background thread drives the connection and
+//! reconnects after failures. Payloads use the configured `payload_format`.
+
+use log::{debug, info, warn};
+use rumqttc::{Client, Connection, Event, MqttOptions, Packet, QoS};
+use serde::Deserialize;
+use std::io;
+use std::sync::OnceLock;
+use std::thread;
+use std::time::Duration;
+
+use crate::config::config_instance::Config;
+use crate::network::batch_util::BatchConfig;
+
+/// Number of publishes that may be queued while the broker is unreachable.
+const QUEUE_CAPACITY: usize = 64;
+/// Largest packet accepted or sent, large enough for a full report.
+const MAX_PACKET_BYTES: usize = 1024 * 1024;
+/// Delay before reconnecting after a connection error.
+const RECONNECT_DELAY: Duration = Duration::from_secs(5);
+
+/// Configuration of the `[mqtt]` section. When present, payloads are published to the
+/// broker instead of being sent to `server`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct MqttConfig {
+    /// Broker host name or address.
+    pub host: String,
+    /// Broker port.
+    pub port: u16,
+    /// MQTT client id; defaults to `gilded-sentinel-<hostname>`.
+    pub client_id: Option<String>,
+    /// User name for broker authentication.
+    pub username: Option<String>,
+    /// Password for broker authentication.
+    pub password: Option<String>,
+    /// Topic template; `{hostname}`, `{tenant_id}`, `{site_id}` and `{kind}` (`sensors`,
+    /// `sensors_batch`, `gap`, `black_box`) are substituted.
+    pub topic: String,
+    /// Quality of service level (0, 1 or 2).
+    pub qos: u8,
+    /// Whether the broker retains the last message on each topic.
+    pub retain: bool,
+    /// Keep-alive interval in seconds.
+    pub keep_alive_secs: u64,
+    /// Whether Home Assistant discovery configs are published for each sensor.
+    pub ha_discovery: bool,
+    /// Home Assistant discovery topic prefix.
+    pub ha_discovery_prefix: String,
+    /// Batching of sensor reports published to the broker; each report is sent alone when absent.
+    pub batch: Option<BatchConfig>,
+}
+
+impl Default for MqttConfig {
+    fn default() -> Self {
+        Self {
+            host: "localhost".to_string(),
+            port: 1883,
+            client_id: None,
+            username: None,
+            password: None,
+            topic: "sentinel/{hostname}/{kind}".to_string(),
+            qos: 1,
+            retain: false,
+            keep_alive_secs: 30,
+            ha_discovery: false,
+            ha_discovery_prefix: "homeassistant".to_string(),
+            batch: None,
+        }
+    }
+}
+
+impl MqttConfig {
+    fn qos(&self) -> QoS {
+        match self.qos {
+            0 => QoS::AtMostOnce,
+            2 => QoS::ExactlyOnce,
+            _ => QoS::AtLeastOnce,
+        }
+    }
+}
+
+/// The shared client, connected on first use.
+static CLIENT: OnceLock<Client> = OnceLock::new();
+
+/// Static utility class for publishing payloads over MQTT.
+pub struct MqttSink;
+
+impl MqttSink {
+    /// Returns whether payloads are published over MQTT rather than HTTP.
+    pub fn is_enabled() -> bool {
+        Config::get().mqtt.is_some()
+    }
+
+    /// Queues `body` for publishing on the topic for payloads of `kind`.
+    ///
+    /// Fails if MQTT is not configured or the queue is full because the broker has been
+    /// unreachable for a while.
+    pub fn publish(kind: &str, body: &[u8]) -> io::Result<()> {
+        let config = Self::config()?;
+        Self::publish_to(&Self::topic_for(config, kind), body, config.retain)
+    }
+
+    /// Queues `body` for publishing on an explicit `topic` with the configured QoS.
+    pub fn publish_to(topic: &str, body: &[u8], retain: bool) -> io::Result<()> {
+        let config = Self::config()?;
+        let client = CLIENT.get_or_init(|| Self::connect(config));
+
+        client
+            .try_publish(topic, config.qos(), retain, body.to_vec())
+            .map_err(|e| io::Error::other(format!("MQTT publish to {} failed: {}", topic, e)))?;
+        debug!("Queued {} byte(s) for MQTT topic {}.", body.len(), topic);
+        Ok(())
+    }
+
+    /// Expands the topic template for payloads of `kind`.
+    pub fn topic_for(config: &MqttConfig, kind: &str) -> String {
+        Self::expand_topic(&Config::expand_ids(&config.topic), &Self::hostname(), kind)
+    }
+
+    /// Substitutes `{hostname}` and `{kind}` in a topic template.
+    fn expand_topic(template: &str, hostname: &str, kind: &str) -> String {
+        template
+            .replace("{hostname}", hostname)
+            .replace("{kind}", kind)
+    }
+
+    fn config() -> io::Result<&'static MqttConfig> {
+        Config::get()
+            .mqtt
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "MQTT is not configured"))
+    }
+
+    fn connect(config: &MqttConfig) -> Client {
+        let client_id = config
+            .client_id
+            .clone()
+            .unwrap_or_else(|| format!("gilded-sentinel-{}", Self::hostname()));
+        let mut options = MqttOptions::new(client_id, &config.host, config.port);
+        options
+            .set_keep_alive(Duration::from_secs(config.keep_alive_secs.max(5)))
+            .set_max_packet_size(MAX_PACKET_BYTES, MAX_PACKET_BYTES);
+        if let Some(username) = &config.username {
+            options.set_credentials(username, config.password.clone().unwrap_or_default());
+        }
+
+        let (client, mut connection) = Client::new(options, QUEUE_CAPACITY);
+        Self::bind_interface(&mut connection);
+        let broker = format!("{}:{}", config.host, config.port);
+        thread::Builder::new()
+            .name("mqtt".to_string())
+            .spawn(move || Self::drive(connection, &broker))
+            .expect("Failed to spawn MQTT thread");
+        client
+    }
+
+    /// Binds the broker connection to `bind_interface`, if configured.
+    #[cfg(target_os = "linux")]
+    fn bind_interface(connection: &mut Connection) {
+        if let Some(interface) = &Config::get().bind_interface {
+            let mut network = rumqttc::NetworkOptions::new();
+            network.set_bind_device(interface);
+            connection.eventloop.set_network_options(network);
+        }
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn bind_interface(_connection: &mut Connection) {}
+
+    /// Polls the connection forever; polling again after an error reconnects.
+    fn drive(mut connection: Connection, broker: &str) {
+        for event in connection.iter() {
+            match event {
+                Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                    info!("Connected to MQTT broker {}.", broker)
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    warn!("MQTT connection to {} failed: {}", broker, e);
+                    thread::sleep(RECONNECT_DELAY);
+                }
+            }
+        }
+    }
+
+    fn hostname() -> String {
+        sysinfo::System::host_name().unwrap_or_else(|| "unknown".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn expands_topics_and_maps_qos_levels() {
+        let config = MqttConfig::default();
+        assert_eq!(
+            MqttSink::expand_topic(&config.topic, "rack-1", "sensors_batch"),
+            "sentinel/rack-1/sensors_batch"
+        );
+        assert_eq!(
+            MqttSink::expand_topic("site/{kind}/{hostname}/{kind}", "rack-1", "gap"),
+            "site/gap/rack-1/gap"
+        );
+
+        let qos = |qos| {
+            MqttConfig {
+                qos,
+                ..MqttConfig::default()
+            }
+            .qos()
+        };
+        assert_eq!(qos(0), QoS::AtMostOnce);
+        assert_eq!(qos(1), QoS::AtLeastOnce);
+        assert_eq!(qos(2), QoS::ExactlyOnce);
+        assert_eq!(qos(7), QoS::AtLeastOnce);
+    }
+}
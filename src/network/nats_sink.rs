@@ -0,0 +1,440 @@
+#![cfg(unix)]
+
+//! NATS Sink
+//!
+//! This module publishes payloads to a NATS server instead of the HTTP server, speaking the
+//! NATS client protocol directly over one connection that is opened lazily and reopened
+//! after a failure. Every publish is followed by a `PING` so server errors (e.g. a denied
+//! subject) surface as send failures. With `jetstream = true`, each publish carries a reply
+//! subject and waits for the stream's acknowledgement, so payloads count as delivered only
+//! once JetStream has persisted them. Payloads use the configured `payload_format`.
+
+use log::{debug, info};
+use serde::Deserialize;
+use serde_json::json;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::config_instance::Config;
+use crate::network::batch_util::BatchConfig;
+use crate::network::socket_util::SocketUtil;
+use crate::network::tls_util::TlsUtil;
+
+/// Subscription id of the JetStream acknowledgement inbox.
+const ACK_SID: &str = "1";
+
+/// Configuration of the `[nats]` section. When present, payloads are published to NATS
+/// instead of being sent to `server`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct NatsConfig {
+    /// Server address (`host:port`).
+    pub server: String,
+    /// Subject template; `{hostname}`, `{tenant_id}`, `{site_id}` and `{kind}` (`sensors`,
+    /// `sensors_batch`, `gap`, `black_box`) are substituted.
+    pub subject: String,
+    /// User name for authentication.
+    pub username: Option<String>,
+    /// Password for authentication.
+    pub password: Option<String>,
+    /// Token for token authentication.
+    pub token: Option<String>,
+    /// Whether the connection is upgraded to TLS (trusting `server_ca_file`).
+    pub tls: bool,
+    /// Whether each publish waits for a JetStream acknowledgement.
+    pub jetstream: bool,
+    /// Timeout in seconds for connecting and for each acknowledgement.
+    pub timeout_secs: u64,
+    /// Batching of sensor reports published to NATS; each report is sent alone when absent.
+    pub batch: Option<BatchConfig>,
+}
+
+impl Default for NatsConfig {
+    fn default() -> Self {
+        Self {
+            server: "127.0.0.1:4222".to_string(),
+            subject: "sentinel.{hostname}.{kind}".to_string(),
+            username: None,
+            password: None,
+            token: None,
+            tls: false,
+            jetstream: false,
+            timeout_secs: 10,
+            batch: None,
+        }
+    }
+}
+
+/// A connection to the server, either plain TCP or TLS.
+trait NatsStream: Read + Write + Send {}
+impl<T: Read + Write + Send> NatsStream for T {}
+
+/// An established connection.
+struct Connection {
+    stream: BufReader<Box<dyn NatsStream>>,
+    /// Prefix of the reply subjects of JetStream publishes.
+    inbox: String,
+    /// Number of the next reply subject.
+    next_reply: u64,
+}
+
+/// The open connection, if any.
+static CONNECTION: Mutex<Option<Connection>> = Mutex::new(None);
+
+/// Static utility class for publishing payloads to NATS.
+pub struct NatsSink;
+
+impl NatsSink {
+    /// Returns whether payloads are published to NATS rather than sent over HTTP.
+    pub fn is_enabled() -> bool {
+        Config::get().nats.is_some()
+    }
+
+    /// Publishes an already serialized payload of `kind` on its subject.
+    ///
+    /// Fails if NATS is not configured, the server cannot be reached, rejects the publish
+    /// or, with `jetstream`, does not acknowledge it.
+    pub fn publish(kind: &str, body: &[u8]) -> io::Result<()> {
+        let config = Config::get()
+            .nats
+            .as_ref()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "NATS is not configured"))?;
+        let subject = Self::subject_for(config, kind);
+        let mut connection = CONNECTION.lock().unwrap_or_else(|e| e.into_inner());
+
+        // Retry once on a fresh connection, in case the previous one has gone away.
+        if let Some(open) = connection.as_mut() {
+            match Self::publish_on(open, config, &subject, body) {
+                Ok(()) => return Ok(()),
+                Err(e) => debug!("Publish to {} failed ({}); reconnecting.", subject, e),
+            }
+        }
+        *connection = None;
+        let mut open = Self::connect(config)?;
+        Self::publish_on(&mut open, config, &subject, body)?;
+        *connection = Some(open);
+        Ok(())
+    }
+
+    /// Expands the subject template for payloads of `kind`. Substituted values have dots,
+    /// whitespace and wildcards replaced, so each stays a single subject token.
+    pub fn subject_for(config: &NatsConfig, kind: &str) -> String {
+        let app = Config::get();
+        let hostname = sysinfo::System::host_name().unwrap_or_else(|| "unknown".to_string());
+        config
+            .subject
+            .replace("{hostname}", &Self::token(&hostname))
+            .replace(
+                "{tenant_id}",
+                &Self::token(app.tenant_id.as_deref().unwrap_or_default()),
+            )
+            .replace(
+                "{site_id}",
+                &Self::token(app.site_id.as_deref().unwrap_or_default()),
+            )
+            .replace("{kind}", kind)
+    }
+
+    fn token(value: &str) -> String {
+        if value.is_empty() {
+            return "_".to_string();
+        }
+        value
+            .chars()
+            .map(|c| match c {
+                '.' | '*' | '>' => '_',
+                c if c.is_whitespace() => '_',
+                c => c,
+            })
+            .collect()
+    }
+
+    fn connect(config: &NatsConfig) -> io::Result<Connection> {
+        let timeout = Duration::from_secs(config.timeout_secs.max(1));
+        let addresses = SocketUtil::resolve(&config.server)?;
+        let (tcp, _) = SocketUtil::connect_tcp(&addresses, timeout).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("Failed to connect to {}: {}", config.server, e),
+            )
+        })?;
+        tcp.set_read_timeout(Some(timeout))?;
+        tcp.set_write_timeout(Some(timeout))?;
+
+        // The server greets with INFO in plaintext; TLS starts after it.
+        let mut plain = BufReader::new(tcp);
+        let info = Self::read_line(&mut plain)?;
+        if !info.starts_with("INFO ") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unexpected NATS greeting: {}", info),
+            ));
+        }
+        let stream: Box<dyn NatsStream> = if config.tls {
+            let host = config
+                .server
+                .rsplit_once(':')
+                .map_or(&*config.server, |(host, _)| host);
+            Box::new(TlsUtil::connect(plain.into_inner(), host)?)
+        } else {
+            Box::new(plain.into_inner())
+        };
+        let mut stream = BufReader::new(stream);
+
+        let mut options = json!({
+            "verbose": false,
+            "pedantic": false,
+            "name": "gilded-sentinel",
+            "lang": "rust",
+            "version": env!("CARGO_PKG_VERSION"),
+            "protocol": 1,
+        });
+        if let Some(user) = &config.username {
+            options["user"] = json!(user);
+            options["pass"] = json!(config.password.clone().unwrap_or_default());
+        }
+        if let Some(token) = &config.token {
+            options["auth_token"] = json!(token);
+        }
+        let inbox = format!(
+            "_INBOX.sentinel{}",
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or_default()
+        );
+        let mut handshake = format!("CONNECT {}\r\n", options);
+        if config.jetstream {
+            handshake.push_str(&format!("SUB {}.* {}\r\n", inbox, ACK_SID));
+        }
+        handshake.push_str("PING\r\n");
+        stream.get_mut().write_all(handshake.as_bytes())?;
+        Self::await_pong(&mut stream)?;
+
+        info!("Connected to NATS server {}.", config.server);
+        Ok(Connection {
+            stream,
+            inbox,
+            next_reply: 0,
+        })
+    }
+
+    fn publish_on(
+        connection: &mut Connection,
+        config: &NatsConfig,
+        subject: &str,
+        body: &[u8],
+    ) -> io::Result<()> {
+        let reply = config.jetstream.then(|| {
+            connection.next_reply += 1;
+            format!("{}.{}", connection.inbox, connection.next_reply)
+        });
+        let header = match &reply {
+            Some(reply) => format!("PUB {} {} {}\r\n", subject, reply, body.len()),
+            None => format!("PUB {} {}\r\n", subject, body.len()),
+        };
+        let stream = connection.stream.get_mut();
+        stream.write_all(header.as_bytes())?;
+        stream.write_all(body)?;
+        stream.write_all(b"\r\n")?;
+
+        match reply {
+            Some(reply) => Self::await_ack(&mut connection.stream, &reply)?,
+            None => {
+                stream.write_all(b"PING\r\n")?;
+                Self::await_pong(&mut connection.stream)?;
+            }
+        }
+        debug!(
+            "Published {} byte(s) on NATS subject {}.",
+            body.len(),
+            subject
+        );
+        Ok(())
+    }
+
+    /// Reads until the server's `PONG`, answering its pings and failing on `-ERR`.
+    fn await_pong(stream: &mut BufReader<Box<dyn NatsStream>>) -> io::Result<()> {
+        loop {
+            match Self::read_op(stream)? {
+                Op::Pong => return Ok(()),
+                Op::Msg { .. } | Op::Other => {}
+            }
+        }
+    }
+
+    /// Reads until the JetStream acknowledgement on `reply` arrives.
+    fn await_ack(stream: &mut BufReader<Box<dyn NatsStream>>, reply: &str) -> io::Result<()> {
+        loop {
+            let Op::Msg { subject, payload } = Self::read_op(stream)? else {
+                continue;
+            };
+            if subject != reply {
+                continue; // A late acknowledgement of an earlier, timed-out publish.
+            }
+            let ack: serde_json::Value = serde_json::from_slice(&payload)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            return match ack.get("error") {
+                Some(error) => Err(io::Error::other(format!(
+                    "JetStream rejected publish: {}",
+                    error
+                ))),
+                None => Ok(()),
+            };
+        }
+    }
+
+    /// Reads one server operation, answering `PING` and turning `-ERR` into an error.
+    fn read_op(stream: &mut BufReader<Box<dyn NatsStream>>) -> io::Result<Op> {
+        let line = Self::read_line(stream)?;
+        let mut words = line.split_whitespace();
+        match words.next() {
+            Some("PING") => {
+                stream.get_mut().write_all(b"PONG\r\n")?;
+                Ok(Op::Other)
+            }
+            Some("PONG") => Ok(Op::Pong),
+            Some("-ERR") => Err(io::Error::other(format!(
+                "NATS server error: {}",
+                line.trim_start_matches("-ERR").trim()
+            ))),
+            Some("MSG") => {
+                // MSG <subject> <sid> [reply-to] <#bytes>
+                let fields: Vec<&str> = words.collect();
+                let (Some(subject), Some(Ok(len))) = (
+                    fields.first(),
+                    fields.last().map(|len| len.parse::<usize>()),
+                ) else {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Malformed NATS message: {}", line),
+                    ));
+                };
+                let mut payload = vec![0; len + 2];
+                stream.read_exact(&mut payload)?;
+                payload.truncate(len);
+                Ok(Op::Msg {
+                    subject: subject.to_string(),
+                    payload,
+                })
+            }
+            _ => Ok(Op::Other),
+        }
+    }
+
+    fn read_line<R: BufRead>(stream: &mut R) -> io::Result<String> {
+        let mut line = String::new();
+        if stream.read_line(&mut line)? == 0 {
+            return Err(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "NATS server closed the connection",
+            ));
+        }
+        Ok(line.trim_end().to_string())
+    }
+}
+
+/// A server operation relevant to publishing.
+enum Op {
+    Pong,
+    Msg { subject: String, payload: Vec<u8> },
+    Other,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// A server replaying `input` and recording what the client writes.
+    struct FakeServer {
+        input: io::Cursor<Vec<u8>>,
+        output: Arc<Mutex<Vec<u8>>>,
+    }
+
+    impl Read for FakeServer {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            self.input.read(buf)
+        }
+    }
+
+    impl Write for FakeServer {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.output.lock().unwrap().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn connect_to(input: &str) -> (Connection, Arc<Mutex<Vec<u8>>>) {
+        let output = Arc::new(Mutex::new(Vec::new()));
+        let server = FakeServer {
+            input: io::Cursor::new(input.as_bytes().to_vec()),
+            output: Arc::clone(&output),
+        };
+        let connection = Connection {
+            stream: BufReader::new(Box::new(server)),
+            inbox: "_INBOX.test".to_string(),
+            next_reply: 0,
+        };
+        (connection, output)
+    }
+
+    fn written(output: &Arc<Mutex<Vec<u8>>>) -> String {
+        String::from_utf8(output.lock().unwrap().clone()).unwrap()
+    }
+
+    #[test]
+    fn publishes_then_pings_and_answers_server_pings() {
+        let (mut connection, output) = connect_to("PING\r\nPONG\r\n");
+        NatsSink::publish_on(
+            &mut connection,
+            &NatsConfig::default(),
+            "sentinel.rack-1.sensors",
+            b"hello",
+        )
+        .unwrap();
+        assert_eq!(
+            written(&output),
+            "PUB sentinel.rack-1.sensors 5\r\nhello\r\nPING\r\nPONG\r\n"
+        );
+
+        let (mut connection, _) = connect_to("-ERR 'Permissions Violation'\r\n");
+        let error = NatsSink::publish_on(&mut connection, &NatsConfig::default(), "denied", b"x")
+            .unwrap_err();
+        assert!(error.to_string().contains("Permissions Violation"));
+    }
+
+    #[test]
+    fn jetstream_publishes_wait_for_their_own_ack() {
+        let config = NatsConfig {
+            jetstream: true,
+            ..NatsConfig::default()
+        };
+        let (mut connection, output) = connect_to(
+            "MSG _INBOX.test.0 1 2\r\n{}\r\n\
+             MSG _INBOX.test.1 1 29\r\n{\"stream\":\"SENTINEL\",\"seq\":7}\r\n\
+             MSG _INBOX.test.2 1 22\r\n{\"error\":{\"code\":503}}\r\n",
+        );
+        NatsSink::publish_on(&mut connection, &config, "sentinel.a.gap", b"{}").unwrap();
+        assert_eq!(
+            written(&output),
+            "PUB sentinel.a.gap _INBOX.test.1 2\r\n{}\r\n"
+        );
+
+        let error =
+            NatsSink::publish_on(&mut connection, &config, "sentinel.a.gap", b"{}").unwrap_err();
+        assert!(error.to_string().contains("503"));
+    }
+
+    #[test]
+    fn subject_tokens_have_separators_replaced() {
+        assert_eq!(NatsSink::token("rack 1.example.com"), "rack_1_example_com");
+        assert_eq!(NatsSink::token("a*b>c"), "a_b_c");
+        assert_eq!(NatsSink::token(""), "_");
+    }
+}
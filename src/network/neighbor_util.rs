@@ -0,0 +1,183 @@
+#![cfg(unix)]
+
+//! Neighbor Table Collection
+//!
+//! This module snapshots the ARP (IPv4) and NDP (IPv6) neighbor tables so the
+//! server can use the agent as a lightweight LAN presence monitor.
+
+use log::{debug, warn};
+use std::fs;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::config_instance::Config;
+use crate::data::models::NeighborInfo;
+use crate::system::execution_util::ExecutionUtil;
+
+/// Time of the last neighbor table snapshot, used for rate limiting.
+static LAST_SNAPSHOT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// A utility class for reading the kernel neighbor table.
+pub struct NeighborUtil;
+
+impl NeighborUtil {
+    /// Collects the neighbor table if discovery is enabled and the configured
+    /// snapshot interval has elapsed since the previous snapshot.
+    ///
+    /// Returns `None` when discovery is disabled or no snapshot is due.
+    pub fn collect_if_due() -> Option<Vec<NeighborInfo>> {
+        let config = Config::get();
+        if !config.neighbor_discovery {
+            return None;
+        }
+
+        let mut last = LAST_SNAPSHOT.lock().unwrap_or_else(|e| e.into_inner());
+        let interval = Duration::from_secs(config.neighbor_interval_secs);
+        if last.is_some_and(|taken| taken.elapsed() < interval) {
+            return None;
+        }
+        *last = Some(Instant::now());
+
+        Some(Self::collect_neighbors())
+    }
+
+    /// Reads the neighbor table using `ip neigh`, falling back to `/proc/net/arp`
+    /// (IPv4 only) when the `ip` utility is unavailable.
+    pub fn collect_neighbors() -> Vec<NeighborInfo> {
+        match ExecutionUtil::execute_with_method("direct", "ip", &["neigh", "show"]) {
+            Ok(output) => Self::parse_ip_neigh(&output),
+            Err(e) => {
+                debug!(
+                    "`ip neigh` failed ({}); falling back to /proc/net/arp.",
+                    e.trim()
+                );
+                match fs::read_to_string("/proc/net/arp") {
+                    Ok(contents) => Self::parse_proc_net_arp(&contents),
+                    Err(e) => {
+                        warn!("Failed to read the neighbor table: {}", e);
+                        Vec::new()
+                    }
+                }
+            }
+        }
+    }
+
+    /// Parses `ip neigh show` output.
+    ///
+    /// Example line: `192.168.1.1 dev eth0 lladdr aa:bb:cc:dd:ee:ff REACHABLE`
+    fn parse_ip_neigh(raw_data: &str) -> Vec<NeighborInfo> {
+        raw_data
+            .lines()
+            .filter_map(|line| {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                let ip_address = parts.first()?.to_string();
+                let value_after = |key: &str| {
+                    parts
+                        .iter()
+                        .position(|part| *part == key)
+                        .and_then(|i| parts.get(i + 1))
+                        .map(|value| value.to_string())
+                };
+
+                Some(NeighborInfo {
+                    ip_address,
+                    mac_address: value_after("lladdr"),
+                    state: parts.last().unwrap_or(&"UNKNOWN").to_string(),
+                    interface_name: value_after("dev").unwrap_or_default(),
+                })
+            })
+            .collect()
+    }
+
+    /// Parses the `/proc/net/arp` table.
+    ///
+    /// Columns: IP address, HW type, Flags, HW address, Mask, Device.
+    fn parse_proc_net_arp(raw_data: &str) -> Vec<NeighborInfo> {
+        raw_data
+            .lines()
+            .skip(1)
+            .filter_map(|line| {
+                let parts: Vec<&str> = line.split_whitespace().collect();
+                if parts.len() < 6 {
+                    return None;
+                }
+
+                // ATF_COM (0x2) marks a completed entry.
+                let complete = u32::from_str_radix(parts[2].trim_start_matches("0x"), 16)
+                    .map(|flags| flags & 0x2 != 0)
+                    .unwrap_or(false);
+
+                Some(NeighborInfo {
+                    ip_address: parts[0].to_string(),
+                    mac_address: complete.then(|| parts[3].to_string()),
+                    state: if complete { "REACHABLE" } else { "INCOMPLETE" }.to_string(),
+                    interface_name: parts[5].to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// (IP address, MAC address, state, interface) of each neighbor.
+    fn fields(neighbors: &[NeighborInfo]) -> Vec<(&str, Option<&str>, &str, &str)> {
+        neighbors
+            .iter()
+            .map(|neighbor| {
+                (
+                    neighbor.ip_address.as_str(),
+                    neighbor.mac_address.as_deref(),
+                    neighbor.state.as_str(),
+                    neighbor.interface_name.as_str(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn parses_ip_neigh_output() {
+        let raw = "\
+192.168.1.1 dev eth0 lladdr aa:bb:cc:dd:ee:ff REACHABLE
+192.168.1.7 dev eth0  FAILED
+fe80::1 dev eth0 lladdr 00:11:22:33:44:55 router STALE
+";
+        assert_eq!(
+            fields(&NeighborUtil::parse_ip_neigh(raw)),
+            [
+                (
+                    "192.168.1.1",
+                    Some("aa:bb:cc:dd:ee:ff"),
+                    "REACHABLE",
+                    "eth0"
+                ),
+                ("192.168.1.7", None, "FAILED", "eth0"),
+                ("fe80::1", Some("00:11:22:33:44:55"), "STALE", "eth0"),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_proc_net_arp() {
+        let raw = "\
+IP address       HW type     Flags       HW address            Mask     Device
+192.168.1.1      0x1         0x2         aa:bb:cc:dd:ee:ff     *        eth0
+192.168.1.7      0x1         0x0         00:00:00:00:00:00     *        eth0
+10.0.0.1         0x1
+";
+        assert_eq!(
+            fields(&NeighborUtil::parse_proc_net_arp(raw)),
+            [
+                (
+                    "192.168.1.1",
+                    Some("aa:bb:cc:dd:ee:ff"),
+                    "REACHABLE",
+                    "eth0"
+                ),
+                ("192.168.1.7", None, "INCOMPLETE", "eth0"),
+            ]
+        );
+    }
+}
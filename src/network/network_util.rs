@@ -1,11 +1,95 @@
 #![cfg(unix)]
 
 use get_if_addrs::{get_if_addrs, IfAddr};
-use log::{debug, error, info};
-use serde::Serialize;
-use std::net::{TcpStream, ToSocketAddrs};
-use std::time::Duration;
-use std::{io, thread};
+use log::{debug, error, info, warn};
+use serde::Deserialize;
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::time::{Duration, Instant, SystemTime};
+use std::{fs, io, thread};
+
+use crate::config::config_instance::Config;
+use crate::config::remote_config::RemoteConfig;
+use crate::data::payload_format::{PayloadFormat, WirePayload};
+use crate::data::transform::PayloadTransform;
+use crate::network::ack_util::{AckUtil, SEQUENCE_HEADER};
+use crate::network::batch_util::BatchConfig;
+use crate::network::connection_manager::ConnectionManager;
+use crate::network::grpc_sink::GrpcSink;
+use crate::network::http_client::HttpClient;
+use crate::network::mqtt_sink::MqttSink;
+use crate::network::nats_sink::NatsSink;
+use crate::network::send_log::SendLog;
+use crate::network::send_window::SendWindow;
+use crate::network::socket_util::SocketUtil;
+use crate::network::spool_util::SpoolUtil;
+use crate::network::trace_util::TraceUtil;
+use crate::network::unix_socket_sink::UnixSocketSink;
+use crate::system::self_metrics::SelfMetrics;
+
+/// Magic prefix of split UDP datagrams, followed by the format version.
+const UDP_SPLIT_MAGIC: &[u8; 3] = b"GS\x01";
+/// Size of the split datagram header: magic, message id (u32), index (u16), count (u16).
+const UDP_SPLIT_HEADER_BYTES: usize = UDP_SPLIT_MAGIC.len() + 8;
+/// Largest UDP payload over IPv4.
+const UDP_MAX_PAYLOAD_BYTES: usize = 65_507;
+
+/// Id of the next split UDP message.
+static UDP_MESSAGE_ID: AtomicU32 = AtomicU32::new(0);
+
+/// Handling of payloads larger than `max_datagram_bytes`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UdpOversize {
+    /// Split into numbered datagrams the receiver reassembles.
+    #[default]
+    Split,
+    /// Send only the first `max_datagram_bytes` bytes.
+    Truncate,
+    /// Do not send the payload.
+    Drop,
+}
+
+/// Configuration of the `[udp]` section. When present, payloads are sent as
+/// fire-and-forget datagrams instead of HTTP requests.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct UdpConfig {
+    /// Receiver address (`host:port`); defaults to the host and port of `server`.
+    pub target: Option<String>,
+    /// Largest datagram sent, in bytes.
+    pub max_datagram_bytes: usize,
+    /// What to do with payloads that do not fit into one datagram.
+    pub oversize: UdpOversize,
+    /// Batching of sensor reports sent as datagrams; each report is sent alone when absent.
+    pub batch: Option<BatchConfig>,
+}
+
+impl Default for UdpConfig {
+    fn default() -> Self {
+        Self {
+            target: None,
+            max_datagram_bytes: 1400,
+            oversize: UdpOversize::Split,
+            batch: None,
+        }
+    }
+}
+
+/// Request header carrying the payload's `WirePayload::KIND`.
+pub const KIND_HEADER: &str = "X-Sentinel-Kind";
+
+/// A payload serialized for the transport in use.
+pub struct EncodedPayload {
+    /// `WirePayload::KIND` of the payload, e.g. used in MQTT topics and the
+    /// `X-Sentinel-Kind` header.
+    pub kind: String,
+    pub content_type: String,
+    pub body: Vec<u8>,
+    /// Whether `body` is a protobuf message for the gRPC endpoint.
+    pub grpc: bool,
+    /// Sequence number the server acknowledges the payload by.
+    pub sequence: u64,
+}
 
 /// A utility class for handling network operations, such as sending data to a server.
 pub struct NetworkUtil;
@@ -61,14 +145,14 @@ impl NetworkUtil {
     /// Sends a generic serializable object to the server with a configurable number of retries.
     ///
     /// # Parameters
-    /// - `data`: The data to send, which must implement the `Serialize` trait.
+    /// - `data`: The data to send, which must implement the `WirePayload` trait.
     /// - `server`: The server address (e.g., "127.0.0.1:5000").
     /// - `retries`: The maximum number of retries for sending the data.
     ///
     /// # Returns
     /// - `Ok(())` if the data is successfully sent.
     /// - `Err(io::Error)` if all retries fail.
-    pub fn send_with_retries<T: Serialize>(
+    pub fn send_with_retries<T: WirePayload>(
         data: &T,
         server: &str,
         retries: usize,
@@ -79,7 +163,7 @@ impl NetworkUtil {
     /// Sends a generic serializable object to the server with a configurable number of retries.
     ///
     /// # Parameters
-    /// - `data`: The data to send, which must implement the `Serialize` trait.
+    /// - `data`: The data to send, which must implement the `WirePayload` trait.
     /// - `server`: The server address (e.g., "127.0.0.1:5000").
     /// - `retries`: The maximum number of retries for sending the data.
     /// - `retry_delay`: The delay between retries.
@@ -87,14 +171,30 @@ impl NetworkUtil {
     /// # Returns
     /// - `Ok(())` if the data is successfully sent.
     /// - `Err(io::Error)` if all retries fail.
-    pub fn send_with_retries_define_timeout<T: Serialize>(
+    pub fn send_with_retries_define_timeout<T: WirePayload>(
         data: &T,
         server: &str,
         retries: usize,
         retry_delay: Duration,
     ) -> io::Result<()> {
+        let payload = Self::encode(data)?;
+        if !SendWindow::is_open() {
+            SpoolUtil::store(&payload);
+            return Err(io::Error::new(
+                io::ErrorKind::WouldBlock,
+                "Outside the send windows; payload spooled",
+            ));
+        }
+        if !SpoolUtil::drain(server) {
+            // Older payloads are still undelivered; queue this one behind them.
+            SpoolUtil::store(&payload);
+            return Err(io::Error::other(
+                "Spooled payloads are still undelivered; payload spooled behind them",
+            ));
+        }
+
         for attempt in 1..=retries {
-            match Self::send_object_to_server(data, server) {
+            match Self::deliver(&payload, server) {
                 Ok(_) => {
                     info!(
                         "Data successfully sent to the server on attempt {}/{}",
@@ -115,6 +215,7 @@ impl NetworkUtil {
             }
         }
 
+        SpoolUtil::store(&payload);
         Err(io::Error::new(
             io::ErrorKind::Other,
             "Failed to send data after multiple retries.",
@@ -149,65 +250,387 @@ impl NetworkUtil {
         Ok((host_port, path))
     }
 
-    /// Sends a generic serializable object as JSON to the server.
+    /// Sends a generic serializable object to the server.
+    ///
+    /// Field names are rewritten according to the configured `field_casing`, and the
+    /// object is encoded in the configured `payload_format`. When a `[grpc]` section is
+    /// configured, payloads with a protobuf schema are sent to the gRPC endpoint; otherwise,
+    /// when an `[mqtt]` section is configured, the payload is published to the broker, when
+    /// a `[nats]` section is configured, it is published to NATS, when a `[unix_socket]`
+    /// section is configured, it is written to the socket, and when a `[udp]` section is
+    /// configured, it is sent as datagrams.
     ///
     /// # Parameters
-    /// - `data`: The data to send, serialized as JSON.
+    /// - `data`: The data to send.
     /// - `server`: The server address (e.g., "127.0.0.1:5000").
     ///
     /// # Returns
     /// - `Ok(())` if the data is successfully sent.
     /// - `Err(io::Error)` if the connection or transmission fails.
-    pub fn send_object_to_server<T: Serialize>(data: &T, server: &str) -> io::Result<()> {
-        // Extract host:port and path, applying fallbacks
-        let (host_port, path) = Self::extract_host_and_path_with_fallback(server)?;
+    pub fn send_object_to_server<T: WirePayload>(data: &T, server: &str) -> io::Result<()> {
+        Self::deliver(&Self::encode(data)?, server)
+    }
 
-        // Resolve the host:port
-        let server_addr = host_port
-            .to_socket_addrs()?
-            .next()
-            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidInput, "Invalid server address"))?;
-
-        info!("Connecting to server at: {}", server_addr);
-
-        // Attempt to connect to the server with a timeout
-        let stream_result = TcpStream::connect_timeout(&server_addr, Duration::from_secs(10));
-
-        match stream_result {
-            Ok(mut stream) => {
-                info!("Successfully connected to the server at {}", server_addr);
-
-                // Serialize the data into JSON format
-                let json_data = serde_json::to_string(data).map_err(|e| {
-                    error!("Serialization error: {}", e);
-                    io::Error::new(io::ErrorKind::InvalidData, "Failed to serialize data")
-                })?;
-
-                debug!("Serialized data: {}", json_data);
-
-                // Construct the HTTP request dynamically using the extracted path
-                let host = host_port.split(':').next().unwrap_or("127.0.0.1");
-                let request = format!(
-                    "POST {} HTTP/1.1\r\nHost: {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{}",
-                    path,
-                    host,
-                    json_data.len(),
-                    json_data
+    /// Serializes `data` for the transport in use: a protobuf message when it goes to the
+    /// gRPC endpoint, otherwise the configured `payload_format` and `field_casing`, passed
+    /// through the `[transform]` script if one is configured.
+    ///
+    /// The size and serialization time are recorded in the self-metrics, with a warning
+    /// when a payload grows beyond `payload_warn_bytes`.
+    pub fn encode<T: WirePayload>(data: &T) -> io::Result<EncodedPayload> {
+        let started = Instant::now();
+        let payload = Self::encode_for_transport(data)?;
+        let size = payload.body.len();
+        let previous = SelfMetrics::record_payload(T::KIND, size, started.elapsed());
+        if let Some(limit) = Config::get().payload_warn_bytes {
+            if size > limit && previous.is_none_or(|previous| previous <= limit) {
+                warn!(
+                    "{} payloads grew to {} byte(s), beyond payload_warn_bytes ({}).",
+                    T::KIND,
+                    size,
+                    limit
                 );
+            }
+        }
+        Ok(payload)
+    }
+
+    fn encode_for_transport<T: WirePayload>(data: &T) -> io::Result<EncodedPayload> {
+        if GrpcSink::is_enabled() {
+            if let Some(message) = data.to_protobuf() {
+                return Ok(EncodedPayload {
+                    kind: T::KIND.to_string(),
+                    content_type: "application/grpc".to_string(),
+                    body: message,
+                    grpc: true,
+                    sequence: AckUtil::next_sequence(),
+                });
+            }
+            debug!(
+                "No gRPC schema for {} payloads; using the default transport.",
+                T::KIND
+            );
+        }
+
+        let config = Config::get();
+        let encoded = if PayloadTransform::is_configured()
+            && config.payload_format != PayloadFormat::Protobuf
+        {
+            config
+                .field_casing
+                .to_value(data)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+                .and_then(|value| {
+                    let value = PayloadTransform::run(T::KIND, value)?;
+                    config.payload_format.encode_value(&value)
+                })
+        } else {
+            config.payload_format.encode(data, config.field_casing)
+        };
+        let (body, content_type) = encoded.inspect_err(|e| error!("Serialization error: {}", e))?;
+
+        debug!("Serialized {} byte(s) as {}.", body.len(), content_type);
+        Ok(EncodedPayload {
+            kind: T::KIND.to_string(),
+            content_type: content_type.to_string(),
+            body,
+            grpc: false,
+            sequence: AckUtil::next_sequence(),
+        })
+    }
 
-                debug!("Constructed HTTP request: {}", request);
+    /// Sends an encoded payload over the transport in use (see `send_object_to_server`).
+    ///
+    /// Fails with `InvalidData` if the payload was encoded for gRPC and gRPC is no longer
+    /// configured. The outcome is counted in the agent's self-metrics.
+    pub fn deliver(payload: &EncodedPayload, server: &str) -> io::Result<()> {
+        let started = SystemTime::now();
+        let timer = Instant::now();
+        let result = Self::deliver_via_transport(payload, server);
+        SelfMetrics::record_send(&payload.kind, result.is_ok());
+        SendLog::record(
+            &payload.kind,
+            &Self::endpoint_of(payload, server),
+            payload.body.len(),
+            started,
+            timer.elapsed(),
+            result.as_ref().err().map(ToString::to_string),
+        );
+        result
+    }
 
-                // Send the HTTP request
-                io::Write::write_all(&mut stream, request.as_bytes())?;
-                io::Write::flush(&mut stream)?;
+    /// Describes where `deliver_via_transport` sends a payload, for the send log.
+    fn endpoint_of(payload: &EncodedPayload, server: &str) -> String {
+        let transport = if payload.grpc {
+            "grpc"
+        } else if MqttSink::is_enabled() {
+            "mqtt"
+        } else if NatsSink::is_enabled() {
+            "nats"
+        } else if UnixSocketSink::is_enabled() {
+            "unix"
+        } else if Config::get().udp.is_some() {
+            "udp"
+        } else {
+            server
+        };
+        transport.to_string()
+    }
 
-                info!("Data successfully sent to the server.");
-                Ok(())
+    fn deliver_via_transport(payload: &EncodedPayload, server: &str) -> io::Result<()> {
+        if payload.grpc {
+            if !GrpcSink::is_enabled() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "Payload was encoded for gRPC, which is not configured",
+                ));
             }
-            Err(e) => {
-                error!("Failed to connect to server at {}: {}", server_addr, e);
-                Err(e)
+            return GrpcSink::ingest(payload.body.clone());
+        }
+
+        let body = &payload.body;
+        if MqttSink::is_enabled() {
+            return MqttSink::publish(&payload.kind, body);
+        }
+        if NatsSink::is_enabled() {
+            return NatsSink::publish(&payload.kind, body);
+        }
+        if UnixSocketSink::is_enabled() {
+            return UnixSocketSink::send(body);
+        }
+        if let Some(udp) = &Config::get().udp {
+            return Self::send_payload_udp(body, udp, server);
+        }
+        Self::send_payload_to_server(
+            body,
+            &payload.content_type,
+            server,
+            Some(&payload.kind),
+            Some(payload.sequence),
+        )
+    }
+
+    /// Sends an already serialized payload as UDP datagrams, without any delivery guarantee.
+    ///
+    /// Payloads that fit into `max_datagram_bytes` are sent as-is. Larger payloads are
+    /// handled according to `oversize`; split datagrams start with `GS\x01`, a big-endian
+    /// message id (u32), chunk index and chunk count (u16 each), followed by the chunk.
+    ///
+    /// # Parameters
+    /// - `body`: The payload.
+    /// - `udp`: The UDP settings.
+    /// - `server`: The server address, used when `udp.target` is not set.
+    pub fn send_payload_udp(body: &[u8], udp: &UdpConfig, server: &str) -> io::Result<()> {
+        let target = match &udp.target {
+            Some(target) => target.clone(),
+            None => Self::extract_host_and_path_with_fallback(server)?.0,
+        };
+        let max = udp
+            .max_datagram_bytes
+            .clamp(UDP_SPLIT_HEADER_BYTES + 1, UDP_MAX_PAYLOAD_BYTES);
+
+        let datagrams: Vec<Vec<u8>> = if body.len() <= max {
+            vec![body.to_vec()]
+        } else {
+            match udp.oversize {
+                UdpOversize::Drop => {
+                    warn!(
+                        "Dropping {} byte payload larger than max_datagram_bytes ({}).",
+                        body.len(),
+                        max
+                    );
+                    return Ok(());
+                }
+                UdpOversize::Truncate => {
+                    warn!("Truncating {} byte payload to {} bytes.", body.len(), max);
+                    vec![body[..max].to_vec()]
+                }
+                UdpOversize::Split => Self::split_datagrams(body, max)?,
             }
+        };
+
+        // Sending a datagram cannot fail over, so only the preferred address is used.
+        let address = SocketUtil::resolve(&target)?[0];
+        let socket = SocketUtil::udp_socket(address)?;
+        socket.connect(address)?;
+        for datagram in &datagrams {
+            socket.send(datagram)?;
+        }
+        debug!(
+            "Sent {} byte(s) to {} in {} datagram(s).",
+            body.len(),
+            target,
+            datagrams.len()
+        );
+        Ok(())
+    }
+
+    /// Splits `body` into datagrams of at most `max` bytes, each with a split header.
+    fn split_datagrams(body: &[u8], max: usize) -> io::Result<Vec<Vec<u8>>> {
+        let chunks: Vec<&[u8]> = body.chunks(max - UDP_SPLIT_HEADER_BYTES).collect();
+        let count = u16::try_from(chunks.len()).map_err(|_| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Payload of {} bytes needs too many datagrams", body.len()),
+            )
+        })?;
+        let id = UDP_MESSAGE_ID.fetch_add(1, Ordering::Relaxed);
+
+        Ok(chunks
+            .iter()
+            .enumerate()
+            .map(|(index, chunk)| {
+                let mut datagram = Vec::with_capacity(UDP_SPLIT_HEADER_BYTES + chunk.len());
+                datagram.extend_from_slice(UDP_SPLIT_MAGIC);
+                datagram.extend_from_slice(&id.to_be_bytes());
+                datagram.extend_from_slice(&(index as u16).to_be_bytes());
+                datagram.extend_from_slice(&count.to_be_bytes());
+                datagram.extend_from_slice(chunk);
+                datagram
+            })
+            .collect())
+    }
+
+    /// Sends an already serialized payload to the server.
+    ///
+    /// # Parameters
+    /// - `body`: The request body.
+    /// - `content_type`: MIME type of the body (e.g. `application/json`).
+    /// - `server`: The server address (e.g., "127.0.0.1:5000").
+    /// - `kind`: The payload's `WirePayload::KIND`, sent in `X-Sentinel-Kind` so the server
+    ///   can tell sensor data from other payloads posted to the same endpoint.
+    /// - `sequence`: The payload's sequence number, sent in `X-Sentinel-Sequence`.
+    ///
+    /// # Returns
+    /// - `Ok(())` if the server acknowledged the data with a 2xx status (and, with
+    ///   `require_ack`, acknowledged `sequence`).
+    /// - `Err(io::Error)` if the connection or transmission fails, or the server
+    ///   responded with an error status.
+    pub fn send_payload_to_server(
+        body: &[u8],
+        content_type: &str,
+        server: &str,
+        kind: Option<&str>,
+        sequence: Option<u64>,
+    ) -> io::Result<()> {
+        // Extract host:port and path, applying fallbacks
+        let (host_port, path) = Self::extract_host_and_path_with_fallback(server)?;
+
+        // Resolve the host:port
+        let addresses = SocketUtil::resolve(&host_port)?;
+
+        let host = host_port.split(':').next().unwrap_or("127.0.0.1");
+        let config = Config::get();
+        let path = Config::expand_ids(config.endpoint_path.as_deref().unwrap_or(&path));
+        let mut headers = vec![("Content-Type".to_string(), content_type.to_string())];
+        headers.extend(Self::server_headers()?);
+        if let Some(kind) = kind {
+            headers.push((KIND_HEADER.to_string(), kind.to_string()));
+        }
+        if let Some(sequence) = sequence {
+            headers.push((SEQUENCE_HEADER.to_string(), sequence.to_string()));
+        }
+        let request = HttpClient::build_request(
+            &config.endpoint_method,
+            &path,
+            host,
+            &headers,
+            body,
+            ConnectionManager::keep_alive(),
+        );
+
+        debug!(
+            "Constructed HTTP request for {} {} ({} byte body)",
+            config.endpoint_method,
+            path,
+            body.len()
+        );
+
+        // Send the HTTP request and read the server's reply
+        let result = ConnectionManager::exchange(&addresses, host, &request);
+
+        if TraceUtil::is_active() {
+            let response = result.as_ref().ok().map(|response| response.raw.as_slice());
+            TraceUtil::record(&request, response);
+        }
+
+        let response = result
+            .inspect_err(|e| error!("Failed to send to server at {}: {}", host_port, e))?
+            .error_for_status()?;
+        if let Some(sequence) = sequence {
+            AckUtil::verify(&response, sequence)?;
+        }
+        RemoteConfig::apply_from_response(&response.body);
+        info!(
+            "Data successfully sent to the server ({} {}).",
+            response.status_code, response.reason
+        );
+        Ok(())
+    }
+
+    /// Fetches `path` from the server with a `GET` request and returns the body of the
+    /// 2xx response. The endpoint headers and bearer token are sent as with uploads.
+    pub fn fetch_from_server(path: &str, server: &str) -> io::Result<Vec<u8>> {
+        let (host_port, _) = Self::extract_host_and_path_with_fallback(server)?;
+        let addresses = SocketUtil::resolve(&host_port)?;
+        let host = host_port.split(':').next().unwrap_or("127.0.0.1");
+        let request = HttpClient::build_request(
+            "GET",
+            path,
+            host,
+            &Self::server_headers()?,
+            &[],
+            ConnectionManager::keep_alive(),
+        );
+
+        let result = ConnectionManager::exchange(&addresses, host, &request);
+        if TraceUtil::is_active() {
+            let response = result.as_ref().ok().map(|response| response.raw.as_slice());
+            TraceUtil::record(&request, response);
+        }
+        Ok(result?.error_for_status()?.body)
+    }
+
+    /// Returns the configured `endpoint_headers` and the `Authorization` header, if any.
+    fn server_headers() -> io::Result<Vec<(String, String)>> {
+        let mut headers: Vec<(String, String)> = Config::get()
+            .endpoint_headers
+            .iter()
+            .map(|(name, value)| (name.clone(), value.clone()))
+            .collect();
+        if let Some(token) = Self::auth_token()? {
+            headers.push(("Authorization".to_string(), format!("Bearer {}", token)));
+        }
+        Ok(headers)
+    }
+
+    /// Returns the bearer token for uploads, from `auth_token` or `auth_token_file`.
+    ///
+    /// The token file is read on every call so a rotated token takes effect without a
+    /// restart. An unreadable or empty token file is an error, to avoid silently sending
+    /// unauthenticated requests.
+    fn auth_token() -> io::Result<Option<String>> {
+        let config = Config::get();
+        if let Some(token) = &config.auth_token {
+            return Ok(Some(token.trim().to_string()));
+        }
+        let Some(token_file) = &config.auth_token_file else {
+            return Ok(None);
+        };
+
+        let token = fs::read_to_string(token_file).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("Failed to read auth token file {}: {}", token_file, e),
+            )
+        })?;
+        let token = token.trim();
+        if token.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Auth token file {} is empty", token_file),
+            ));
         }
+        Ok(Some(token.to_string()))
     }
 }
@@ -0,0 +1,309 @@
+//! OTLP Sink
+//!
+//! This module pushes temperatures, utilization and disk metrics to an OpenTelemetry
+//! collector as OTLP/HTTP JSON (`POST /v1/metrics`), in addition to the regular reports.
+//! Every export carries resource attributes identifying the host and the agent version, so
+//! the metrics can be routed by any OpenTelemetry pipeline without a custom receiver.
+
+use log::{debug, warn};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::config::config_instance::Config;
+use crate::data::models::SensorData;
+
+/// Configuration of the `[otlp]` section; metrics are exported when present.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct OtlpConfig {
+    /// OTLP/HTTP metrics endpoint of the collector.
+    pub endpoint: String,
+    /// Extra request headers, e.g. an `Authorization` header for a hosted collector.
+    pub headers: BTreeMap<String, String>,
+    /// Value of the `service.name` resource attribute.
+    pub service_name: String,
+    /// Extra resource attributes attached to every export.
+    pub resource_attributes: BTreeMap<String, String>,
+    /// Minimum seconds between exports; 0 exports with every report.
+    pub interval_secs: u64,
+    /// Seconds to wait for the collector before giving up on an export.
+    pub timeout_secs: u64,
+}
+
+impl Default for OtlpConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "http://127.0.0.1:4318/v1/metrics".to_string(),
+            headers: BTreeMap::new(),
+            service_name: "gilded-sentinel".to_string(),
+            resource_attributes: BTreeMap::new(),
+            interval_secs: 0,
+            timeout_secs: 10,
+        }
+    }
+}
+
+/// Time of the last export.
+static LAST_EXPORT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// HTTP agent used for exports, built from the configured timeout.
+static AGENT: OnceLock<ureq::Agent> = OnceLock::new();
+
+/// A gauge metric and its data points, in export order.
+struct Metric {
+    name: &'static str,
+    unit: &'static str,
+    points: Vec<(Vec<(&'static str, String)>, f64)>,
+}
+
+/// Static utility class for exporting metrics over OTLP.
+pub struct OtlpSink;
+
+impl OtlpSink {
+    /// Exports metrics for `data` if OTLP is configured and `interval_secs` have passed.
+    /// Failures are logged; OTLP delivery is best effort.
+    pub fn emit(data: &SensorData) {
+        let Some(config) = &Config::get().otlp else {
+            return;
+        };
+        {
+            let mut last = LAST_EXPORT.lock().unwrap_or_else(|e| e.into_inner());
+            let interval = Duration::from_secs(config.interval_secs);
+            if last.is_some_and(|exported| exported.elapsed() < interval) {
+                return;
+            }
+            *last = Some(Instant::now());
+        }
+
+        let agent = AGENT.get_or_init(|| {
+            ureq::Agent::config_builder()
+                .timeout_global(Some(Duration::from_secs(config.timeout_secs.max(1))))
+                .build()
+                .into()
+        });
+        let request = Self::export_request(config, data);
+        let mut post = agent.post(&config.endpoint);
+        for (name, value) in &config.headers {
+            post = post.header(name, value);
+        }
+        match post.send_json(&request) {
+            Ok(_) => debug!("Exported OTLP metrics to {}.", config.endpoint),
+            Err(e) => warn!(
+                "Failed to export OTLP metrics to {}: {}",
+                config.endpoint, e
+            ),
+        }
+    }
+
+    /// Builds an `ExportMetricsServiceRequest` in the OTLP JSON encoding.
+    fn export_request(config: &OtlpConfig, data: &SensorData) -> Value {
+        let time = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default()
+            .to_string();
+
+        let mut resource = vec![
+            ("host.name", data.system_info.hostname.as_str()),
+            ("service.name", config.service_name.as_str()),
+            ("service.version", env!("CARGO_PKG_VERSION")),
+        ];
+        resource.extend(
+            config
+                .resource_attributes
+                .iter()
+                .map(|(key, value)| (key.as_str(), value.as_str())),
+        );
+
+        let metrics: Vec<Value> = Self::metrics(data)
+            .into_iter()
+            .filter_map(|metric| {
+                let points: Vec<Value> = metric
+                    .points
+                    .iter()
+                    .filter(|(_, value)| value.is_finite())
+                    .map(|(labels, value)| {
+                        json!({
+                            "attributes": Self::attributes(
+                                labels.iter().map(|(key, value)| (*key, value.as_str()))
+                            ),
+                            "timeUnixNano": time,
+                            "asDouble": value,
+                        })
+                    })
+                    .collect();
+                (!points.is_empty()).then(|| {
+                    json!({
+                        "name": metric.name,
+                        "unit": metric.unit,
+                        "gauge": { "dataPoints": points },
+                    })
+                })
+            })
+            .collect();
+
+        json!({
+            "resourceMetrics": [{
+                "resource": { "attributes": Self::attributes(resource) },
+                "scopeMetrics": [{
+                    "scope": {
+                        "name": env!("CARGO_PKG_NAME"),
+                        "version": env!("CARGO_PKG_VERSION"),
+                    },
+                    "metrics": metrics,
+                }],
+            }],
+        })
+    }
+
+    fn metrics(data: &SensorData) -> Vec<Metric> {
+        let metric = |name, unit| Metric {
+            name,
+            unit,
+            points: Vec::new(),
+        };
+
+        let mut cpu_usage = metric("sentinel.cpu.usage", "%");
+        for (index, usage) in data.cpu_info.usage_per_core.iter().enumerate() {
+            cpu_usage
+                .points
+                .push((vec![("cpu", index.to_string())], usage.0 as f64));
+        }
+
+        let mut package_temperature = metric("sentinel.cpu_package.temperature", "Cel");
+        let mut core_temperature = metric("sentinel.cpu_core.temperature", "Cel");
+        for package in &data.cpu_packages {
+            package_temperature.points.push((
+                vec![("package", package.package_id.clone())],
+                package.package_temperature.0 as f64,
+            ));
+            for core in &package.cores {
+                core_temperature.points.push((
+                    vec![
+                        ("package", package.package_id.clone()),
+                        ("core", core.core_name.clone()),
+                    ],
+                    core.temperature.0 as f64,
+                ));
+            }
+        }
+
+        let memory = &data.memory_info;
+        let mut memory_used = metric("sentinel.memory.used", "By");
+        memory_used.points.push((vec![], memory.used.0 as f64));
+        let mut memory_total = metric("sentinel.memory.total", "By");
+        memory_total.points.push((vec![], memory.total.0 as f64));
+
+        let mut disk_available = metric("sentinel.disk.available_space", "By");
+        let mut disk_total = metric("sentinel.disk.total_space", "By");
+        let mut disk_read = metric("sentinel.disk.read_rate", "By/s");
+        let mut disk_written = metric("sentinel.disk.write_rate", "By/s");
+        for disk in &data.disks {
+            let labels = vec![("disk", disk.name.clone())];
+            disk_available
+                .points
+                .push((labels.clone(), disk.available_space.0 as f64));
+            disk_total
+                .points
+                .push((labels.clone(), disk.total_space.0 as f64));
+            if let Some(rate) = disk.read_bytes_per_sec {
+                disk_read.points.push((labels.clone(), rate));
+            }
+            if let Some(rate) = disk.written_bytes_per_sec {
+                disk_written.points.push((labels, rate));
+            }
+        }
+
+        let mut component_temperature = metric("sentinel.component.temperature", "Cel");
+        for component in &data.components {
+            if let Some(temperature) = component.temperature {
+                component_temperature.points.push((
+                    vec![("label", component.label.clone())],
+                    temperature.0 as f64,
+                ));
+            }
+        }
+
+        vec![
+            cpu_usage,
+            package_temperature,
+            core_temperature,
+            memory_used,
+            memory_total,
+            disk_available,
+            disk_total,
+            disk_read,
+            disk_written,
+            component_temperature,
+        ]
+    }
+
+    /// Renders key/value pairs as OTLP string attributes.
+    fn attributes<'a>(pairs: impl IntoIterator<Item = (&'a str, &'a str)>) -> Vec<Value> {
+        pairs
+            .into_iter()
+            .map(|(key, value)| json!({ "key": key, "value": { "stringValue": value } }))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::units::Celsius;
+    use crate::test_util::sample_sensor_data;
+
+    #[test]
+    fn export_request_follows_the_otlp_json_encoding() {
+        let config = OtlpConfig {
+            resource_attributes: BTreeMap::from([("site".to_string(), "lab".to_string())]),
+            ..OtlpConfig::default()
+        };
+        let mut data = sample_sensor_data();
+        data.cpu_packages[0].cores[0].temperature = Celsius(f32::NAN);
+
+        let request = OtlpSink::export_request(&config, &data);
+        let resource = &request["resourceMetrics"][0];
+        assert_eq!(
+            resource["resource"]["attributes"][0],
+            json!({ "key": "host.name", "value": { "stringValue": "sentinel-test" } })
+        );
+        assert_eq!(resource["resource"]["attributes"][3]["key"], "site");
+
+        let metrics = resource["scopeMetrics"][0]["metrics"].as_array().unwrap();
+        let names: Vec<&str> = metrics
+            .iter()
+            .map(|metric| metric["name"].as_str().unwrap())
+            .collect();
+        // Metrics without finite points (the core and the unset disk rates) are left out.
+        assert_eq!(
+            names,
+            [
+                "sentinel.cpu.usage",
+                "sentinel.cpu_package.temperature",
+                "sentinel.memory.used",
+                "sentinel.memory.total",
+                "sentinel.disk.available_space",
+                "sentinel.disk.total_space",
+                "sentinel.component.temperature",
+            ]
+        );
+
+        let package = &metrics[1];
+        assert_eq!(package["unit"], "Cel");
+        let point = &package["gauge"]["dataPoints"][0];
+        assert_eq!(point["asDouble"], 45.0);
+        assert_eq!(
+            point["attributes"],
+            json!([{ "key": "package", "value": { "stringValue": "0" } }])
+        );
+        assert!(point["timeUnixNano"]
+            .as_str()
+            .unwrap()
+            .parse::<u128>()
+            .is_ok());
+    }
+}
@@ -0,0 +1,140 @@
+//! Public IP Detection
+//!
+//! Hosts behind a home connection with a dynamic address can report the address the
+//! internet sees them as, looked up from an HTTPS echo service (`[public_ip]`). The answer
+//! is cached for `refresh_secs`; after a failed lookup the cached address is kept and the
+//! next attempt waits `retry_secs`, so the service is never queried more often than that.
+//! A changed address triggers the `[ddns]` update, if configured.
+
+use log::{debug, warn};
+use serde::Deserialize;
+use std::net::IpAddr;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::config::config_instance::Config;
+use crate::network::ddns::Ddns;
+
+/// Configuration of the `[public_ip]` section; the public IP is reported when present.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PublicIpConfig {
+    /// Service answering with the caller's address, as plain text or `{"ip": "..."}`.
+    pub url: String,
+    /// Seconds a looked-up address is reused before it is looked up again.
+    pub refresh_secs: u64,
+    /// Seconds to wait after a failed lookup before trying again.
+    pub retry_secs: u64,
+    /// Seconds to wait for the service before giving up on a lookup.
+    pub timeout_secs: u64,
+}
+
+impl Default for PublicIpConfig {
+    fn default() -> Self {
+        Self {
+            url: "https://api.ipify.org".to_string(),
+            refresh_secs: 3600,
+            retry_secs: 300,
+            timeout_secs: 10,
+        }
+    }
+}
+
+/// The last looked-up address and when the next lookup is due.
+struct Cache {
+    address: Option<IpAddr>,
+    next_lookup: Option<Instant>,
+}
+
+static CACHE: Mutex<Cache> = Mutex::new(Cache {
+    address: None,
+    next_lookup: None,
+});
+
+/// HTTP agent used for lookups, built from the configured timeout.
+static AGENT: OnceLock<ureq::Agent> = OnceLock::new();
+
+/// JSON answer of echo services such as `https://api.ipify.org?format=json`.
+#[derive(Deserialize)]
+struct JsonAnswer {
+    ip: String,
+}
+
+/// Static utility class for looking up the host's public IP address.
+pub struct PublicIp;
+
+impl PublicIp {
+    /// Returns the public IP address if `[public_ip]` is configured, looking it up when the
+    /// cached address is due for a refresh; `None` until a lookup has succeeded.
+    pub fn collect_if_configured() -> Option<String> {
+        let config = Config::get().public_ip.as_ref()?;
+        let mut cache = CACHE.lock().unwrap_or_else(|e| e.into_inner());
+        let mut change = None;
+        if cache.next_lookup.is_none_or(|due| Instant::now() >= due) {
+            let wait = match Self::lookup(config) {
+                Ok(address) => {
+                    if cache.address != Some(address) {
+                        debug!("Public IP address is {}.", address);
+                        change = Some((address, cache.address));
+                    }
+                    cache.address = Some(address);
+                    config.refresh_secs
+                }
+                Err(e) => {
+                    warn!("Failed to look up the public IP from {}: {}", config.url, e);
+                    config.retry_secs
+                }
+            };
+            cache.next_lookup = Some(Instant::now() + Duration::from_secs(wait));
+        }
+        let address = cache.address;
+        drop(cache);
+
+        if let Some((address, previous)) = change {
+            Ddns::update_if_configured(address, previous);
+        }
+        address.map(|address| address.to_string())
+    }
+
+    fn lookup(config: &PublicIpConfig) -> Result<IpAddr, String> {
+        let agent = AGENT.get_or_init(|| {
+            ureq::Agent::config_builder()
+                .timeout_global(Some(Duration::from_secs(config.timeout_secs.max(1))))
+                .build()
+                .into()
+        });
+        let body = agent
+            .get(&config.url)
+            .call()
+            .and_then(|mut response| response.body_mut().read_to_string())
+            .map_err(|e| e.to_string())?;
+        Self::parse(&body).ok_or_else(|| format!("unexpected answer {:?}", body.trim()))
+    }
+
+    /// Reads an address from a plain text or JSON answer.
+    fn parse(body: &str) -> Option<IpAddr> {
+        let body = body.trim();
+        match serde_json::from_str::<JsonAnswer>(body) {
+            Ok(answer) => answer.ip.trim().parse().ok(),
+            Err(_) => body.parse().ok(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_plain_and_json_answers() {
+        assert_eq!(
+            PublicIp::parse("203.0.113.7\n"),
+            Some(IpAddr::from([203, 0, 113, 7]))
+        );
+        assert_eq!(
+            PublicIp::parse(r#"{"ip":"2001:db8::1"}"#),
+            "2001:db8::1".parse().ok()
+        );
+        assert_eq!(PublicIp::parse("<html>rate limited</html>"), None);
+    }
+}
@@ -0,0 +1,273 @@
+#![cfg(unix)]
+
+//! Payload Replay
+//!
+//! This module resends previously traced (`--trace-dir`) or spooled payload files to a
+//! server, either to load-test the server or to re-ingest data after server-side loss.
+//! The payload kind and sequence number are sent again as recorded: from the traced
+//! request headers, or from the name of spool files
+//! (`<unix_millis>-<counter>-<sequence>-<kind>.json`).
+
+use log::{error, info, warn};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::Duration;
+
+use crate::config::cli::ReplayOptions;
+use crate::config::config_instance::Config;
+use crate::network::ack_util::SEQUENCE_HEADER;
+use crate::network::network_util::{NetworkUtil, KIND_HEADER};
+
+/// A payload read back from a file, with what is known about how it was first sent.
+#[derive(Debug, PartialEq)]
+struct ReplayedPayload {
+    body: Vec<u8>,
+    content_type: String,
+    kind: Option<String>,
+    sequence: Option<u64>,
+}
+
+/// A utility class for replaying payload files.
+pub struct ReplayUtil;
+
+impl ReplayUtil {
+    /// Replays all payload files referenced by `options`, oldest first.
+    ///
+    /// Files named `<unix_millis>-...` keep their recorded spacing, scaled by the
+    /// configured speed. Returns an error if any payload failed to send.
+    pub fn replay(options: &ReplayOptions) -> io::Result<()> {
+        let target = options.target.as_deref().unwrap_or(Config::server());
+        let files = Self::collect_payload_files(&options.paths)?;
+        info!("Replaying {} payload file(s) to {}.", files.len(), target);
+
+        let mut previous_timestamp: Option<u64> = None;
+        let mut failures = 0;
+
+        for file in &files {
+            let timestamp = Self::timestamp_of(file);
+            if let (Some(previous), Some(current)) = (previous_timestamp, timestamp) {
+                if options.speed > 0.0 && current > previous {
+                    let delay = (current - previous) as f64 / options.speed;
+                    thread::sleep(Duration::from_millis(delay as u64));
+                }
+            }
+            previous_timestamp = timestamp.or(previous_timestamp);
+
+            let result = Self::read_payload(file).and_then(|payload| {
+                NetworkUtil::send_payload_to_server(
+                    &payload.body,
+                    &payload.content_type,
+                    target,
+                    payload.kind.as_deref(),
+                    payload.sequence,
+                )
+            });
+            match result {
+                Ok(_) => info!("Replayed {}.", file.display()),
+                Err(e) => {
+                    error!("Failed to replay {}: {}", file.display(), e);
+                    failures += 1;
+                }
+            }
+        }
+
+        if failures > 0 {
+            return Err(io::Error::other(format!(
+                "{} of {} payload(s) failed to replay",
+                failures,
+                files.len()
+            )));
+        }
+        Ok(())
+    }
+
+    /// Expands the given paths into a sorted list of payload files.
+    ///
+    /// Directories are scanned non-recursively for `*-request.http` trace files and
+    /// `*.json` payload files; traced responses are skipped.
+    fn collect_payload_files(paths: &[String]) -> io::Result<Vec<PathBuf>> {
+        let mut files = Vec::new();
+        for path in paths.iter().map(Path::new) {
+            if path.is_dir() {
+                for entry in fs::read_dir(path)? {
+                    let entry_path = entry?.path();
+                    if Self::is_payload_file(&entry_path) {
+                        files.push(entry_path);
+                    }
+                }
+            } else {
+                files.push(path.to_path_buf());
+            }
+        }
+
+        files.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+        Ok(files)
+    }
+
+    fn is_payload_file(path: &Path) -> bool {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy())
+            .unwrap_or_default();
+        name.ends_with("-request.http") || name.ends_with(".json")
+    }
+
+    /// Extracts the recording timestamp (unix milliseconds) from a file name, if present.
+    fn timestamp_of(path: &Path) -> Option<u64> {
+        path.file_name()?
+            .to_string_lossy()
+            .split('-')
+            .next()?
+            .parse()
+            .ok()
+    }
+
+    /// Reads the payload of a file: the body of a traced HTTP request (with the traced
+    /// `Content-Type`, kind and sequence headers), or the whole file as JSON (with the
+    /// kind and sequence of a spool file name).
+    fn read_payload(path: &Path) -> io::Result<ReplayedPayload> {
+        let contents = fs::read(path)?;
+        if path.extension().is_none_or(|ext| ext != "http") {
+            let (kind, sequence) = Self::spooled_kind_and_sequence(path);
+            return Ok(ReplayedPayload {
+                body: contents,
+                content_type: "application/json".to_string(),
+                kind,
+                sequence,
+            });
+        }
+
+        let Some(separator) = contents.windows(4).position(|window| window == b"\r\n\r\n") else {
+            warn!("{} has no HTTP body separator.", path.display());
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "Trace file contains no request body",
+            ));
+        };
+
+        let head = String::from_utf8_lossy(&contents[..separator]);
+        let header = |wanted: &str| {
+            head.lines()
+                .filter_map(|line| line.split_once(':'))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case(wanted))
+                .map(|(_, value)| value.trim().to_string())
+        };
+
+        Ok(ReplayedPayload {
+            body: contents[separator + 4..].to_vec(),
+            content_type: header("content-type").unwrap_or_else(|| "application/json".to_string()),
+            kind: header(KIND_HEADER),
+            sequence: header(SEQUENCE_HEADER).and_then(|sequence| sequence.parse().ok()),
+        })
+    }
+
+    /// The kind and sequence in the name of a spool file,
+    /// `<unix_millis>-<counter>-<sequence>-<kind>`; files spooled before payloads were
+    /// numbered are named `<unix_millis>-<counter>-<kind>`.
+    fn spooled_kind_and_sequence(path: &Path) -> (Option<String>, Option<u64>) {
+        let Some(name) = path.file_stem().map(|name| name.to_string_lossy()) else {
+            return (None, None);
+        };
+        let numeric = |part: &str| part.parse::<u64>().is_ok();
+        match name.splitn(4, '-').collect::<Vec<_>>().as_slice() {
+            [millis, counter, rest @ ..] if numeric(millis) && numeric(counter) => match rest {
+                [sequence, kind] => (Some(kind.to_string()), sequence.parse().ok()),
+                [kind] => (Some(kind.to_string()), None),
+                _ => (None, None),
+            },
+            _ => (None, None),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn collects_requests_and_json_files_in_name_order() {
+        let dir = TempDir::new("replay-collect");
+        for name in [
+            "1700000000100-000001-request.http",
+            "1700000000100-000001-response.http",
+            "1700000000000-000000-request.http",
+            "1700000000050-0-3-sensors.json",
+            "notes.txt",
+        ] {
+            fs::write(dir.join(name), "").unwrap();
+        }
+        let extra = dir.join("notes.txt").display().to_string();
+
+        let files = ReplayUtil::collect_payload_files(&[dir.display().to_string(), extra]).unwrap();
+        let names: Vec<_> = files
+            .iter()
+            .map(|file| file.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                "1700000000000-000000-request.http",
+                "1700000000050-0-3-sensors.json",
+                "1700000000100-000001-request.http",
+                "notes.txt",
+            ]
+        );
+    }
+
+    #[test]
+    fn timestamps_come_from_the_file_name() {
+        assert_eq!(
+            ReplayUtil::timestamp_of(Path::new("/tmp/1700000000000-000000-request.http")),
+            Some(1_700_000_000_000)
+        );
+        assert_eq!(ReplayUtil::timestamp_of(Path::new("payload.json")), None);
+    }
+
+    #[test]
+    fn traced_requests_keep_their_headers() {
+        let dir = TempDir::new("replay-trace");
+        let path = dir.join("1700000000000-000000-request.http");
+        fs::write(
+            &path,
+            "POST /api HTTP/1.1\r\nHost: server\r\ncontent-type: application/msgpack\r\n\
+             X-Sentinel-Kind: heartbeat\r\nX-Sentinel-Sequence: 42\r\n\r\nbody",
+        )
+        .unwrap();
+        assert_eq!(
+            ReplayUtil::read_payload(&path).unwrap(),
+            ReplayedPayload {
+                body: b"body".to_vec(),
+                content_type: "application/msgpack".to_string(),
+                kind: Some("heartbeat".to_string()),
+                sequence: Some(42),
+            }
+        );
+
+        fs::write(&path, "POST /api HTTP/1.1\r\nHost: server\r\n").unwrap();
+        let error = ReplayUtil::read_payload(&path).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn spool_files_keep_their_kind_and_sequence() {
+        let dir = TempDir::new("replay-spool");
+        let read = |name: &str| {
+            fs::write(dir.join(name), "{}").unwrap();
+            let payload = ReplayUtil::read_payload(&dir.join(name)).unwrap();
+            assert_eq!(payload.content_type, "application/json");
+            (payload.kind, payload.sequence)
+        };
+        assert_eq!(
+            read("1700000000000-0-17-sensors_batch.json"),
+            (Some("sensors_batch".to_string()), Some(17))
+        );
+        assert_eq!(
+            read("1700000000000-1-sensors.json"),
+            (Some("sensors".to_string()), None)
+        );
+        assert_eq!(read("export.json"), (None, None));
+    }
+}
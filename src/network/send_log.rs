@@ -0,0 +1,82 @@
+//! Send Attempt Log
+//!
+//! This module keeps the last `send_log_size` attempts to deliver a payload (when, where
+//! to, how large, how long it took and how it ended) in memory. They are served on
+//! `GET /status` of the local API and summarized in the self-metrics, which tells quickly
+//! whether failures come from the agent's side or the server's.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::config_instance::Config;
+
+/// Recorded attempts, oldest first.
+static ATTEMPTS: Mutex<VecDeque<SendAttempt>> = Mutex::new(VecDeque::new());
+
+/// One attempt to deliver a payload.
+#[derive(Serialize, Debug, Clone)]
+pub struct SendAttempt {
+    /// Milliseconds since the Unix epoch at which the attempt started.
+    pub timestamp_ms: u64,
+    /// `WirePayload::KIND` of the payload.
+    pub kind: String,
+    /// Where the payload went: the server URL, or the transport used instead
+    /// (`grpc`, `mqtt`, `nats`, `unix`, `udp`).
+    pub endpoint: String,
+    pub bytes: usize,
+    pub latency_ms: u64,
+    pub success: bool,
+    /// Why the attempt failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Static accessor for the send attempt ring buffer.
+pub struct SendLog;
+
+impl SendLog {
+    /// Records an attempt that started at `started` and took `latency`, evicting the
+    /// oldest attempts beyond `send_log_size`.
+    pub fn record(
+        kind: &str,
+        endpoint: &str,
+        bytes: usize,
+        started: SystemTime,
+        latency: Duration,
+        error: Option<String>,
+    ) {
+        let capacity = Config::get().send_log_size;
+        if capacity == 0 {
+            return;
+        }
+        let attempt = SendAttempt {
+            timestamp_ms: started
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_millis() as u64)
+                .unwrap_or_default(),
+            kind: kind.to_string(),
+            endpoint: endpoint.to_string(),
+            bytes,
+            latency_ms: latency.as_millis() as u64,
+            success: error.is_none(),
+            error,
+        };
+        let mut attempts = ATTEMPTS.lock().unwrap_or_else(|e| e.into_inner());
+        attempts.push_back(attempt);
+        while attempts.len() > capacity {
+            attempts.pop_front();
+        }
+    }
+
+    /// Returns the recorded attempts, oldest first.
+    pub fn recent() -> Vec<SendAttempt> {
+        ATTEMPTS
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .cloned()
+            .collect()
+    }
+}
@@ -0,0 +1,132 @@
+//! Send Windows
+//!
+//! Hosts on metered or shared links can restrict when the agent uses the network by
+//! configuring `send_windows`. Outside every window, payloads are spooled to disk instead
+//! of being sent (see `spool_util`) and go out, oldest first, with the first send inside
+//! the next window. Windows are given in local time, either daily (`"22:00-06:00"`) or
+//! hourly with `*` as the hour (`"*:00-*:05"`, the first five minutes of every hour);
+//! both wrap around midnight or the full hour respectively. The end minute is exclusive.
+
+use serde::Deserialize;
+use std::fmt;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::config_instance::Config;
+
+/// A period during which payloads may be sent.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum SendWindow {
+    /// Minutes of the day, `[start, end)`.
+    Daily { start: u32, end: u32 },
+    /// Minutes of every hour, `[start, end)`.
+    Hourly { start: u32, end: u32 },
+}
+
+impl SendWindow {
+    /// Whether payloads may be sent now: always when no windows are configured.
+    pub fn is_open() -> bool {
+        let windows = &Config::get().send_windows;
+        windows.is_empty() || {
+            let minute = Self::local_minute_of_day();
+            windows.iter().any(|window| window.contains(minute))
+        }
+    }
+
+    /// Whether the window contains `minute_of_day` (0..1440).
+    pub fn contains(&self, minute_of_day: u32) -> bool {
+        let (start, end, minute) = match *self {
+            SendWindow::Daily { start, end } => (start, end, minute_of_day),
+            SendWindow::Hourly { start, end } => (start, end, minute_of_day % 60),
+        };
+        if start <= end {
+            (start..end).contains(&minute)
+        } else {
+            minute >= start || minute < end
+        }
+    }
+
+    fn local_minute_of_day() -> u32 {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default() as libc::time_t;
+        // SAFETY: `localtime_r` only writes to the provided `tm`.
+        let mut tm: libc::tm = unsafe { std::mem::zeroed() };
+        if unsafe { libc::localtime_r(&now, &mut tm) }.is_null() {
+            return ((now / 60) % 1440) as u32;
+        }
+        (tm.tm_hour * 60 + tm.tm_min) as u32
+    }
+
+    fn parse_time(value: &str) -> Option<(Option<u32>, u32)> {
+        let (hour, minute) = value.trim().split_once(':')?;
+        let minute: u32 = minute.parse().ok().filter(|m| *m < 60)?;
+        match hour {
+            "*" => Some((None, minute)),
+            hour => Some((Some(hour.parse().ok().filter(|h| *h < 24)?), minute)),
+        }
+    }
+}
+
+impl TryFrom<String> for SendWindow {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        let invalid = || {
+            format!(
+                "invalid send window '{}' (expected e.g. \"22:00-06:00\" or \"*:00-*:05\")",
+                value
+            )
+        };
+        let (start, end) = value.split_once('-').ok_or_else(invalid)?;
+        match (Self::parse_time(start), Self::parse_time(end)) {
+            (Some((Some(start_hour), start)), Some((Some(end_hour), end))) => {
+                Ok(SendWindow::Daily {
+                    start: start_hour * 60 + start,
+                    end: end_hour * 60 + end,
+                })
+            }
+            (Some((None, start)), Some((None, end))) => Ok(SendWindow::Hourly { start, end }),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+impl fmt::Display for SendWindow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            SendWindow::Daily { start, end } => write!(
+                f,
+                "{:02}:{:02}-{:02}:{:02}",
+                start / 60,
+                start % 60,
+                end / 60,
+                end % 60
+            ),
+            SendWindow::Hourly { start, end } => write!(f, "*:{:02}-*:{:02}", start, end),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn windows_parse_and_wrap_around() {
+        let night = SendWindow::try_from("22:00-06:00".to_string()).unwrap();
+        assert!(night.contains(23 * 60));
+        assert!(night.contains(5 * 60 + 59));
+        assert!(!night.contains(6 * 60));
+        assert!(!night.contains(12 * 60));
+
+        let hourly = SendWindow::try_from("*:00-*:05".to_string()).unwrap();
+        assert!(hourly.contains(14 * 60 + 3));
+        assert!(!hourly.contains(14 * 60 + 5));
+        assert_eq!(hourly.to_string(), "*:00-*:05");
+
+        assert!(SendWindow::try_from("*:00-06:00".to_string()).is_err());
+        assert!(SendWindow::try_from("25:00-06:00".to_string()).is_err());
+    }
+}
@@ -0,0 +1,522 @@
+#![cfg(unix)]
+
+//! SNMP Agent
+//!
+//! This module runs a tiny embedded SNMP v1/v2c responder (read-only, community-checked)
+//! on UDP, so legacy network management systems can poll the host's temperatures. It
+//! answers `get`, `getnext` and `getbulk`; `set` is rejected. The sub-tree lives under
+//! `base_oid` (by default the net-snmp experimental range, replace it with your own
+//! private enterprise OID) and is rebuilt from every collected report:
+//! - `<base>.1.1.0` host name, `<base>.1.2.0` uptime (TimeTicks), `<base>.1.3.0` average
+//!   CPU usage (%).
+//! - `<base>.2.1.<column>.<n>` CPU packages, `<base>.3.1.<column>.<n>` CPU cores and
+//!   `<base>.4.1.<column>.<n>` other components, each with the columns 1 index, 2 name,
+//!   3 temperature, 4 high and 5 critical threshold. Temperatures are integers in tenths
+//!   of a degree Celsius, as SNMP has no floating-point type; missing readings are omitted.
+//! - `<base>.5.1.<column>.<n>` fans, with the columns 1 index, 2 name (`<chip>/<label>`),
+//!   3 speed and 4 minimum speed, in RPM (Gauge32).
+//!   Table numbers 6 and up are reserved for other readings.
+
+use log::{debug, error, info, warn};
+use serde::Deserialize;
+use std::net::UdpSocket;
+use std::sync::Mutex;
+use std::thread;
+
+use crate::config::config_instance::Config;
+use crate::data::models::{FanInfo, SensorData};
+use crate::data::units::Celsius;
+use crate::network::snmp_ber::{
+    Oid, Reader, SnmpBer, PDU_GET, PDU_GET_BULK, PDU_GET_NEXT, PDU_RESPONSE, PDU_SET,
+    TAG_END_OF_MIB_VIEW, TAG_GAUGE32, TAG_INTEGER, TAG_NO_SUCH_INSTANCE, TAG_NO_SUCH_OBJECT,
+    TAG_NULL, TAG_OCTET_STRING, TAG_OID, TAG_SEQUENCE, TAG_TIMETICKS, VERSION_1, VERSION_2C,
+};
+
+/// Largest request accepted and response sent.
+const MAX_MESSAGE_BYTES: usize = 65_507;
+/// Upper bound on `max-repetitions` of a `getbulk` request.
+const MAX_REPETITIONS: usize = 64;
+
+const ERROR_NO_SUCH_NAME: i64 = 2;
+const ERROR_NOT_WRITABLE: i64 = 17;
+
+/// Configuration of the `[snmp]` section; the responder runs when present.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SnmpConfig {
+    /// UDP address to listen on; port 161 requires root. Loopback by default, as v1/v2c
+    /// communities travel in clear text.
+    pub listen: String,
+    /// Community string requests must carry; others are ignored.
+    pub community: String,
+    /// OID under which the sub-tree is served, in dotted notation.
+    pub base_oid: String,
+}
+
+impl Default for SnmpConfig {
+    fn default() -> Self {
+        Self {
+            listen: "127.0.0.1:1161".to_string(),
+            community: "public".to_string(),
+            base_oid: "1.3.6.1.4.1.8072.9999.9999".to_string(),
+        }
+    }
+}
+
+/// A variable value, or one of the v2c exceptions.
+#[derive(Debug, Clone, PartialEq)]
+enum SnmpValue {
+    Integer(i64),
+    OctetString(String),
+    Gauge32(u32),
+    TimeTicks(u32),
+    Null,
+    NoSuchObject,
+    NoSuchInstance,
+    EndOfMibView,
+}
+
+/// The served variables, sorted by OID.
+static MIB: Mutex<Vec<(Oid, SnmpValue)>> = Mutex::new(Vec::new());
+
+/// Static utility class for the SNMP responder.
+pub struct SnmpAgent;
+
+impl SnmpAgent {
+    /// Starts the responder on a background thread if `[snmp]` is configured.
+    pub fn start_if_configured() {
+        let Some(config) = Config::get().snmp.clone() else {
+            return;
+        };
+        if SnmpBer::parse_oid(&config.base_oid).is_none() {
+            error!("Invalid SNMP base_oid {:?}; not starting.", config.base_oid);
+            return;
+        }
+
+        match UdpSocket::bind(&config.listen) {
+            Ok(socket) => {
+                let loopback = socket
+                    .local_addr()
+                    .is_ok_and(|addr| addr.ip().is_loopback());
+                if !loopback && config.community == SnmpConfig::default().community {
+                    warn!(
+                        "SNMP agent on {} accepts the default community; set `community` in [snmp].",
+                        config.listen
+                    );
+                }
+                info!(
+                    "SNMP agent listening on {} under {}",
+                    config.listen, config.base_oid
+                );
+                thread::spawn(move || Self::serve(socket, &config.community));
+            }
+            Err(e) => error!("Failed to bind SNMP agent to {}: {}", config.listen, e),
+        }
+    }
+
+    /// Rebuilds the served sub-tree from `data` if `[snmp]` is configured.
+    pub fn update(data: &SensorData) {
+        let Some(config) = &Config::get().snmp else {
+            return;
+        };
+        let Some(base) = SnmpBer::parse_oid(&config.base_oid) else {
+            return;
+        };
+        *MIB.lock().unwrap_or_else(|e| e.into_inner()) = Self::build_mib(&base, data);
+    }
+
+    fn serve(socket: UdpSocket, community: &str) {
+        let mut buffer = vec![0u8; MAX_MESSAGE_BYTES];
+        loop {
+            let (len, peer) = match socket.recv_from(&mut buffer) {
+                Ok(received) => received,
+                Err(e) => {
+                    warn!("Failed to receive SNMP request: {}", e);
+                    continue;
+                }
+            };
+            let response = {
+                let mib = MIB.lock().unwrap_or_else(|e| e.into_inner());
+                Self::respond(community, &mib, &buffer[..len])
+            };
+            match response {
+                Some(response) => {
+                    if let Err(e) = socket.send_to(&response, peer) {
+                        debug!("Failed to send SNMP response to {}: {}", peer, e);
+                    }
+                }
+                None => debug!("Ignored SNMP request from {}.", peer),
+            }
+        }
+    }
+
+    /// Lays out the sub-tree for `data` under `base`, sorted by OID.
+    fn build_mib(base: &[u32], data: &SensorData) -> Vec<(Oid, SnmpValue)> {
+        let oid = |suffix: &[u32]| -> Oid { base.iter().chain(suffix).copied().collect() };
+        let tenths = |celsius: Celsius| SnmpValue::Integer((celsius.0 * 10.0).round() as i64);
+        let mut mib = Vec::new();
+
+        mib.push((
+            oid(&[1, 1, 0]),
+            SnmpValue::OctetString(data.system_info.hostname.clone()),
+        ));
+        let ticks = data.system_info.uptime.total_seconds.saturating_mul(100);
+        mib.push((
+            oid(&[1, 2, 0]),
+            SnmpValue::TimeTicks(ticks.min(u32::MAX as u64) as u32),
+        ));
+        if let Some(average) = data.cpu_info.average_usage() {
+            mib.push((
+                oid(&[1, 3, 0]),
+                SnmpValue::Gauge32(average.0.round() as u32),
+            ));
+        }
+
+        // Rows of (name, temperature, high, critical) for each temperature table.
+        let packages = data.cpu_packages.iter().map(|package| {
+            (
+                format!("{}/{}", package.adapter_name, package.package_id),
+                Some(package.package_temperature),
+                Some(package.high_threshold),
+                Some(package.critical_threshold),
+            )
+        });
+        let cores = data.cpu_packages.iter().flat_map(|package| {
+            package.cores.iter().map(|core| {
+                (
+                    format!("{}/{}", package.package_id, core.core_name),
+                    Some(core.temperature),
+                    Some(core.high_threshold),
+                    Some(core.critical_threshold),
+                )
+            })
+        });
+        let components = data.components.iter().map(|component| {
+            (
+                component.label.clone(),
+                component.temperature,
+                component.max_temperature,
+                component.critical_temperature,
+            )
+        });
+        let tables: [(u32, Vec<_>); 3] = [
+            (2, packages.collect()),
+            (3, cores.collect()),
+            (4, components.collect()),
+        ];
+        for (table, rows) in tables {
+            for (n, (name, temperature, high, critical)) in rows.into_iter().enumerate() {
+                let index = n as u32 + 1;
+                mib.push((oid(&[table, 1, 1, index]), SnmpValue::Integer(index as i64)));
+                mib.push((oid(&[table, 1, 2, index]), SnmpValue::OctetString(name)));
+                for (column, reading) in [(3, temperature), (4, high), (5, critical)] {
+                    if let Some(celsius) = reading.filter(|c| c.0.is_finite()) {
+                        mib.push((oid(&[table, 1, column, index]), tenths(celsius)));
+                    }
+                }
+            }
+        }
+        if let Some(fans) = &data.fans {
+            mib.extend(Self::fan_table(base, fans));
+        }
+
+        mib.sort_by(|a, b| a.0.cmp(&b.0));
+        mib
+    }
+
+    /// Lays out the fan table (`<base>.5`) for `fans`.
+    fn fan_table(base: &[u32], fans: &[FanInfo]) -> Vec<(Oid, SnmpValue)> {
+        let oid = |suffix: &[u32]| -> Oid { base.iter().chain(suffix).copied().collect() };
+        let rpm = |speed: f32| SnmpValue::Gauge32(speed.max(0.0).round() as u32);
+        let mut table = Vec::new();
+        for (n, fan) in fans.iter().enumerate() {
+            let index = n as u32 + 1;
+            table.push((oid(&[5, 1, 1, index]), SnmpValue::Integer(index as i64)));
+            table.push((
+                oid(&[5, 1, 2, index]),
+                SnmpValue::OctetString(format!("{}/{}", fan.adapter_name, fan.label)),
+            ));
+            table.push((oid(&[5, 1, 3, index]), rpm(fan.speed)));
+            if let Some(min_speed) = fan.min_speed {
+                table.push((oid(&[5, 1, 4, index]), rpm(min_speed)));
+            }
+        }
+        table
+    }
+
+    /// Answers one request datagram; `None` for malformed requests, unknown PDUs and
+    /// requests with the wrong community.
+    fn respond(community: &str, mib: &[(Oid, SnmpValue)], request: &[u8]) -> Option<Vec<u8>> {
+        let mut message = Reader::new(request).expect(TAG_SEQUENCE)?;
+        let version = message.integer()?;
+        if version != VERSION_1 && version != VERSION_2C {
+            return None;
+        }
+        let request_community = message.expect(TAG_OCTET_STRING)?.data;
+        if request_community != community.as_bytes() {
+            return None;
+        }
+        let (pdu_type, mut pdu) = message.any()?;
+        let request_id = pdu.integer()?;
+        let non_repeaters = pdu.integer()?.max(0) as usize;
+        let max_repetitions = (pdu.integer()?.max(0) as usize).min(MAX_REPETITIONS);
+        let mut varbinds = pdu.expect(TAG_SEQUENCE)?;
+        let mut oids = Vec::new();
+        while !varbinds.is_empty() {
+            let mut varbind = varbinds.expect(TAG_SEQUENCE)?;
+            oids.push(varbind.oid()?);
+        }
+
+        let lookup = |oid: &Oid| match mib.binary_search_by(|(entry, _)| entry.cmp(oid)) {
+            Ok(i) => (oid.clone(), mib[i].1.clone()),
+            Err(_)
+                if mib
+                    .iter()
+                    .any(|(entry, _)| entry.starts_with(&oid[..oid.len().saturating_sub(1)])) =>
+            {
+                (oid.clone(), SnmpValue::NoSuchInstance)
+            }
+            Err(_) => (oid.clone(), SnmpValue::NoSuchObject),
+        };
+        let next = |oid: &Oid| match mib.iter().find(|(entry, _)| entry > oid) {
+            Some(entry) => entry.clone(),
+            None => (oid.clone(), SnmpValue::EndOfMibView),
+        };
+
+        let mut error = (0, 0);
+        let results: Vec<(Oid, SnmpValue)> = match pdu_type {
+            PDU_GET => oids.iter().map(lookup).collect(),
+            PDU_GET_NEXT => oids.iter().map(next).collect(),
+            PDU_GET_BULK if version == VERSION_2C => {
+                let split = non_repeaters.min(oids.len());
+                let mut results: Vec<_> = oids[..split].iter().map(next).collect();
+                let mut cursors = oids[split..].to_vec();
+                for _ in 0..max_repetitions {
+                    if cursors.is_empty() {
+                        break;
+                    }
+                    let row: Vec<_> = cursors.iter().map(next).collect();
+                    let done = row
+                        .iter()
+                        .all(|(_, value)| *value == SnmpValue::EndOfMibView);
+                    cursors = row.iter().map(|(oid, _)| oid.clone()).collect();
+                    results.extend(row);
+                    if done {
+                        break;
+                    }
+                }
+                results
+            }
+            PDU_SET => {
+                let status = if version == VERSION_1 {
+                    ERROR_NO_SUCH_NAME
+                } else {
+                    ERROR_NOT_WRITABLE
+                };
+                error = (status, 1);
+                oids.iter()
+                    .map(|oid| (oid.clone(), SnmpValue::Null))
+                    .collect()
+            }
+            _ => return None,
+        };
+
+        // SNMPv1 has no exception values; report the first one as noSuchName instead.
+        let results = match results.iter().position(|(_, value)| value.is_exception()) {
+            Some(i) if version == VERSION_1 => {
+                error = (ERROR_NO_SUCH_NAME, i as i64 + 1);
+                oids.iter()
+                    .map(|oid| (oid.clone(), SnmpValue::Null))
+                    .collect()
+            }
+            _ => results,
+        };
+
+        let mut varbinds = Vec::new();
+        for (oid, value) in &results {
+            let mut varbind = Vec::new();
+            SnmpBer::encode_tlv(TAG_OID, &SnmpBer::encode_oid(oid), &mut varbind);
+            value.encode(&mut varbind);
+            SnmpBer::encode_tlv(TAG_SEQUENCE, &varbind, &mut varbinds);
+        }
+        let mut pdu = Vec::new();
+        SnmpBer::encode_tlv(TAG_INTEGER, &SnmpBer::encode_integer(request_id), &mut pdu);
+        SnmpBer::encode_tlv(TAG_INTEGER, &SnmpBer::encode_integer(error.0), &mut pdu);
+        SnmpBer::encode_tlv(TAG_INTEGER, &SnmpBer::encode_integer(error.1), &mut pdu);
+        SnmpBer::encode_tlv(TAG_SEQUENCE, &varbinds, &mut pdu);
+        let mut message = Vec::new();
+        SnmpBer::encode_tlv(TAG_INTEGER, &SnmpBer::encode_integer(version), &mut message);
+        SnmpBer::encode_tlv(TAG_OCTET_STRING, community.as_bytes(), &mut message);
+        SnmpBer::encode_tlv(PDU_RESPONSE, &pdu, &mut message);
+        let mut response = Vec::new();
+        SnmpBer::encode_tlv(TAG_SEQUENCE, &message, &mut response);
+
+        (response.len() <= MAX_MESSAGE_BYTES).then_some(response)
+    }
+}
+
+impl SnmpValue {
+    fn is_exception(&self) -> bool {
+        matches!(
+            self,
+            SnmpValue::NoSuchObject | SnmpValue::NoSuchInstance | SnmpValue::EndOfMibView
+        )
+    }
+
+    fn encode(&self, out: &mut Vec<u8>) {
+        match self {
+            SnmpValue::Integer(v) => {
+                SnmpBer::encode_tlv(TAG_INTEGER, &SnmpBer::encode_integer(*v), out)
+            }
+            SnmpValue::OctetString(v) => SnmpBer::encode_tlv(TAG_OCTET_STRING, v.as_bytes(), out),
+            SnmpValue::Gauge32(v) => {
+                SnmpBer::encode_tlv(TAG_GAUGE32, &SnmpBer::encode_unsigned(*v), out)
+            }
+            SnmpValue::TimeTicks(v) => {
+                SnmpBer::encode_tlv(TAG_TIMETICKS, &SnmpBer::encode_unsigned(*v), out)
+            }
+            SnmpValue::Null => SnmpBer::encode_tlv(TAG_NULL, &[], out),
+            SnmpValue::NoSuchObject => SnmpBer::encode_tlv(TAG_NO_SUCH_OBJECT, &[], out),
+            SnmpValue::NoSuchInstance => SnmpBer::encode_tlv(TAG_NO_SUCH_INSTANCE, &[], out),
+            SnmpValue::EndOfMibView => SnmpBer::encode_tlv(TAG_END_OF_MIB_VIEW, &[], out),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_request(community: &str, pdu_type: u8, oids: &[&[u32]]) -> Vec<u8> {
+        let mut varbinds = Vec::new();
+        for oid in oids {
+            let mut varbind = Vec::new();
+            SnmpBer::encode_tlv(TAG_OID, &SnmpBer::encode_oid(oid), &mut varbind);
+            SnmpBer::encode_tlv(TAG_NULL, &[], &mut varbind);
+            SnmpBer::encode_tlv(TAG_SEQUENCE, &varbind, &mut varbinds);
+        }
+        let mut pdu = Vec::new();
+        SnmpBer::encode_tlv(TAG_INTEGER, &SnmpBer::encode_integer(4242), &mut pdu);
+        SnmpBer::encode_tlv(TAG_INTEGER, &SnmpBer::encode_integer(0), &mut pdu);
+        SnmpBer::encode_tlv(TAG_INTEGER, &SnmpBer::encode_integer(0), &mut pdu);
+        SnmpBer::encode_tlv(TAG_SEQUENCE, &varbinds, &mut pdu);
+        let mut message = Vec::new();
+        SnmpBer::encode_tlv(
+            TAG_INTEGER,
+            &SnmpBer::encode_integer(VERSION_2C),
+            &mut message,
+        );
+        SnmpBer::encode_tlv(TAG_OCTET_STRING, community.as_bytes(), &mut message);
+        SnmpBer::encode_tlv(pdu_type, &pdu, &mut message);
+        let mut out = Vec::new();
+        SnmpBer::encode_tlv(TAG_SEQUENCE, &message, &mut out);
+        out
+    }
+
+    /// A decoded variable binding: OID, value tag and value contents.
+    type Varbind = (Oid, u8, Vec<u8>);
+
+    /// Decodes a response into its request id, error status and variable bindings.
+    fn decode(response: &[u8]) -> (i64, i64, Vec<Varbind>) {
+        let mut message = Reader::new(response).expect(TAG_SEQUENCE).unwrap();
+        message.integer().unwrap();
+        message.expect(TAG_OCTET_STRING).unwrap();
+        let mut pdu = message.expect(PDU_RESPONSE).unwrap();
+        let request_id = pdu.integer().unwrap();
+        let status = pdu.integer().unwrap();
+        pdu.integer().unwrap();
+        let mut varbinds = pdu.expect(TAG_SEQUENCE).unwrap();
+        let mut results = Vec::new();
+        while !varbinds.is_empty() {
+            let mut varbind = varbinds.expect(TAG_SEQUENCE).unwrap();
+            let oid = varbind.oid().unwrap();
+            let (tag, value) = varbind.any().unwrap();
+            results.push((oid, tag, value.data.to_vec()));
+        }
+        (request_id, status, results)
+    }
+
+    fn sample_mib() -> Vec<(Oid, SnmpValue)> {
+        vec![
+            (
+                vec![1, 3, 6, 1, 4, 1, 99, 1, 1, 0],
+                SnmpValue::OctetString("nas".into()),
+            ),
+            (
+                vec![1, 3, 6, 1, 4, 1, 99, 2, 1, 3, 1],
+                SnmpValue::Integer(-55),
+            ),
+        ]
+    }
+
+    #[test]
+    fn get_returns_values_and_exceptions() {
+        let request = encode_request(
+            "public",
+            PDU_GET,
+            &[
+                &[1, 3, 6, 1, 4, 1, 99, 2, 1, 3, 1],
+                &[1, 3, 6, 1, 4, 1, 99, 7],
+            ],
+        );
+        let (request_id, status, results) =
+            decode(&SnmpAgent::respond("public", &sample_mib(), &request).unwrap());
+
+        assert_eq!((request_id, status), (4242, 0));
+        assert_eq!(results[0].1, TAG_INTEGER);
+        assert_eq!(results[0].2, [0xC9]);
+        assert_eq!(results[1].1, TAG_NO_SUCH_INSTANCE);
+    }
+
+    #[test]
+    fn get_next_walks_the_tree_in_order() {
+        let request = encode_request("public", PDU_GET_NEXT, &[&[1, 3, 6, 1, 4, 1, 99]]);
+        let (_, _, results) =
+            decode(&SnmpAgent::respond("public", &sample_mib(), &request).unwrap());
+        assert_eq!(results[0].0, sample_mib()[0].0);
+        assert_eq!(results[0].2, b"nas");
+
+        let request = encode_request(
+            "public",
+            PDU_GET_NEXT,
+            &[&[1, 3, 6, 1, 4, 1, 99, 2, 1, 3, 1]],
+        );
+        let (_, _, results) =
+            decode(&SnmpAgent::respond("public", &sample_mib(), &request).unwrap());
+        assert_eq!(results[0].1, TAG_END_OF_MIB_VIEW);
+    }
+
+    #[test]
+    fn fans_are_laid_out_in_table_5() {
+        let fans = [FanInfo {
+            adapter_name: "nct6798-isa-0290".to_string(),
+            label: "CPU Fan".to_string(),
+            speed: 1234.6,
+            min_speed: None,
+        }];
+        let table = SnmpAgent::fan_table(&[1, 3, 6, 1, 4, 1, 99], &fans);
+        assert_eq!(
+            table,
+            [
+                (
+                    vec![1, 3, 6, 1, 4, 1, 99, 5, 1, 1, 1],
+                    SnmpValue::Integer(1)
+                ),
+                (
+                    vec![1, 3, 6, 1, 4, 1, 99, 5, 1, 2, 1],
+                    SnmpValue::OctetString("nct6798-isa-0290/CPU Fan".to_string())
+                ),
+                (
+                    vec![1, 3, 6, 1, 4, 1, 99, 5, 1, 3, 1],
+                    SnmpValue::Gauge32(1235)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn wrong_community_is_ignored() {
+        let request = encode_request("private", PDU_GET, &[&[1, 3, 6, 1, 4, 1, 99, 1, 1, 0]]);
+        assert!(SnmpAgent::respond("public", &sample_mib(), &request).is_none());
+    }
+}
@@ -0,0 +1,237 @@
+//! SNMP BER Encoding
+//!
+//! The subset of the Basic Encoding Rules used by SNMP v1/v2c messages, shared by the
+//! embedded agent and the poller of adjacent devices.
+
+/// An object identifier as its arcs.
+pub(crate) type Oid = Vec<u32>;
+
+pub(crate) const TAG_INTEGER: u8 = 0x02;
+pub(crate) const TAG_OCTET_STRING: u8 = 0x04;
+pub(crate) const TAG_NULL: u8 = 0x05;
+pub(crate) const TAG_OID: u8 = 0x06;
+pub(crate) const TAG_SEQUENCE: u8 = 0x30;
+pub(crate) const TAG_COUNTER32: u8 = 0x41;
+pub(crate) const TAG_GAUGE32: u8 = 0x42;
+pub(crate) const TAG_TIMETICKS: u8 = 0x43;
+pub(crate) const TAG_COUNTER64: u8 = 0x46;
+pub(crate) const TAG_NO_SUCH_OBJECT: u8 = 0x80;
+pub(crate) const TAG_NO_SUCH_INSTANCE: u8 = 0x81;
+pub(crate) const TAG_END_OF_MIB_VIEW: u8 = 0x82;
+pub(crate) const PDU_GET: u8 = 0xA0;
+pub(crate) const PDU_GET_NEXT: u8 = 0xA1;
+pub(crate) const PDU_RESPONSE: u8 = 0xA2;
+pub(crate) const PDU_SET: u8 = 0xA3;
+pub(crate) const PDU_GET_BULK: u8 = 0xA5;
+
+pub(crate) const VERSION_1: i64 = 0;
+pub(crate) const VERSION_2C: i64 = 1;
+
+/// Static utility class for encoding and decoding BER values.
+pub struct SnmpBer;
+
+impl SnmpBer {
+    /// Parses a dotted OID such as `1.3.6.1.4.1.8072`.
+    pub fn parse_oid(value: &str) -> Option<Oid> {
+        let oid: Option<Oid> = value
+            .trim_start_matches('.')
+            .split('.')
+            .map(|arc| arc.parse().ok())
+            .collect();
+        oid.filter(|oid| oid.len() >= 2 && oid[0] <= 2)
+    }
+
+    /// Decodes the two's-complement contents of an INTEGER.
+    pub fn decode_integer(contents: &[u8]) -> Option<i64> {
+        if contents.is_empty() || contents.len() > 8 {
+            return None;
+        }
+        let sign = if contents[0] & 0x80 != 0 { -1i64 } else { 0 };
+        Some(
+            contents
+                .iter()
+                .fold(sign, |value, &byte| (value << 8) | byte as i64),
+        )
+    }
+
+    /// Appends a TLV with `contents` to `out`, using the long length form above 127 bytes.
+    pub fn encode_tlv(tag: u8, contents: &[u8], out: &mut Vec<u8>) {
+        out.push(tag);
+        let len = contents.len();
+        if len < 0x80 {
+            out.push(len as u8);
+        } else {
+            let bytes = len.to_be_bytes();
+            let skip = bytes.iter().take_while(|&&byte| byte == 0).count();
+            out.push(0x80 | (bytes.len() - skip) as u8);
+            out.extend_from_slice(&bytes[skip..]);
+        }
+        out.extend_from_slice(contents);
+    }
+
+    /// Minimal two's-complement encoding of a signed integer.
+    pub fn encode_integer(value: i64) -> Vec<u8> {
+        let bytes = value.to_be_bytes();
+        let mut start = 0;
+        while start < bytes.len() - 1
+            && ((bytes[start] == 0x00 && bytes[start + 1] & 0x80 == 0)
+                || (bytes[start] == 0xFF && bytes[start + 1] & 0x80 != 0))
+        {
+            start += 1;
+        }
+        bytes[start..].to_vec()
+    }
+
+    /// Encoding of an unsigned application type (Gauge32, TimeTicks).
+    pub fn encode_unsigned(value: u32) -> Vec<u8> {
+        Self::encode_integer(value as i64)
+    }
+
+    /// Encodes the arcs of an OID, the first two combined and the rest in base 128.
+    pub fn encode_oid(oid: &[u32]) -> Vec<u8> {
+        let mut out = Vec::new();
+        let first = oid.first().copied().unwrap_or(0) * 40 + oid.get(1).copied().unwrap_or(0);
+        for arc in std::iter::once(first).chain(oid.iter().skip(2).copied()) {
+            let mut chunk = vec![(arc & 0x7F) as u8];
+            let mut rest = arc >> 7;
+            while rest > 0 {
+                chunk.push(0x80 | (rest & 0x7F) as u8);
+                rest >>= 7;
+            }
+            out.extend(chunk.iter().rev());
+        }
+        out
+    }
+}
+
+/// A cursor over BER-encoded TLVs.
+pub(crate) struct Reader<'a> {
+    pub(crate) data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    pub(crate) fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Reads the next TLV, returning its tag and a reader over its contents.
+    pub(crate) fn any(&mut self) -> Option<(u8, Reader<'a>)> {
+        let (&tag, rest) = self.data.split_first()?;
+        let (&first, mut rest) = rest.split_first()?;
+        let len = if first < 0x80 {
+            first as usize
+        } else {
+            let count = (first & 0x7F) as usize;
+            if count == 0 || count > 4 || rest.len() < count {
+                return None;
+            }
+            let len = rest[..count]
+                .iter()
+                .fold(0usize, |len, &byte| (len << 8) | byte as usize);
+            rest = &rest[count..];
+            len
+        };
+        if rest.len() < len {
+            return None;
+        }
+        let (contents, rest) = rest.split_at(len);
+        self.data = rest;
+        Some((tag, Reader::new(contents)))
+    }
+
+    /// Reads the next TLV, which must have `tag`.
+    pub(crate) fn expect(&mut self, tag: u8) -> Option<Reader<'a>> {
+        self.any()
+            .and_then(|(found, contents)| (found == tag).then_some(contents))
+    }
+
+    pub(crate) fn integer(&mut self) -> Option<i64> {
+        SnmpBer::decode_integer(self.expect(TAG_INTEGER)?.data)
+    }
+
+    pub(crate) fn oid(&mut self) -> Option<Oid> {
+        let contents = self.expect(TAG_OID)?.data;
+        let mut arcs = Vec::new();
+        let mut arc: u32 = 0;
+        for &byte in contents {
+            arc = arc.checked_mul(128)? | (byte & 0x7F) as u32;
+            if byte & 0x80 == 0 {
+                if arcs.is_empty() {
+                    let first = (arc / 40).min(2);
+                    arcs.push(first);
+                    arcs.push(arc - first * 40);
+                } else {
+                    arcs.push(arc);
+                }
+                arc = 0;
+            }
+        }
+        (!arcs.is_empty()).then_some(arcs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn oids_use_base_128_arcs() {
+        let oid = vec![1, 3, 6, 1, 4, 1, 8072, 9999];
+        let encoded = SnmpBer::encode_oid(&oid);
+        assert_eq!(encoded, [0x2B, 6, 1, 4, 1, 0xBF, 0x08, 0xCE, 0x0F]);
+
+        let mut tlv = Vec::new();
+        SnmpBer::encode_tlv(TAG_OID, &encoded, &mut tlv);
+        assert_eq!(Reader::new(&tlv).oid(), Some(oid));
+        assert_eq!(
+            SnmpBer::parse_oid(".1.3.6.1.4.1.8072.9999"),
+            Some(vec![1, 3, 6, 1, 4, 1, 8072, 9999])
+        );
+        assert_eq!(SnmpBer::parse_oid("3.1"), None);
+        assert_eq!(SnmpBer::parse_oid("1.3.x"), None);
+    }
+
+    #[test]
+    fn integers_are_minimal_twos_complement() {
+        assert_eq!(SnmpBer::encode_integer(0), [0x00]);
+        assert_eq!(SnmpBer::encode_integer(128), [0x00, 0x80]);
+        assert_eq!(SnmpBer::encode_integer(-55), [0xC9]);
+        assert_eq!(SnmpBer::encode_integer(-129), [0xFF, 0x7F]);
+        assert_eq!(
+            SnmpBer::encode_unsigned(u32::MAX),
+            [0x00, 0xFF, 0xFF, 0xFF, 0xFF]
+        );
+
+        for value in [0, 127, 128, -1, -128, -129, i64::MAX, i64::MIN] {
+            let encoded = SnmpBer::encode_integer(value);
+            assert_eq!(SnmpBer::decode_integer(&encoded), Some(value));
+        }
+        assert_eq!(SnmpBer::decode_integer(&[]), None);
+        assert_eq!(SnmpBer::decode_integer(&[0; 9]), None);
+    }
+
+    #[test]
+    fn lengths_above_127_use_the_long_form() {
+        let contents = vec![0x41; 300];
+        let mut tlv = Vec::new();
+        SnmpBer::encode_tlv(TAG_OCTET_STRING, &contents, &mut tlv);
+        assert_eq!(tlv[..4], [TAG_OCTET_STRING, 0x82, 0x01, 0x2C]);
+
+        let mut reader = Reader::new(&tlv);
+        assert_eq!(reader.expect(TAG_OCTET_STRING).unwrap().data, contents);
+        assert!(reader.is_empty());
+    }
+
+    #[test]
+    fn truncated_or_unexpected_tlvs_are_rejected() {
+        let mut tlv = Vec::new();
+        SnmpBer::encode_tlv(TAG_INTEGER, &[0x01, 0x02], &mut tlv);
+        assert!(Reader::new(&tlv[..3]).any().is_none());
+        assert!(Reader::new(&tlv).expect(TAG_OID).is_none());
+        assert_eq!(Reader::new(&tlv).integer(), Some(0x0102));
+    }
+}
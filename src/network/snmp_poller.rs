@@ -0,0 +1,352 @@
+#![cfg(unix)]
+
+//! SNMP Poller
+//!
+//! This module lets one agent cover LAN devices that cannot run the client (switches,
+//! UPSes, NAS boxes): every cycle it sends one SNMP v1/v2c `get` per configured device for
+//! a handful of numeric OIDs (temperatures, PoE power draw, ...) and attaches the results
+//! to the report as proxied readings. Devices are polled in parallel so one unreachable
+//! device delays the cycle by at most its timeout.
+
+use log::{debug, warn};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::io;
+use std::net::{ToSocketAddrs, UdpSocket};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::config::config_instance::Config;
+use crate::data::models::{ProxiedDevice, ProxiedReading};
+use crate::data::parse::parse_number;
+use crate::network::snmp_ber::{
+    Oid, Reader, SnmpBer, PDU_GET, PDU_RESPONSE, TAG_COUNTER32, TAG_COUNTER64, TAG_GAUGE32,
+    TAG_INTEGER, TAG_NULL, TAG_OCTET_STRING, TAG_OID, TAG_SEQUENCE, TAG_TIMETICKS, VERSION_1,
+    VERSION_2C,
+};
+
+/// Largest response accepted.
+const MAX_MESSAGE_BYTES: usize = 65_507;
+
+/// A device polled over SNMP, one `[[snmp_targets]]` entry.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SnmpTargetConfig {
+    /// Name the device is reported under, e.g. `core-switch`.
+    pub name: String,
+    /// Agent address (`host` or `host:port`, port 161 by default).
+    pub address: String,
+    /// Read community.
+    #[serde(default = "default_community")]
+    pub community: String,
+    /// SNMP version, `"1"` or `"2c"`.
+    #[serde(default)]
+    pub version: SnmpVersion,
+    /// Seconds to wait for the response.
+    #[serde(default = "default_timeout_secs")]
+    pub timeout_secs: u64,
+    /// Variables to read.
+    pub oids: Vec<SnmpOidConfig>,
+    /// Tags of the device's entry in grouped reports (`group_hosts`).
+    #[serde(default)]
+    pub tags: BTreeMap<String, String>,
+}
+
+/// Protocol version used to poll a device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum SnmpVersion {
+    #[serde(rename = "1", alias = "v1")]
+    V1,
+    #[default]
+    #[serde(rename = "2c", alias = "v2c")]
+    V2c,
+}
+
+impl SnmpVersion {
+    /// Value of the version field of a message.
+    fn number(self) -> i64 {
+        match self {
+            SnmpVersion::V1 => VERSION_1,
+            SnmpVersion::V2c => VERSION_2C,
+        }
+    }
+}
+
+/// A variable read from a polled device.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SnmpOidConfig {
+    /// Name of the reading, e.g. `temperature` or `poe_power`.
+    pub name: String,
+    /// Dotted OID of a scalar instance, e.g. `1.3.6.1.2.1.33.1.2.7.0`.
+    pub oid: String,
+    /// Factor applied to the raw value, e.g. `0.1` for tenths of a degree.
+    #[serde(default = "default_scale")]
+    pub scale: f64,
+    /// Unit of the scaled value, e.g. `celsius` or `watts`.
+    pub unit: Option<String>,
+}
+
+fn default_community() -> String {
+    "public".to_string()
+}
+
+fn default_timeout_secs() -> u64 {
+    2
+}
+
+fn default_scale() -> f64 {
+    1.0
+}
+
+/// A utility class for polling adjacent devices over SNMP.
+pub struct SnmpPoller;
+
+impl SnmpPoller {
+    /// Polls every configured device in parallel.
+    ///
+    /// Returns `None` when no `[[snmp_targets]]` are configured.
+    pub fn collect_if_configured() -> Option<Vec<ProxiedDevice>> {
+        let targets = &Config::get().snmp_targets;
+        if targets.is_empty() {
+            return None;
+        }
+        let devices = thread::scope(|scope| {
+            let handles: Vec<_> = targets
+                .iter()
+                .map(|target| scope.spawn(move || Self::poll(target)))
+                .collect();
+            handles
+                .into_iter()
+                .zip(targets)
+                .map(|(handle, target)| handle.join().unwrap_or_else(|_| Self::unreachable(target)))
+                .collect()
+        });
+        Some(devices)
+    }
+
+    fn poll(target: &SnmpTargetConfig) -> ProxiedDevice {
+        match Self::get(target) {
+            Ok(values) => ProxiedDevice {
+                name: target.name.clone(),
+                address: target.address.clone(),
+                reachable: true,
+                readings: target
+                    .oids
+                    .iter()
+                    .zip(values)
+                    .map(|(oid, value)| ProxiedReading {
+                        name: oid.name.clone(),
+                        oid: oid.oid.clone(),
+                        value: value.map(|value| value * oid.scale),
+                        unit: oid.unit.clone(),
+                    })
+                    .collect(),
+            },
+            Err(e) => {
+                warn!(
+                    "SNMP poll of {} ({}) failed: {}",
+                    target.name, target.address, e
+                );
+                Self::unreachable(target)
+            }
+        }
+    }
+
+    fn unreachable(target: &SnmpTargetConfig) -> ProxiedDevice {
+        ProxiedDevice {
+            name: target.name.clone(),
+            address: target.address.clone(),
+            reachable: false,
+            readings: Vec::new(),
+        }
+    }
+
+    /// Reads all OIDs of `target` with one `get`, returning a value per OID in order
+    /// (`None` for missing or non-numeric variables).
+    fn get(target: &SnmpTargetConfig) -> io::Result<Vec<Option<f64>>> {
+        let oids: Vec<Oid> = target
+            .oids
+            .iter()
+            .map(|oid| {
+                SnmpBer::parse_oid(&oid.oid).ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("invalid OID {}", oid.oid),
+                    )
+                })
+            })
+            .collect::<io::Result<_>>()?;
+        let address = target
+            .address
+            .to_socket_addrs()
+            .or_else(|_| (target.address.trim_matches(['[', ']']), 161).to_socket_addrs())?
+            .next()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "address does not resolve"))?;
+
+        let socket = UdpSocket::bind(if address.is_ipv4() {
+            "0.0.0.0:0"
+        } else {
+            "[::]:0"
+        })?;
+        socket.set_read_timeout(Some(Duration::from_secs(target.timeout_secs.max(1))))?;
+        socket.connect(address)?;
+        let request_id = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.subsec_nanos() as i64)
+            .unwrap_or_default();
+        socket.send(&Self::encode_get(
+            target.version.number(),
+            &target.community,
+            request_id,
+            &oids,
+        ))?;
+
+        let mut buffer = vec![0; MAX_MESSAGE_BYTES];
+        loop {
+            let len = socket.recv(&mut buffer)?;
+            match Self::decode_response(&buffer[..len], request_id, &oids) {
+                Some(values) => return values,
+                None => debug!("Ignoring unrelated SNMP datagram from {}.", address),
+            }
+        }
+    }
+
+    fn encode_get(version: i64, community: &str, request_id: i64, oids: &[Oid]) -> Vec<u8> {
+        let mut varbinds = Vec::new();
+        for oid in oids {
+            let mut varbind = Vec::new();
+            SnmpBer::encode_tlv(TAG_OID, &SnmpBer::encode_oid(oid), &mut varbind);
+            SnmpBer::encode_tlv(TAG_NULL, &[], &mut varbind);
+            SnmpBer::encode_tlv(TAG_SEQUENCE, &varbind, &mut varbinds);
+        }
+        let mut pdu = Vec::new();
+        SnmpBer::encode_tlv(TAG_INTEGER, &SnmpBer::encode_integer(request_id), &mut pdu);
+        SnmpBer::encode_tlv(TAG_INTEGER, &SnmpBer::encode_integer(0), &mut pdu);
+        SnmpBer::encode_tlv(TAG_INTEGER, &SnmpBer::encode_integer(0), &mut pdu);
+        SnmpBer::encode_tlv(TAG_SEQUENCE, &varbinds, &mut pdu);
+        let mut message = Vec::new();
+        SnmpBer::encode_tlv(TAG_INTEGER, &SnmpBer::encode_integer(version), &mut message);
+        SnmpBer::encode_tlv(TAG_OCTET_STRING, community.as_bytes(), &mut message);
+        SnmpBer::encode_tlv(PDU_GET, &pdu, &mut message);
+        let mut out = Vec::new();
+        SnmpBer::encode_tlv(TAG_SEQUENCE, &message, &mut out);
+        out
+    }
+
+    /// Decodes the response to request `request_id`; `None` if the datagram is not it.
+    ///
+    /// A v1 agent answers `noSuchName` for the whole request if one OID is missing, which
+    /// is reported as an error naming the offending variable.
+    fn decode_response(
+        response: &[u8],
+        request_id: i64,
+        oids: &[Oid],
+    ) -> Option<io::Result<Vec<Option<f64>>>> {
+        let mut message = Reader::new(response).expect(TAG_SEQUENCE)?;
+        message.integer()?;
+        message.expect(TAG_OCTET_STRING)?;
+        let mut pdu = message.expect(PDU_RESPONSE)?;
+        if pdu.integer()? != request_id {
+            return None;
+        }
+        let status = pdu.integer()?;
+        let index = pdu.integer()?;
+        if status != 0 {
+            return Some(Err(io::Error::other(format!(
+                "agent returned error status {} for variable {}",
+                status, index
+            ))));
+        }
+
+        let mut values = vec![None; oids.len()];
+        let mut varbinds = pdu.expect(TAG_SEQUENCE)?;
+        while !varbinds.is_empty() {
+            let mut varbind = varbinds.expect(TAG_SEQUENCE)?;
+            let oid = varbind.oid()?;
+            let (tag, contents) = varbind.any()?;
+            if let Some(slot) = oids.iter().position(|wanted| *wanted == oid) {
+                values[slot] = Self::numeric_value(tag, contents.data);
+            }
+        }
+        Some(Ok(values))
+    }
+
+    /// Interprets a variable as a number: integers, counters, gauges and time ticks, or an
+    /// octet string holding a decimal number (as some NAS MIBs report temperatures).
+    fn numeric_value(tag: u8, contents: &[u8]) -> Option<f64> {
+        match tag {
+            TAG_INTEGER => SnmpBer::decode_integer(contents).map(|value| value as f64),
+            TAG_COUNTER32 | TAG_GAUGE32 | TAG_TIMETICKS | TAG_COUNTER64 => {
+                if contents.is_empty() || contents.len() > 9 {
+                    return None;
+                }
+                let value = contents
+                    .iter()
+                    .fold(0u128, |value, &byte| (value << 8) | byte as u128);
+                Some(value as f64)
+            }
+            TAG_OCTET_STRING => parse_number(std::str::from_utf8(contents).ok()?),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_are_parsed_from_the_configuration() {
+        let target = |version: &str| {
+            toml::from_str::<SnmpTargetConfig>(&format!(
+                "name = \"ups\"\naddress = \"192.0.2.1\"\noids = []\n{}",
+                version
+            ))
+            .map(|target| target.version)
+        };
+        assert_eq!(target("").unwrap(), SnmpVersion::V2c);
+        assert_eq!(target("version = \"1\"").unwrap(), SnmpVersion::V1);
+        assert_eq!(target("version = \"v2c\"").unwrap(), SnmpVersion::V2c);
+        assert!(target("version = \"3\"").is_err());
+    }
+
+    #[test]
+    fn decodes_numeric_varbinds_in_request_order() {
+        let temperature = vec![1, 3, 6, 1, 2, 1, 33, 1, 2, 7, 0];
+        let power = vec![1, 3, 6, 1, 2, 1, 105, 1, 3, 1, 1, 4, 1];
+        let missing = vec![1, 3, 6, 1, 4, 1, 9, 9, 9, 0];
+
+        let mut varbinds = Vec::new();
+        for (oid, tag, value) in [
+            (&power, TAG_GAUGE32, SnmpBer::encode_integer(185)),
+            (&temperature, TAG_OCTET_STRING, b"41.5".to_vec()),
+            (&missing, 0x81, Vec::new()),
+        ] {
+            let mut varbind = Vec::new();
+            SnmpBer::encode_tlv(TAG_OID, &SnmpBer::encode_oid(oid), &mut varbind);
+            SnmpBer::encode_tlv(tag, &value, &mut varbind);
+            SnmpBer::encode_tlv(TAG_SEQUENCE, &varbind, &mut varbinds);
+        }
+        let mut pdu = Vec::new();
+        for value in [77, 0, 0] {
+            SnmpBer::encode_tlv(TAG_INTEGER, &SnmpBer::encode_integer(value), &mut pdu);
+        }
+        SnmpBer::encode_tlv(TAG_SEQUENCE, &varbinds, &mut pdu);
+        let mut message = Vec::new();
+        SnmpBer::encode_tlv(
+            TAG_INTEGER,
+            &SnmpBer::encode_integer(VERSION_2C),
+            &mut message,
+        );
+        SnmpBer::encode_tlv(TAG_OCTET_STRING, b"public", &mut message);
+        SnmpBer::encode_tlv(PDU_RESPONSE, &pdu, &mut message);
+        let mut response = Vec::new();
+        SnmpBer::encode_tlv(TAG_SEQUENCE, &message, &mut response);
+
+        let oids = [temperature, power, missing];
+        assert!(SnmpPoller::decode_response(&response, 78, &oids).is_none());
+        let values = SnmpPoller::decode_response(&response, 77, &oids)
+            .unwrap()
+            .unwrap();
+        assert_eq!(values, [Some(41.5), Some(185.0), None]);
+    }
+}
@@ -0,0 +1,207 @@
+//! Outgoing Socket Setup
+//!
+//! Hosts with a dedicated management NIC need report traffic to leave through it rather
+//! than along the default route. With `bind_interface` set, sockets to the server are bound
+//! to that interface (`SO_BINDTODEVICE`, which needs `CAP_NET_RAW` on older kernels) and
+//! use its address as their source; `bind_address` pins the source address explicitly.
+//! `ip_preference` picks which address family is tried first when the server name resolves
+//! to both; TCP connections fall back to the remaining addresses in turn.
+
+use get_if_addrs::get_if_addrs;
+use log::{debug, warn};
+use serde::Deserialize;
+use socket2::{Domain, Protocol, Socket, Type};
+use std::io;
+use std::net::{IpAddr, SocketAddr, TcpStream, ToSocketAddrs, UdpSocket};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use crate::config::config_instance::Config;
+
+/// Whether failing to bind to `bind_interface` has already been reported.
+static BIND_DEVICE_WARNED: AtomicBool = AtomicBool::new(false);
+
+/// Address family used when a name resolves to both IPv4 and IPv6 addresses.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IpPreference {
+    /// The resolver's order.
+    #[default]
+    Any,
+    /// IPv4 first, IPv6 as a fallback.
+    Ipv4,
+    /// IPv6 first, IPv4 as a fallback.
+    Ipv6,
+}
+
+/// A utility class for opening sockets to the server.
+pub struct SocketUtil;
+
+impl SocketUtil {
+    /// Resolves `target` (`host:port`) to the addresses to try in turn, honoring
+    /// `ip_preference` and, when set, the family of `bind_address`. The list is never
+    /// empty.
+    pub fn resolve(target: &str) -> io::Result<Vec<SocketAddr>> {
+        let config = Config::get();
+        let addresses = Self::order(
+            target.to_socket_addrs()?.collect(),
+            config.bind_address,
+            config.ip_preference,
+        );
+        if addresses.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No usable address for {}", target),
+            ));
+        }
+        Ok(addresses)
+    }
+
+    /// Drops the `addresses` of the other family than `source`, if set, and moves those of
+    /// the `preference` to the front, keeping the resolver's order otherwise.
+    fn order(
+        mut addresses: Vec<SocketAddr>,
+        source: Option<IpAddr>,
+        preference: IpPreference,
+    ) -> Vec<SocketAddr> {
+        if let Some(source) = source {
+            addresses.retain(|address| address.is_ipv4() == source.is_ipv4());
+        }
+        match preference {
+            IpPreference::Any => {}
+            IpPreference::Ipv4 => addresses.sort_by_key(|address| !address.is_ipv4()),
+            IpPreference::Ipv6 => addresses.sort_by_key(|address| !address.is_ipv6()),
+        }
+        addresses
+    }
+
+    /// Opens a TCP connection from the configured source to the first of `addresses` that
+    /// accepts it, and returns it with the address it is connected to.
+    pub fn connect_tcp(
+        addresses: &[SocketAddr],
+        timeout: Duration,
+    ) -> io::Result<(TcpStream, SocketAddr)> {
+        let mut last_error = None;
+        for &address in addresses {
+            match Self::connect_tcp_to(address, timeout) {
+                Ok(stream) => return Ok((stream, address)),
+                Err(e) => {
+                    debug!("Cannot connect to {}: {}", address, e);
+                    last_error = Some(io::Error::new(e.kind(), format!("{}: {}", address, e)));
+                }
+            }
+        }
+        Err(last_error
+            .unwrap_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No address to connect to")))
+    }
+
+    fn connect_tcp_to(address: SocketAddr, timeout: Duration) -> io::Result<TcpStream> {
+        let socket = Socket::new(
+            Domain::for_address(address),
+            Type::STREAM,
+            Some(Protocol::TCP),
+        )?;
+        Self::bind(&socket, address)?;
+        socket.connect_timeout(&address.into(), timeout)?;
+        Ok(socket.into())
+    }
+
+    /// Opens a UDP socket for sending to `address` from the configured source.
+    pub fn udp_socket(address: SocketAddr) -> io::Result<UdpSocket> {
+        let socket = Socket::new(
+            Domain::for_address(address),
+            Type::DGRAM,
+            Some(Protocol::UDP),
+        )?;
+        Self::bind(&socket, address)?;
+        Ok(socket.into())
+    }
+
+    /// The configured source address, or the address of `bind_interface` in the preferred
+    /// family; `None` to let the kernel choose.
+    pub fn source_address() -> Option<IpAddr> {
+        let config = Config::get();
+        config
+            .bind_address
+            .or_else(|| Self::interface_address(config.ip_preference != IpPreference::Ipv6))
+    }
+
+    fn bind(socket: &Socket, peer: SocketAddr) -> io::Result<()> {
+        let config = Config::get();
+        #[cfg(target_os = "linux")]
+        if let Some(interface) = &config.bind_interface {
+            if let Err(e) = socket.bind_device(Some(interface.as_bytes())) {
+                if !BIND_DEVICE_WARNED.swap(true, Ordering::Relaxed) {
+                    warn!(
+                        "Cannot bind to interface {} ({}); using its address as the source only.",
+                        interface, e
+                    );
+                }
+            }
+        }
+        #[cfg(not(target_os = "linux"))]
+        if config.bind_interface.is_some() && !BIND_DEVICE_WARNED.swap(true, Ordering::Relaxed) {
+            warn!("bind_interface is only enforced on Linux; using its address as the source.");
+        }
+
+        let source = config
+            .bind_address
+            .or_else(|| Self::interface_address(peer.is_ipv4()));
+        match source {
+            Some(ip) => socket.bind(&SocketAddr::new(ip, 0).into()),
+            None => Ok(()),
+        }
+    }
+
+    /// The first address of `bind_interface` in the requested family.
+    fn interface_address(ipv4: bool) -> Option<IpAddr> {
+        let interface = Config::get().bind_interface.as_ref()?;
+        get_if_addrs()
+            .ok()?
+            .into_iter()
+            .filter(|candidate| &candidate.name == interface)
+            .map(|candidate| candidate.ip())
+            .find(|ip| ip.is_ipv4() == ipv4)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addresses(addresses: &[&str]) -> Vec<SocketAddr> {
+        addresses
+            .iter()
+            .map(|address| address.parse().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn orders_addresses_by_preference() {
+        let resolved = addresses(&[
+            "[2001:db8::1]:5000",
+            "192.0.2.1:5000",
+            "[2001:db8::2]:5000",
+            "192.0.2.2:5000",
+        ]);
+        assert_eq!(
+            SocketUtil::order(resolved.clone(), None, IpPreference::Any),
+            resolved
+        );
+        assert_eq!(
+            SocketUtil::order(resolved.clone(), None, IpPreference::Ipv4),
+            addresses(&[
+                "192.0.2.1:5000",
+                "192.0.2.2:5000",
+                "[2001:db8::1]:5000",
+                "[2001:db8::2]:5000",
+            ])
+        );
+        // A source address limits the candidates to its family.
+        let source = "2001:db8::10".parse().ok();
+        assert_eq!(
+            SocketUtil::order(resolved, source, IpPreference::Ipv4),
+            addresses(&["[2001:db8::1]:5000", "[2001:db8::2]:5000"])
+        );
+    }
+}
@@ -0,0 +1,383 @@
+#![cfg(unix)]
+
+//! Disk Spool
+//!
+//! When a payload cannot be delivered after all retries, it is written to a bounded spool
+//! directory instead of being lost. Before each later send, spooled payloads are replayed
+//! oldest first; as long as some remain, new payloads are spooled behind them so the
+//! server receives everything in collection order once connectivity returns. Spool files
+//! hold the payload exactly as it would have been sent and are named
+//! `<unix_millis>-<counter>-<sequence>-<kind>.<format>` (the counter orders payloads
+//! spooled within the same millisecond, the sequence is the one sent to the server), so
+//! JSON files can also be resent with the `replay` subcommand. Payloads the server rejects
+//! for good (a 4xx status other than 408 and 429) are moved to the `rejected` subdirectory
+//! instead of blocking the spool.
+
+use log::{debug, error, info, warn};
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::config_instance::Config;
+use crate::network::ack_util::AckUtil;
+use crate::network::http_client::StatusError;
+use crate::network::network_util::{EncodedPayload, NetworkUtil};
+
+/// Default spool directory name inside the state directory.
+const SPOOL_DIR_NAME: &str = "spool";
+
+/// Subdirectory of the spool holding payloads the server rejected.
+const REJECTED_DIR_NAME: &str = "rejected";
+
+/// Sequence number distinguishing payloads spooled within the same millisecond.
+static SEQUENCE: AtomicU64 = AtomicU64::new(0);
+
+/// Configuration of the `[spool]` section. When present, undeliverable payloads are kept
+/// on disk and replayed later.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SpoolConfig {
+    /// Spool directory; defaults to `spool` in the state directory.
+    pub dir: Option<String>,
+    /// Maximum number of spooled payloads; the oldest are dropped beyond it.
+    pub max_files: usize,
+    /// Maximum total size of spooled payloads in bytes; the oldest are dropped beyond it.
+    pub max_bytes: u64,
+    /// Maximum number of payloads replayed before one new send, so a long backlog does
+    /// not stall collection.
+    pub replay_per_cycle: usize,
+}
+
+impl Default for SpoolConfig {
+    fn default() -> Self {
+        Self {
+            dir: None,
+            max_files: 10_000,
+            max_bytes: 64 * 1024 * 1024,
+            replay_per_cycle: 500,
+        }
+    }
+}
+
+/// A utility class for spooling and replaying undeliverable payloads.
+pub struct SpoolUtil;
+
+impl SpoolUtil {
+    /// Writes an undeliverable payload to the spool, dropping the oldest payloads if the
+    /// spool exceeds its bounds. Does nothing when spooling is disabled.
+    pub fn store(payload: &EncodedPayload) {
+        let Some(config) = &Self::config() else {
+            return;
+        };
+        let dir = Self::dir(config);
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let name = format!(
+            "{}-{:06}-{}-{}.{}",
+            now,
+            SEQUENCE.fetch_add(1, Ordering::Relaxed) % 1_000_000,
+            payload.sequence,
+            payload.kind,
+            Self::extension(payload)
+        );
+        let result = fs::create_dir_all(&dir).and_then(|_| {
+            // Written under a temporary name so a crash never leaves a partial payload.
+            let partial = dir.join(format!(".{}", name));
+            fs::write(&partial, &payload.body)?;
+            fs::rename(&partial, dir.join(&name))
+        });
+        match result {
+            Ok(()) => info!("Spooled undelivered {} payload as {}.", payload.kind, name),
+            Err(e) => {
+                error!("Failed to spool payload to {}: {}", dir.display(), e);
+                return;
+            }
+        }
+        Self::enforce_bounds(config, &dir);
+    }
+
+    /// Replays spooled payloads to `server`, oldest first, stopping at the first failure.
+    ///
+    /// Returns whether the spool is now empty (always `true` when spooling is disabled),
+    /// i.e. whether new payloads may be sent directly without overtaking older ones.
+    pub fn drain(server: &str) -> bool {
+        let Some(config) = &Self::config() else {
+            return true;
+        };
+        Self::replay(config, &Self::dir(config), |payload| {
+            NetworkUtil::deliver(payload, server)
+        })
+    }
+
+    /// Replays the payloads spooled in `dir` with `deliver` (see `drain`).
+    fn replay(
+        config: &SpoolConfig,
+        dir: &Path,
+        mut deliver: impl FnMut(&EncodedPayload) -> io::Result<()>,
+    ) -> bool {
+        let files = Self::spooled_files(dir);
+        if files.is_empty() {
+            return true;
+        }
+
+        let mut replayed = 0;
+        for path in files.iter().take(config.replay_per_cycle.max(1)) {
+            let Some(payload) = Self::read(path) else {
+                warn!("Dropping unreadable spool file {}.", path.display());
+                let _ = fs::remove_file(path);
+                continue;
+            };
+            match deliver(&payload) {
+                Ok(()) => {
+                    let _ = fs::remove_file(path);
+                    replayed += 1;
+                }
+                Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+                    warn!("Dropping spooled payload {}: {}", path.display(), e);
+                    let _ = fs::remove_file(path);
+                }
+                Err(e) if StatusError::of(&e).is_some_and(StatusError::is_permanent) => {
+                    warn!("Server rejected spooled payload {}: {}", path.display(), e);
+                    Self::reject(config, dir, path);
+                }
+                Err(e) => {
+                    debug!("Replay of {} failed: {}", path.display(), e);
+                    break;
+                }
+            }
+        }
+
+        let remaining = Self::spooled_files(dir).len();
+        if replayed > 0 {
+            info!(
+                "Replayed {} spooled payload(s); {} remaining.",
+                replayed, remaining
+            );
+        }
+        remaining == 0
+    }
+
+    /// Moves a rejected payload out of the spool into its `rejected` subdirectory, which is
+    /// bounded like the spool itself.
+    fn reject(config: &SpoolConfig, dir: &Path, path: &Path) {
+        let rejected = dir.join(REJECTED_DIR_NAME);
+        let result = fs::create_dir_all(&rejected)
+            .and_then(|_| fs::rename(path, rejected.join(path.file_name().unwrap_or_default())));
+        if let Err(e) = result {
+            error!(
+                "Failed to move {} aside: {}; dropping it.",
+                path.display(),
+                e
+            );
+            let _ = fs::remove_file(path);
+            return;
+        }
+        Self::enforce_bounds(config, &rejected);
+    }
+
+    /// Returns the number of payloads waiting in the spool (0 when spooling is disabled).
+    pub fn depth() -> usize {
+        Self::config()
+            .map(|config| Self::spooled_files(&Self::dir(&config)).len())
+            .unwrap_or_default()
+    }
+
+    /// The spool settings in effect: the `[spool]` section, or its defaults when only
+    /// `send_windows` are configured, since payloads must be held between windows.
+    fn config() -> Option<SpoolConfig> {
+        let config = Config::get();
+        config
+            .spool
+            .clone()
+            .or_else(|| (!config.send_windows.is_empty()).then(SpoolConfig::default))
+    }
+
+    fn dir(config: &SpoolConfig) -> PathBuf {
+        config
+            .dir
+            .as_ref()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| Config::state_file(SPOOL_DIR_NAME))
+    }
+
+    /// Completely written spool files, oldest first.
+    fn spooled_files(dir: &Path) -> Vec<PathBuf> {
+        let Ok(entries) = fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut files: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.is_file()
+                    && path
+                        .file_name()
+                        .is_some_and(|name| !name.to_string_lossy().starts_with('.'))
+            })
+            .collect();
+        files.sort_by(|a, b| a.file_name().cmp(&b.file_name()));
+        files
+    }
+
+    fn enforce_bounds(config: &SpoolConfig, dir: &Path) {
+        let files = Self::spooled_files(dir);
+        let mut total: u64 = files
+            .iter()
+            .filter_map(|path| fs::metadata(path).ok())
+            .map(|metadata| metadata.len())
+            .sum();
+        let mut count = files.len();
+        for path in &files {
+            if count <= config.max_files && total <= config.max_bytes {
+                break;
+            }
+            let size = fs::metadata(path).map(|m| m.len()).unwrap_or_default();
+            if fs::remove_file(path).is_ok() {
+                warn!("Spool full; dropped oldest payload {}.", path.display());
+                count -= 1;
+                total = total.saturating_sub(size);
+            }
+        }
+    }
+
+    fn extension(payload: &EncodedPayload) -> &'static str {
+        if payload.grpc {
+            return "grpc";
+        }
+        match payload.content_type.as_str() {
+            "application/msgpack" => "msgpack",
+            "application/cbor" => "cbor",
+            "application/x-protobuf" => "pb",
+            _ => "json",
+        }
+    }
+
+    /// Reads a spool file back into the payload it was written from.
+    fn read(path: &Path) -> Option<EncodedPayload> {
+        let name = path.file_stem()?.to_string_lossy().into_owned();
+        // `<millis>-<counter>-<sequence>-<kind>`; files spooled before payloads were
+        // numbered lack the sequence and get a new one.
+        let parts: Vec<&str> = name.splitn(4, '-').collect();
+        let (sequence, kind) = match parts.as_slice() {
+            [_, _, sequence, kind] if sequence.parse::<u64>().is_ok() => {
+                (sequence.parse().ok()?, kind.to_string())
+            }
+            [_, _, kind] => (AckUtil::next_sequence(), kind.to_string()),
+            _ => return None,
+        };
+        let extension = path.extension()?.to_string_lossy().into_owned();
+        let content_type = match extension.as_str() {
+            "grpc" => "application/grpc",
+            "msgpack" => "application/msgpack",
+            "cbor" => "application/cbor",
+            "pb" => "application/x-protobuf",
+            "json" => "application/json",
+            _ => return None,
+        };
+        Some(EncodedPayload {
+            kind,
+            content_type: content_type.to_string(),
+            body: fs::read(path).ok()?,
+            grpc: extension == "grpc",
+            sequence,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    fn status(status_code: u16) -> io::Error {
+        io::Error::other(StatusError {
+            status_code,
+            reason: String::new(),
+            excerpt: String::new(),
+        })
+    }
+
+    fn spool(name: &str, files: &[&str]) -> TempDir {
+        let dir = TempDir::new(&format!("spool-{}", name));
+        for file in files {
+            fs::write(dir.join(file), file).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn reads_payloads_back_from_file_names() {
+        let dir = spool("read", &["1700000000000-000001-42-sensors_batch.cbor"]);
+        let payload = SpoolUtil::read(&dir.join("1700000000000-000001-42-sensors_batch.cbor"));
+
+        let payload = payload.unwrap();
+        assert_eq!(payload.sequence, 42);
+        assert_eq!(payload.kind, "sensors_batch");
+        assert_eq!(payload.content_type, "application/cbor");
+        assert!(!payload.grpc);
+    }
+
+    #[test]
+    fn rejected_payloads_are_moved_aside() {
+        let files = [
+            "1-000001-1-sensors.json",
+            "2-000002-2-gap.json",
+            "3-000003-3-sensors.json",
+        ];
+        let dir = spool("reject", &files);
+        let mut attempts = Vec::new();
+        let drained = SpoolUtil::replay(&SpoolConfig::default(), &dir, |payload| {
+            attempts.push(payload.sequence);
+            match payload.sequence {
+                2 => Err(status(400)),
+                _ => Ok(()),
+            }
+        });
+        let rejected = SpoolUtil::spooled_files(&dir.join(REJECTED_DIR_NAME));
+
+        assert!(drained);
+        assert_eq!(attempts, [1, 2, 3]);
+        assert_eq!(rejected.len(), 1);
+        assert!(rejected[0].ends_with("2-000002-2-gap.json"));
+    }
+
+    #[test]
+    fn transient_failures_keep_the_spool_in_order() {
+        for code in [408, 429, 503] {
+            let dir = spool(
+                "transient",
+                &["1-000001-1-sensors.json", "2-000002-2-sensors.json"],
+            );
+            let mut attempts = 0;
+            let drained = SpoolUtil::replay(&SpoolConfig::default(), &dir, |_| {
+                attempts += 1;
+                Err(status(code))
+            });
+            let remaining = SpoolUtil::spooled_files(&dir).len();
+
+            assert!(!drained);
+            assert_eq!((attempts, remaining), (1, 2), "status {}", code);
+        }
+    }
+
+    #[test]
+    fn bounds_drop_the_oldest_payloads() {
+        let dir = spool("bounds", &["1-a.json", "2-b.json", "3-c.json"]);
+        let config = SpoolConfig {
+            max_files: 2,
+            ..SpoolConfig::default()
+        };
+        SpoolUtil::enforce_bounds(&config, &dir);
+        let remaining: Vec<_> = SpoolUtil::spooled_files(&dir)
+            .iter()
+            .map(|path| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+
+        assert_eq!(remaining, ["2-b.json", "3-c.json"]);
+    }
+}
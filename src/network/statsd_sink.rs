@@ -0,0 +1,298 @@
+#![cfg(unix)]
+
+//! StatsD Sink
+//!
+//! This module emits temperatures and utilization as StatsD gauges over UDP, in addition to
+//! the regular reports, for Datadog agents and other StatsD-compatible collectors. Without
+//! tags, the host name and labels become part of the metric name
+//! (`gilded_sentinel.<host>.cpu_package.0.temperature`); with `tags = true` they are sent
+//! as DogStatsD tags (`gilded_sentinel.cpu_package.temperature:48|g|#host:<host>,package:0`).
+
+use log::{debug, warn};
+use serde::Deserialize;
+use std::io;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::config_instance::Config;
+use crate::data::models::SensorData;
+use crate::network::socket_util::SocketUtil;
+
+/// Configuration of the `[statsd]` section; gauges are emitted when present.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct StatsdConfig {
+    /// StatsD server address (`host:port`).
+    pub target: String,
+    /// Prefix of every metric name.
+    pub prefix: String,
+    /// Minimum seconds between emissions; 0 emits with every report.
+    pub interval_secs: u64,
+    /// Whether the host name and labels are sent as DogStatsD tags.
+    pub tags: bool,
+    /// Largest datagram sent, in bytes; gauges are batched up to this size.
+    pub max_datagram_bytes: usize,
+}
+
+impl Default for StatsdConfig {
+    fn default() -> Self {
+        Self {
+            target: "127.0.0.1:8125".to_string(),
+            prefix: "gilded_sentinel".to_string(),
+            interval_secs: 0,
+            tags: false,
+            max_datagram_bytes: 1432,
+        }
+    }
+}
+
+/// Time of the last emission.
+static LAST_EMIT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// A gauge value with its labels, in emission order.
+struct Gauge {
+    name: &'static str,
+    labels: Vec<(&'static str, String)>,
+    value: f64,
+}
+
+/// Static utility class for emitting StatsD gauges.
+pub struct StatsdSink;
+
+impl StatsdSink {
+    /// Emits gauges for `data` if StatsD is configured and `interval_secs` have passed.
+    /// Failures are logged; StatsD delivery is best effort.
+    pub fn emit(data: &SensorData) {
+        let Some(config) = &Config::get().statsd else {
+            return;
+        };
+        {
+            let mut last = LAST_EMIT.lock().unwrap_or_else(|e| e.into_inner());
+            let interval = Duration::from_secs(config.interval_secs);
+            if last.is_some_and(|emitted| emitted.elapsed() < interval) {
+                return;
+            }
+            *last = Some(Instant::now());
+        }
+
+        if let Err(e) = Self::send(config, data) {
+            warn!("Failed to send StatsD gauges to {}: {}", config.target, e);
+        }
+    }
+
+    fn send(config: &StatsdConfig, data: &SensorData) -> io::Result<()> {
+        let host = &data.system_info.hostname;
+        let lines: Vec<String> = Self::gauges(data)
+            .iter()
+            .filter(|gauge| gauge.value.is_finite())
+            .map(|gauge| Self::render(config, host, gauge))
+            .collect();
+
+        let address = SocketUtil::resolve(&config.target)?[0];
+        let socket = SocketUtil::udp_socket(address)?;
+        socket.connect(address)?;
+
+        let mut datagram = String::new();
+        let mut datagrams = 0;
+        for line in &lines {
+            if !datagram.is_empty() && datagram.len() + 1 + line.len() > config.max_datagram_bytes {
+                socket.send(datagram.as_bytes())?;
+                datagrams += 1;
+                datagram.clear();
+            }
+            if !datagram.is_empty() {
+                datagram.push('\n');
+            }
+            datagram.push_str(line);
+        }
+        if !datagram.is_empty() {
+            socket.send(datagram.as_bytes())?;
+            datagrams += 1;
+        }
+        debug!(
+            "Sent {} StatsD gauge(s) to {} in {} datagram(s).",
+            lines.len(),
+            config.target,
+            datagrams
+        );
+        Ok(())
+    }
+
+    fn gauges(data: &SensorData) -> Vec<Gauge> {
+        let gauge = |name, labels, value| Gauge {
+            name,
+            labels,
+            value,
+        };
+        let mut gauges = Vec::new();
+
+        if let Some(average) = data.cpu_info.average_usage() {
+            gauges.push(gauge("cpu.usage_average", vec![], average.0 as f64));
+        }
+        for (index, usage) in data.cpu_info.usage_per_core.iter().enumerate() {
+            gauges.push(gauge(
+                "cpu.usage",
+                vec![("cpu", index.to_string())],
+                usage.0 as f64,
+            ));
+        }
+
+        for package in &data.cpu_packages {
+            gauges.push(gauge(
+                "cpu_package.temperature",
+                vec![("package", package.package_id.clone())],
+                package.package_temperature.0 as f64,
+            ));
+            for core in &package.cores {
+                gauges.push(gauge(
+                    "cpu_core.temperature",
+                    vec![
+                        ("package", package.package_id.clone()),
+                        ("core", core.core_name.clone()),
+                    ],
+                    core.temperature.0 as f64,
+                ));
+            }
+        }
+
+        let memory = &data.memory_info;
+        gauges.push(gauge("memory.used", vec![], memory.used.0 as f64));
+        gauges.push(gauge("memory.total", vec![], memory.total.0 as f64));
+
+        for disk in &data.disks {
+            gauges.push(gauge(
+                "disk.available_space",
+                vec![("disk", disk.name.clone())],
+                disk.available_space.0 as f64,
+            ));
+            gauges.push(gauge(
+                "disk.total_space",
+                vec![("disk", disk.name.clone())],
+                disk.total_space.0 as f64,
+            ));
+        }
+
+        for component in &data.components {
+            if let Some(temperature) = component.temperature {
+                gauges.push(gauge(
+                    "component.temperature",
+                    vec![("label", component.label.clone())],
+                    temperature.0 as f64,
+                ));
+            }
+        }
+
+        gauges
+    }
+
+    /// Renders one gauge as a StatsD line.
+    fn render(config: &StatsdConfig, host: &str, gauge: &Gauge) -> String {
+        let mut name = config.prefix.trim_end_matches('.').to_string();
+        if config.tags {
+            name.push('.');
+            name.push_str(gauge.name);
+            let tags: Vec<String> = std::iter::once(("host", host))
+                .chain(
+                    gauge
+                        .labels
+                        .iter()
+                        .map(|(key, value)| (*key, value.as_str())),
+                )
+                .map(|(key, value)| format!("{}:{}", key, Self::tag_value(value)))
+                .collect();
+            format!("{}:{}|g|#{}", name, gauge.value, tags.join(","))
+        } else {
+            // Labels go between the measurement and the field: cpu_core.<package>.<core>.temperature
+            let (measurement, field) = gauge.name.rsplit_once('.').unwrap_or(("", gauge.name));
+            name.push('.');
+            name.push_str(&Self::segment(host));
+            if !measurement.is_empty() {
+                name.push('.');
+                name.push_str(measurement);
+            }
+            for (_, value) in &gauge.labels {
+                name.push('.');
+                name.push_str(&Self::segment(value));
+            }
+            name.push('.');
+            name.push_str(field);
+            format!("{}:{}|g", name, gauge.value)
+        }
+    }
+
+    /// Reduces a label to a single metric name segment.
+    fn segment(value: &str) -> String {
+        value
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '_' || c == '-' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect()
+    }
+
+    /// Replaces characters with special meaning in DogStatsD tags.
+    fn tag_value(value: &str) -> String {
+        value
+            .chars()
+            .map(|c| match c {
+                ',' | '|' | '#' | '\n' => '_',
+                c if c.is_whitespace() => '_',
+                c => c,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::sample_sensor_data;
+
+    fn render_all(config: &StatsdConfig, host: &str) -> Vec<String> {
+        StatsdSink::gauges(&sample_sensor_data())
+            .iter()
+            .map(|gauge| StatsdSink::render(config, host, gauge))
+            .collect()
+    }
+
+    #[test]
+    fn labels_become_name_segments_without_tags() {
+        let lines = render_all(&StatsdConfig::default(), "rack-1.example");
+        assert_eq!(
+            lines[..5],
+            [
+                "gilded_sentinel.rack-1_example.cpu.usage_average:7.75|g",
+                "gilded_sentinel.rack-1_example.cpu.0.usage:12.5|g",
+                "gilded_sentinel.rack-1_example.cpu.1.usage:3|g",
+                "gilded_sentinel.rack-1_example.cpu_package.0.temperature:45|g",
+                "gilded_sentinel.rack-1_example.cpu_core.0.Core_0.temperature:42|g",
+            ]
+        );
+        assert!(lines.contains(
+            &"gilded_sentinel.rack-1_example.disk._dev_sda1.available_space:250000000000|g"
+                .to_string()
+        ));
+    }
+
+    #[test]
+    fn labels_become_dogstatsd_tags() {
+        let config = StatsdConfig {
+            prefix: "sentinel.".to_string(),
+            tags: true,
+            ..StatsdConfig::default()
+        };
+        let lines = render_all(&config, "rack 1");
+        assert_eq!(
+            lines[4],
+            "sentinel.cpu_core.temperature:42|g|#host:rack_1,package:0,core:Core_0"
+        );
+        assert_eq!(
+            lines.last().unwrap(),
+            "sentinel.component.temperature:27.5|g|#host:rack_1,label:acpitz_temp1"
+        );
+    }
+}
@@ -0,0 +1,73 @@
+#![cfg(unix)]
+
+//! TLS Transport
+//!
+//! This module wraps server connections in TLS (rustls) when `server_tls` is enabled,
+//! trusting the bundled Mozilla roots plus an optional CA bundle from `server_ca_file`.
+
+use log::info;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, ServerName};
+use rustls::{ClientConfig, ClientConnection, RootCertStore, StreamOwned};
+use std::io;
+use std::net::TcpStream;
+use std::sync::{Arc, OnceLock};
+
+use crate::config::config_instance::Config;
+
+/// TLS client configuration, built once on first use.
+static CLIENT_CONFIG: OnceLock<Arc<ClientConfig>> = OnceLock::new();
+
+/// A TLS stream over TCP.
+pub type TlsStream = StreamOwned<ClientConnection, TcpStream>;
+
+/// A utility class for establishing TLS connections.
+pub struct TlsUtil;
+
+impl TlsUtil {
+    /// Performs a TLS handshake over `stream`, verifying the certificate against `host`.
+    pub fn connect(stream: TcpStream, host: &str) -> io::Result<TlsStream> {
+        let config = Arc::clone(CLIENT_CONFIG.get_or_init(|| Arc::new(Self::build_config())));
+        let server_name = ServerName::try_from(host.to_string()).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Invalid TLS server name `{}`: {}", host, e),
+            )
+        })?;
+
+        let connection = ClientConnection::new(config, server_name).map_err(io::Error::other)?;
+        let mut tls_stream = StreamOwned::new(connection, stream);
+
+        // Complete the handshake eagerly so certificate errors surface at connect time.
+        while tls_stream.conn.is_handshaking() {
+            tls_stream
+                .conn
+                .complete_io(&mut tls_stream.sock)
+                .map_err(|e| io::Error::new(e.kind(), format!("TLS handshake failed: {}", e)))?;
+        }
+        Ok(tls_stream)
+    }
+
+    fn build_config() -> ClientConfig {
+        let mut roots = RootCertStore::empty();
+        roots.extend(webpki_roots::TLS_SERVER_ROOTS.iter().cloned());
+
+        if let Some(ca_file) = &Config::get().server_ca_file {
+            match CertificateDer::pem_file_iter(ca_file) {
+                Ok(certificates) => {
+                    let (added, ignored) =
+                        roots.add_parsable_certificates(certificates.filter_map(Result::ok));
+                    info!(
+                        "Loaded {} CA certificate(s) from {} ({} ignored).",
+                        added, ca_file, ignored
+                    );
+                }
+                Err(e) => log::error!("Failed to read CA bundle {}: {}", ca_file, e),
+            }
+        }
+
+        ClientConfig::builder()
+            .with_root_certificates(roots)
+            .with_no_client_auth()
+    }
+}
@@ -0,0 +1,147 @@
+#![cfg(unix)]
+
+//! Request Tracing
+//!
+//! This module mirrors outgoing requests and the corresponding server responses into
+//! timestamped files, to troubleshoot ingestion mismatches without resorting to tcpdump.
+
+use log::{error, info, warn};
+use std::fs;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::config::config_instance::Config;
+
+/// Time at which tracing was first requested; the trace window starts here.
+static TRACE_START: OnceLock<Instant> = OnceLock::new();
+/// Sequence number distinguishing traces written within the same millisecond.
+static TRACE_SEQUENCE: AtomicU64 = AtomicU64::new(0);
+/// Whether the end of the trace window has already been logged.
+static TRACE_EXPIRED: AtomicBool = AtomicBool::new(false);
+
+/// A utility class for writing request/response traces.
+pub struct TraceUtil;
+
+impl TraceUtil {
+    /// Returns `true` if a trace directory is configured and the trace window is still open.
+    pub fn is_active() -> bool {
+        let config = Config::get();
+        if config.trace_dir.is_none() {
+            return false;
+        }
+
+        let start = TRACE_START.get_or_init(Instant::now);
+        if start.elapsed() <= Duration::from_secs(config.trace_duration_secs) {
+            return true;
+        }
+
+        if !TRACE_EXPIRED.swap(true, Ordering::Relaxed) {
+            info!(
+                "Trace window of {} seconds elapsed; tracing disabled.",
+                config.trace_duration_secs
+            );
+        }
+        false
+    }
+
+    /// Writes the request and, if one was received, the response to the trace directory.
+    ///
+    /// Files are named `<unix_millis>-<sequence>-request.http` and
+    /// `<unix_millis>-<sequence>-response.http`. Credentials in the `Authorization`
+    /// header are redacted. Failures are logged and otherwise ignored.
+    pub fn record(request: &[u8], response: Option<&[u8]>) {
+        if let Some(trace_dir) = Config::get().trace_dir.as_deref() {
+            Self::record_in(trace_dir, request, response);
+        }
+    }
+
+    fn record_in(trace_dir: &str, request: &[u8], response: Option<&[u8]>) {
+        if let Err(e) = fs::create_dir_all(trace_dir) {
+            error!("Failed to create trace directory {}: {}", trace_dir, e);
+            return;
+        }
+
+        let millis = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let sequence = TRACE_SEQUENCE.fetch_add(1, Ordering::Relaxed);
+        let prefix = format!("{}-{:06}", millis, sequence);
+
+        Self::write_file(
+            trace_dir,
+            &format!("{}-request.http", prefix),
+            &Self::redact_authorization(request),
+        );
+        match response {
+            Some(response) => {
+                Self::write_file(trace_dir, &format!("{}-response.http", prefix), response)
+            }
+            None => warn!("No server response received for traced request {}.", prefix),
+        }
+    }
+
+    /// Replaces the value of an `Authorization` request header with `[redacted]`.
+    fn redact_authorization(request: &[u8]) -> Vec<u8> {
+        let head_end = request
+            .windows(4)
+            .position(|window| window == b"\r\n\r\n")
+            .unwrap_or(request.len());
+        let (head, body) = request.split_at(head_end);
+
+        let head = String::from_utf8_lossy(head)
+            .split("\r\n")
+            .map(|line| match line.split_once(':') {
+                Some((name, _)) if name.eq_ignore_ascii_case("authorization") => {
+                    format!("{}: [redacted]", name)
+                }
+                _ => line.to_string(),
+            })
+            .collect::<Vec<_>>()
+            .join("\r\n");
+
+        let mut redacted = head.into_bytes();
+        redacted.extend_from_slice(body);
+        redacted
+    }
+
+    fn write_file(trace_dir: &str, name: &str, contents: &[u8]) {
+        let path = Path::new(trace_dir).join(name);
+        if let Err(e) = fs::write(&path, contents) {
+            error!("Failed to write trace file {}: {}", path.display(), e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn traced_requests_do_not_contain_the_bearer_token() {
+        let dir = TempDir::new("trace");
+        let request = b"POST /api HTTP/1.1\r\nHost: server\r\n\
+                        authorization: Bearer s3cr3t-t0ken\r\n\r\n{\"a\":1}";
+        TraceUtil::record_in(
+            &dir.display().to_string(),
+            request,
+            Some(b"HTTP/1.1 200 OK\r\n\r\n"),
+        );
+
+        let mut names: Vec<_> = fs::read_dir(&*dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        assert_eq!(names.len(), 2);
+        assert!(names[0].ends_with("-request.http"));
+        assert!(names[1].ends_with("-response.http"));
+
+        let traced = fs::read_to_string(dir.join(&names[0])).unwrap();
+        assert!(!traced.contains("s3cr3t-t0ken"));
+        assert!(traced.contains("authorization: [redacted]\r\n\r\n{\"a\":1}"));
+    }
+}
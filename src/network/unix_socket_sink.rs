@@ -0,0 +1,147 @@
+#![cfg(unix)]
+
+//! Unix Socket Sink
+//!
+//! This module writes payloads to a Unix domain socket instead of the HTTP server, so a
+//! local relay such as Vector or Fluent Bit can forward them without the agent opening
+//! network ports. Stream sockets carry framed messages over one connection that is opened
+//! lazily and reopened after a failed write; datagram sockets carry one payload per datagram.
+
+use log::{debug, info};
+use serde::Deserialize;
+use std::io::{self, Write};
+use std::os::unix::net::{UnixDatagram, UnixStream};
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::config::config_instance::Config;
+use crate::network::batch_util::BatchConfig;
+
+/// Timeout for a single write to the socket.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Socket type of the receiver.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnixSocketMode {
+    /// `SOCK_STREAM`; payloads are framed according to `framing`.
+    #[default]
+    Stream,
+    /// `SOCK_DGRAM`; each payload is one datagram.
+    Datagram,
+}
+
+/// Framing of payloads on stream sockets.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum UnixSocketFraming {
+    /// Each payload is followed by `\n`; suits the JSON payload format.
+    #[default]
+    Newline,
+    /// Each payload is preceded by its length as a big-endian u32; suits binary formats.
+    Length,
+}
+
+/// Configuration of the `[unix_socket]` section. When present, payloads are written to the
+/// socket instead of being sent to `server`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct UnixSocketConfig {
+    /// Path of the socket the relay listens on.
+    pub path: String,
+    /// Socket type of the receiver.
+    pub mode: UnixSocketMode,
+    /// Framing of payloads in `stream` mode.
+    pub framing: UnixSocketFraming,
+    /// Batching of sensor reports written to the socket; each report is sent alone when absent.
+    pub batch: Option<BatchConfig>,
+}
+
+impl Default for UnixSocketConfig {
+    fn default() -> Self {
+        Self {
+            path: "/run/gilded-sentinel/sentinel.sock".to_string(),
+            mode: UnixSocketMode::Stream,
+            framing: UnixSocketFraming::Newline,
+            batch: None,
+        }
+    }
+}
+
+/// The open stream connection, if any.
+static STREAM: Mutex<Option<UnixStream>> = Mutex::new(None);
+
+/// Static utility class for writing payloads to a Unix domain socket.
+pub struct UnixSocketSink;
+
+impl UnixSocketSink {
+    /// Returns whether payloads are written to a Unix socket rather than sent over HTTP.
+    pub fn is_enabled() -> bool {
+        Config::get().unix_socket.is_some()
+    }
+
+    /// Writes an already serialized payload to the configured socket.
+    ///
+    /// Fails if the socket is not configured, nothing listens on it, or the write fails.
+    pub fn send(body: &[u8]) -> io::Result<()> {
+        let config = Config::get().unix_socket.as_ref().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "Unix socket output is not configured",
+            )
+        })?;
+
+        match config.mode {
+            UnixSocketMode::Datagram => {
+                UnixDatagram::unbound()?.send_to(body, &config.path)?;
+            }
+            UnixSocketMode::Stream => Self::send_stream(config, &Self::frame(body, config)?)?,
+        }
+        debug!("Wrote {} byte(s) to {}.", body.len(), config.path);
+        Ok(())
+    }
+
+    /// Writes a frame over the shared connection, reconnecting once if the previous
+    /// connection has gone away (e.g. the relay was restarted).
+    fn send_stream(config: &UnixSocketConfig, frame: &[u8]) -> io::Result<()> {
+        let mut stream = STREAM.lock().unwrap_or_else(|e| e.into_inner());
+
+        if let Some(connection) = stream.as_mut() {
+            match connection.write_all(frame) {
+                Ok(()) => return Ok(()),
+                Err(e) => debug!("Write to {} failed ({}); reconnecting.", config.path, e),
+            }
+        }
+
+        *stream = None;
+        let mut connection = UnixStream::connect(&config.path).map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("Failed to connect to {}: {}", config.path, e),
+            )
+        })?;
+        connection.set_write_timeout(Some(WRITE_TIMEOUT))?;
+        info!("Connected to Unix socket {}.", config.path);
+        connection.write_all(frame)?;
+        *stream = Some(connection);
+        Ok(())
+    }
+
+    fn frame(body: &[u8], config: &UnixSocketConfig) -> io::Result<Vec<u8>> {
+        let mut frame = Vec::with_capacity(body.len() + 4);
+        match config.framing {
+            UnixSocketFraming::Newline => {
+                frame.extend_from_slice(body);
+                frame.push(b'\n');
+            }
+            UnixSocketFraming::Length => {
+                let length = u32::try_from(body.len()).map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidInput, "Payload too large to frame")
+                })?;
+                frame.extend_from_slice(&length.to_be_bytes());
+                frame.extend_from_slice(body);
+            }
+        }
+        Ok(frame)
+    }
+}
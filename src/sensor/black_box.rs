@@ -0,0 +1,193 @@
+#![cfg(unix)]
+
+//! Thermal Black Box
+//!
+//! When a CPU package approaches its critical temperature, this module starts a 1 Hz
+//! recorder that keeps the last two minutes of thermal, fan and load data in a file on disk.
+//! If the host then shuts down (or crashes) the file survives and is uploaded on the next
+//! start, so thermal shutdowns can be analysed after the fact. If temperatures recover,
+//! the recorder disarms and removes the file.
+
+use log::{error, info, warn};
+use std::collections::VecDeque;
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::alert::alert_util::{Alert, AlertSeverity, AlertUtil};
+use crate::config::config_instance::Config;
+use crate::data::models::{CpuPackageData, FanInfo, ThermalForensicRecord, ThermalSample};
+use crate::data::units::Celsius;
+use crate::network::network_util::NetworkUtil;
+use crate::sensor::gap_util::GapUtil;
+use crate::sensor::sensor_util::SensorUtils;
+
+/// File name of the black box record inside the state directory.
+const BLACK_BOX_FILE_NAME: &str = "thermal-black-box.json";
+/// Number of 1 Hz samples retained (two minutes).
+const BLACK_BOX_CAPACITY: usize = 120;
+/// Consecutive cool samples after which the recorder disarms.
+const DISARM_AFTER_COOL_SAMPLES: usize = 60;
+
+/// Whether the recorder thread is currently running.
+static ARMED: AtomicBool = AtomicBool::new(false);
+
+/// The thermal black box recorder.
+pub struct BlackBox;
+
+impl BlackBox {
+    /// Arms the recorder if any package is within the configured margin of its critical threshold.
+    pub fn observe(packages: &[CpuPackageData], hostname: &str, boot_time: u64) {
+        let config = Config::get();
+        if !config.black_box_enabled || !Self::is_near_critical(packages) {
+            return;
+        }
+
+        if ARMED.swap(true, Ordering::SeqCst) {
+            return;
+        }
+
+        warn!(
+            "CPU temperature within {:.1}°C of critical; arming thermal black box.",
+            config.black_box_margin_celsius
+        );
+        AlertUtil::raise(Alert::new(
+            AlertSeverity::Critical,
+            "thermal black box",
+            format!(
+                "CPU temperature within {:.1}°C of critical; recording started",
+                config.black_box_margin_celsius
+            ),
+        ));
+        let record = ThermalForensicRecord {
+            hostname: hostname.to_string(),
+            boot_time,
+            triggered_at: Self::now_millis(),
+            samples: Vec::new(),
+        };
+        thread::spawn(move || Self::record(record));
+    }
+
+    /// Uploads a black box record left behind by a previous run, removing it once delivered.
+    pub fn upload_pending(server: &str) {
+        let path = Self::record_path();
+        let Ok(contents) = fs::read_to_string(&path) else {
+            return;
+        };
+
+        match serde_json::from_str::<ThermalForensicRecord>(&contents) {
+            Ok(record) => {
+                info!(
+                    "Found thermal black box record with {} sample(s) from a previous run; uploading.",
+                    record.samples.len()
+                );
+                match NetworkUtil::send_with_retries(&record, server, 3) {
+                    Ok(_) => {
+                        GapUtil::note_recovered(record.samples.len() as u64);
+                        Self::remove_record();
+                    }
+                    Err(e) => error!("Failed to upload thermal black box record: {}", e),
+                }
+            }
+            Err(e) => {
+                warn!("Discarding unreadable thermal black box record: {}", e);
+                Self::remove_record();
+            }
+        }
+    }
+
+    /// Returns `true` if any package is within the margin of its critical threshold.
+    fn is_near_critical(packages: &[CpuPackageData]) -> bool {
+        let margin = Config::get().black_box_margin_celsius;
+        packages.iter().any(|package| {
+            package.critical_threshold.is_known()
+                && package.package_temperature.0 >= package.critical_threshold.0 - margin
+        })
+    }
+
+    /// Samples at 1 Hz, persisting the rolling window after every sample, until temperatures
+    /// have stayed below the trigger for `DISARM_AFTER_COOL_SAMPLES` seconds.
+    fn record(mut record: ThermalForensicRecord) {
+        let mut samples: VecDeque<ThermalSample> = VecDeque::with_capacity(BLACK_BOX_CAPACITY);
+        let mut cool_samples = 0;
+
+        while cool_samples < DISARM_AFTER_COOL_SAMPLES {
+            let (packages, chips) = SensorUtils::collect_cpu_packages_and_chips();
+            if Self::is_near_critical(&packages) {
+                cool_samples = 0;
+            } else {
+                cool_samples += 1;
+            }
+
+            if samples.len() == BLACK_BOX_CAPACITY {
+                samples.pop_front();
+            }
+            let fans = chips.map(|chips| chips.fans).unwrap_or_default();
+            samples.push_back(Self::sample(&packages, fans));
+
+            record.samples = samples.iter().cloned().collect();
+            Self::persist(&record);
+            thread::sleep(Duration::from_secs(1));
+        }
+
+        info!("Temperatures recovered; disarming thermal black box.");
+        Self::remove_record();
+        ARMED.store(false, Ordering::SeqCst);
+    }
+
+    fn sample(packages: &[CpuPackageData], fans: Vec<FanInfo>) -> ThermalSample {
+        ThermalSample {
+            timestamp: Self::now_millis(),
+            package_temperatures: packages.iter().map(|p| p.package_temperature).collect(),
+            max_core_temperature: Celsius(
+                packages
+                    .iter()
+                    .flat_map(|p| p.cores.iter().map(|core| core.temperature.0))
+                    .fold(0.0, f32::max),
+            ),
+            load_average_one: sysinfo::System::load_average().one,
+            fans,
+        }
+    }
+
+    /// Writes the record durably: to a temporary file that is synced and then renamed.
+    fn persist(record: &ThermalForensicRecord) {
+        let path = Self::record_path();
+        let tmp_path = path.with_extension("json.tmp");
+        let result = serde_json::to_vec(record)
+            .map_err(std::io::Error::other)
+            .and_then(|json| {
+                let mut file = File::create(&tmp_path)?;
+                file.write_all(&json)?;
+                file.sync_all()
+            })
+            .and_then(|_| fs::rename(&tmp_path, &path));
+
+        if let Err(e) = result {
+            error!("Failed to persist thermal black box record: {}", e);
+        }
+    }
+
+    fn remove_record() {
+        let path = Self::record_path();
+        if path.exists() {
+            if let Err(e) = fs::remove_file(&path) {
+                error!("Failed to remove {}: {}", path.display(), e);
+            }
+        }
+    }
+
+    fn record_path() -> PathBuf {
+        Config::state_file(BLACK_BOX_FILE_NAME)
+    }
+
+    fn now_millis() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default()
+    }
+}
@@ -0,0 +1,310 @@
+#![cfg(unix)]
+
+//! Nagios/Icinga Check Mode
+//!
+//! This module implements the `check` subcommand: one read of temperatures, memory or disk
+//! usage, evaluated against thresholds and reported in the monitoring plugin format (one
+//! status line with performance data, exit code 0 = OK, 1 = WARNING, 2 = CRITICAL,
+//! 3 = UNKNOWN), so existing Icinga/Nagios setups can reuse the agent's parsers.
+//!
+//! Thresholds given on the command line take precedence over the `[check]` section; for
+//! temperatures, the sensor's own thresholds apply when neither sets one.
+
+use serde::Deserialize;
+
+use crate::alert::alert_util::{AlertSeverity, AlertUtil};
+use crate::config::cli::{CheckMetric, CheckOptions};
+use crate::config::config_instance::Config;
+use crate::config::i18n::Text;
+use crate::data::units::{Bytes, Celsius, Percent};
+use crate::hardware::system_information_monitor::SysInfoMonitor;
+use crate::sensor::sensor_util::SensorUtils;
+
+/// Plugin exit codes.
+const EXIT_OK: i32 = 0;
+const EXIT_WARNING: i32 = 1;
+const EXIT_CRITICAL: i32 = 2;
+const EXIT_UNKNOWN: i32 = 3;
+
+/// Configuration of the `[check]` section: default thresholds of the `check` subcommand.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct CheckConfig {
+    /// Temperature warning threshold in °C; the sensor's high threshold when absent.
+    pub warning_celsius: Option<Celsius>,
+    /// Temperature critical threshold in °C; the sensor's critical threshold when absent.
+    pub critical_celsius: Option<Celsius>,
+    /// Memory usage warning threshold in percent.
+    pub memory_warning_percent: Percent,
+    /// Memory usage critical threshold in percent.
+    pub memory_critical_percent: Percent,
+    /// Disk usage warning threshold in percent.
+    pub disk_warning_percent: Percent,
+    /// Disk usage critical threshold in percent.
+    pub disk_critical_percent: Percent,
+}
+
+impl Default for CheckConfig {
+    fn default() -> Self {
+        Self {
+            warning_celsius: None,
+            critical_celsius: None,
+            memory_warning_percent: Percent(80.0),
+            memory_critical_percent: Percent(90.0),
+            disk_warning_percent: Percent(80.0),
+            disk_critical_percent: Percent(90.0),
+        }
+    }
+}
+
+/// A reading with its effective thresholds.
+struct CheckReading {
+    label: String,
+    value: f32,
+    warning: f32,
+    critical: f32,
+    severity: AlertSeverity,
+}
+
+/// A utility class for running plugin-style checks.
+pub struct CheckUtil;
+
+impl CheckUtil {
+    /// Runs the check, prints the plugin output and returns the exit code.
+    pub fn run(options: &CheckOptions) -> i32 {
+        let config = Config::get().check.clone().unwrap_or_default();
+        let mut readings = match options.metric {
+            CheckMetric::Temperature => Self::temperature_readings(options, &config),
+            CheckMetric::Memory => Self::memory_readings(options, &config),
+            CheckMetric::Disk => Self::disk_readings(options, &config),
+        };
+        if let Some(sensor) = &options.sensor {
+            readings.retain(|reading| reading.label.eq_ignore_ascii_case(sensor.trim()));
+        }
+
+        let (code, output) = Self::report(options, &readings);
+        println!("{}", output);
+        code
+    }
+
+    /// Reads all package and core temperatures.
+    fn temperature_readings(options: &CheckOptions, config: &CheckConfig) -> Vec<CheckReading> {
+        let reading = |label, temperature: Celsius, high, critical| {
+            let warning = options
+                .warning
+                .map(Celsius)
+                .or(config.warning_celsius)
+                .unwrap_or(high);
+            let critical = options
+                .critical
+                .map(Celsius)
+                .or(config.critical_celsius)
+                .unwrap_or(critical);
+            CheckReading {
+                label,
+                value: temperature.0,
+                warning: warning.0,
+                critical: critical.0,
+                severity: AlertUtil::classify(temperature, warning, critical),
+            }
+        };
+
+        let mut readings = Vec::new();
+        for package in SensorUtils::collect_cpu_package_data() {
+            readings.push(reading(
+                format!("Package id {}", package.package_id),
+                package.package_temperature,
+                package.high_threshold,
+                package.critical_threshold,
+            ));
+            for core in package.cores {
+                readings.push(reading(
+                    core.core_name,
+                    core.temperature,
+                    core.high_threshold,
+                    core.critical_threshold,
+                ));
+            }
+        }
+        readings
+    }
+
+    /// Reads the host's memory usage.
+    fn memory_readings(options: &CheckOptions, config: &CheckConfig) -> Vec<CheckReading> {
+        let memory = SysInfoMonitor::new().get_memory_info();
+        if memory.total == Bytes(0) {
+            return Vec::new();
+        }
+        vec![Self::percent_reading(
+            options,
+            "memory".to_string(),
+            memory.used.percent_of(memory.total),
+            config.memory_warning_percent,
+            config.memory_critical_percent,
+        )]
+    }
+
+    /// Reads the usage of every disk.
+    fn disk_readings(options: &CheckOptions, config: &CheckConfig) -> Vec<CheckReading> {
+        SysInfoMonitor::new()
+            .get_disk_info()
+            .into_iter()
+            .filter(|disk| disk.total_space > Bytes(0))
+            .map(|disk| {
+                let used = disk.total_space.saturating_sub(disk.available_space);
+                Self::percent_reading(
+                    options,
+                    disk.name,
+                    used.percent_of(disk.total_space),
+                    config.disk_warning_percent,
+                    config.disk_critical_percent,
+                )
+            })
+            .collect()
+    }
+
+    fn percent_reading(
+        options: &CheckOptions,
+        label: String,
+        percent: Percent,
+        warning: Percent,
+        critical: Percent,
+    ) -> CheckReading {
+        let warning = options.warning.unwrap_or(warning.0);
+        let critical = options.critical.unwrap_or(critical.0);
+        CheckReading {
+            label,
+            value: percent.0,
+            warning,
+            critical,
+            severity: AlertUtil::classify_with(percent.0, warning, critical, None, None),
+        }
+    }
+
+    /// Builds the exit code and the plugin output line.
+    fn report(options: &CheckOptions, readings: &[CheckReading]) -> (i32, String) {
+        // Perfdata has no unit of measurement for temperatures.
+        let (service, unit, perf_unit) = match options.metric {
+            CheckMetric::Temperature => ("TEMPERATURE", "°C", ""),
+            CheckMetric::Memory => ("MEMORY", "%", "%"),
+            CheckMetric::Disk => ("DISK", "%", "%"),
+        };
+        let Some(worst) = readings.iter().max_by(|a, b| {
+            a.severity
+                .cmp(&b.severity)
+                .then(a.value.total_cmp(&b.value))
+        }) else {
+            let message = match &options.sensor {
+                Some(sensor) => format!(
+                    "{} UNKNOWN - {}",
+                    service,
+                    Text::SensorNotFound(sensor).render()
+                ),
+                None => format!("{} UNKNOWN - {}", service, Text::NoReadings.render()),
+            };
+            return (EXIT_UNKNOWN, message);
+        };
+
+        let (code, status) = match worst.severity {
+            AlertSeverity::Resolved => (EXIT_OK, "OK"),
+            AlertSeverity::Warning => (EXIT_WARNING, "WARNING"),
+            AlertSeverity::Critical => (EXIT_CRITICAL, "CRITICAL"),
+        };
+
+        let perfdata = readings
+            .iter()
+            .map(|reading| {
+                format!(
+                    "'{}'={:.1}{};{};{}",
+                    reading.label,
+                    reading.value,
+                    perf_unit,
+                    Self::threshold(reading.warning),
+                    Self::threshold(reading.critical)
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        (
+            code,
+            format!(
+                "{} {} - {} {:.1}{} | {}",
+                service, status, worst.label, worst.value, unit, perfdata
+            ),
+        )
+    }
+
+    /// Formats a threshold for perfdata; unknown (non-positive) thresholds are left empty.
+    fn threshold(value: f32) -> String {
+        if value > 0.0 {
+            format!("{}", value)
+        } else {
+            String::new()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn options(metric: CheckMetric, warning: Option<f32>) -> CheckOptions {
+        CheckOptions {
+            metric,
+            sensor: None,
+            warning,
+            critical: None,
+        }
+    }
+
+    #[test]
+    fn reports_the_worst_reading_with_perfdata() {
+        let options = options(CheckMetric::Disk, None);
+        let config = CheckConfig::default();
+        let readings = [
+            CheckUtil::percent_reading(
+                &options,
+                "/".to_string(),
+                Percent(85.0),
+                config.disk_warning_percent,
+                config.disk_critical_percent,
+            ),
+            CheckUtil::percent_reading(
+                &options,
+                "/var".to_string(),
+                Percent(40.0),
+                config.disk_warning_percent,
+                config.disk_critical_percent,
+            ),
+        ];
+
+        assert_eq!(
+            CheckUtil::report(&options, &readings),
+            (
+                EXIT_WARNING,
+                "DISK WARNING - / 85.0% | '/'=85.0%;80;90 '/var'=40.0%;80;90".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn temperatures_leave_unknown_thresholds_empty() {
+        let options = options(CheckMetric::Temperature, Some(70.0));
+        let readings = [CheckReading {
+            label: "Package id 0".to_string(),
+            value: 45.0,
+            warning: 70.0,
+            critical: 0.0,
+            severity: AlertUtil::classify_with(45.0, 70.0, 0.0, None, None),
+        }];
+
+        assert_eq!(
+            CheckUtil::report(&options, &readings),
+            (
+                EXIT_OK,
+                "TEMPERATURE OK - Package id 0 45.0°C | 'Package id 0'=45.0;70;".to_string()
+            )
+        );
+    }
+}
@@ -0,0 +1,83 @@
+#![cfg(unix)]
+
+//! Gap Declarations
+//!
+//! When the agent was down (or the server unreachable) for longer than a couple of report
+//! intervals, the first delivered report after the outage is followed by a gap declaration
+//! describing the missed range and how many locally stored samples were recovered, so the
+//! server can annotate charts instead of showing misleading flat lines.
+
+use log::{error, info};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use crate::config::config_instance::Config;
+use crate::data::models::GapDeclaration;
+use crate::network::network_util::NetworkUtil;
+
+/// Elapsed report intervals after which the time since the last report counts as a gap.
+const GAP_THRESHOLD_INTERVALS: u64 = 2;
+
+/// Locally stored samples delivered since the last gap declaration.
+static RECOVERED_SAMPLES: AtomicU64 = AtomicU64::new(0);
+
+/// A utility class for detecting and declaring reporting gaps.
+pub struct GapUtil;
+
+impl GapUtil {
+    /// Records that `samples` locally stored samples were delivered after an outage.
+    pub fn note_recovered(samples: u64) {
+        RECOVERED_SAMPLES.fetch_add(samples, Ordering::Relaxed);
+    }
+
+    /// Builds a gap declaration if the time between `last_report_at` and `now` exceeds
+    /// `GAP_THRESHOLD_INTERVALS` report intervals.
+    pub fn detect(
+        hostname: &str,
+        last_report_at: Option<u64>,
+        now: u64,
+        rebooted: bool,
+    ) -> Option<GapDeclaration> {
+        let last = last_report_at?;
+        let missed_samples = Self::missed_samples(last, now, Config::interval_secs())?;
+
+        Some(GapDeclaration {
+            hostname: hostname.to_string(),
+            gap_start: last,
+            gap_end: now,
+            missed_samples,
+            recovered_samples: RECOVERED_SAMPLES.load(Ordering::Relaxed),
+            rebooted,
+        })
+    }
+
+    /// Returns the number of reports missed between `last` and `now` if the time between
+    /// them is a gap. A clock that went backwards never yields a gap.
+    pub fn missed_samples(last: u64, now: u64, interval_secs: u64) -> Option<u64> {
+        let interval = interval_secs.max(1);
+        let elapsed = now.saturating_sub(last);
+        if elapsed <= interval.saturating_mul(GAP_THRESHOLD_INTERVALS) {
+            return None;
+        }
+        Some((elapsed / interval).saturating_sub(1))
+    }
+
+    /// Sends the declaration to the server, returning whether it was delivered.
+    pub fn declare(gap: &GapDeclaration, server: &str) -> bool {
+        info!(
+            "Reporting gap of {} second(s) ({} missed, {} recovered sample(s)).",
+            gap.gap_end - gap.gap_start,
+            gap.missed_samples,
+            gap.recovered_samples
+        );
+        match NetworkUtil::send_with_retries(gap, server, 3) {
+            Ok(_) => {
+                RECOVERED_SAMPLES.fetch_sub(gap.recovered_samples, Ordering::Relaxed);
+                true
+            }
+            Err(e) => {
+                error!("Failed to send gap declaration: {}", e);
+                false
+            }
+        }
+    }
+}
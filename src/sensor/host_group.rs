@@ -0,0 +1,97 @@
+//! Host Grouping
+//!
+//! With `group_hosts = true`, a report is submitted as a `HostGroupReport`: one entry per
+//! host identity the agent speaks for, i.e. its own host plus every device polled over
+//! SNMP and every BMC read over IPMI. Each entry carries its own tags and a sequence
+//! number that increases by one with every report of that identity and survives agent
+//! restarts, so the server can store and order proxied hosts like first-class agents.
+
+use std::collections::BTreeMap;
+
+use crate::config::config_instance::Config;
+use crate::data::models::{
+    HostEntry, HostGroupReport, HostReportData, HostTag, SensorData, SCHEMA_VERSION,
+};
+use crate::system::state::AgentState;
+
+/// A utility class for grouping a report by host identity.
+pub struct HostGroup;
+
+impl HostGroup {
+    /// Splits a collected report into one entry per host identity, moving the proxied
+    /// devices and remote hosts out of the agent's own entry, and assigns each entry the
+    /// next sequence number of its identity.
+    pub fn build(mut data: SensorData) -> HostGroupReport {
+        let config = Config::get();
+        let agent = data.system_info.hostname.clone();
+        let agent_id = data.agent_id.clone().unwrap_or_default();
+        let proxied = data.proxied_devices.take().unwrap_or_default();
+        let remote = data.remote_hosts.take().unwrap_or_default();
+
+        let mut hosts = vec![(
+            "agent",
+            agent.clone(),
+            Self::tags(&config.host_tags),
+            HostReportData::Sensors(Box::new(data)),
+        )];
+        for device in proxied {
+            let tags = config
+                .snmp_targets
+                .iter()
+                .find(|target| target.name == device.name)
+                .map(|target| Self::tags(&target.tags))
+                .unwrap_or_default();
+            hosts.push((
+                "snmp",
+                device.name.clone(),
+                tags,
+                HostReportData::Snmp(device),
+            ));
+        }
+        for host in remote {
+            let tags = config
+                .ipmi_hosts
+                .iter()
+                .find(|ipmi| ipmi.name == host.name)
+                .map(|ipmi| Self::tags(&ipmi.tags))
+                .unwrap_or_default();
+            hosts.push(("ipmi", host.name.clone(), tags, HostReportData::Ipmi(host)));
+        }
+
+        let hosts = AgentState::update(|state| {
+            hosts
+                .into_iter()
+                .map(|(source, host, tags, report)| {
+                    let sequence = state
+                        .host_sequences
+                        .entry(format!("{}:{}", source, host))
+                        .or_default();
+                    *sequence += 1;
+                    HostEntry {
+                        host,
+                        source: source.to_string(),
+                        sequence: *sequence,
+                        tags,
+                        report,
+                    }
+                })
+                .collect()
+        });
+
+        HostGroupReport {
+            schema_version: SCHEMA_VERSION,
+            agent,
+            agent_id,
+            hosts,
+        }
+    }
+
+    fn tags(tags: &BTreeMap<String, String>) -> Vec<HostTag> {
+        tags.iter()
+            .map(|(key, value)| HostTag {
+                key: key.clone(),
+                value: value.clone(),
+            })
+            .collect()
+    }
+}
@@ -1 +1,6 @@
+pub mod black_box;
+pub mod check_util;
+pub mod gap_util;
+pub mod host_group;
 pub mod sensor_util;
+pub mod thermal_reconcile;
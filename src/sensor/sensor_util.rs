@@ -1,13 +1,54 @@
 #![cfg(unix)]
 
-use log::{error, info};
-use serde::Serialize;
+use log::{debug, error, info};
+use std::collections::BTreeMap;
 use std::io;
 use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
 
-use crate::data::models::{CpuCoreData, CpuPackageData, SensorData, SystemInfo};
+use crate::alert::alert_util::AlertUtil;
+use crate::config::config_instance::Config;
+use crate::config::remote_config::RemoteConfig;
+use crate::data::history::History;
+use crate::data::models::{
+    CpuCoreData, CpuPackageData, FanInfo, PowerInfo, SectionStatus, SensorData, SensorDataBatch,
+    SystemInfo, VoltageInfo, SCHEMA_VERSION,
+};
+use crate::data::parse::{
+    first_token, named_token, parse_number, parse_temp, split_label, ParseError, ParseResult,
+};
+use crate::data::payload_format::WirePayload;
+use crate::data::rate::RateUtil;
+use crate::data::units::Celsius;
+use crate::hardware::gpu_util::GpuUtil;
+use crate::hardware::hwmon_thermal::{ChipReadings, HwmonThermal};
+use crate::hardware::hwmon_util::HwmonUtil;
+use crate::hardware::ipmi_util::IpmiUtil;
+use crate::hardware::liquidctl_util::LiquidctlUtil;
+use crate::hardware::msr_thermal::{MsrThermal, ThermalSource};
+use crate::hardware::power_util::PowerUtil;
+use crate::hardware::smart_util::SmartUtil;
 use crate::hardware::system_information_monitor::SysInfoMonitor;
+use crate::hardware::system_source::SystemSource;
+use crate::network::batch_util::BatchUtil;
+use crate::network::chunk_util::ChunkUtil;
+use crate::network::ha_discovery::HaDiscovery;
+use crate::network::neighbor_util::NeighborUtil;
 use crate::network::network_util::NetworkUtil;
+use crate::network::otlp_sink::OtlpSink;
+use crate::network::public_ip::PublicIp;
+use crate::network::snmp_agent::SnmpAgent;
+use crate::network::snmp_poller::SnmpPoller;
+use crate::network::statsd_sink::StatsdSink;
+use crate::sensor::black_box::BlackBox;
+use crate::sensor::gap_util::GapUtil;
+use crate::sensor::host_group::HostGroup;
+use crate::sensor::thermal_reconcile::ThermalReconcile;
+use crate::system::agent_id::AgentId;
+use crate::system::container_util::ContainerUtil;
+use crate::system::inventory_util::InventoryUtil;
+use crate::system::self_metrics::SelfMetrics;
+use crate::system::state::AgentState;
 
 /// Static utility class for sensor-related operations.
 ///
@@ -17,20 +58,64 @@ use crate::network::network_util::NetworkUtil;
 pub struct SensorUtils;
 
 impl SensorUtils {
-    /// Collects CPU package data.
+    /// Collects CPU package data from the configured `thermal_source`.
     ///
-    /// On Unix-like systems, this executes the `sensors` command and parses its output.
+    /// By default, this executes the `sensors` command and parses its output.
     pub fn collect_cpu_package_data() -> Vec<CpuPackageData> {
-        // Execute `sensors` command on Unix-like systems.
-        match Self::execute_sensors_command() {
-            Ok(data) => Self::parse_sensor_data(&data),
+        Self::collect_cpu_packages_and_chips().0
+    }
+
+    /// Collects CPU package data from the configured `thermal_source`, along with the fan,
+    /// voltage and power readings if `sensors` or hwmon was read.
+    pub fn collect_cpu_packages_and_chips() -> (Vec<CpuPackageData>, Option<ChipReadings>) {
+        match Self::collect_with_chips(Config::get().thermal_source) {
+            Ok(readings) => readings,
             Err(e) => {
                 error!("Error retrieving sensor data: {}", e);
-                Vec::new() // Return an empty vector on failure.
+                (Vec::new(), None) // Return no readings on failure.
             }
         }
     }
 
+    /// Collects CPU package data from one thermal source.
+    pub fn collect_from(source: ThermalSource) -> io::Result<Vec<CpuPackageData>> {
+        Self::collect_with_chips(source).map(|(packages, _)| packages)
+    }
+
+    /// Collects CPU package data from one thermal source, and the fan, voltage and power
+    /// readings when the source involves the `sensors` command or hwmon.
+    fn collect_with_chips(
+        source: ThermalSource,
+    ) -> io::Result<(Vec<CpuPackageData>, Option<ChipReadings>)> {
+        match source {
+            ThermalSource::Sensors => Self::execute_sensors_command().map(|data| {
+                let (packages, chips) = Self::parse_sensor_data(&data);
+                (packages, Some(chips))
+            }),
+            ThermalSource::Msr => {
+                MsrThermal::collect_cpu_packages().map(|packages| (packages, None))
+            }
+            ThermalSource::Hwmon => {
+                HwmonThermal::collect().map(|(packages, chips)| (packages, Some(chips)))
+            }
+            ThermalSource::Auto => match Self::execute_sensors_command() {
+                Ok(data) => {
+                    let (packages, chips) = Self::parse_sensor_data(&data);
+                    if packages.is_empty() {
+                        debug!("`sensors` reported no CPU packages; reading MSRs.");
+                        MsrThermal::collect_cpu_packages().map(|packages| (packages, Some(chips)))
+                    } else {
+                        Ok((packages, Some(chips)))
+                    }
+                }
+                Err(e) => {
+                    debug!("{}; reading MSRs.", e);
+                    MsrThermal::collect_cpu_packages().map(|packages| (packages, None))
+                }
+            },
+        }
+    }
+
     /// Executes the `sensors` command to retrieve sensor data.
     ///
     /// Captures both `stdout` and `stderr` and logs errors if the command fails.
@@ -53,28 +138,50 @@ impl SensorUtils {
         Ok(String::from_utf8_lossy(&output.stdout).to_string())
     }
 
-    /// Parses raw `sensors` command output into structured `CpuPackageData`.
+    /// Parses raw `sensors` command output into structured `CpuPackageData` and the
+    /// readings of other chips.
     ///
-    /// Lines are parsed to identify adapter, package, and core information,
-    /// which are stored in a vector of `CpuPackageData`.
-    fn parse_sensor_data(raw_data: &str) -> Vec<CpuPackageData> {
+    /// Lines are parsed to identify adapter, package, and core information, which are
+    /// stored in a vector of `CpuPackageData`, and fan, voltage and power readings of any
+    /// chip.
+    fn parse_sensor_data(raw_data: &str) -> (Vec<CpuPackageData>, ChipReadings) {
         let mut cpu_packages = Vec::new();
         let mut current_package: Option<CpuPackageData> = None;
+        let mut chips = ChipReadings::default();
+        let mut current_chip = "Unknown";
 
-        for line in raw_data.lines() {
-            if Self::is_adapter_line(line) {
+        for (index, line) in raw_data.lines().enumerate() {
+            if Self::is_chip_line(line) {
+                current_chip = line.trim();
+            }
+            let result = if Self::is_fan_line(line) {
+                Self::parse_fan_line(line, current_chip).map(|fan| chips.fans.push(fan))
+            } else if Self::is_voltage_line(line) {
+                Self::parse_voltage_line(line, current_chip)
+                    .map(|voltage| chips.voltages.push(voltage))
+            } else if Self::is_power_line(line) {
+                Self::parse_power_line(line, current_chip).map(|power| chips.power.push(power))
+            } else if Self::is_adapter_line(line) {
                 if let Some(package) = current_package.take() {
                     cpu_packages.push(package);
                 }
                 current_package = Some(Self::parse_adapter_line(line));
+                Ok(())
             } else if Self::is_package_line(line) {
-                if let Some(ref mut package) = current_package {
-                    Self::parse_package_line(line, package);
+                match current_package {
+                    Some(ref mut package) => Self::parse_package_line(line, package),
+                    None => Ok(()),
                 }
             } else if Self::is_core_line(line) {
-                if let Some(ref mut package) = current_package {
-                    Self::parse_core_line(line, package);
+                match current_package {
+                    Some(ref mut package) => Self::parse_core_line(line, package),
+                    None => Ok(()),
                 }
+            } else {
+                Ok(())
+            };
+            if let Err(e) = result {
+                debug!("Skipping `sensors` reading: {}", e.at_line(index + 1));
             }
         }
 
@@ -82,36 +189,132 @@ impl SensorUtils {
             cpu_packages.push(package);
         }
 
-        cpu_packages
+        (cpu_packages, chips)
     }
 
-    /// Sends sensor data to the server using the `NetworkUtil`.
-    pub fn process_sensor_data(server: &str, monitor: &mut SysInfoMonitor) {
-        /// Sends data with retries and logs the outcome.
-        fn send_and_log<T: Serialize>(data: &T, description: &str, server: &str) {
-            match NetworkUtil::send_with_retries(data, server, 3) {
-                Ok(_) => info!("{} data sent successfully.", description),
-                Err(e) => error!("Failed to send {} data: {}.", description, e),
-            }
+    /// Collects one `SensorData` snapshot from the system monitor and `sensors`.
+    ///
+    /// `rebooted_since_last_report` is left unset; it depends on persisted agent state.
+    pub fn collect_sensor_data<S: SystemSource>(monitor: &mut SysInfoMonitor<S>) -> SensorData {
+        let config = Config::get();
+        let (cpu_packages, chips, thermal_disagreements) =
+            SelfMetrics::time("cpu_packages", || match &config.thermal_reconcile {
+                Some(reconcile) => {
+                    let (packages, disagreements) = ThermalReconcile::collect(reconcile);
+                    (packages, None, disagreements)
+                }
+                None => {
+                    let (packages, chips) = Self::collect_cpu_packages_and_chips();
+                    (packages, chips, None)
+                }
+            });
+        let mut sensor_data = SelfMetrics::time("system", || {
+            Self::build_sensor_data(
+                monitor,
+                RemoteConfig::collect_disks(),
+                RemoteConfig::collect_network(),
+                cpu_packages,
+            )
+        });
+        ContainerUtil::apply(&mut sensor_data.memory_info, &mut sensor_data.cpu_info);
+        RateUtil::apply(&mut sensor_data.network_interfaces, &mut sensor_data.disks);
+        sensor_data.system_info.management_ip = NetworkUtil::get_primary_ipv4();
+        sensor_data.system_info.public_ip =
+            SelfMetrics::time("public_ip", PublicIp::collect_if_configured);
+        sensor_data.neighbors = SelfMetrics::time("neighbors", NeighborUtil::collect_if_due);
+        let mut meters = None;
+        if let Some(chips) = chips {
+            sensor_data.fans = Some(chips.fans);
+            sensor_data.voltages = Some(chips.voltages);
+            meters = Some(chips.power);
         }
+        // Fans already reported by the thermal source are not repeated per hwmon device.
+        let include_fans = sensor_data.fans.is_none();
+        sensor_data.hwmon_devices =
+            SelfMetrics::time("hwmon", || HwmonUtil::collect_if_enabled(include_fans));
+        sensor_data.cooling_devices =
+            SelfMetrics::time("liquidctl", LiquidctlUtil::collect_if_configured);
+        sensor_data.proxied_devices =
+            SelfMetrics::time("snmp_targets", SnmpPoller::collect_if_configured);
+        sensor_data.remote_hosts =
+            SelfMetrics::time("ipmi_hosts", IpmiUtil::collect_remote_if_configured);
+        sensor_data.smart_devices = SelfMetrics::time("smart", SmartUtil::collect_if_due);
+        sensor_data.gpus = SelfMetrics::time("gpus", GpuUtil::collect_if_enabled);
+        sensor_data.power = SelfMetrics::time("power", || PowerUtil::collect_if_enabled(meters));
+        sensor_data.thermal_disagreements = thermal_disagreements;
+        sensor_data.agent_id = Some(AgentId::get().to_string());
+        sensor_data.tenant_id = config.tenant_id.clone();
+        sensor_data.site_id = config.site_id.clone();
+        sensor_data
+    }
+
+    /// Builds a `SensorData` snapshot from the monitor's readings and already collected
+    /// CPU packages, leaving out disks and networks when their collectors are disabled.
+    ///
+    /// Host-dependent extras (container limits, management IP, neighbors, hwmon devices)
+    /// are not filled in, so this works on any `SystemSource`.
+    pub fn build_sensor_data<S: SystemSource>(
+        monitor: &mut SysInfoMonitor<S>,
+        collect_disks: bool,
+        collect_network: bool,
+        cpu_packages: Vec<CpuPackageData>,
+    ) -> SensorData {
+        let supported = monitor.source().is_supported();
+        let mut section_status = BTreeMap::new();
+        let mut status = |section: &str, status| {
+            section_status.insert(section.to_string(), status);
+        };
 
-        // Collect data from the system monitor
         let cpu_info = monitor.get_cpu_info();
+        if !supported || cpu_info.core_count == 0 {
+            status("cpu_info", SectionStatus::Unsupported);
+        }
         let memory_info = monitor.get_memory_info();
-        let disks = monitor.get_disk_info();
-        let networks = monitor.get_network_info();
+        if !supported || memory_info.total.0 == 0 {
+            status("memory_info", SectionStatus::Unsupported);
+        }
+        let disks = if !collect_disks {
+            status("disks", SectionStatus::Disabled);
+            Vec::new()
+        } else if !supported {
+            status("disks", SectionStatus::Unsupported);
+            Vec::new()
+        } else {
+            monitor.get_disk_info()
+        };
+        let networks = if !collect_network {
+            status("network_interfaces", SectionStatus::Disabled);
+            Vec::new()
+        } else if !supported {
+            status("network_interfaces", SectionStatus::Unsupported);
+            Vec::new()
+        } else {
+            monitor.get_network_info()
+        };
         let uptime = monitor.get_uptime();
         //let components = monitor.get_components_info();
         let components = Vec::new();
-        let cpu_packages = Self::collect_cpu_package_data();
+        status(
+            "components",
+            if supported {
+                SectionStatus::Disabled
+            } else {
+                SectionStatus::Unsupported
+            },
+        );
         let system_info: SystemInfo = SystemInfo {
             hostname: monitor.get_host_name(),
+            rebooted_since_last_report: false,
             uptime,
-            management_ip: NetworkUtil::get_primary_ipv4(),
+            management_ip: String::new(),
+            public_ip: None,
         };
 
-        // Construct the SensorData DTO
-        let sensor_data = SensorData {
+        SensorData {
+            schema_version: SCHEMA_VERSION,
+            agent_id: None,
+            tenant_id: None,
+            site_id: None,
             system_info,
             cpu_info,
             memory_info,
@@ -119,10 +322,166 @@ impl SensorUtils {
             network_interfaces: networks,
             components,
             cpu_packages,
+            neighbors: None,
+            fans: None,
+            voltages: None,
+            hwmon_devices: None,
+            cooling_devices: None,
+            proxied_devices: None,
+            remote_hosts: None,
+            smart_devices: None,
+            gpus: None,
+            power: None,
+            thermal_disagreements: None,
+            inventory: None,
+            inventory_hash: None,
+            section_status,
+        }
+    }
+
+    /// Sends sensor data to the server using the `NetworkUtil`, alone or, when the sink in
+    /// use has batching configured, together with the following samples.
+    pub fn process_sensor_data<S: SystemSource>(server: &str, monitor: &mut SysInfoMonitor<S>) {
+        let mut sensor_data = Self::collect_sensor_data(monitor);
+        let state = AgentState::load();
+        let boot_time = sensor_data.system_info.uptime.boot_time;
+        let rebooted = state.is_reboot(boot_time);
+        sensor_data.system_info.rebooted_since_last_report = rebooted;
+        BlackBox::observe(
+            &sensor_data.cpu_packages,
+            &sensor_data.system_info.hostname,
+            boot_time,
+        );
+
+        History::record(&sensor_data);
+        SnmpAgent::update(&sensor_data);
+        AlertUtil::process(&sensor_data);
+
+        HaDiscovery::announce(&sensor_data);
+        StatsdSink::emit(&sensor_data);
+        OtlpSink::emit(&sensor_data);
+        InventoryUtil::attach(&mut sensor_data, monitor);
+
+        if Config::get().group_hosts {
+            let hostname = sensor_data.system_info.hostname.clone();
+            let report = HostGroup::build(sensor_data);
+            let description = format!("HostGroupReport ({} hosts)", report.hosts.len());
+            if Self::send_and_log(&report, &description, server) {
+                Self::record_delivery(server, &hostname, None, boot_time, rebooted);
+            } else {
+                InventoryUtil::invalidate();
+            }
+            return;
+        }
+
+        // Send data to the server, then declare any gap and remember what was reported
+        let Some(batch_config) = BatchUtil::config() else {
+            let hostname = sensor_data.system_info.hostname.clone();
+            if Self::send_report(sensor_data, server) {
+                Self::record_delivery(server, &hostname, None, boot_time, rebooted);
+            } else {
+                InventoryUtil::invalidate();
+            }
+            return;
+        };
+        let collected_at_ms = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_millis() as u64)
+            .unwrap_or_default();
+        if let Some(batch) = BatchUtil::push(sensor_data, collected_at_ms, batch_config) {
+            Self::send_batch(&batch, server);
+        }
+    }
+
+    /// Sends the samples still waiting to be batched, e.g. on shutdown.
+    pub fn flush_pending_batch(server: &str) {
+        if let Some(batch) = BatchUtil::take_pending() {
+            Self::send_batch(&batch, server);
+        }
+    }
+
+    /// Sends a single report, in chunks when it exceeds `max_payload_bytes`, and returns
+    /// whether all of it was delivered.
+    fn send_report(sensor_data: SensorData, server: &str) -> bool {
+        let max_bytes = match Config::get().max_payload_bytes {
+            Some(max_bytes) if ChunkUtil::is_oversized(&sensor_data) => max_bytes,
+            _ => return Self::send_and_log(&sensor_data, "SensorDataDTO", server),
+        };
+        let chunks = ChunkUtil::split(sensor_data, max_bytes);
+        let mut delivered = true;
+        for chunk in &chunks {
+            let description = format!(
+                "SensorDataChunk ({}/{})",
+                chunk.chunk_index + 1,
+                chunk.chunk_count
+            );
+            delivered &= Self::send_and_log(chunk, &description, server);
+        }
+        delivered
+    }
+
+    /// Sends data with retries, logs the outcome and returns whether it was delivered.
+    fn send_and_log<T: WirePayload>(data: &T, description: &str, server: &str) -> bool {
+        match NetworkUtil::send_with_retries(data, server, 3) {
+            Ok(_) => {
+                info!("{} data sent successfully.", description);
+                true
+            }
+            Err(e) if e.kind() == io::ErrorKind::WouldBlock => {
+                info!("{} data held for the next send window.", description);
+                false
+            }
+            Err(e) => {
+                error!("Failed to send {} data: {}.", description, e);
+                false
+            }
+        }
+    }
+
+    /// Sends a batch and, once delivered, records it like a single report.
+    fn send_batch(batch: &SensorDataBatch, server: &str) {
+        let description = format!("SensorDataBatch ({} samples)", batch.samples.len());
+        if !Self::send_and_log(batch, &description, server) {
+            InventoryUtil::invalidate();
+            return;
+        }
+        let (Some(first), Some(last)) = (batch.samples.first(), batch.samples.last()) else {
+            return;
         };
+        Self::record_delivery(
+            server,
+            &last.data.system_info.hostname,
+            Some(first.timestamp_ms / 1000),
+            last.data.system_info.uptime.boot_time,
+            first.data.system_info.rebooted_since_last_report,
+        );
+    }
 
-        // Send data to the server
-        send_and_log(&sensor_data, "SensorDataDTO", server);
+    /// Declares any gap before a delivered report and remembers what was reported.
+    ///
+    /// `oldest_sample_at` is the collection time (seconds since the Unix epoch) of the
+    /// oldest delivered sample if it was collected before now, as with batches.
+    fn record_delivery(
+        server: &str,
+        hostname: &str,
+        oldest_sample_at: Option<u64>,
+        boot_time: u64,
+        rebooted: bool,
+    ) {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let last_report_at = AgentState::update(|state| {
+            state.last_reported_boot_time = Some(boot_time);
+            state.last_report_at.replace(now)
+        });
+        if Config::get().report_gaps {
+            let gap_end = oldest_sample_at.unwrap_or(now);
+            if let Some(gap) = GapUtil::detect(hostname, last_report_at, gap_end, rebooted) {
+                GapUtil::declare(&gap, server);
+            }
+        }
     }
 
     // --------------------------------------
@@ -134,6 +493,37 @@ impl SensorUtils {
         line.contains("coretemp-")
     }
 
+    /// Checks if a line names a chip, e.g. `nct6798-isa-0290`; reading lines carry a label
+    /// and `Adapter:` lines are skipped by the same check.
+    fn is_chip_line(line: &str) -> bool {
+        !line.trim().is_empty() && !line.starts_with(char::is_whitespace) && !line.contains(':')
+    }
+
+    /// Checks if a line holds a fan speed, e.g. `fan1: 1200 RPM` or a label from
+    /// `sensors.conf` such as `CPU Fan: 1200 RPM`.
+    fn is_fan_line(line: &str) -> bool {
+        line.split_once(':')
+            .is_some_and(|(_, reading)| reading.split_whitespace().nth(1) == Some("RPM"))
+    }
+
+    /// Checks if a line holds a voltage, e.g. `in0: 304.00 mV`, `Vcore: +1.02 V` or
+    /// `+12V: +12.10 V`.
+    fn is_voltage_line(line: &str) -> bool {
+        line.split_once(':').is_some_and(|(_, reading)| {
+            matches!(reading.split_whitespace().nth(1), Some("V" | "mV"))
+        })
+    }
+
+    /// Checks if a line holds a power reading, e.g. `power1: 130.00 W`.
+    fn is_power_line(line: &str) -> bool {
+        line.split_once(':').is_some_and(|(_, reading)| {
+            matches!(
+                reading.split_whitespace().nth(1),
+                Some("uW" | "mW" | "W" | "kW")
+            )
+        })
+    }
+
     /// Checks if a line indicates a package.
     fn is_package_line(line: &str) -> bool {
         line.contains("Package id")
@@ -150,67 +540,266 @@ impl SensorUtils {
 
     /// Parses an adapter line into a `CpuPackageData` placeholder.
     fn parse_adapter_line(line: &str) -> CpuPackageData {
-        let adapter_name = line
-            .split_whitespace()
-            .next()
-            .unwrap_or("Unknown")
-            .to_string();
+        let adapter_name = first_token(line).unwrap_or("Unknown").to_string();
         CpuPackageData {
             package_id: String::new(),
             adapter_name,
-            package_temperature: 0.0,
-            high_threshold: 0.0,
-            critical_threshold: 0.0,
+            package_temperature: Celsius::default(),
+            high_threshold: Celsius::default(),
+            critical_threshold: Celsius::default(),
             cores: Vec::new(),
         }
     }
 
     /// Parses a package line and updates the `CpuPackageData`.
-    fn parse_package_line(line: &str, package: &mut CpuPackageData) {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 10 {
-            package.package_id = parts[2].to_string();
-            package.package_temperature = parts[3]
-                .trim_start_matches('+')
-                .trim_end_matches("°C")
-                .parse()
-                .unwrap_or(0.0);
-            package.high_threshold = parts[6]
-                .trim_start_matches('+')
-                .trim_end_matches("°C")
-                .parse()
-                .unwrap_or(0.0);
-            package.critical_threshold = parts[9]
-                .trim_start_matches('+')
-                .trim_end_matches("°C")
-                .parse()
-                .unwrap_or(0.0);
-        }
+    ///
+    /// Expects lines such as `Package id 0:  +48.0°C  (high = +80.0°C, crit = +100.0°C)`.
+    fn parse_package_line(line: &str, package: &mut CpuPackageData) -> ParseResult<()> {
+        let (label, reading) = split_label(line)?;
+        package.package_id = label.trim_start_matches("Package id").trim().to_string();
+        package.high_threshold = Self::parse_threshold(reading, "high");
+        package.critical_threshold = Self::parse_threshold(reading, "crit");
+        package.package_temperature = Self::parse_reading(label, reading)?;
+        Ok(())
     }
 
     /// Parses a core line and adds a `CpuCoreData` to the `CpuPackageData`.
-    fn parse_core_line(line: &str, package: &mut CpuPackageData) {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 6 {
-            let core_data = CpuCoreData {
-                core_name: parts[0].to_string(),
-                temperature: parts[1]
-                    .trim_start_matches('+')
-                    .trim_end_matches("°C")
-                    .parse()
-                    .unwrap_or(0.0),
-                high_threshold: parts[4]
-                    .trim_start_matches('+')
-                    .trim_end_matches("°C")
-                    .parse()
-                    .unwrap_or(0.0),
-                critical_threshold: parts[5]
-                    .trim_start_matches('+')
-                    .trim_end_matches("°C")
-                    .parse()
-                    .unwrap_or(0.0),
-            };
-            package.cores.push(core_data);
+    ///
+    /// Expects lines such as `Core 0:        +45.0°C  (high = +80.0°C, crit = +100.0°C)`.
+    fn parse_core_line(line: &str, package: &mut CpuPackageData) -> ParseResult<()> {
+        let (label, reading) = split_label(line)?;
+        package.cores.push(CpuCoreData {
+            core_name: label.to_string(),
+            temperature: Self::parse_reading(label, reading)?,
+            high_threshold: Self::parse_threshold(reading, "high"),
+            critical_threshold: Self::parse_threshold(reading, "crit"),
+        });
+        Ok(())
+    }
+
+    /// Parses a fan line of `chip` into a `FanInfo`.
+    ///
+    /// Expects lines such as `fan2:  1200 RPM  (min =  300 RPM)`.
+    fn parse_fan_line(line: &str, chip: &str) -> ParseResult<FanInfo> {
+        let (label, reading) = split_label(line)?;
+        let speed = first_token(reading)
+            .and_then(parse_number)
+            .ok_or_else(|| ParseError::new(format!("expected a fan speed, found `{}`", reading)))
+            .map_err(|e| e.context(label))?;
+        Ok(FanInfo {
+            adapter_name: chip.to_string(),
+            label: label.to_string(),
+            speed: speed as f32,
+            min_speed: named_token(reading, "min")
+                .and_then(parse_number)
+                .map(|value| value as f32),
+        })
+    }
+
+    /// Parses a voltage line of `chip` into a `VoltageInfo`.
+    ///
+    /// Expects lines such as `in0:  304.00 mV (min =  +0.00 V, max =  +1.74 V)`.
+    fn parse_voltage_line(line: &str, chip: &str) -> ParseResult<VoltageInfo> {
+        let (label, reading) = split_label(line)?;
+        let voltage = Self::parse_volts(reading)
+            .ok_or_else(|| ParseError::new(format!("expected a voltage, found `{}`", reading)))
+            .map_err(|e| e.context(label))?;
+        let threshold = |name: &str| {
+            reading
+                .split_once(&format!("{} =", name))
+                .and_then(|(_, rest)| Self::parse_volts(rest))
+        };
+        Ok(VoltageInfo {
+            adapter_name: chip.to_string(),
+            label: label.to_string(),
+            voltage,
+            min_voltage: threshold("min"),
+            max_voltage: threshold("max"),
+        })
+    }
+
+    /// Parses a power line of `chip` into a `PowerInfo`.
+    ///
+    /// Expects lines such as `power1:  130.00 W  (interval =   1.00 s)`.
+    fn parse_power_line(line: &str, chip: &str) -> ParseResult<PowerInfo> {
+        let (label, reading) = split_label(line)?;
+        let mut tokens = reading.split_whitespace();
+        let watts = tokens
+            .next()
+            .and_then(parse_number)
+            .zip(tokens.next())
+            .and_then(|(value, unit)| match unit {
+                "uW" => Some(value / 1_000_000.0),
+                "mW" => Some(value / 1000.0),
+                "W" => Some(value),
+                "kW" => Some(value * 1000.0),
+                _ => None,
+            })
+            .ok_or_else(|| ParseError::new(format!("expected a power, found `{}`", reading)))
+            .map_err(|e| e.context(label))?;
+        Ok(PowerInfo {
+            adapter_name: chip.to_string(),
+            label: label.to_string(),
+            power: watts as f32,
+            energy: None,
+        })
+    }
+
+    /// Parses the voltage at the start of `text` (e.g. `304.00 mV` or `+1.74 V,`) in volts.
+    fn parse_volts(text: &str) -> Option<f32> {
+        let mut tokens = text.split_whitespace();
+        let value = parse_number(tokens.next()?)?;
+        match tokens.next()?.trim_end_matches([',', ')']) {
+            "V" => Some(value as f32),
+            "mV" => Some((value / 1000.0) as f32),
+            _ => None,
         }
     }
+
+    /// Parses the first temperature of a reading, e.g. `+45.0°C  (high = ...)`.
+    fn parse_reading(label: &str, reading: &str) -> ParseResult<Celsius> {
+        parse_temp(first_token(reading).unwrap_or_default()).map_err(|e| e.context(label))
+    }
+
+    /// Parses the threshold following `name =` in a reading, e.g. `crit = +100.0°C)`;
+    /// 0 when absent or unreadable.
+    fn parse_threshold(reading: &str, name: &str) -> Celsius {
+        named_token(reading, name)
+            .and_then(|token| parse_temp(token).ok())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_packages_fans_voltages_and_power_of_every_chip() {
+        let raw = "\
+coretemp-isa-0000
+Adapter: ISA adapter
+Package id 0:  +48.0°C  (high = +80.0°C, crit = +100.0°C)
+Core 0:        +45.0°C  (high = +80.0°C, crit = +100.0°C)
+
+nct6798-isa-0290
+Adapter: ISA adapter
+in0:                      304.00 mV (min =  +0.00 V, max =  +1.74 V)
+Vcore:                    +1.02 V  (min =  +0.80 V, max =  +1.55 V)
++12V:                    +12.10 V  (min = +10.80 V, max = +13.20 V)
+CPU Fan:                  1200 RPM  (min =  300 RPM)
+fan2:                        0 RPM  (min =    0 RPM)
+SYSTIN:                    +32.0°C  (high = +80.0°C, hyst = +75.0°C)
+
+power_meter-acpi-0
+Adapter: ACPI interface
+power1:                   130.00 W  (interval =   1.00 s)
+";
+        let (packages, chips) = SensorUtils::parse_sensor_data(raw);
+        assert_eq!(packages.len(), 1);
+        assert_eq!(packages[0].cores.len(), 1);
+
+        let voltages: Vec<_> = chips
+            .voltages
+            .iter()
+            .map(|rail| {
+                (
+                    rail.label.as_str(),
+                    rail.voltage,
+                    rail.min_voltage,
+                    rail.max_voltage,
+                )
+            })
+            .collect();
+        assert_eq!(
+            voltages,
+            [
+                ("in0", 0.304, Some(0.0), Some(1.74)),
+                ("Vcore", 1.02, Some(0.8), Some(1.55)),
+                ("+12V", 12.1, Some(10.8), Some(13.2)),
+            ]
+        );
+
+        let fans: Vec<_> = chips
+            .fans
+            .iter()
+            .map(|fan| {
+                (
+                    fan.adapter_name.as_str(),
+                    fan.label.as_str(),
+                    fan.speed,
+                    fan.min_speed,
+                )
+            })
+            .collect();
+        assert_eq!(
+            fans,
+            [
+                ("nct6798-isa-0290", "CPU Fan", 1200.0, Some(300.0)),
+                ("nct6798-isa-0290", "fan2", 0.0, Some(0.0)),
+            ]
+        );
+
+        let power: Vec<_> = chips
+            .power
+            .iter()
+            .map(|power| {
+                (
+                    power.adapter_name.as_str(),
+                    power.label.as_str(),
+                    power.power,
+                )
+            })
+            .collect();
+        assert_eq!(power, [("power_meter-acpi-0", "power1", 130.0)]);
+    }
+
+    #[test]
+    fn parses_package_and_core_lines() {
+        let raw = "\
+coretemp-isa-0001
+Adapter: ISA adapter
+Package id 1:  +48.5°C  (high = +80.0°C, crit = +100.0°C)
+Core 0:        +45.0°C  (high = +82.0°C, crit = +102.0°C)
+Core 12:       +51.0°C
+Core 13:       N/A
+";
+        let (packages, _) = SensorUtils::parse_sensor_data(raw);
+        let package = &packages[0];
+        assert_eq!(package.adapter_name, "coretemp-isa-0001");
+        assert_eq!(package.package_id, "1");
+        assert_eq!(
+            (
+                package.package_temperature,
+                package.high_threshold,
+                package.critical_threshold
+            ),
+            (Celsius(48.5), Celsius(80.0), Celsius(100.0))
+        );
+
+        let cores: Vec<_> = package
+            .cores
+            .iter()
+            .map(|core| {
+                (
+                    core.core_name.as_str(),
+                    core.temperature,
+                    core.high_threshold,
+                    core.critical_threshold,
+                )
+            })
+            .collect();
+        assert_eq!(
+            cores,
+            [
+                ("Core 0", Celsius(45.0), Celsius(82.0), Celsius(102.0)),
+                (
+                    "Core 12",
+                    Celsius(51.0),
+                    Celsius::default(),
+                    Celsius::default()
+                ),
+            ]
+        );
+    }
 }
@@ -0,0 +1,220 @@
+#![cfg(unix)]
+
+//! Thermal Source Reconciliation
+//!
+//! When several thermal sources can read the same CPU packages, a mismatch between them
+//! usually means a misread sensor (wrong TjMax, a stale driver, a mislabelled chip) rather
+//! than a real temperature. With a `[thermal_reconcile]` section, every listed source is
+//! read on each cycle; the first one in priority order that returns packages is reported
+//! in `cpu_packages`, and packages whose readings spread wider than the tolerance are
+//! listed in `thermal_disagreements`. Any local thermal source (`sensors`, `hwmon`, `msr`)
+//! can take part; IPMI is not one, as the agent only queries the BMCs of remote hosts.
+
+use log::{debug, error, info, warn};
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use crate::data::models::{CpuPackageData, ThermalDisagreement, ThermalReading};
+use crate::hardware::msr_thermal::ThermalSource;
+use crate::sensor::sensor_util::SensorUtils;
+
+/// Configuration of the `[thermal_reconcile]` section.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ThermalReconcileConfig {
+    /// Sources read on every cycle, highest priority first.
+    pub sources: Vec<ThermalSource>,
+    /// Largest tolerated spread between the sources' package temperatures, in °C.
+    pub max_disagreement_celsius: f32,
+}
+
+impl Default for ThermalReconcileConfig {
+    fn default() -> Self {
+        Self {
+            sources: vec![ThermalSource::Sensors, ThermalSource::Msr],
+            max_disagreement_celsius: 5.0,
+        }
+    }
+}
+
+/// Package ids currently in disagreement, so transitions are logged once.
+static DISAGREEING: Mutex<Option<HashSet<String>>> = Mutex::new(None);
+
+/// Static utility class for reconciling thermal sources.
+pub struct ThermalReconcile;
+
+impl ThermalReconcile {
+    /// Reads all configured sources and returns the primary source's packages together
+    /// with the packages the sources disagree on, if any.
+    pub fn collect(
+        config: &ThermalReconcileConfig,
+    ) -> (Vec<CpuPackageData>, Option<Vec<ThermalDisagreement>>) {
+        let mut readings: Vec<(ThermalSource, Vec<CpuPackageData>)> = Vec::new();
+        for &source in &config.sources {
+            if readings.iter().any(|(read, _)| *read == source) {
+                continue;
+            }
+            match SensorUtils::collect_from(source) {
+                Ok(packages) if !packages.is_empty() => readings.push((source, packages)),
+                Ok(_) => debug!("Thermal source {} reported no packages.", source.as_str()),
+                Err(e) => debug!("Thermal source {} failed: {}", source.as_str(), e),
+            }
+        }
+
+        if readings.is_empty() {
+            error!("Error retrieving sensor data: no thermal source returned any packages");
+            return (Vec::new(), None);
+        }
+        let (primary_source, primary) = readings.remove(0);
+        let disagreements = Self::disagreements(
+            primary_source,
+            &primary,
+            &readings,
+            config.max_disagreement_celsius,
+        );
+
+        Self::log_transitions(&disagreements);
+        (
+            primary,
+            (!disagreements.is_empty()).then_some(disagreements),
+        )
+    }
+
+    /// Compares every package of the primary source with the same package of the other
+    /// sources, and returns those whose readings spread wider than `max_spread` °C.
+    pub fn disagreements(
+        primary_source: ThermalSource,
+        primary: &[CpuPackageData],
+        others: &[(ThermalSource, Vec<CpuPackageData>)],
+        max_spread: f32,
+    ) -> Vec<ThermalDisagreement> {
+        let mut disagreements = Vec::new();
+        for package in primary {
+            let mut package_readings = vec![ThermalReading {
+                source: primary_source.as_str().to_string(),
+                temperature: package.package_temperature,
+            }];
+            package_readings.extend(others.iter().filter_map(|(source, packages)| {
+                let other = packages
+                    .iter()
+                    .find(|other| other.package_id == package.package_id)?;
+                Some(ThermalReading {
+                    source: source.as_str().to_string(),
+                    temperature: other.package_temperature,
+                })
+            }));
+
+            let (min, max) =
+                package_readings
+                    .iter()
+                    .fold((f32::MAX, f32::MIN), |(min, max), reading| {
+                        (
+                            min.min(reading.temperature.0),
+                            max.max(reading.temperature.0),
+                        )
+                    });
+            let spread = max - min;
+            if spread > max_spread {
+                disagreements.push(ThermalDisagreement {
+                    package_id: package.package_id.clone(),
+                    primary_source: primary_source.as_str().to_string(),
+                    spread,
+                    readings: package_readings,
+                });
+            }
+        }
+        disagreements
+    }
+
+    /// Logs packages that started or stopped disagreeing since the previous cycle.
+    fn log_transitions(disagreements: &[ThermalDisagreement]) {
+        let mut guard = DISAGREEING.lock().unwrap_or_else(|e| e.into_inner());
+        let previous = guard.take().unwrap_or_default();
+        let current: HashSet<String> = disagreements
+            .iter()
+            .map(|disagreement| disagreement.package_id.clone())
+            .collect();
+
+        for disagreement in disagreements {
+            if !previous.contains(&disagreement.package_id) {
+                let readings: Vec<String> = disagreement
+                    .readings
+                    .iter()
+                    .map(|reading| format!("{} {:.1}", reading.source, reading.temperature))
+                    .collect();
+                warn!(
+                    "Thermal sources disagree on package {} by {:.1}°C ({}).",
+                    disagreement.package_id,
+                    disagreement.spread,
+                    readings.join(", ")
+                );
+            }
+        }
+        for package_id in previous.difference(&current) {
+            info!("Thermal sources agree again on package {}.", package_id);
+        }
+        *guard = Some(current);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::units::Celsius;
+
+    fn package(package_id: &str, temperature: f32) -> CpuPackageData {
+        CpuPackageData {
+            package_id: package_id.to_string(),
+            adapter_name: String::new(),
+            package_temperature: Celsius(temperature),
+            high_threshold: Celsius::default(),
+            critical_threshold: Celsius::default(),
+            cores: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn flags_packages_spreading_wider_than_the_tolerance() {
+        let primary = [package("0", 60.0), package("1", 55.0)];
+        let others = [
+            (
+                ThermalSource::Msr,
+                vec![package("0", 62.0), package("1", 70.0)],
+            ),
+            (ThermalSource::Hwmon, vec![package("1", 54.0)]),
+        ];
+
+        let disagreements =
+            ThermalReconcile::disagreements(ThermalSource::Sensors, &primary, &others, 5.0);
+
+        assert_eq!(disagreements.len(), 1);
+        let disagreement = &disagreements[0];
+        assert_eq!(disagreement.package_id, "1");
+        assert_eq!(disagreement.primary_source, "sensors");
+        assert_eq!(disagreement.spread, 16.0);
+        let readings: Vec<_> = disagreement
+            .readings
+            .iter()
+            .map(|reading| (reading.source.as_str(), reading.temperature.0))
+            .collect();
+        assert_eq!(
+            readings,
+            [("sensors", 55.0), ("msr", 70.0), ("hwmon", 54.0)]
+        );
+    }
+
+    #[test]
+    fn tolerates_spreads_up_to_the_limit_and_unmatched_packages() {
+        let primary = [package("0", 60.0), package("1", 40.0)];
+        let others = [(
+            ThermalSource::Msr,
+            vec![package("0", 65.0), package("2", 90.0)],
+        )];
+
+        let disagreements =
+            ThermalReconcile::disagreements(ThermalSource::Sensors, &primary, &others, 5.0);
+
+        assert!(disagreements.is_empty());
+    }
+}
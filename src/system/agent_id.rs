@@ -0,0 +1,126 @@
+//! Persistent Agent ID
+//!
+//! Host names and addresses change, so each agent identifies itself with a random UUID
+//! generated on first start and kept in `agent-id` next to the configuration file (or in
+//! the state directory if the executable's directory is read-only). The ID is included in
+//! every report, and the agent registers it with the server once, retrying at each start
+//! until the server has accepted it.
+
+use log::{error, info, warn};
+use ring::rand::{SecureRandom, SystemRandom};
+use std::fs;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use crate::config::config_instance::Config;
+use crate::config::config_loader::executable_dir;
+use crate::data::models::{AgentRegistration, SCHEMA_VERSION};
+use crate::network::network_util::NetworkUtil;
+use crate::system::state::AgentState;
+
+/// File name of the persisted agent ID.
+const AGENT_ID_FILE_NAME: &str = "agent-id";
+
+/// The agent ID, loaded or generated on first use.
+static AGENT_ID: OnceLock<String> = OnceLock::new();
+
+/// A utility class for the agent's persistent identity.
+pub struct AgentId;
+
+impl AgentId {
+    /// Returns the agent ID, generating and persisting it on first start.
+    pub fn get() -> &'static str {
+        AGENT_ID.get_or_init(Self::load_or_create)
+    }
+
+    /// Registers the agent ID with the server unless it has already been accepted.
+    pub fn register_if_needed(server: &str) {
+        let agent_id = Self::get();
+        if AgentState::load().registered_agent_id.as_deref() == Some(agent_id) {
+            return;
+        }
+
+        let registration = AgentRegistration {
+            schema_version: SCHEMA_VERSION,
+            agent_id: agent_id.to_string(),
+            hostname: sysinfo::System::host_name().unwrap_or_default(),
+            agent_version: env!("CARGO_PKG_VERSION").to_string(),
+            os_name: sysinfo::System::name().unwrap_or_default(),
+            os_version: sysinfo::System::os_version().unwrap_or_default(),
+            kernel_version: sysinfo::System::kernel_version().unwrap_or_default(),
+            cpu_arch: sysinfo::System::cpu_arch(),
+        };
+        match NetworkUtil::send_object_to_server(&registration, server) {
+            Ok(_) => {
+                info!("Registered agent {} with the server.", agent_id);
+                AgentState::update(|state| {
+                    state.registered_agent_id = Some(agent_id.to_string());
+                });
+            }
+            Err(e) => warn!(
+                "Failed to register agent {} ({}); retrying at the next start.",
+                agent_id, e
+            ),
+        }
+    }
+
+    fn load_or_create() -> String {
+        let paths = [
+            Path::new(&executable_dir()).join(AGENT_ID_FILE_NAME),
+            Config::state_file(AGENT_ID_FILE_NAME),
+        ];
+        if let Some(agent_id) = paths.iter().find_map(|path| Self::read(path)) {
+            return agent_id;
+        }
+
+        let agent_id = Self::generate();
+        match paths.iter().find(|path| fs::write(path, &agent_id).is_ok()) {
+            Some(path) => info!("Generated agent ID {} ({}).", agent_id, path.display()),
+            None => error!(
+                "Failed to persist agent ID {}; a new one is generated at the next start.",
+                agent_id
+            ),
+        }
+        agent_id
+    }
+
+    fn read(path: &Path) -> Option<String> {
+        let agent_id = fs::read_to_string(path).ok()?.trim().to_string();
+        (!agent_id.is_empty()).then_some(agent_id)
+    }
+
+    /// Generates a random (version 4) UUID from the operating system's random number
+    /// generator (`getrandom`, or `/dev/urandom` on older kernels).
+    fn generate() -> String {
+        let mut bytes = [0u8; 16];
+        SystemRandom::new()
+            .fill(&mut bytes)
+            .expect("the operating system's random number generator failed");
+        bytes[6] = (bytes[6] & 0x0f) | 0x40;
+        bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+        let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+        format!(
+            "{}-{}-{}-{}-{}",
+            &hex[0..8],
+            &hex[8..12],
+            &hex[12..16],
+            &hex[16..20],
+            &hex[20..32]
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generates_distinct_version_4_uuids() {
+        let first = AgentId::generate();
+        assert_eq!(first.len(), 36);
+        assert_eq!(&first[14..15], "4");
+        assert!("89ab".contains(&first[19..20]));
+        assert_ne!(first, AgentId::generate());
+    }
+}
@@ -0,0 +1,225 @@
+#![cfg(unix)]
+
+//! Air-Gapped Bundles
+//!
+//! This module implements the `bundle` subcommands, which move data from hosts without any
+//! network path to the server on removable media:
+//! - `bundle export` writes a tar archive of the running agent's recent history (read over
+//!   the control socket), a fresh inventory and the agent's status.
+//! - `bundle import` sends the history of such an archive to a server from a connected host.
+//!
+//! Archive layout (`BUNDLE_VERSION` 1), for servers importing bundles directly:
+//! - `manifest.json`: a `BundleManifest`.
+//! - `inventory.json`: the `HostInventory` at export time.
+//! - `status.json`: a `BundleStatus`.
+//! - `history/<unix_millis>-sensors.json`: one `SensorData` payload (snake_case) per file,
+//!   oldest first; the same naming `replay` uses, so an extracted directory can be replayed.
+
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::cli::{BundleExportOptions, BundleImportOptions};
+use crate::config::config_instance::Config;
+use crate::data::history::HistoryEntry;
+use crate::data::models::HostInventory;
+use crate::hardware::system_information_monitor::SysInfoMonitor;
+use crate::network::network_util::NetworkUtil;
+use crate::sensor::sensor_util::SensorUtils;
+use crate::system::control_socket::ControlSocket;
+use crate::system::inventory_util::InventoryUtil;
+use crate::system::state::AgentState;
+
+/// Version of the bundle layout. Bump on any incompatible change.
+pub const BUNDLE_VERSION: u32 = 1;
+
+/// Describes the contents of a bundle.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct BundleManifest {
+    pub bundle_version: u32,
+    pub hostname: String,
+    pub agent_version: String,
+    /// Seconds since the Unix epoch at which the bundle was written.
+    pub exported_at: u64,
+    /// Minutes of history requested from the agent.
+    pub history_minutes: u64,
+    /// Number of payloads under `history/`.
+    pub sample_count: usize,
+    /// Seconds since the Unix epoch of the oldest and newest payload.
+    pub first_sample_at: Option<u64>,
+    pub last_sample_at: Option<u64>,
+}
+
+/// State of the agent at export time.
+#[derive(Serialize, Debug)]
+pub struct BundleStatus {
+    /// Whether the running agent answered on the control socket.
+    pub agent_reachable: bool,
+    /// The agent's `status` reply, e.g. `log_level=info`.
+    pub agent_status: Option<String>,
+    /// Persisted agent state (last delivered report, last reported boot).
+    pub state: AgentState,
+}
+
+/// A utility class for exporting and importing air-gapped bundles.
+pub struct BundleUtil;
+
+impl BundleUtil {
+    /// Writes a bundle to `options.output`, or to `gilded-sentinel-<host>-<time>.tar`.
+    pub fn export(options: &BundleExportOptions) -> io::Result<()> {
+        let exported_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let history = Self::agent_history(options.minutes);
+        let agent_status = ControlSocket::request("status").ok();
+        let inventory = Self::inventory();
+
+        let manifest = BundleManifest {
+            bundle_version: BUNDLE_VERSION,
+            hostname: inventory.hostname.clone(),
+            agent_version: env!("CARGO_PKG_VERSION").to_string(),
+            exported_at,
+            history_minutes: options.minutes,
+            sample_count: history.len(),
+            first_sample_at: history.first().map(|entry| entry.timestamp),
+            last_sample_at: history.last().map(|entry| entry.timestamp),
+        };
+        let status = BundleStatus {
+            agent_reachable: agent_status.is_some(),
+            agent_status,
+            state: AgentState::load(),
+        };
+
+        let output = options.output.clone().unwrap_or_else(|| {
+            format!("gilded-sentinel-{}-{}.tar", inventory.hostname, exported_at)
+        });
+        let mut archive = tar::Builder::new(File::create(&output)?);
+        Self::append_json(&mut archive, "manifest.json", &manifest, exported_at)?;
+        Self::append_json(&mut archive, "inventory.json", &inventory, exported_at)?;
+        Self::append_json(&mut archive, "status.json", &status, exported_at)?;
+        for entry in &history {
+            let name = format!("history/{}-sensors.json", entry.timestamp * 1000);
+            Self::append_json(&mut archive, &name, &entry.data, entry.timestamp)?;
+        }
+        archive.into_inner()?.sync_all()?;
+
+        info!("Wrote bundle {} with {} payload(s).", output, history.len());
+        println!("{}", output);
+        Ok(())
+    }
+
+    /// Sends the history payloads of a bundle to `options.target` or the configured server.
+    pub fn import(options: &BundleImportOptions) -> io::Result<()> {
+        let target = options.target.as_deref().unwrap_or(Config::server());
+        let casing = Config::get().field_casing;
+        let mut archive = tar::Archive::new(File::open(&options.path)?);
+
+        let mut sent = 0;
+        let mut failures = 0;
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let path = entry.path()?.into_owned();
+            let mut contents = Vec::new();
+            entry.read_to_end(&mut contents)?;
+
+            if path == Path::new("manifest.json") {
+                let manifest: BundleManifest = serde_json::from_slice(&contents)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                if manifest.bundle_version > BUNDLE_VERSION {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "Bundle version {} is newer than supported ({})",
+                            manifest.bundle_version, BUNDLE_VERSION
+                        ),
+                    ));
+                }
+                info!(
+                    "Importing {} payload(s) from {} exported at {}.",
+                    manifest.sample_count, manifest.hostname, manifest.exported_at
+                );
+                continue;
+            }
+            if !path.starts_with("history") {
+                continue;
+            }
+
+            // Payloads are stored in snake_case; apply the configured casing on the way out.
+            let result = serde_json::from_slice(&contents)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+                .and_then(|value| {
+                    serde_json::to_vec(&casing.apply(value))
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+                })
+                .and_then(|body| {
+                    NetworkUtil::send_payload_to_server(
+                        &body,
+                        "application/json",
+                        target,
+                        None,
+                        None,
+                    )
+                });
+            match result {
+                Ok(_) => sent += 1,
+                Err(e) => {
+                    error!("Failed to import {}: {}", path.display(), e);
+                    failures += 1;
+                }
+            }
+        }
+
+        info!("Imported {} payload(s) to {}.", sent, target);
+        if failures > 0 {
+            return Err(io::Error::other(format!(
+                "{} of {} payload(s) failed to import",
+                failures,
+                sent + failures
+            )));
+        }
+        Ok(())
+    }
+
+    /// Reads recent history from the running agent; empty if it cannot be reached.
+    fn agent_history(minutes: u64) -> Vec<HistoryEntry> {
+        match ControlSocket::request(&format!("history {}", minutes)) {
+            Ok(reply) => serde_json::from_str(&reply).unwrap_or_else(|e| {
+                warn!("Ignoring unreadable history from the agent: {}", e);
+                Vec::new()
+            }),
+            Err(e) => {
+                warn!(
+                    "Could not read history from the running agent ({}); exporting without it.",
+                    e
+                );
+                Vec::new()
+            }
+        }
+    }
+
+    fn inventory() -> HostInventory {
+        let mut monitor = SysInfoMonitor::new();
+        let readings = SensorUtils::collect_sensor_data(&mut monitor);
+        InventoryUtil::build(&monitor, &readings)
+    }
+
+    fn append_json<W: io::Write, T: Serialize>(
+        archive: &mut tar::Builder<W>,
+        name: &str,
+        value: &T,
+        mtime: u64,
+    ) -> io::Result<()> {
+        let contents = serde_json::to_vec_pretty(value)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+        let mut header = tar::Header::new_ustar();
+        header.set_size(contents.len() as u64);
+        header.set_mode(0o644);
+        header.set_mtime(mtime);
+        header.set_cksum();
+        archive.append_data(&mut header, name, contents.as_slice())
+    }
+}
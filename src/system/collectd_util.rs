@@ -0,0 +1,99 @@
+#![cfg(unix)]
+
+//! collectd Output Mode
+//!
+//! This module implements the `collectd` subcommand, which writes `PUTVAL` commands to stdout
+//! for collectd's `exec` plugin instead of sending payloads to the server. The process prints
+//! a snapshot every interval until collectd closes the pipe:
+//! - The host name is taken from `COLLECTD_HOSTNAME`, which collectd sets for exec plugins,
+//!   falling back to the local host name.
+//! - The interval is taken from `COLLECTD_INTERVAL` (seconds), falling back to
+//!   `interval_secs`.
+//!
+//! ```text
+//! <Plugin exec>
+//!   Exec "nobody" "/opt/gilded-sentinel/Gilded-Sentinel-Client" "collectd"
+//! </Plugin>
+//! ```
+
+use log::{info, warn};
+use std::env;
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::config::cli::CollectdOptions;
+use crate::config::config_instance::Config;
+use crate::data::collectd::PutVal;
+use crate::hardware::system_information_monitor::SysInfoMonitor;
+use crate::sensor::sensor_util::SensorUtils;
+
+/// A utility class for the collectd output mode.
+pub struct CollectdUtil;
+
+impl CollectdUtil {
+    /// Runs the collectd output mode described by `options`.
+    pub fn run(options: &CollectdOptions) -> io::Result<()> {
+        let interval = Self::interval();
+        let host = env::var("COLLECTD_HOSTNAME")
+            .ok()
+            .filter(|host| !host.is_empty());
+        let mut monitor = SysInfoMonitor::new();
+
+        // CPU usage is computed between two refreshes; prime the first one.
+        monitor.get_cpu_info();
+        thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+
+        if options.once {
+            return Self::emit(&mut monitor, host.as_deref(), interval);
+        }
+
+        info!("collectd exec mode started with a {:?} interval.", interval);
+        loop {
+            let started = Instant::now();
+            if let Err(e) = Self::emit(&mut monitor, host.as_deref(), interval) {
+                // collectd closes the pipe when it stops or reloads the plugin.
+                if e.kind() == io::ErrorKind::BrokenPipe {
+                    info!("Stdout closed; leaving collectd exec mode.");
+                    return Ok(());
+                }
+                return Err(e);
+            }
+            thread::sleep(interval.saturating_sub(started.elapsed()));
+        }
+    }
+
+    /// Collects one snapshot and writes it to stdout as `PUTVAL` commands.
+    fn emit(
+        monitor: &mut SysInfoMonitor,
+        host: Option<&str>,
+        interval: Duration,
+    ) -> io::Result<()> {
+        let data = SensorUtils::collect_sensor_data(monitor);
+        let timestamp_secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs_f64())
+            .unwrap_or_default();
+        let host = host.unwrap_or(&data.system_info.hostname);
+
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(
+            PutVal::encode(&data, host, interval.as_secs_f64(), timestamp_secs).as_bytes(),
+        )?;
+        stdout.flush()
+    }
+
+    /// The collection interval requested by collectd, or `interval_secs`.
+    fn interval() -> Duration {
+        match env::var("COLLECTD_INTERVAL") {
+            Ok(value) => match value.trim().parse::<f64>() {
+                Ok(secs) if secs.is_finite() && secs > 0.0 => Duration::from_secs_f64(secs),
+                _ => {
+                    warn!("Ignoring invalid COLLECTD_INTERVAL {:?}.", value);
+                    Duration::from_secs(Config::interval_secs())
+                }
+            },
+            Err(_) => Duration::from_secs(Config::interval_secs()),
+        }
+    }
+}
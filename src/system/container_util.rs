@@ -0,0 +1,195 @@
+#![cfg(unix)]
+
+//! Container Awareness
+//!
+//! Inside a container, `/proc` describes either the host (plain namespaces) or the
+//! container (lxcfs), and the container's own cgroup limits are not visible at all. When
+//! the agent detects that it is containerized, it reads the cgroup v2 limits of its own
+//! cgroup and, if the host's root filesystem is mounted at `host_root`, takes the host
+//! totals from there. Reports keep host totals in the usual `MemoryInfo`/`CpuInfo` fields
+//! and carry the container's limits in separate `container_*` fields.
+
+use log::{debug, info};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use crate::config::config_instance::Config;
+use crate::data::models::{CpuInfo, MemoryInfo};
+use crate::data::units::Bytes;
+
+/// Root of the unified (v2) cgroup hierarchy.
+const CGROUP_ROOT: &str = "/sys/fs/cgroup";
+/// Substrings of `/proc/1/cgroup` entries that indicate a container runtime.
+const RUNTIME_MARKERS: &[&str] = &["docker", "kubepods", "containerd", "libpod", "lxc"];
+
+/// Whether the agent runs in a container, detected once.
+static CONTAINERIZED: OnceLock<bool> = OnceLock::new();
+
+/// A utility class for container detection and cgroup limits.
+pub struct ContainerUtil;
+
+impl ContainerUtil {
+    /// Returns whether the agent runs inside a container.
+    pub fn is_containerized() -> bool {
+        *CONTAINERIZED.get_or_init(|| {
+            let containerized = Path::new("/.dockerenv").exists()
+                || Path::new("/run/.containerenv").exists()
+                || std::env::var_os("container").is_some()
+                || fs::read_to_string("/proc/1/cgroup").is_ok_and(|cgroups| {
+                    RUNTIME_MARKERS
+                        .iter()
+                        .any(|marker| cgroups.contains(marker))
+                });
+            if containerized {
+                info!("Running in a container; reporting container limits separately.");
+            }
+            containerized
+        })
+    }
+
+    /// Replaces the container's view with host totals (when `host_root` is configured) and
+    /// fills in the container's limits. Does nothing outside containers.
+    pub fn apply(memory: &mut MemoryInfo, cpu: &mut CpuInfo) {
+        if !Self::is_containerized() {
+            return;
+        }
+
+        if let Some(host_root) = &Config::get().host_root {
+            let host_root = Path::new(host_root);
+            match Self::host_memory(host_root) {
+                Some((total, used, total_swap, used_swap)) => {
+                    memory.total = Bytes(total);
+                    memory.used = Bytes(used);
+                    memory.total_swap = Bytes(total_swap);
+                    memory.used_swap = Bytes(used_swap);
+                }
+                None => debug!("No host meminfo under {}.", host_root.display()),
+            }
+            match Self::host_cpu_count(host_root) {
+                Some(count) => cpu.core_count = count,
+                None => debug!("No host CPU list under {}.", host_root.display()),
+            }
+        }
+
+        let Some(cgroup) = Self::own_cgroup() else {
+            debug!("Agent cgroup not found under {}.", CGROUP_ROOT);
+            return;
+        };
+        memory.container_limit = Self::read_u64(&cgroup.join("memory.max")).map(Bytes);
+        memory.container_used = Self::read_u64(&cgroup.join("memory.current")).map(Bytes);
+        cpu.container_cpu_limit = Self::cpu_limit(&cgroup.join("cpu.max"));
+    }
+
+    /// Directory of the agent's own cgroup in the v2 hierarchy.
+    pub fn own_cgroup() -> Option<PathBuf> {
+        let cgroups = fs::read_to_string("/proc/self/cgroup").ok()?;
+        let path = cgroups.lines().find_map(|line| line.strip_prefix("0::"))?;
+        let dir = Path::new(CGROUP_ROOT).join(path.trim_start_matches('/'));
+        // With a private cgroup namespace the container's cgroup is mounted as the root.
+        [dir, PathBuf::from(CGROUP_ROOT)]
+            .into_iter()
+            .find(|dir| dir.join("cgroup.controllers").exists())
+    }
+
+    /// Reads host memory totals in bytes from `<host_root>/proc/meminfo`:
+    /// (total, used, total swap, used swap).
+    fn host_memory(host_root: &Path) -> Option<(u64, u64, u64, u64)> {
+        let meminfo = fs::read_to_string(host_root.join("proc/meminfo")).ok()?;
+        let field = |name: &str| {
+            meminfo.lines().find_map(|line| {
+                let value = line.strip_prefix(name)?.strip_prefix(':')?;
+                value
+                    .trim()
+                    .trim_end_matches("kB")
+                    .trim()
+                    .parse::<u64>()
+                    .ok()
+            })
+        };
+
+        let total = field("MemTotal")?;
+        let available = field("MemAvailable")?;
+        let total_swap = field("SwapTotal").unwrap_or(0);
+        let free_swap = field("SwapFree").unwrap_or(0);
+        Some((
+            total * 1024,
+            total.saturating_sub(available) * 1024,
+            total_swap * 1024,
+            total_swap.saturating_sub(free_swap) * 1024,
+        ))
+    }
+
+    /// Counts the host's online CPUs from a list such as `0-3,6,8-9`.
+    fn host_cpu_count(host_root: &Path) -> Option<usize> {
+        let online = fs::read_to_string(host_root.join("sys/devices/system/cpu/online")).ok()?;
+        online.trim().split(',').try_fold(0, |count, range| {
+            let (first, last) = range.split_once('-').unwrap_or((range, range));
+            let (first, last) = (first.parse::<usize>().ok()?, last.parse::<usize>().ok()?);
+            Some(count + last.checked_sub(first)? + 1)
+        })
+    }
+
+    /// Parses a CPU quota of the form `<quota> <period>` (or `max <period>`) into cores.
+    fn cpu_limit(path: &Path) -> Option<f64> {
+        let content = fs::read_to_string(path).ok()?;
+        let mut fields = content.split_whitespace();
+        let quota = fields.next()?.parse::<f64>().ok()?;
+        let period = fields.next()?.parse::<f64>().ok()?;
+        (period > 0.0).then(|| quota / period)
+    }
+
+    /// Reads a single number; `max` (unlimited) and missing files yield `None`.
+    fn read_u64(path: &Path) -> Option<u64> {
+        fs::read_to_string(path).ok()?.trim().parse().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn reads_host_totals_under_host_root() {
+        let host_root = TempDir::new("host-root");
+        fs::create_dir_all(host_root.join("proc")).unwrap();
+        fs::create_dir_all(host_root.join("sys/devices/system/cpu")).unwrap();
+        fs::write(
+            host_root.join("proc/meminfo"),
+            "MemTotal:       16384 kB\nMemFree:         1024 kB\nMemAvailable:    4096 kB\n\
+             SwapTotal:       2048 kB\nSwapFree:        1536 kB\n",
+        )
+        .unwrap();
+        fs::write(
+            host_root.join("sys/devices/system/cpu/online"),
+            "0-3,6,8-9\n",
+        )
+        .unwrap();
+
+        assert_eq!(
+            ContainerUtil::host_memory(&host_root),
+            Some((16384 * 1024, 12288 * 1024, 2048 * 1024, 512 * 1024))
+        );
+        assert_eq!(ContainerUtil::host_cpu_count(&host_root), Some(7));
+    }
+
+    #[test]
+    fn reads_cgroup_limits() {
+        let cgroup = TempDir::new("cgroup-limits");
+        fs::write(cgroup.join("cpu.max"), "150000 100000\n").unwrap();
+        fs::write(cgroup.join("memory.max"), "max\n").unwrap();
+        fs::write(cgroup.join("memory.current"), "524288\n").unwrap();
+
+        assert_eq!(ContainerUtil::cpu_limit(&cgroup.join("cpu.max")), Some(1.5));
+        assert_eq!(ContainerUtil::read_u64(&cgroup.join("memory.max")), None);
+        assert_eq!(
+            ContainerUtil::read_u64(&cgroup.join("memory.current")),
+            Some(524288)
+        );
+
+        // An unlimited quota reads as `max <period>`.
+        fs::write(cgroup.join("cpu.max"), "max 100000\n").unwrap();
+        assert_eq!(ContainerUtil::cpu_limit(&cgroup.join("cpu.max")), None);
+    }
+}
@@ -0,0 +1,194 @@
+#![cfg(unix)]
+
+//! Control Socket
+//!
+//! This module runs a local Unix socket through which a running agent can be inspected and
+//! adjusted, and implements the `status` subcommand that talks to it. Access is governed by
+//! the socket file's permissions (owner and group only). Each connection sends one command
+//! line and receives one reply line:
+//! - `status`: the current log level and the seconds until it reverts.
+//! - `set-log-level <level> [secs]`: changes the log level, reverting after `secs` (default
+//!   `log_level_revert_secs`, `0` for never).
+//! - `history <minutes>`: the payloads recorded in the last `minutes`, as a JSON array.
+//!
+//! `status` prints the reply as localized text in the current `OutputStyle`.
+
+use log::{debug, error, info, warn};
+use std::fs;
+use std::io::{self, BufRead, BufReader, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::thread;
+use std::time::Duration;
+
+use crate::config::cli::StatusOptions;
+use crate::config::config_instance::Config;
+use crate::config::i18n::Text;
+use crate::data::history::History;
+use crate::system::log_level::LogLevelUtil;
+use crate::system::output_style::OutputStyle;
+
+/// Timeout for reading a command and writing its reply.
+const IO_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A utility class for the local control socket.
+pub struct ControlSocket;
+
+impl ControlSocket {
+    /// Starts the listener on a background thread if `control_socket` is configured.
+    pub fn start_if_configured() {
+        let Some(path) = Config::get().control_socket.clone() else {
+            return;
+        };
+
+        // A socket left behind by a previous run would make the bind fail.
+        if fs::symlink_metadata(&path).is_ok() {
+            let _ = fs::remove_file(&path);
+        }
+        match UnixListener::bind(&path) {
+            Ok(listener) => {
+                if let Err(e) = fs::set_permissions(&path, fs::Permissions::from_mode(0o660)) {
+                    warn!("Failed to restrict permissions of {}: {}", path, e);
+                }
+                info!("Control socket listening on {}", path);
+                thread::spawn(move || Self::serve(listener));
+            }
+            Err(e) => error!("Failed to bind control socket to {}: {}", path, e),
+        }
+    }
+
+    fn serve(listener: UnixListener) {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    if let Err(e) = Self::handle_connection(stream) {
+                        debug!("Control socket connection failed: {}", e);
+                    }
+                }
+                Err(e) => warn!("Failed to accept control socket connection: {}", e),
+            }
+        }
+    }
+
+    fn handle_connection(mut stream: UnixStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        stream.set_write_timeout(Some(IO_TIMEOUT))?;
+
+        let mut command = String::new();
+        BufReader::new(stream.try_clone()?).read_line(&mut command)?;
+        let reply = Self::execute(&command);
+        writeln!(stream, "{}", reply)?;
+        stream.flush()
+    }
+
+    /// Executes one command line and returns the reply line.
+    fn execute(command: &str) -> String {
+        let mut words = command.split_whitespace();
+        match (words.next(), words.next(), words.next()) {
+            (Some("status"), None, None) => Self::status_line(),
+            (Some("set-log-level"), Some(level), secs) => {
+                let Some(level) = LogLevelUtil::parse(level) else {
+                    return format!("error unknown log level '{}'", level);
+                };
+                match secs.map(str::parse::<u64>) {
+                    None => LogLevelUtil::set_temporarily(level),
+                    Some(Ok(0)) => LogLevelUtil::set(level, None),
+                    Some(Ok(secs)) => LogLevelUtil::set(level, Some(Duration::from_secs(secs))),
+                    Some(Err(_)) => return "error invalid duration".to_string(),
+                }
+                Self::status_line()
+            }
+            (Some("history"), Some(minutes), None) => match minutes.parse::<u64>() {
+                Ok(minutes) => serde_json::to_string(&History::since_minutes(minutes))
+                    .unwrap_or_else(|e| format!("error {}", e)),
+                Err(_) => "error invalid number of minutes".to_string(),
+            },
+            _ => format!("error unknown command '{}'", command.trim()),
+        }
+    }
+
+    fn status_line() -> String {
+        let (level, revert_in) = LogLevelUtil::status();
+        let mut line = format!("log_level={}", level.as_str().to_lowercase());
+        if let Some(secs) = revert_in {
+            line.push_str(&format!(" revert_in_secs={}", secs));
+        }
+        line
+    }
+
+    /// Sends the command described by `options` to the running agent and prints its reply.
+    pub fn print_status(options: &StatusOptions) -> io::Result<()> {
+        let command = match &options.set_log_level {
+            Some(level) => match options.duration_secs {
+                Some(secs) => format!("set-log-level {} {}", level, secs),
+                None => format!("set-log-level {}", level),
+            },
+            None => "status".to_string(),
+        };
+        let reply = Self::request(&command)?;
+        println!("{}", Self::describe_status(&reply, OutputStyle::current()));
+        Ok(())
+    }
+
+    /// Renders a `status` reply as localized text: one sentence in the rich style, one
+    /// fact per line in the plain style.
+    fn describe_status(reply: &str, style: OutputStyle) -> String {
+        let mut facts = Vec::new();
+        for (key, value) in reply
+            .split_whitespace()
+            .filter_map(|pair| pair.split_once('='))
+        {
+            match (key, value.parse::<u64>()) {
+                ("log_level", _) => facts.push(Text::LogLevel(value).render()),
+                ("revert_in_secs", Ok(secs)) => facts.push(Text::RevertsIn(secs).render()),
+                _ => {}
+            }
+        }
+        match (style, facts.split_first()) {
+            (_, None) => reply.to_string(),
+            (OutputStyle::Rich, Some((first, rest))) if !rest.is_empty() => {
+                format!("{} ({})", first, rest.join(", "))
+            }
+            _ => facts
+                .iter()
+                .map(|fact| Self::capitalize(fact))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        }
+    }
+
+    fn capitalize(text: &str) -> String {
+        let mut chars = text.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    }
+
+    /// Sends one command to the running agent and returns its reply; error replies are
+    /// returned as errors.
+    pub fn request(command: &str) -> io::Result<String> {
+        let path = Config::get().control_socket.clone().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                "No control_socket configured; the agent does not accept commands",
+            )
+        })?;
+
+        let mut stream = UnixStream::connect(&path)
+            .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path, e)))?;
+        stream.set_read_timeout(Some(IO_TIMEOUT))?;
+        writeln!(stream, "{}", command)?;
+        let mut reply = String::new();
+        BufReader::new(stream).read_line(&mut reply)?;
+
+        let reply = reply.trim();
+        if let Some(message) = reply.strip_prefix("error ") {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                message.to_string(),
+            ));
+        }
+        Ok(reply.to_string())
+    }
+}
@@ -0,0 +1,60 @@
+#![cfg(unix)]
+
+//! Host Facts
+//!
+//! This module implements the `facts` subcommand: one collection pass printed to stdout as
+//! a single JSON document (inventory plus current readings), suitable for Ansible local
+//! facts (`/etc/ansible/facts.d/*.fact`) or other configuration management tools.
+
+use std::io::{self, Write};
+use std::thread;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::cli::FactsOptions;
+use crate::data::models::{FactsDocument, FACTS_VERSION};
+use crate::hardware::system_information_monitor::SysInfoMonitor;
+use crate::sensor::sensor_util::SensorUtils;
+use crate::system::inventory_util::InventoryUtil;
+
+/// A utility class for producing host facts.
+pub struct FactsUtil;
+
+impl FactsUtil {
+    /// Collects one snapshot and writes the facts document to stdout.
+    pub fn print(options: &FactsOptions) -> io::Result<()> {
+        let document = Self::collect();
+        let json = if options.pretty {
+            serde_json::to_string_pretty(&document)
+        } else {
+            serde_json::to_string(&document)
+        }
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+        let mut stdout = io::stdout().lock();
+        writeln!(stdout, "{}", json)?;
+        stdout.flush()
+    }
+
+    /// Builds the facts document from a fresh collection pass.
+    pub fn collect() -> FactsDocument {
+        let mut monitor = SysInfoMonitor::new();
+
+        // CPU usage is computed between two refreshes; prime the first one.
+        monitor.get_cpu_info();
+        thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+
+        let readings = SensorUtils::collect_sensor_data(&mut monitor);
+        let inventory = InventoryUtil::build(&monitor, &readings);
+
+        FactsDocument {
+            facts_version: FACTS_VERSION,
+            agent_version: env!("CARGO_PKG_VERSION").to_string(),
+            collected_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default(),
+            inventory,
+            readings,
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! Host Inventory
+//!
+//! This module builds the slow-changing description of a host (OS, kernel, CPU layout,
+//! memory, disk and interface names). Reports carry the inventory only when its hash
+//! changed since the last delivered report, or when `inventory_heartbeat_secs` have passed
+//! so a server that lost its state converges again; every report carries the hash.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::config_instance::Config;
+use crate::data::models::{HostInventory, SensorData};
+use crate::hardware::system_information_monitor::SysInfoMonitor;
+use crate::hardware::system_source::SystemSource;
+
+/// Hash of the last inventory handed out for delivery, and when.
+static LAST_SENT: Mutex<Option<(String, Instant)>> = Mutex::new(None);
+
+/// A utility class for building and deduplicating the host inventory.
+pub struct InventoryUtil;
+
+impl InventoryUtil {
+    /// Builds the inventory from the monitor and a snapshot collected from it.
+    pub fn build<S: SystemSource>(
+        monitor: &SysInfoMonitor<S>,
+        readings: &SensorData,
+    ) -> HostInventory {
+        HostInventory {
+            hostname: readings.system_info.hostname.clone(),
+            management_ip: readings.system_info.management_ip.clone(),
+            os_name: monitor.get_os_name(),
+            os_version: monitor.get_os_version(),
+            kernel_version: monitor.get_kernel_version(),
+            boot_time: readings.system_info.uptime.boot_time,
+            cpu_arch: readings.cpu_info.cpu_arch.clone(),
+            core_count: readings.cpu_info.core_count,
+            cpu_package_count: readings.cpu_packages.len(),
+            memory_total: readings.memory_info.total,
+            swap_total: readings.memory_info.total_swap,
+            disk_names: readings.disks.iter().map(|d| d.name.clone()).collect(),
+            network_interface_names: readings
+                .network_interfaces
+                .iter()
+                .map(|n| n.interface_name.clone())
+                .collect(),
+        }
+    }
+
+    /// Sets the inventory hash of a report, and attaches the inventory itself if it changed
+    /// or the heartbeat is due.
+    pub fn attach<S: SystemSource>(data: &mut SensorData, monitor: &SysInfoMonitor<S>) {
+        let inventory = Self::build(monitor, data);
+        let hash = Self::hash(&inventory);
+        let heartbeat = Duration::from_secs(Config::get().inventory_heartbeat_secs);
+
+        let mut last = LAST_SENT.lock().unwrap_or_else(|e| e.into_inner());
+        let due = match &*last {
+            Some((sent_hash, sent_at)) => *sent_hash != hash || sent_at.elapsed() >= heartbeat,
+            None => true,
+        };
+        if due {
+            *last = Some((hash.clone(), Instant::now()));
+            data.inventory = Some(inventory);
+        }
+        data.inventory_hash = Some(hash);
+    }
+
+    /// Forgets the last sent inventory after a report carrying it was not delivered, so the
+    /// next report carries it again.
+    pub fn invalidate() {
+        *LAST_SENT.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+
+    /// FNV-1a hash of the inventory's JSON encoding, as 16 hex digits; stable across
+    /// agent restarts and versions with the same inventory fields. The boot time is left
+    /// out: it is derived from the uptime and jitters by a second between reports, and
+    /// reboots are reported on their own (`rebooted_since_last_report`).
+    fn hash(inventory: &HostInventory) -> String {
+        let mut value = serde_json::to_value(inventory).unwrap_or_default();
+        if let Some(fields) = value.as_object_mut() {
+            fields.shift_remove("boot_time");
+        }
+        let json = serde_json::to_vec(&value).unwrap_or_default();
+        let hash = json.iter().fold(0xcbf2_9ce4_8422_2325u64, |hash, byte| {
+            (hash ^ u64::from(*byte)).wrapping_mul(0x0000_0100_0000_01b3)
+        });
+        format!("{:016x}", hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::units::Bytes;
+
+    fn inventory(boot_time: u64, kernel_version: &str) -> HostInventory {
+        HostInventory {
+            hostname: "sentinel-test".to_string(),
+            management_ip: "192.168.1.10".to_string(),
+            os_name: "Debian GNU/Linux".to_string(),
+            os_version: "12".to_string(),
+            kernel_version: kernel_version.to_string(),
+            boot_time,
+            cpu_arch: "x86_64".to_string(),
+            core_count: 8,
+            cpu_package_count: 1,
+            memory_total: Bytes(16_000_000_000),
+            swap_total: Bytes(0),
+            disk_names: vec!["/dev/sda1".to_string()],
+            network_interface_names: vec!["eth0".to_string()],
+        }
+    }
+
+    #[test]
+    fn hash_ignores_boot_time_jitter() {
+        let hash = InventoryUtil::hash(&inventory(1_700_000_000, "6.1.0"));
+        assert_eq!(hash.len(), 16);
+        assert_eq!(
+            InventoryUtil::hash(&inventory(1_700_000_001, "6.1.0")),
+            hash
+        );
+        assert_ne!(
+            InventoryUtil::hash(&inventory(1_700_000_000, "6.1.1")),
+            hash
+        );
+    }
+}
@@ -0,0 +1,165 @@
+//! Live Log Level
+//!
+//! This module raises or lowers the log verbosity of a running agent, so intermittent
+//! issues can be chased without a restart. Overrides come from SIGUSR1 (toggles `debug`) or
+//! the control socket (`status --set-log-level`), and revert to the startup level after
+//! `log_level_revert_secs` unless another override replaced them first. The startup level
+//! is the one `RUST_LOG` selects (`info` by default).
+
+use log::{info, warn, LevelFilter, Log, Metadata, Record};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::config::config_instance::Config;
+use crate::system::signal::register_debug_toggle_signal;
+
+/// How often the SIGUSR1 watcher checks for toggle requests.
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Log level in effect at startup, restored when an override expires.
+static STARTUP_LEVEL: OnceLock<LevelFilter> = OnceLock::new();
+
+/// Incremented with every override, so a stale revert does not undo a newer one.
+static GENERATION: AtomicU64 = AtomicU64::new(0);
+
+/// Time at which the active override reverts, if any.
+static REVERT_AT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// Static utility class for adjusting the log level at runtime.
+pub struct LogLevelUtil;
+
+impl LogLevelUtil {
+    /// Records the log level selected at startup and applies it.
+    pub fn set_startup_level(level: LevelFilter) {
+        let _ = STARTUP_LEVEL.set(level);
+        log::set_max_level(level);
+    }
+
+    /// Returns the log level selected at startup (`info` until it is recorded).
+    pub fn startup_level() -> LevelFilter {
+        STARTUP_LEVEL.get().copied().unwrap_or(LevelFilter::Info)
+    }
+
+    /// Sets the log level, reverting to the startup level after `duration` (never if `None`).
+    pub fn set(level: LevelFilter, duration: Option<Duration>) {
+        // Logged before the change, so lowering the level does not hide the message.
+        let name = level.as_str().to_lowercase();
+        match duration {
+            Some(duration) => info!("Log level set to {} for {} s.", name, duration.as_secs()),
+            None => info!("Log level set to {}.", name),
+        }
+
+        let generation = GENERATION.fetch_add(1, Ordering::SeqCst) + 1;
+        *REVERT_AT.lock().unwrap_or_else(|e| e.into_inner()) =
+            duration.map(|duration| Instant::now() + duration);
+        log::set_max_level(level);
+
+        if let Some(duration) = duration {
+            thread::spawn(move || {
+                thread::sleep(duration);
+                if GENERATION.load(Ordering::SeqCst) == generation {
+                    Self::reset();
+                }
+            });
+        }
+    }
+
+    /// Sets the log level for the configured `log_level_revert_secs`.
+    pub fn set_temporarily(level: LevelFilter) {
+        Self::set(level, Some(Self::revert_after()));
+    }
+
+    /// Toggles debug logging whenever the process receives SIGUSR1.
+    pub fn listen_for_signal() {
+        let toggle = Arc::new(AtomicBool::new(false));
+        if let Err(e) = register_debug_toggle_signal(&toggle) {
+            warn!("Failed to register SIGUSR1 for log level toggling: {}", e);
+            return;
+        }
+        thread::spawn(move || loop {
+            if toggle.swap(false, Ordering::Relaxed) {
+                Self::toggle_debug();
+            }
+            thread::sleep(POLL_INTERVAL);
+        });
+    }
+
+    /// Switches to `debug`, or back to the startup level if an override is active.
+    pub fn toggle_debug() {
+        if log::max_level() == Self::startup_level() {
+            Self::set_temporarily(LevelFilter::Debug);
+        } else {
+            Self::reset();
+        }
+    }
+
+    /// Restores the startup log level.
+    pub fn reset() {
+        GENERATION.fetch_add(1, Ordering::SeqCst);
+        *REVERT_AT.lock().unwrap_or_else(|e| e.into_inner()) = None;
+        let level = Self::startup_level();
+        log::set_max_level(level);
+        info!("Log level reverted to {}.", level.as_str().to_lowercase());
+    }
+
+    /// Returns the current log level and the seconds until it reverts, if it will.
+    pub fn status() -> (LevelFilter, Option<u64>) {
+        let revert_at = *REVERT_AT.lock().unwrap_or_else(|e| e.into_inner());
+        (
+            log::max_level(),
+            revert_at.map(|at| at.saturating_duration_since(Instant::now()).as_secs()),
+        )
+    }
+
+    /// Parses a level name (`error`, `warn`, `info`, `debug`, `trace`, `off`).
+    pub fn parse(level: &str) -> Option<LevelFilter> {
+        LevelFilter::from_str(level.trim()).ok()
+    }
+
+    fn revert_after() -> Duration {
+        Duration::from_secs(Config::get().log_level_revert_secs.max(1))
+    }
+}
+
+/// Logger that applies the `RUST_LOG` filter, and passes the agent's own records up to the
+/// current level while it is raised above the startup level.
+pub struct RuntimeLevelLogger {
+    configured: env_logger::Logger,
+    raised: env_logger::Logger,
+}
+
+impl RuntimeLevelLogger {
+    /// Creates the logger from one built from `RUST_LOG` and one that lets the agent's own
+    /// records pass at every level.
+    pub fn new(configured: env_logger::Logger, raised: env_logger::Logger) -> Self {
+        Self { configured, raised }
+    }
+
+    fn is_raised_for(metadata: &Metadata) -> bool {
+        metadata.target().starts_with(env!("CARGO_CRATE_NAME"))
+            && log::max_level() > LogLevelUtil::startup_level()
+            && metadata.level() <= log::max_level()
+    }
+}
+
+impl Log for RuntimeLevelLogger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.configured.enabled(metadata) || Self::is_raised_for(metadata)
+    }
+
+    fn log(&self, record: &Record) {
+        if self.configured.matches(record) {
+            self.configured.log(record);
+        } else if Self::is_raised_for(record.metadata()) {
+            self.raised.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        self.configured.flush();
+        self.raised.flush();
+    }
+}
@@ -1,4 +1,19 @@
+pub mod agent_id;
+pub mod bundle_util;
+pub mod collectd_util;
+pub mod container_util;
+pub mod control_socket;
 pub mod execution_util;
+pub mod facts_util;
 pub mod installer;
+pub mod inventory_util;
+pub mod log_level;
+pub mod output_style;
+pub mod package_util;
+pub mod priority_util;
+pub mod scheduler;
+pub mod self_metrics;
 pub mod signal;
+pub mod state;
 pub mod system_util;
+pub mod telegraf_util;
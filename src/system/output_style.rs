@@ -0,0 +1,41 @@
+//! Output Style
+//!
+//! Human-readable subcommand output comes in two styles: `Rich`, which may use colors and
+//! compact sentences on an interactive terminal, and `Plain`, which uses no colors or
+//! decoration and puts one fact per line, for screen readers, braille displays and pipes.
+//! `Plain` is selected by `--plain`, `plain_output = true`, `NO_COLOR` or `TERM=dumb`, and
+//! automatically when stdout is not a terminal.
+
+use std::env;
+
+use crate::config::config_instance::Config;
+use crate::system::system_util::SystemUtil;
+
+/// Presentation of human-readable output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputStyle {
+    Rich,
+    Plain,
+}
+
+impl OutputStyle {
+    /// The style selected by the configuration, the environment and stdout.
+    pub fn current() -> Self {
+        if Config::get().plain_output || Self::plain_from_environment() || !SystemUtil::is_tty() {
+            OutputStyle::Plain
+        } else {
+            OutputStyle::Rich
+        }
+    }
+
+    /// Whether plain output is requested before the configuration is loaded; the logger
+    /// starts before the command line is parsed, so `--plain` is looked up directly.
+    pub fn plain_requested_early() -> bool {
+        Self::plain_from_environment() || env::args().any(|arg| arg == "--plain")
+    }
+
+    fn plain_from_environment() -> bool {
+        env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+            || env::var("TERM").is_ok_and(|term| term == "dumb")
+    }
+}
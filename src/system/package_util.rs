@@ -0,0 +1,609 @@
+#![cfg(unix)]
+
+//! Packaging
+//!
+//! This module implements the `package` subcommand, which writes the files that are not
+//! part of the build but go into a distribution package, so they are generated from the
+//! same binary they ship with:
+//! - `package --deb` writes the maintainer scripts, systemd unit, installed configuration,
+//!   man page and bash completion that `cargo deb` picks up through the
+//!   `[package.metadata.deb]` section of `Cargo.toml`:
+//!
+//!   ```text
+//!   cargo build --release
+//!   target/release/Gilded-Sentinel-Client package --deb
+//!   cargo deb --no-build
+//!   ```
+//!
+//!   The package installs the agent to `/opt/gilded-sentinel`, since the configuration is
+//!   read from the executable's directory, and runs it as the `gilded-sentinel` system user
+//!   with its state in `/var/lib/gilded-sentinel`. Collectors that need root (MSRs, SMART,
+//!   RAPL) stay empty unless the unit is overridden with `User=root`.
+//! - `package --esxi-vib` writes a VIB holding the running executable, a configuration and
+//!   an init script, so the agent survives reboots of an ESXi host instead of being copied
+//!   to a datastore and started by hand. The VIB is unsigned, so it is installed with
+//!   community acceptance:
+//!
+//!   ```text
+//!   esxcli software acceptance set --level=CommunitySupported
+//!   esxcli software vib install -v /vmfs/volumes/datastore1/gilded-sentinel-<version>.vib --no-sig-check
+//!   ```
+//!
+//!   Files installed from a VIB are read-only on the host, so the VIB carries the
+//!   configuration (the effective `server` and `interval_secs`, or `--config`) and keeps
+//!   state in `/scratch/gilded-sentinel`.
+
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use log::info;
+use ring::digest;
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::cli::{build_cli, PackageFormat, PackageOptions};
+use crate::config::config_instance::Config;
+
+/// Name of the package, system user and systemd unit.
+const PACKAGE_NAME: &str = "gilded-sentinel";
+
+/// Directory holding the executable and its configuration.
+const INSTALL_DIR: &str = "/opt/gilded-sentinel";
+
+/// Directory for persisted agent state, created by systemd (`StateDirectory=`).
+const STATE_DIR: &str = "/var/lib/gilded-sentinel";
+
+/// Link to the executable on the `PATH`.
+const BIN_LINK: &str = "/usr/bin/gilded-sentinel";
+
+/// Directory for persisted agent state on ESXi, which survives reboots unlike `/var`.
+const ESXI_STATE_DIR: &str = "/scratch/gilded-sentinel";
+
+/// A utility class for generating packaging files.
+pub struct PackageUtil;
+
+impl PackageUtil {
+    /// Writes the files for `options.format` to `options.output` and prints their paths.
+    pub fn run(options: &PackageOptions) -> io::Result<()> {
+        let output = Path::new(&options.output);
+        let written = match options.format {
+            PackageFormat::Deb => Self::write_deb_assets(output)?,
+            PackageFormat::EsxiVib => {
+                let config = match &options.config {
+                    Some(path) => fs::read_to_string(path)?,
+                    None => Self::esxi_config(Config::server(), Config::interval_secs()),
+                };
+                vec![Self::write_esxi_vib(
+                    output,
+                    &env::current_exe()?,
+                    &config,
+                    &options.acceptance_level,
+                )?]
+            }
+        };
+        info!(
+            "Wrote {} packaging file(s) to {}.",
+            written.len(),
+            output.display()
+        );
+        for path in written {
+            println!("{}", path.display());
+        }
+        Ok(())
+    }
+
+    /// Writes the `cargo deb` assets and maintainer scripts to `dir`.
+    pub fn write_deb_assets(dir: &Path) -> io::Result<Vec<PathBuf>> {
+        fs::create_dir_all(dir)?;
+        let executable = format!("{}/{}", INSTALL_DIR, env!("CARGO_PKG_NAME"));
+
+        let mut completion = Vec::new();
+        clap_complete::generate(
+            clap_complete::Shell::Bash,
+            &mut build_cli(),
+            PACKAGE_NAME,
+            &mut completion,
+        );
+        let mut man_page = Vec::new();
+        clap_mangen::Man::new(build_cli().name(PACKAGE_NAME)).render(&mut man_page)?;
+
+        let files: [(&str, Vec<u8>, u32); 7] = [
+            ("postinst", Self::postinst(&executable).into_bytes(), 0o755),
+            ("prerm", Self::prerm().into_bytes(), 0o755),
+            ("postrm", Self::postrm().into_bytes(), 0o755),
+            (
+                "gilded-sentinel.service",
+                Self::systemd_unit(&executable).into_bytes(),
+                0o644,
+            ),
+            ("config.toml", Self::installed_config().into_bytes(), 0o644),
+            ("gilded-sentinel.1", man_page, 0o644),
+            ("gilded-sentinel.bash", completion, 0o644),
+        ];
+        let mut written = Vec::new();
+        for (name, contents, mode) in files {
+            let path = dir.join(name);
+            fs::write(&path, contents)?;
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode))?;
+            written.push(path);
+        }
+        Ok(written)
+    }
+
+    /// Writes `gilded-sentinel-<version>.vib` to `dir`, holding `executable`, `config` and
+    /// the init script, and returns its path.
+    ///
+    /// A VIB is an `ar` archive of a `descriptor.xml`, an (empty) signature and a gzipped tar
+    /// payload whose checksums the descriptor lists.
+    pub fn write_esxi_vib(
+        dir: &Path,
+        executable: &Path,
+        config: &str,
+        acceptance_level: &str,
+    ) -> io::Result<PathBuf> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let files: [(String, Vec<u8>, u32); 3] = [
+            (
+                format!("{}/{}", &INSTALL_DIR[1..], env!("CARGO_PKG_NAME")),
+                fs::read(executable)?,
+                0o755,
+            ),
+            (
+                format!("{}/config.toml", &INSTALL_DIR[1..]),
+                config.as_bytes().to_vec(),
+                0o644,
+            ),
+            (
+                format!("etc/init.d/{}", PACKAGE_NAME),
+                Self::esxi_init_script().into_bytes(),
+                0o755,
+            ),
+        ];
+
+        let mut payload = tar::Builder::new(Vec::new());
+        for (path, contents, mode) in &files {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(*mode);
+            header.set_mtime(now);
+            header.set_cksum();
+            payload.append_data(&mut header, path, contents.as_slice())?;
+        }
+        let payload = payload.into_inner()?;
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(&payload)?;
+        let compressed = encoder.finish()?;
+
+        let file_list: String = files
+            .iter()
+            .map(|(path, _, _)| format!("    <file>{}</file>\n", path))
+            .collect();
+        let descriptor = format!(
+            r#"<vib version="5.0">
+  <type>bootbank</type>
+  <name>{name}</name>
+  <version>{version}-1</version>
+  <vendor>{vendor}</vendor>
+  <summary>Gilded-Sentinel monitoring agent</summary>
+  <description>{description}</description>
+  <release-date>{release_date}</release-date>
+  <urls>
+    <url key="repository">{repository}</url>
+  </urls>
+  <relationships>
+    <depends/>
+    <conflicts/>
+    <replaces/>
+    <provides/>
+    <compatibleWith/>
+  </relationships>
+  <software-tags/>
+  <system-requires>
+    <maintenance-mode>false</maintenance-mode>
+  </system-requires>
+  <file-list>
+{file_list}  </file-list>
+  <acceptance-level>{acceptance_level}</acceptance-level>
+  <live-install-allowed>true</live-install-allowed>
+  <live-remove-allowed>true</live-remove-allowed>
+  <cimom-restart>false</cimom-restart>
+  <stateless-ready>true</stateless-ready>
+  <overlay>false</overlay>
+  <payloads>
+    <payload name="payload1" type="tgz" size="{size}">
+      <checksum checksum-type="sha-256">{sha256}</checksum>
+      <checksum checksum-type="sha-1" verify-process="gunzip">{sha1}</checksum>
+    </payload>
+  </payloads>
+</vib>
+"#,
+            name = PACKAGE_NAME,
+            version = env!("CARGO_PKG_VERSION"),
+            vendor = Self::xml_escape(env!("CARGO_PKG_AUTHORS")),
+            description = Self::xml_escape(env!("CARGO_PKG_DESCRIPTION")),
+            release_date = Self::iso_date(now),
+            repository = Self::xml_escape(env!("CARGO_PKG_REPOSITORY")),
+            file_list = file_list,
+            acceptance_level = acceptance_level,
+            size = compressed.len(),
+            sha256 = Self::hex(digest::digest(&digest::SHA256, &compressed).as_ref()),
+            sha1 = Self::hex(digest::digest(&digest::SHA1_FOR_LEGACY_USE_ONLY, &payload).as_ref()),
+        );
+
+        let mut vib = b"!<arch>\n".to_vec();
+        Self::append_ar_member(&mut vib, "descriptor.xml", descriptor.as_bytes(), now);
+        Self::append_ar_member(&mut vib, "sig.pkcs7", &[], now);
+        Self::append_ar_member(&mut vib, "payload1", &compressed, now);
+
+        fs::create_dir_all(dir)?;
+        let path = dir.join(format!(
+            "{}-{}.vib",
+            PACKAGE_NAME,
+            env!("CARGO_PKG_VERSION")
+        ));
+        fs::write(&path, vib)?;
+        Ok(path)
+    }
+
+    /// Appends one member to an `ar` archive: a 60-byte header, then the data padded to an
+    /// even length.
+    fn append_ar_member(archive: &mut Vec<u8>, name: &str, data: &[u8], mtime: u64) {
+        let header = format!(
+            "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+            name,
+            mtime,
+            0,
+            0,
+            "100644",
+            data.len()
+        );
+        archive.extend_from_slice(header.as_bytes());
+        archive.extend_from_slice(data);
+        if data.len() % 2 == 1 {
+            archive.push(b'\n');
+        }
+    }
+
+    /// Escapes the characters with a special meaning in XML text and attribute values.
+    fn xml_escape(value: &str) -> String {
+        let mut escaped = String::with_capacity(value.len());
+        for c in value.chars() {
+            match c {
+                '&' => escaped.push_str("&amp;"),
+                '<' => escaped.push_str("&lt;"),
+                '>' => escaped.push_str("&gt;"),
+                '"' => escaped.push_str("&quot;"),
+                '\'' => escaped.push_str("&apos;"),
+                _ => escaped.push(c),
+            }
+        }
+        escaped
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+    }
+
+    /// Formats seconds since the Unix epoch as an ISO 8601 UTC date and time.
+    fn iso_date(secs: u64) -> String {
+        // Civil date from days since the epoch (Howard Hinnant's algorithm).
+        let days = (secs / 86_400) as i64 + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        let time = secs % 86_400;
+        format!(
+            "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}",
+            year,
+            month,
+            day,
+            time / 3600,
+            time / 60 % 60,
+            time % 60
+        )
+    }
+
+    /// Starts and stops the agent on ESXi; `chkconfig: on` starts it at boot.
+    fn esxi_init_script() -> String {
+        format!(
+            r#"#!/bin/sh
+#
+# chkconfig: on 99 1
+# description: Gilded-Sentinel monitoring agent
+
+NAME={name}
+BIN={install}/{binary}
+PIDFILE=/var/run/{name}.pid
+
+is_running() {{
+    [ -f "$PIDFILE" ] && kill -0 "$(cat "$PIDFILE")" 2>/dev/null
+}}
+
+case "$1" in
+    start)
+        if is_running; then
+            echo "$NAME is already running"
+            exit 0
+        fi
+        mkdir -p {state}
+        setsid "$BIN" </dev/null >/dev/null 2>&1 &
+        echo $! >"$PIDFILE"
+        ;;
+    stop)
+        if is_running; then
+            kill "$(cat "$PIDFILE")"
+        fi
+        rm -f "$PIDFILE"
+        ;;
+    restart)
+        "$0" stop
+        "$0" start
+        ;;
+    status)
+        if is_running; then
+            echo "$NAME is running"
+        else
+            echo "$NAME is not running"
+            exit 3
+        fi
+        ;;
+    *)
+        echo "Usage: $0 {{start|stop|restart|status}}"
+        exit 1
+        ;;
+esac
+"#,
+            name = PACKAGE_NAME,
+            install = INSTALL_DIR,
+            binary = env!("CARGO_PKG_NAME"),
+            state = ESXI_STATE_DIR,
+        )
+    }
+
+    /// The configuration shipped in the VIB unless `--config` is given.
+    fn esxi_config(server: &str, interval_secs: u64) -> String {
+        format!(
+            r#"# Gilded-Sentinel agent configuration, installed read-only by the {name} VIB.
+# Rebuild the VIB with `package --esxi-vib --config <file>` to change it.
+
+server = "{server}"
+interval_secs = {interval_secs}
+
+# Persists across reboots, unlike /var.
+state_dir = "{state}"
+"#,
+            name = PACKAGE_NAME,
+            server = server,
+            interval_secs = interval_secs,
+            state = ESXI_STATE_DIR,
+        )
+    }
+
+    /// Creates the system user, links the executable and starts the service.
+    fn postinst(executable: &str) -> String {
+        format!(
+            r#"#!/bin/sh
+set -e
+
+if [ "$1" = "configure" ]; then
+    if ! getent passwd {user} >/dev/null; then
+        adduser --system --group --quiet --no-create-home --home {state} {user}
+    fi
+    ln -sf {executable} {link}
+    if [ -d /run/systemd/system ]; then
+        systemctl daemon-reload
+        systemctl enable {user}.service >/dev/null
+        if [ -n "$2" ]; then
+            systemctl restart {user}.service
+        else
+            systemctl start {user}.service
+        fi
+    fi
+fi
+"#,
+            user = PACKAGE_NAME,
+            state = STATE_DIR,
+            executable = executable,
+            link = BIN_LINK,
+        )
+    }
+
+    /// Stops and disables the service before the package is removed (not upgraded).
+    fn prerm() -> String {
+        format!(
+            r#"#!/bin/sh
+set -e
+
+if [ "$1" = "remove" ] && [ -d /run/systemd/system ]; then
+    systemctl stop {unit}.service || true
+    systemctl disable {unit}.service >/dev/null || true
+fi
+"#,
+            unit = PACKAGE_NAME,
+        )
+    }
+
+    /// Removes the link, and on purge the agent's state and system user.
+    fn postrm() -> String {
+        format!(
+            r#"#!/bin/sh
+set -e
+
+case "$1" in
+    remove|purge)
+        rm -f {link}
+        if [ -d /run/systemd/system ]; then
+            systemctl daemon-reload || true
+        fi
+        ;;
+esac
+
+if [ "$1" = "purge" ]; then
+    rm -rf {state}
+    if getent passwd {user} >/dev/null; then
+        deluser --system --quiet {user} || true
+    fi
+fi
+"#,
+            link = BIN_LINK,
+            state = STATE_DIR,
+            user = PACKAGE_NAME,
+        )
+    }
+
+    fn systemd_unit(executable: &str) -> String {
+        format!(
+            r#"[Unit]
+Description=Gilded-Sentinel monitoring agent
+Documentation=man:{name}(1)
+Wants=network-online.target
+After=network-online.target
+
+[Service]
+ExecStart={executable}
+User={name}
+Group={name}
+StateDirectory={name}
+# Lets `cpu_quota_percent` put the agent in a child cgroup with a CPU quota.
+Delegate=cpu
+Restart=on-failure
+RestartSec=10
+
+[Install]
+WantedBy=multi-user.target
+"#,
+            name = PACKAGE_NAME,
+            executable = executable,
+        )
+    }
+
+    /// The configuration installed next to the executable; a conffile, so local changes
+    /// survive upgrades.
+    fn installed_config() -> String {
+        format!(
+            r#"# Gilded-Sentinel agent configuration.
+# Every available key is described in /usr/share/doc/{name}/config.toml.example.
+
+server = "localhost:5000"
+interval_secs = 10
+
+# Written by the `{name}` service user.
+state_dir = "{state}"
+"#,
+            name = PACKAGE_NAME,
+            state = STATE_DIR,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn writes_deb_assets() {
+        let dir = TempDir::new("deb");
+        let written = PackageUtil::write_deb_assets(&dir).unwrap();
+        assert_eq!(written.len(), 7);
+
+        let read = |name: &str| fs::read_to_string(dir.join(name)).unwrap();
+        let mode = |name: &str| fs::metadata(dir.join(name)).unwrap().permissions().mode() & 0o777;
+        let postinst = read("postinst");
+        let unit = read("gilded-sentinel.service");
+        let config: toml::Table = toml::from_str(&read("config.toml")).unwrap();
+        let (postinst_mode, unit_mode) = (mode("postinst"), mode("gilded-sentinel.service"));
+        let completion = read("gilded-sentinel.bash");
+
+        assert!(postinst.starts_with("#!/bin/sh\n"));
+        assert!(postinst.contains("adduser --system --group --quiet --no-create-home"));
+        assert!(postinst.contains("ln -sf /opt/gilded-sentinel/Gilded-Sentinel-Client"));
+        assert_eq!(postinst_mode, 0o755);
+        assert!(unit.contains("ExecStart=/opt/gilded-sentinel/Gilded-Sentinel-Client\n"));
+        assert!(unit.contains("User=gilded-sentinel\n"));
+        assert_eq!(unit_mode, 0o644);
+        assert_eq!(config["state_dir"].as_str(), Some(STATE_DIR));
+        assert!(completion.contains("gilded-sentinel"));
+    }
+
+    #[test]
+    fn escapes_xml_text() {
+        assert_eq!(
+            PackageUtil::xml_escape(r#"Laurus <dev@example.com> & "Sentinel's""#),
+            "Laurus &lt;dev@example.com&gt; &amp; &quot;Sentinel&apos;s&quot;"
+        );
+    }
+
+    #[test]
+    fn writes_an_esxi_vib() {
+        let dir = TempDir::new("vib");
+        let executable = dir.join("agent");
+        fs::write(&executable, b"\x7fELF").unwrap();
+        let config = PackageUtil::esxi_config("10.0.0.5:5000", 30);
+        let path = PackageUtil::write_esxi_vib(&dir, &executable, &config, "community").unwrap();
+        let vib = fs::read(&path).unwrap();
+
+        // Split the `ar` archive into its members.
+        assert!(vib.starts_with(b"!<arch>\n"));
+        let mut members = Vec::new();
+        let mut offset = 8;
+        while offset < vib.len() {
+            let header = std::str::from_utf8(&vib[offset..offset + 60]).unwrap();
+            let size: usize = header[48..58].trim().parse().unwrap();
+            let data = &vib[offset + 60..offset + 60 + size];
+            members.push((header[..16].trim().to_string(), data.to_vec()));
+            offset += 60 + size + size % 2;
+        }
+        let names: Vec<_> = members.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["descriptor.xml", "sig.pkcs7", "payload1"]);
+
+        let descriptor = String::from_utf8(members[0].1.clone()).unwrap();
+        let payload = &members[2].1;
+        assert!(descriptor.contains("<acceptance-level>community</acceptance-level>"));
+        assert!(descriptor.contains("<file>etc/init.d/gilded-sentinel</file>"));
+        let sha256 = PackageUtil::hex(digest::digest(&digest::SHA256, payload).as_ref());
+        assert!(descriptor.contains(&format!("<checksum checksum-type=\"sha-256\">{}<", sha256)));
+
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(payload.as_slice()));
+        let mut entries: Vec<(String, u32)> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let entry = entry.unwrap();
+                let path = entry.path().unwrap().to_string_lossy().into_owned();
+                (path, entry.header().mode().unwrap())
+            })
+            .collect();
+        entries.sort();
+        assert_eq!(
+            entries,
+            [
+                ("etc/init.d/gilded-sentinel".to_string(), 0o755),
+                (
+                    "opt/gilded-sentinel/Gilded-Sentinel-Client".to_string(),
+                    0o755
+                ),
+                ("opt/gilded-sentinel/config.toml".to_string(), 0o644),
+            ]
+        );
+        let config: toml::Table = toml::from_str(&config).unwrap();
+        assert_eq!(config["state_dir"].as_str(), Some(ESXI_STATE_DIR));
+        assert_eq!(PackageUtil::iso_date(1_792_022_400), "2026-10-15T00:00:00");
+    }
+}
@@ -0,0 +1,208 @@
+#![cfg(unix)]
+
+//! Process Priority Limits
+//!
+//! This module lowers the agent's own scheduling priority at startup so collectors can't
+//! compete with production workloads: a nice level, an I/O scheduling class (ionice) and,
+//! on cgroup v2 hosts, a CPU quota enforced by moving the agent into a child of the cgroup
+//! it was started in, so the quota stays within the service's own slice. Under systemd
+//! that cgroup needs `Delegate=cpu`; setting `CPUQuota=` on the unit instead achieves the
+//! same without a child cgroup. Commands spawned by the agent (e.g. `sensors`) inherit all
+//! three. Every step is best-effort: failures are logged and the agent keeps running
+//! unrestricted.
+
+use log::{info, warn};
+use serde::Deserialize;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::config::config_instance::Config;
+use crate::system::container_util::ContainerUtil;
+
+/// CFS period used for the CPU quota, in microseconds.
+const CPU_PERIOD_MICROS: u64 = 100_000;
+
+/// I/O scheduling class, as understood by `ionice`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum IoniceClass {
+    Realtime,
+    BestEffort,
+    Idle,
+}
+
+impl IoniceClass {
+    fn ioprio_class(self) -> i32 {
+        match self {
+            IoniceClass::Realtime => 1,
+            IoniceClass::BestEffort => 2,
+            IoniceClass::Idle => 3,
+        }
+    }
+}
+
+/// A utility class for applying process priority limits.
+pub struct PriorityUtil;
+
+impl PriorityUtil {
+    /// Applies the configured nice level, ionice class and CPU quota to this process.
+    pub fn apply() {
+        let config = Config::get();
+
+        if let Some(nice) = config.nice {
+            match Self::set_nice(nice) {
+                Ok(()) => info!("Set nice level to {}.", nice),
+                Err(e) => warn!("Failed to set nice level {}: {}", nice, e),
+            }
+        }
+
+        if let Some(class) = config.ionice_class {
+            match Self::set_ionice(class, config.ionice_level) {
+                Ok(()) => info!(
+                    "Set I/O scheduling class to {:?} (level {}).",
+                    class, config.ionice_level
+                ),
+                Err(e) => warn!("Failed to set I/O scheduling class: {}", e),
+            }
+        }
+
+        if let Some(percent) = config.cpu_quota_percent {
+            let cgroup = ContainerUtil::own_cgroup()
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::Unsupported,
+                        "this process is not in a cgroup v2 hierarchy",
+                    )
+                })
+                .and_then(|own| Self::set_cpu_quota(&own, &config.cgroup_name, percent));
+            match cgroup {
+                Ok(cgroup) => info!(
+                    "Limited CPU usage to {}% of one CPU via cgroup {}.",
+                    percent,
+                    cgroup.display()
+                ),
+                Err(e) => warn!("Failed to apply CPU quota: {}", e),
+            }
+        }
+    }
+
+    fn set_nice(nice: i32) -> io::Result<()> {
+        // The `which` parameter's type differs between libc targets.
+        let result = unsafe { libc::setpriority(libc::PRIO_PROCESS as _, 0, nice) };
+        if result == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    #[cfg(target_os = "linux")]
+    fn set_ionice(class: IoniceClass, level: u8) -> io::Result<()> {
+        const IOPRIO_WHO_PROCESS: libc::c_int = 1;
+        const IOPRIO_CLASS_SHIFT: i32 = 13;
+
+        let priority = (class.ioprio_class() << IOPRIO_CLASS_SHIFT) | i32::from(level.min(7));
+        let result =
+            unsafe { libc::syscall(libc::SYS_ioprio_set, IOPRIO_WHO_PROCESS, 0, priority) };
+        if result == -1 {
+            return Err(io::Error::last_os_error());
+        }
+        Ok(())
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn set_ionice(_class: IoniceClass, _level: u8) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "ionice is only supported on Linux",
+        ))
+    }
+
+    /// Creates (or reuses) a child of `own`, this process's cgroup, with a `cpu.max` quota
+    /// and moves this process into it. Returns the child cgroup.
+    fn set_cpu_quota(own: &Path, cgroup_name: &str, percent: u32) -> io::Result<PathBuf> {
+        // Reuse the cgroup when the agent was started inside it already.
+        let (parent, cgroup) = match own.file_name() {
+            Some(name) if name == cgroup_name => {
+                (own.parent().unwrap_or(own).to_path_buf(), own.to_path_buf())
+            }
+            _ => (own.to_path_buf(), own.join(cgroup_name)),
+        };
+        fs::create_dir_all(&cgroup)?;
+
+        // A cgroup with processes cannot hand controllers to its children, so move out of
+        // the parent before enabling the cpu controller there.
+        fs::write(cgroup.join("cgroup.procs"), std::process::id().to_string())?;
+        let enabled = fs::read_to_string(cgroup.join("cgroup.controllers"))
+            .is_ok_and(|controllers| controllers.split_whitespace().any(|c| c == "cpu"));
+        if !enabled {
+            fs::write(parent.join("cgroup.subtree_control"), "+cpu").map_err(|e| {
+                io::Error::new(
+                    e.kind(),
+                    format!(
+                        "cannot enable the cpu controller in {} (delegate it, e.g. \
+                         Delegate=cpu, or set CPUQuota= on the systemd unit instead): {}",
+                        parent.display(),
+                        e
+                    ),
+                )
+            })?;
+        }
+
+        let quota = (CPU_PERIOD_MICROS * u64::from(percent.max(1)) / 100).max(1_000);
+        fs::write(
+            cgroup.join("cpu.max"),
+            format!("{} {}", quota, CPU_PERIOD_MICROS),
+        )?;
+        Ok(cgroup)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::TempDir;
+
+    #[test]
+    fn creates_the_quota_cgroup_under_the_own_cgroup() {
+        let root = TempDir::new("cpu-quota-child");
+        let own = root.join("system.slice/gilded-sentinel.service");
+        fs::create_dir_all(&own).unwrap();
+
+        let cgroup = PriorityUtil::set_cpu_quota(&own, "gilded-sentinel", 25).unwrap();
+        assert_eq!(cgroup, own.join("gilded-sentinel"));
+        assert_eq!(
+            fs::read_to_string(cgroup.join("cgroup.procs")).unwrap(),
+            std::process::id().to_string()
+        );
+        assert_eq!(
+            fs::read_to_string(own.join("cgroup.subtree_control")).unwrap(),
+            "+cpu"
+        );
+        assert_eq!(
+            fs::read_to_string(cgroup.join("cpu.max")).unwrap(),
+            "25000 100000"
+        );
+    }
+
+    #[test]
+    fn reuses_the_own_cgroup_when_it_has_the_name() {
+        let root = TempDir::new("cpu-quota-reuse");
+        let own = root.join("system.slice/gilded-sentinel");
+        fs::create_dir_all(&own).unwrap();
+        // The cpu controller is already enabled, so the parent is left alone.
+        fs::write(
+            own.join("cgroup.controllers"),
+            "cpuset cpu io memory pids\n",
+        )
+        .unwrap();
+
+        let cgroup = PriorityUtil::set_cpu_quota(&own, "gilded-sentinel", 0).unwrap();
+        assert_eq!(cgroup, own);
+        assert!(!root.join("system.slice/cgroup.subtree_control").exists());
+        assert_eq!(
+            fs::read_to_string(own.join("cpu.max")).unwrap(),
+            "1000 100000"
+        );
+    }
+}
@@ -0,0 +1,380 @@
+//! Collection Scheduling
+//!
+//! Samples are taken at fixed deadlines `t0 + n·interval` on the monotonic clock rather than
+//! by sleeping a full interval after each collection, so collection time does not make a
+//! long-running agent drift. The clock is injectable for tests.
+//!
+//! Deadlines can be missed because a cycle overran them, the process was descheduled (heavy
+//! load) or the host was suspended; the monotonic clock stops during suspend, so that is
+//! detected by comparing it with the wall clock. What happens then is the `CatchUpPolicy`:
+//! missed ticks are skipped and the next sample waits for the following deadline (the
+//! gap is declared by `report_gaps`), or one catch-up sample is taken right away. Missed
+//! ticks never fire back to back.
+//!
+//! Fleets of agents sharing an interval can spread their load on the server with a random
+//! startup splay and a per-cycle jitter; jitter delays each sample within its slot without
+//! shifting later deadlines.
+//!
+//! Sleeps on the system clock can be cut short with `WakeableClock::wake` for an on-demand
+//! collection (e.g. requested by the server); the deadline that was waited for is kept.
+
+use serde::Deserialize;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::{Condvar, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Set by `WakeableClock::wake` until a sleep consumes it.
+static WAKE_REQUESTED: Mutex<bool> = Mutex::new(false);
+static WAKE: Condvar = Condvar::new();
+
+/// What to do when deadlines were missed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CatchUpPolicy {
+    /// Skip the missed deadlines and wait for the next one.
+    #[default]
+    Skip,
+    /// Take one sample right away, then continue with the next deadline.
+    Sample,
+}
+
+/// Source of monotonic time, wall time and sleeping.
+pub trait Clock {
+    fn now(&self) -> Instant;
+    /// The wall clock, which keeps running while the host is suspended.
+    fn wall(&self) -> SystemTime;
+    fn sleep(&self, duration: Duration);
+}
+
+/// The system clock, whose sleeps end early once `wake` is called.
+pub struct WakeableClock;
+
+impl WakeableClock {
+    /// Ends the current (or next) sleep on this clock.
+    pub fn wake() {
+        *WAKE_REQUESTED.lock().unwrap_or_else(|e| e.into_inner()) = true;
+        WAKE.notify_all();
+    }
+}
+
+impl Clock for WakeableClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+
+    fn wall(&self) -> SystemTime {
+        SystemTime::now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        let requested = WAKE_REQUESTED.lock().unwrap_or_else(|e| e.into_inner());
+        let (mut requested, _) = WAKE
+            .wait_timeout_while(requested, duration, |requested| !*requested)
+            .unwrap_or_else(|e| e.into_inner());
+        *requested = false;
+    }
+}
+
+/// Fixed-rate schedule of collection deadlines.
+pub struct Scheduler<C: Clock = WakeableClock> {
+    clock: C,
+    start: Instant,
+    interval: Duration,
+    /// Last deadline that was sampled or skipped.
+    tick: u64,
+    jitter: Duration,
+    catch_up: CatchUpPolicy,
+    /// Time the host spent suspended, which the monotonic clock does not count.
+    suspended: Duration,
+    /// Monotonic and wall time of the previous reading, to detect suspends.
+    last_reading: (Instant, SystemTime),
+    rng: Rng,
+}
+
+impl Scheduler<WakeableClock> {
+    /// Creates a schedule on the system clock whose first deadline is now.
+    pub fn new(interval: Duration) -> Self {
+        Self::with_clock(WakeableClock, interval)
+    }
+}
+
+impl<C: Clock> Scheduler<C> {
+    /// Creates a schedule on `clock` whose first deadline is the clock's current time.
+    pub fn with_clock(clock: C, interval: Duration) -> Self {
+        Self {
+            start: clock.now(),
+            last_reading: (clock.now(), clock.wall()),
+            clock,
+            interval: interval.max(Duration::from_millis(1)),
+            tick: 0,
+            jitter: Duration::ZERO,
+            catch_up: CatchUpPolicy::Skip,
+            suspended: Duration::ZERO,
+            rng: Rng::new(),
+        }
+    }
+
+    /// Sets what happens when deadlines were missed.
+    pub fn with_catch_up(mut self, catch_up: CatchUpPolicy) -> Self {
+        self.catch_up = catch_up;
+        self
+    }
+
+    /// Delays every sample by a random amount up to `jitter`, capped at half the interval.
+    pub fn with_jitter(mut self, jitter: Duration) -> Self {
+        self.jitter = jitter.min(self.interval / 2);
+        self
+    }
+
+    /// Changes the interval from the current deadline on, keeping the configured jitter
+    /// within half the new interval.
+    pub fn set_interval(&mut self, interval: Duration) {
+        let interval = interval.max(Duration::from_millis(1));
+        if interval == self.interval {
+            return;
+        }
+        self.start = self.deadline(self.tick);
+        self.tick = 0;
+        self.interval = interval;
+        self.jitter = self.jitter.min(interval / 2);
+    }
+
+    /// Sleeps for a random duration up to `splay` and restarts the schedule afterwards,
+    /// so the first deadline is the end of the splay.
+    pub fn splay(&mut self, splay: Duration) {
+        self.clock.sleep(self.rng.duration_up_to(splay));
+        self.start = self.now();
+        self.tick = 0;
+    }
+
+    /// Sleeps until the next deadline and returns the number of deadlines skipped. With
+    /// `CatchUpPolicy::Sample`, the latest missed deadline is sampled late instead. If the
+    /// clock wakes up early, the deadline stays pending and the next call waits for it again.
+    pub fn wait_next(&mut self) -> u64 {
+        let mut missed = 0;
+        loop {
+            let now = self.now();
+            let passed = self.ticks_at(now).saturating_sub(self.tick);
+            if passed > 0 {
+                self.tick += passed;
+                if self.catch_up == CatchUpPolicy::Sample {
+                    return missed + passed - 1;
+                }
+                missed += passed;
+            }
+
+            let next_tick = self.tick + 1;
+            let deadline = self.deadline(next_tick) + self.rng.duration_up_to(self.jitter);
+            self.clock.sleep(deadline.saturating_duration_since(now));
+            let now = self.now();
+            if now < self.deadline(next_tick) {
+                return missed;
+            }
+            if self.ticks_at(now) == next_tick {
+                self.tick = next_tick;
+                return missed;
+            }
+            // Woke up past later deadlines too; the next round applies the policy.
+        }
+    }
+
+    /// The monotonic time including suspends, which are added as soon as the wall clock
+    /// shows that more than an interval went by unnoticed. Smaller differences are left
+    /// to clock adjustments.
+    fn now(&mut self) -> Instant {
+        let (now, wall) = (self.clock.now(), self.clock.wall());
+        let (last_now, last_wall) = self.last_reading;
+        if let Ok(wall_elapsed) = wall.duration_since(last_wall) {
+            let unnoticed = wall_elapsed.saturating_sub(now.saturating_duration_since(last_now));
+            if unnoticed >= self.interval {
+                self.suspended += unnoticed;
+            }
+        }
+        self.last_reading = (now, wall);
+        now + self.suspended
+    }
+
+    /// Number of the last deadline at or before `now`.
+    fn ticks_at(&self, now: Instant) -> u64 {
+        (now.saturating_duration_since(self.start).as_nanos() / self.interval.as_nanos()) as u64
+    }
+
+    fn deadline(&self, tick: u64) -> Instant {
+        self.start
+            + self
+                .interval
+                .saturating_mul(u32::try_from(tick).unwrap_or(u32::MAX))
+    }
+}
+
+/// Small xorshift generator seeded from the process-random hasher keys; good enough for
+/// spreading load, without pulling in a random number crate.
+struct Rng(u64);
+
+impl Rng {
+    fn new() -> Self {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(std::process::id());
+        Self(hasher.finish() | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    /// Returns a uniformly distributed duration in `[0, max]`.
+    fn duration_up_to(&mut self, max: Duration) -> Duration {
+        let max_nanos = max.as_nanos().min(u64::MAX as u128) as u64;
+        if max_nanos == 0 {
+            return Duration::ZERO;
+        }
+        Duration::from_nanos(self.next() % (max_nanos + 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cell::Cell;
+
+    /// Clock that only advances when slept on or advanced explicitly.
+    struct ManualClock {
+        now: Cell<Instant>,
+        wall: Cell<SystemTime>,
+        /// Cuts the next sleep short after this long, like `WakeableClock::wake`.
+        wake_after: Cell<Option<Duration>>,
+    }
+
+    impl ManualClock {
+        fn new(wake_after: Option<Duration>) -> Self {
+            Self {
+                now: Cell::new(Instant::now()),
+                wall: Cell::new(SystemTime::now()),
+                wake_after: Cell::new(wake_after),
+            }
+        }
+
+        fn advance(&self, duration: Duration) {
+            self.now.set(self.now.get() + duration);
+            self.wall.set(self.wall.get() + duration);
+        }
+
+        /// Advances only the wall clock, like a suspend of the host.
+        fn suspend(&self, duration: Duration) {
+            self.wall.set(self.wall.get() + duration);
+        }
+    }
+
+    impl Clock for &ManualClock {
+        fn now(&self) -> Instant {
+            self.now.get()
+        }
+
+        fn wall(&self) -> SystemTime {
+            self.wall.get()
+        }
+
+        fn sleep(&self, duration: Duration) {
+            match self.wake_after.take() {
+                Some(wake_after) => self.advance(duration.min(wake_after)),
+                None => self.advance(duration),
+            }
+        }
+    }
+
+    #[test]
+    fn deadlines_do_not_drift_with_collection_time() {
+        let clock = ManualClock::new(None);
+        let start = clock.now.get();
+        let mut scheduler = Scheduler::with_clock(&clock, Duration::from_secs(10));
+
+        for n in 1..=100 {
+            clock.advance(Duration::from_millis(2_500)); // collection time
+            assert_eq!(scheduler.wait_next(), 0);
+            assert_eq!(clock.now.get() - start, Duration::from_secs(10 * n));
+        }
+    }
+
+    #[test]
+    fn overruns_skip_missed_deadlines() {
+        let clock = ManualClock::new(None);
+        let start = clock.now.get();
+        let mut scheduler = Scheduler::with_clock(&clock, Duration::from_secs(10));
+
+        clock.advance(Duration::from_secs(25));
+        assert_eq!(scheduler.wait_next(), 2);
+        assert_eq!(clock.now.get() - start, Duration::from_secs(30));
+
+        assert_eq!(scheduler.wait_next(), 0);
+        assert_eq!(clock.now.get() - start, Duration::from_secs(40));
+    }
+
+    #[test]
+    fn suspends_follow_the_catch_up_policy() {
+        // Elapsed times are on the monotonic clock, which stood still for 20 s.
+        for (policy, skipped, sampled_at, next_at) in [
+            (CatchUpPolicy::Skip, 2, 20, 30),
+            (CatchUpPolicy::Sample, 1, 15, 20),
+        ] {
+            let clock = ManualClock::new(None);
+            let start = clock.now.get();
+            let mut scheduler =
+                Scheduler::with_clock(&clock, Duration::from_secs(10)).with_catch_up(policy);
+
+            assert_eq!(scheduler.wait_next(), 0);
+            clock.advance(Duration::from_secs(5));
+            clock.suspend(Duration::from_secs(20));
+            assert_eq!(scheduler.wait_next(), skipped, "{:?}", policy);
+            assert_eq!(clock.now.get() - start, Duration::from_secs(sampled_at));
+
+            // Back on the wall-clock schedule afterwards.
+            assert_eq!(scheduler.wait_next(), 0);
+            assert_eq!(clock.now.get() - start, Duration::from_secs(next_at));
+        }
+    }
+
+    #[test]
+    fn interval_changes_apply_from_the_current_deadline() {
+        let clock = ManualClock::new(None);
+        let start = clock.now.get();
+        let mut scheduler = Scheduler::with_clock(&clock, Duration::from_secs(10));
+
+        scheduler.wait_next();
+        scheduler.set_interval(Duration::from_secs(60));
+        scheduler.wait_next();
+        assert_eq!(clock.now.get() - start, Duration::from_secs(70));
+    }
+
+    #[test]
+    fn early_wake_keeps_the_pending_deadline() {
+        let clock = ManualClock::new(Some(Duration::from_secs(4)));
+        let start = clock.now.get();
+        let mut scheduler = Scheduler::with_clock(&clock, Duration::from_secs(10));
+
+        assert_eq!(scheduler.wait_next(), 0);
+        assert_eq!(clock.now.get() - start, Duration::from_secs(4));
+
+        assert_eq!(scheduler.wait_next(), 0);
+        assert_eq!(clock.now.get() - start, Duration::from_secs(10));
+        assert_eq!(scheduler.wait_next(), 0);
+        assert_eq!(clock.now.get() - start, Duration::from_secs(20));
+    }
+
+    #[test]
+    fn jitter_stays_within_its_slot() {
+        let clock = ManualClock::new(None);
+        let start = clock.now.get();
+        let mut scheduler = Scheduler::with_clock(&clock, Duration::from_secs(10))
+            .with_jitter(Duration::from_secs(3));
+
+        for n in 1..=100 {
+            scheduler.wait_next();
+            let offset = clock.now.get() - start - Duration::from_secs(10 * n);
+            assert!(offset <= Duration::from_secs(3), "offset {:?}", offset);
+        }
+    }
+}
@@ -0,0 +1,398 @@
+//! Agent Self-Metrics
+//!
+//! Besides host metrics, the agent keeps a few counters about itself (collection cycles,
+//! collector durations, payload sizes and serialization times, sends, send and write
+//! failures) so fleet health can be scraped with
+//! standard tooling. They are served in the Prometheus text format on `GET /metrics` of
+//! the local API (`api_listen`), together with the current spool depth and a summary of the
+//! recent send attempts. All values reset when the agent restarts.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use crate::network::send_log::{SendAttempt, SendLog};
+
+/// Counters and last observations of the agent itself.
+#[derive(Debug, Default, Clone)]
+struct Metrics {
+    cycles: u64,
+    skipped_cycles: u64,
+    last_cycle: Duration,
+    collectors: BTreeMap<&'static str, Duration>,
+    sends: BTreeMap<String, u64>,
+    send_errors: BTreeMap<String, u64>,
+    consecutive_send_failures: u64,
+    write_failures: BTreeMap<&'static str, u64>,
+    partial_writes: u64,
+    payloads: BTreeMap<&'static str, PayloadStats>,
+}
+
+/// Encoding statistics of one payload kind.
+#[derive(Debug, Default, Clone)]
+struct PayloadStats {
+    last_bytes: usize,
+    last_duration: Duration,
+    bytes_total: u64,
+}
+
+static METRICS: Mutex<Metrics> = Mutex::new(Metrics {
+    cycles: 0,
+    skipped_cycles: 0,
+    last_cycle: Duration::ZERO,
+    collectors: BTreeMap::new(),
+    sends: BTreeMap::new(),
+    send_errors: BTreeMap::new(),
+    consecutive_send_failures: 0,
+    write_failures: BTreeMap::new(),
+    partial_writes: 0,
+    payloads: BTreeMap::new(),
+});
+
+/// Time at which the agent started, for its uptime.
+static STARTED: OnceLock<Instant> = OnceLock::new();
+
+/// Static utility class for the agent's own metrics.
+pub struct SelfMetrics;
+
+impl SelfMetrics {
+    /// Records the start of the agent; its uptime is measured from the first call.
+    pub fn mark_started() {
+        STARTED.get_or_init(Instant::now);
+    }
+
+    /// Records a completed collection cycle and how long it took.
+    pub fn record_cycle(duration: Duration) {
+        let mut metrics = Self::lock();
+        metrics.cycles += 1;
+        metrics.last_cycle = duration;
+    }
+
+    /// Records collection deadlines skipped because a cycle overran them.
+    pub fn record_skipped(skipped: u64) {
+        Self::lock().skipped_cycles += skipped;
+    }
+
+    /// Runs one collector and records its duration under `name`.
+    pub fn time<T>(name: &'static str, collect: impl FnOnce() -> T) -> T {
+        let started = Instant::now();
+        let value = collect();
+        Self::lock().collectors.insert(name, started.elapsed());
+        value
+    }
+
+    /// Records the outcome of sending a payload of `kind`.
+    pub fn record_send(kind: &str, success: bool) {
+        let mut metrics = Self::lock();
+        *metrics.sends.entry(kind.to_string()).or_default() += 1;
+        if success {
+            metrics.consecutive_send_failures = 0;
+        } else {
+            *metrics.send_errors.entry(kind.to_string()).or_default() += 1;
+            metrics.consecutive_send_failures += 1;
+        }
+    }
+
+    /// Records the encoded size of a payload of `kind` and how long serializing it took;
+    /// returns the size of the previous payload of that kind.
+    pub fn record_payload(kind: &'static str, bytes: usize, duration: Duration) -> Option<usize> {
+        let mut metrics = Self::lock();
+        let stats = metrics.payloads.entry(kind).or_default();
+        let previous = (stats.bytes_total > 0).then_some(stats.last_bytes);
+        stats.last_bytes = bytes;
+        stats.last_duration = duration;
+        stats.bytes_total += bytes as u64;
+        previous
+    }
+
+    /// Records a request that could not be written to the server, by `reason`, and
+    /// whether part of it had been written.
+    pub fn record_write_failure(reason: &'static str, partial: bool) {
+        let mut metrics = Self::lock();
+        *metrics.write_failures.entry(reason).or_default() += 1;
+        if partial {
+            metrics.partial_writes += 1;
+        }
+    }
+
+    /// Renders the metrics in the Prometheus text exposition format.
+    pub fn render(spool_depth: usize) -> String {
+        let metrics = Self::lock().clone();
+        let uptime = STARTED.get().map(Instant::elapsed).unwrap_or_default();
+        Self::render_metrics(&metrics, uptime, spool_depth, &SendLog::recent())
+    }
+
+    fn render_metrics(
+        metrics: &Metrics,
+        uptime: Duration,
+        spool_depth: usize,
+        attempts: &[SendAttempt],
+    ) -> String {
+        let mut out = String::new();
+        let mut family = |name: &str, kind: &str, help: &str, samples: Vec<(String, String)>| {
+            let _ = writeln!(out, "# HELP sentinel_agent_{} {}", name, help);
+            let _ = writeln!(out, "# TYPE sentinel_agent_{} {}", name, kind);
+            for (labels, value) in samples {
+                let _ = writeln!(out, "sentinel_agent_{}{} {}", name, labels, value);
+            }
+        };
+        let plain = |value: String| vec![(String::new(), value)];
+        let labelled = |label: &str, values: Vec<(&str, String)>| {
+            values
+                .into_iter()
+                .map(|(key, value)| (format!("{{{}=\"{}\"}}", label, Self::escape(key)), value))
+                .collect::<Vec<_>>()
+        };
+
+        family(
+            "info",
+            "gauge",
+            "Version of the running agent.",
+            labelled(
+                "version",
+                vec![(env!("CARGO_PKG_VERSION"), "1".to_string())],
+            ),
+        );
+        family(
+            "uptime_seconds",
+            "gauge",
+            "Seconds since the agent started.",
+            plain(uptime.as_secs().to_string()),
+        );
+        family(
+            "cycles_total",
+            "counter",
+            "Collection cycles run.",
+            plain(metrics.cycles.to_string()),
+        );
+        family(
+            "skipped_cycles_total",
+            "counter",
+            "Collection deadlines skipped because a cycle overran them.",
+            plain(metrics.skipped_cycles.to_string()),
+        );
+        family(
+            "cycle_duration_seconds",
+            "gauge",
+            "Duration of the last collection cycle, including sending.",
+            plain(metrics.last_cycle.as_secs_f64().to_string()),
+        );
+        family(
+            "collector_duration_seconds",
+            "gauge",
+            "Duration of each collector in the last cycle.",
+            labelled(
+                "collector",
+                metrics
+                    .collectors
+                    .iter()
+                    .map(|(name, duration)| (*name, duration.as_secs_f64().to_string()))
+                    .collect(),
+            ),
+        );
+        family(
+            "payload_bytes",
+            "gauge",
+            "Encoded size of the last payload, by kind.",
+            labelled(
+                "kind",
+                metrics
+                    .payloads
+                    .iter()
+                    .map(|(kind, stats)| (*kind, stats.last_bytes.to_string()))
+                    .collect(),
+            ),
+        );
+        family(
+            "payload_bytes_total",
+            "counter",
+            "Encoded bytes of all payloads, by kind.",
+            labelled(
+                "kind",
+                metrics
+                    .payloads
+                    .iter()
+                    .map(|(kind, stats)| (*kind, stats.bytes_total.to_string()))
+                    .collect(),
+            ),
+        );
+        family(
+            "payload_serialization_seconds",
+            "gauge",
+            "Time taken to serialize the last payload, by kind.",
+            labelled(
+                "kind",
+                metrics
+                    .payloads
+                    .iter()
+                    .map(|(kind, stats)| (*kind, stats.last_duration.as_secs_f64().to_string()))
+                    .collect(),
+            ),
+        );
+        family(
+            "sends_total",
+            "counter",
+            "Payloads sent, by kind, including failed attempts and spool replays.",
+            labelled(
+                "kind",
+                metrics
+                    .sends
+                    .iter()
+                    .map(|(kind, count)| (kind.as_str(), count.to_string()))
+                    .collect(),
+            ),
+        );
+        family(
+            "send_errors_total",
+            "counter",
+            "Failed send attempts, by kind.",
+            labelled(
+                "kind",
+                metrics
+                    .send_errors
+                    .iter()
+                    .map(|(kind, count)| (kind.as_str(), count.to_string()))
+                    .collect(),
+            ),
+        );
+        family(
+            "consecutive_send_failures",
+            "gauge",
+            "Send attempts that failed since the last successful one.",
+            plain(metrics.consecutive_send_failures.to_string()),
+        );
+        family(
+            "write_failures_total",
+            "counter",
+            "Requests that could not be written to the server, by reason (timeout, reset, other).",
+            labelled(
+                "reason",
+                metrics
+                    .write_failures
+                    .iter()
+                    .map(|(reason, count)| (*reason, count.to_string()))
+                    .collect(),
+            ),
+        );
+        family(
+            "partial_writes_total",
+            "counter",
+            "Requests that failed after being partially written to the server.",
+            plain(metrics.partial_writes.to_string()),
+        );
+        family(
+            "spool_depth",
+            "gauge",
+            "Payloads waiting in the spool.",
+            plain(spool_depth.to_string()),
+        );
+
+        let failed = attempts.iter().filter(|attempt| !attempt.success).count();
+        family(
+            "recent_send_attempts",
+            "gauge",
+            "Send attempts in the send log, by outcome.",
+            labelled(
+                "outcome",
+                vec![
+                    ("success", (attempts.len() - failed).to_string()),
+                    ("failure", failed.to_string()),
+                ],
+            ),
+        );
+        let latency_ms = attempts
+            .iter()
+            .map(|attempt| attempt.latency_ms)
+            .sum::<u64>()
+            / (attempts.len().max(1) as u64);
+        family(
+            "recent_send_latency_seconds",
+            "gauge",
+            "Mean latency of the send attempts in the send log.",
+            plain((latency_ms as f64 / 1000.0).to_string()),
+        );
+        if let Some(attempt) = attempts.iter().rev().find(|attempt| attempt.success) {
+            family(
+                "last_successful_send_timestamp_seconds",
+                "gauge",
+                "Unix time of the last successful send attempt.",
+                plain((attempt.timestamp_ms / 1000).to_string()),
+            );
+        }
+        out
+    }
+
+    /// Escapes a label value.
+    fn escape(value: &str) -> String {
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    }
+
+    fn lock() -> std::sync::MutexGuard<'static, Metrics> {
+        METRICS.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn renders_prometheus_text() {
+        let mut metrics = Metrics {
+            cycles: 3,
+            last_cycle: Duration::from_millis(1500),
+            consecutive_send_failures: 2,
+            ..Metrics::default()
+        };
+        metrics
+            .collectors
+            .insert("sensors", Duration::from_millis(250));
+        metrics.sends.insert("sensors".to_string(), 5);
+        metrics.send_errors.insert("sensors".to_string(), 2);
+        metrics.payloads.insert(
+            "sensors",
+            PayloadStats {
+                last_bytes: 2048,
+                last_duration: Duration::from_millis(2),
+                bytes_total: 6144,
+            },
+        );
+
+        let attempt = |timestamp_ms, latency_ms, error: Option<&str>| SendAttempt {
+            timestamp_ms,
+            kind: "sensors".to_string(),
+            endpoint: "http://server:5000/".to_string(),
+            bytes: 512,
+            latency_ms,
+            success: error.is_none(),
+            error: error.map(str::to_string),
+        };
+        let attempts = [
+            attempt(1_700_000_000_000, 100, None),
+            attempt(1_700_000_010_000, 300, Some("Connection refused")),
+        ];
+
+        let text = SelfMetrics::render_metrics(&metrics, Duration::from_secs(60), 4, &attempts);
+        assert!(text.contains("# TYPE sentinel_agent_cycles_total counter\n"));
+        assert!(text.contains("sentinel_agent_cycles_total 3\n"));
+        assert!(text.contains("sentinel_agent_cycle_duration_seconds 1.5\n"));
+        assert!(text
+            .contains("sentinel_agent_collector_duration_seconds{collector=\"sensors\"} 0.25\n"));
+        assert!(text.contains("sentinel_agent_send_errors_total{kind=\"sensors\"} 2\n"));
+        assert!(text.contains("sentinel_agent_consecutive_send_failures 2\n"));
+        assert!(text.contains("sentinel_agent_payload_bytes{kind=\"sensors\"} 2048\n"));
+        assert!(text.contains("sentinel_agent_payload_bytes_total{kind=\"sensors\"} 6144\n"));
+        assert!(
+            text.contains("sentinel_agent_payload_serialization_seconds{kind=\"sensors\"} 0.002\n")
+        );
+        assert!(text.contains("sentinel_agent_spool_depth 4\n"));
+        assert!(text.contains("sentinel_agent_uptime_seconds 60\n"));
+        assert!(text.contains("sentinel_agent_recent_send_attempts{outcome=\"failure\"} 1\n"));
+        assert!(text.contains("sentinel_agent_recent_send_latency_seconds 0.2\n"));
+        assert!(text.contains("sentinel_agent_last_successful_send_timestamp_seconds 1700000000\n"));
+    }
+}
@@ -21,3 +21,34 @@ pub fn setup_signal_handler() -> Result<Arc<AtomicBool>, Box<dyn std::error::Err
 
     Ok(running)
 }
+
+/// Registers SIGHUP, SIGUSR1 and SIGUSR2 to set `flag`, used as "flush now" requests
+/// (e.g. by Telegraf's `execd` input).
+#[cfg(unix)]
+pub fn register_flush_signals(flag: &Arc<AtomicBool>) -> Result<(), Box<dyn std::error::Error>> {
+    for signal in [libc::SIGHUP, libc::SIGUSR1, libc::SIGUSR2] {
+        let f = Arc::clone(flag);
+        unsafe {
+            register(signal, move || {
+                f.store(true, Ordering::Relaxed);
+            })?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Registers SIGUSR1 to set `flag`, used as a "toggle debug logging" request by the agent.
+#[cfg(unix)]
+pub fn register_debug_toggle_signal(
+    flag: &Arc<AtomicBool>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let f = Arc::clone(flag);
+    unsafe {
+        register(libc::SIGUSR1, move || {
+            f.store(true, Ordering::Relaxed);
+        })?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+//! Persisted Agent State
+//!
+//! This module stores small pieces of state that must survive agent restarts, such as
+//! the boot time and time of the last delivered report, in a JSON file inside the configured state directory.
+
+use log::{debug, error, warn};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::sync::Mutex;
+
+use crate::config::config_instance::Config;
+
+/// File name of the persisted state inside the state directory.
+const STATE_FILE_NAME: &str = "sentinel-state.json";
+
+/// Boot times closer together than this are treated as the same boot, since the
+/// kernel derives the boot time from the wall clock and may shift it slightly.
+const BOOT_TIME_TOLERANCE_SECS: u64 = 30;
+
+/// Serializes `AgentState::update` calls, so concurrent updates do not overwrite each other.
+static STATE_LOCK: Mutex<()> = Mutex::new(());
+
+/// State persisted between agent runs.
+#[derive(Serialize, Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct AgentState {
+    /// Boot time (seconds since the Unix epoch) included in the last delivered report.
+    pub last_reported_boot_time: Option<u64>,
+    /// Time (seconds since the Unix epoch) of the last delivered report, used to detect gaps.
+    pub last_report_at: Option<u64>,
+    /// Last sequence number per host identity (`<source>:<host>`) of grouped reports.
+    pub host_sequences: BTreeMap<String, u64>,
+    /// Sequence number of the last encoded payload, as persisted by earlier versions; now
+    /// only read once to continue numbering in `ack_util`'s own sequence file.
+    pub payload_sequence: u64,
+    /// Agent ID the server last accepted a registration for.
+    pub registered_agent_id: Option<String>,
+    /// Local day (`year * 1000 + day of year`) the last report digest was sent on.
+    pub last_digest_day: Option<i64>,
+}
+
+impl AgentState {
+    /// Loads the persisted state, falling back to an empty state if none exists or it is unreadable.
+    pub fn load() -> Self {
+        let path = Config::state_file(STATE_FILE_NAME);
+        match fs::read_to_string(&path) {
+            Ok(contents) => serde_json::from_str(&contents).unwrap_or_else(|e| {
+                warn!("Ignoring unreadable state file {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(e) => {
+                debug!("No state file at {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Persists the state, logging (but otherwise ignoring) failures.
+    pub fn save(&self) {
+        let path = Config::state_file(STATE_FILE_NAME);
+        let result = serde_json::to_string_pretty(self)
+            .map_err(|e| e.to_string())
+            .and_then(|json| fs::write(&path, json).map_err(|e| e.to_string()));
+        if let Err(e) = result {
+            error!("Failed to write state file {}: {}", path.display(), e);
+        }
+    }
+
+    /// Loads the state, applies `update` and persists it, holding a lock so that concurrent
+    /// updates from other threads are not lost. `update` must not block (e.g. on a send).
+    pub fn update<R>(update: impl FnOnce(&mut Self) -> R) -> R {
+        let _guard = STATE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        let mut state = Self::load();
+        let result = update(&mut state);
+        state.save();
+        result
+    }
+
+    /// Returns `true` if `boot_time` differs from the boot time of the last delivered report.
+    ///
+    /// The first report ever sent is not flagged, since there is nothing to compare against.
+    pub fn is_reboot(&self, boot_time: u64) -> bool {
+        self.last_reported_boot_time
+            .is_some_and(|last| last.abs_diff(boot_time) > BOOT_TIME_TOLERANCE_SECS)
+    }
+}
@@ -0,0 +1,106 @@
+#![cfg(unix)]
+
+//! Telegraf Output Mode
+//!
+//! This module implements the `telegraf` subcommand, which writes InfluxDB line protocol
+//! to stdout instead of sending payloads to the server:
+//! - Without `--execd`, one snapshot is printed and the process exits (Telegraf `exec`).
+//! - With `--execd`, the process stays running and prints a snapshot whenever Telegraf asks
+//!   for one: a line on stdin (`signal = "STDIN"`) or SIGHUP/SIGUSR1/SIGUSR2. With
+//!   `--self-timed` it also prints every `interval_secs` (`signal = "none"`). The process
+//!   exits when stdin is closed, which is how Telegraf stops `execd` plugins.
+//!
+//! The top-level `--telegraf` flag is a shorthand for `telegraf --execd`, for plugin
+//! configurations that can only pass a single option:
+//!
+//! ```toml
+//! [[inputs.execd]]
+//!   command = ["/opt/gilded-sentinel/Gilded-Sentinel-Client", "--telegraf"]
+//!   signal = "STDIN"
+//!   data_format = "influx"
+//! ```
+
+use log::{error, info};
+use std::io::{self, BufRead, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use crate::config::cli::TelegrafOptions;
+use crate::config::config_instance::Config;
+use crate::data::line_protocol::LineProtocol;
+use crate::hardware::system_information_monitor::SysInfoMonitor;
+use crate::sensor::sensor_util::SensorUtils;
+use crate::system::signal::register_flush_signals;
+
+/// How often the `execd` loop checks for flush requests.
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A utility class for the Telegraf output mode.
+pub struct TelegrafUtil;
+
+impl TelegrafUtil {
+    /// Runs the Telegraf output mode described by `options`.
+    pub fn run(options: &TelegrafOptions) -> io::Result<()> {
+        let mut monitor = SysInfoMonitor::new();
+
+        // CPU usage is computed between two refreshes; prime the first one.
+        monitor.get_cpu_info();
+        thread::sleep(sysinfo::MINIMUM_CPU_UPDATE_INTERVAL);
+
+        if !options.execd {
+            return Self::emit(&mut monitor);
+        }
+
+        let flush = Arc::new(AtomicBool::new(false));
+        let stdin_open = Arc::new(AtomicBool::new(true));
+        register_flush_signals(&flush).map_err(|e| io::Error::other(e.to_string()))?;
+        Self::watch_stdin(Arc::clone(&flush), Arc::clone(&stdin_open));
+
+        info!("Telegraf execd mode started.");
+        let interval = Duration::from_secs(Config::interval_secs());
+        let mut last_emit: Option<Instant> = None;
+        while stdin_open.load(Ordering::Relaxed) {
+            let timer_due =
+                options.self_timed && last_emit.is_none_or(|emitted| emitted.elapsed() >= interval);
+            if flush.swap(false, Ordering::Relaxed) || timer_due {
+                Self::emit(&mut monitor)?;
+                last_emit = Some(Instant::now());
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+
+        info!("Stdin closed; leaving Telegraf execd mode.");
+        Ok(())
+    }
+
+    /// Collects one snapshot and writes it to stdout as line protocol.
+    fn emit(monitor: &mut SysInfoMonitor) -> io::Result<()> {
+        let data = SensorUtils::collect_sensor_data(monitor);
+        let timestamp_ns = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_nanos())
+            .unwrap_or_default();
+
+        let mut stdout = io::stdout().lock();
+        stdout.write_all(LineProtocol::encode(&data, timestamp_ns).as_bytes())?;
+        stdout.flush()
+    }
+
+    /// Requests a flush for every line read from stdin, and clears `stdin_open` at EOF.
+    fn watch_stdin(flush: Arc<AtomicBool>, stdin_open: Arc<AtomicBool>) {
+        thread::spawn(move || {
+            for line in io::stdin().lock().lines() {
+                match line {
+                    Ok(_) => flush.store(true, Ordering::Relaxed),
+                    Err(e) => {
+                        error!("Failed to read stdin: {}", e);
+                        break;
+                    }
+                }
+            }
+            stdin_open.store(false, Ordering::Relaxed);
+        });
+    }
+}
@@ -0,0 +1,142 @@
+//! Test Utilities
+//!
+//! Fixtures shared by the unit tests of several modules.
+
+use std::fs;
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+use crate::data::models::{
+    ComponentInfo, CpuCoreData, CpuInfo, CpuPackageData, DiskInfo, MemoryInfo, NeighborInfo,
+    NetworkInfo, SensorData, SystemInfo, Uptime, SCHEMA_VERSION,
+};
+use crate::data::units::{Bytes, Celsius, Percent};
+
+/// A scratch directory under the system temporary directory, created empty and removed
+/// again when dropped. Its name includes the process id so concurrent test runs don't
+/// collide; `name` must be unique among the tests of one run.
+pub struct TempDir(PathBuf);
+
+impl TempDir {
+    pub fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("sentinel-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        TempDir(path)
+    }
+}
+
+impl Deref for TempDir {
+    type Target = Path;
+
+    fn deref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl AsRef<Path> for TempDir {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// A representative snapshot of a two-core host with one of each reading, matching the
+/// golden payloads under `tests/golden/`.
+pub fn sample_sensor_data() -> SensorData {
+    SensorData {
+        schema_version: SCHEMA_VERSION,
+        agent_id: None,
+        tenant_id: None,
+        site_id: None,
+        system_info: SystemInfo {
+            hostname: "sentinel-test".to_string(),
+            uptime: Uptime::new(93784, 1_700_000_000),
+            management_ip: "192.168.1.10".to_string(),
+            public_ip: None,
+            rebooted_since_last_report: false,
+        },
+        cpu_info: CpuInfo {
+            usage_per_core: vec![Percent(12.5), Percent(3.0)],
+            core_count: 2,
+            cpu_arch: "x86_64".to_string(),
+            container_cpu_limit: None,
+        },
+        cpu_packages: vec![CpuPackageData {
+            package_id: "0".to_string(),
+            adapter_name: "coretemp-isa-0000".to_string(),
+            package_temperature: Celsius(45.0),
+            high_threshold: Celsius(80.0),
+            critical_threshold: Celsius(100.0),
+            cores: vec![CpuCoreData {
+                core_name: "Core 0".to_string(),
+                temperature: Celsius(42.0),
+                high_threshold: Celsius(80.0),
+                critical_threshold: Celsius(100.0),
+            }],
+        }],
+        memory_info: MemoryInfo {
+            total: Bytes(16_000_000_000),
+            used: Bytes(4_000_000_000),
+            total_swap: Bytes(2_000_000_000),
+            used_swap: Bytes(0),
+            container_limit: None,
+            container_used: None,
+        },
+        disks: vec![DiskInfo {
+            name: "/dev/sda1".to_string(),
+            total_space: Bytes(500_000_000_000),
+            available_space: Bytes(250_000_000_000),
+            read_bytes: Bytes(4096),
+            written_bytes: Bytes(8192),
+            total_read_bytes: 1_000_000,
+            total_written_bytes: 2_000_000,
+            read_bytes_per_sec: None,
+            written_bytes_per_sec: None,
+            counter_reset: false,
+        }],
+        network_interfaces: vec![NetworkInfo {
+            mac_address: None,
+            addresses: Vec::new(),
+            interface_name: "eth0".to_string(),
+            received: Bytes(1024),
+            transmitted: Bytes(2048),
+            mtu: Some(1500),
+            total_received: 1_000_000,
+            total_transmitted: 2_000_000,
+            received_per_sec: None,
+            transmitted_per_sec: None,
+            counter_reset: false,
+        }],
+        components: vec![ComponentInfo {
+            label: "acpitz temp1".to_string(),
+            temperature: Some(Celsius(27.5)),
+            max_temperature: None,
+            critical_temperature: Some(Celsius(119.0)),
+        }],
+        neighbors: Some(vec![NeighborInfo {
+            ip_address: "192.168.1.1".to_string(),
+            mac_address: Some("aa:bb:cc:dd:ee:ff".to_string()),
+            state: "REACHABLE".to_string(),
+            interface_name: "eth0".to_string(),
+        }]),
+        fans: None,
+        voltages: None,
+        hwmon_devices: None,
+        cooling_devices: None,
+        proxied_devices: None,
+        remote_hosts: None,
+        smart_devices: None,
+        gpus: None,
+        power: None,
+        thermal_disagreements: None,
+        inventory: None,
+        inventory_hash: None,
+        section_status: Default::default(),
+    }
+}